@@ -9,12 +9,19 @@
 //! 4) Alternation: a|b|c and capture groups: ( ... )
 //! 5) Anchors: ^, $, and word boundary: \b
 //!    + basic optimization (flattening, singleton simplifications)
+//! 6) Compiled matching: Pike VM (captures, O(n·m)) and a lazy DFA
+//!    (is_match/find) with a literal-prefix prefilter
+//! 7) Extended syntax: (?:...), (?P<name>...), lazy *? +? ??, inline flags
+//!    (?i) (?m) (?s), \p{L}-style categories, \B, \A, \z
+//!    + replace_all with $name expansion, and split
 //!
 //! Run:
 //!   cargo run --bin complete_07_regex_parser
 //! Test:
 //!   cargo test --bin complete_07_regex_parser
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,31 +29,101 @@ pub enum Regex {
     Empty,
     Literal(String), // multi-char literal chunk (optimized)
     Char(char),
-    Wildcard, // .
+    Wildcard,         // . under (?s): any char
+    AnyExceptNewline, // . (default)
     Sequence(Vec<Regex>),
     CharClass {
         ranges: Vec<(char, char)>,
         chars: Vec<char>,
+        categories: Vec<UnicodeCategory>, // \p{..} inside or as the class
         negated: bool,
     },
     Repeat {
         expr: Box<Regex>,
         min: usize,
         max: Option<usize>, // None = unbounded
+        greedy: bool,       // false for *? +? ?? {n,m}?
     },
     Alternation(Vec<Regex>),
     Group {
         id: usize,
+        name: Option<String>, // (?P<name>...)
         expr: Box<Regex>,
     },
+    NonCapturing(Box<Regex>), // (?:...)
     Anchor(AnchorKind),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AnchorKind {
-    StartOfLine,  // ^
-    EndOfLine,    // $
-    WordBoundary, // \b
+    StartOfLine,     // ^ (start of text unless (?m))
+    EndOfLine,       // $ (end of text unless (?m))
+    MultiLineStart,  // ^ under (?m)
+    MultiLineEnd,    // $ under (?m)
+    StartOfText,     // \A
+    EndOfText,       // \z
+    WordBoundary,    // \b
+    NotWordBoundary, // \B
+}
+
+/// `\p{..}` categories, approximated with `char`'s Unicode predicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnicodeCategory {
+    Letter,      // L
+    Uppercase,   // Lu
+    Lowercase,   // Ll
+    Number,      // N
+    Punctuation, // P
+    Separator,   // Z
+    Control,     // Cc
+}
+
+impl UnicodeCategory {
+    pub fn from_name(name: &str) -> Option<Self> {
+        use UnicodeCategory::*;
+        Some(match name {
+            "L" | "Letter" => Letter,
+            "Lu" | "Uppercase_Letter" => Uppercase,
+            "Ll" | "Lowercase_Letter" => Lowercase,
+            "N" | "Nd" | "Number" => Number,
+            "P" | "Punctuation" => Punctuation,
+            "Z" | "Zs" | "Separator" => Separator,
+            "Cc" | "Control" => Control,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        use UnicodeCategory::*;
+        match self {
+            Letter => "L",
+            Uppercase => "Lu",
+            Lowercase => "Ll",
+            Number => "N",
+            Punctuation => "P",
+            Separator => "Z",
+            Control => "Cc",
+        }
+    }
+
+    pub fn contains(self, c: char) -> bool {
+        use UnicodeCategory::*;
+        match self {
+            Letter => c.is_alphabetic(),
+            Uppercase => c.is_uppercase(),
+            Lowercase => c.is_lowercase(),
+            Number => c.is_numeric(),
+            Punctuation => {
+                c.is_ascii_punctuation()
+                    || matches!(c, '\u{00A1}' | '\u{00A7}' | '\u{00AB}' | '\u{00B6}' | '\u{00B7}' | '\u{00BB}' | '\u{00BF}')
+                    || ('\u{2010}'..='\u{2027}').contains(&c)
+                    || ('\u{2030}'..='\u{205E}').contains(&c)
+                    || ('\u{3001}'..='\u{3003}').contains(&c)
+            }
+            Separator => c.is_whitespace() && !c.is_control(),
+            Control => c.is_control(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -58,6 +135,10 @@ pub enum ParseError {
     InvalidRange,
     InvalidQuantifier,
     EmptyAlternationBranch,
+    InvalidGroupName,
+    DuplicateGroupName(String),
+    UnknownFlag(char),
+    UnknownCategory(String),
 }
 
 impl fmt::Display for ParseError {
@@ -71,6 +152,10 @@ impl fmt::Display for ParseError {
             InvalidRange => write!(f, "invalid character range"),
             InvalidQuantifier => write!(f, "invalid quantifier"),
             EmptyAlternationBranch => write!(f, "empty alternation branch"),
+            InvalidGroupName => write!(f, "invalid group name"),
+            DuplicateGroupName(n) => write!(f, "duplicate group name '{n}'"),
+            UnknownFlag(c) => write!(f, "unknown flag '{c}'"),
+            UnknownCategory(n) => write!(f, "unknown unicode category '{n}'"),
         }
     }
 }
@@ -84,6 +169,16 @@ impl Regex {
         Regex::CharClass {
             ranges,
             chars,
+            categories: Vec::new(),
+            negated,
+        }
+    }
+
+    pub fn category(category: UnicodeCategory, negated: bool) -> Self {
+        Regex::CharClass {
+            ranges: Vec::new(),
+            chars: Vec::new(),
+            categories: vec![category],
             negated,
         }
     }
@@ -105,6 +200,7 @@ impl Regex {
             expr: Box::new(expr),
             min: 0,
             max: None,
+            greedy: true,
         }
     }
 
@@ -113,6 +209,7 @@ impl Regex {
             expr: Box::new(expr),
             min: 1,
             max: None,
+            greedy: true,
         }
     }
 
@@ -121,6 +218,7 @@ impl Regex {
             expr: Box::new(expr),
             min: 0,
             max: Some(1),
+            greedy: true,
         }
     }
 
@@ -129,6 +227,7 @@ impl Regex {
             expr: Box::new(expr),
             min: n,
             max: Some(n),
+            greedy: true,
         }
     }
 
@@ -137,6 +236,7 @@ impl Regex {
             expr: Box::new(expr),
             min,
             max: Some(max),
+            greedy: true,
         }
     }
 
@@ -147,10 +247,36 @@ impl Regex {
     pub fn group(expr: Regex, id: usize) -> Self {
         Regex::Group {
             id,
+            name: None,
             expr: Box::new(expr),
         }
     }
 
+    pub fn named_group(expr: Regex, id: usize, name: &str) -> Self {
+        Regex::Group {
+            id,
+            name: Some(name.to_string()),
+            expr: Box::new(expr),
+        }
+    }
+
+    pub fn non_capturing(expr: Regex) -> Self {
+        Regex::NonCapturing(Box::new(expr))
+    }
+
+    /// Turns a quantifier lazy (`*?`, `+?`, `??`); other nodes are returned as-is.
+    pub fn lazy(self) -> Self {
+        match self {
+            Regex::Repeat { expr, min, max, .. } => Regex::Repeat {
+                expr,
+                min,
+                max,
+                greedy: false,
+            },
+            other => other,
+        }
+    }
+
     /* ---------- Public API ---------- */
 
     pub fn parse(pattern: &str) -> Result<Self, ParseError> {
@@ -161,6 +287,11 @@ impl Regex {
         optimize(self)
     }
 
+    /// Compiles into a `Matcher` backed by the Pike VM and lazy DFA.
+    pub fn compile(&self) -> Matcher {
+        Matcher::new(self.clone(), Engine::Automaton)
+    }
+
     /// Returns true if the regex matches anywhere in `text`.
     pub fn is_match(&self, text: &str) -> bool {
         self.find(text).is_some()
@@ -168,41 +299,53 @@ impl Regex {
 
     /// Returns (start, len) of the first match.
     pub fn find(&self, text: &str) -> Option<(usize, usize)> {
-        let anchored = is_anchored_at_start(self);
-        let start_positions: Box<dyn Iterator<Item = usize>> = if anchored {
-            Box::new(std::iter::once(0))
-        } else {
-            Box::new(char_boundaries(text))
-        };
-
-        for start in start_positions {
-            let caps = Captures::new(count_groups(self));
-            for (end, _caps2) in self.match_from(text, start, caps.clone()) {
-                return Some((start, end - start));
-            }
-        }
-        None
+        let m = self.captures_at(text, 0)?.full_match?;
+        Some((m.start, m.end - m.start))
     }
 
     /// Returns captures if the regex matches anywhere in `text`.
     pub fn captures(&self, text: &str) -> Option<Captures> {
-        let anchored = is_anchored_at_start(self);
-        let start_positions: Box<dyn Iterator<Item = usize>> = if anchored {
-            Box::new(std::iter::once(0))
+        self.captures_at(text, 0)
+    }
+
+    /// Captures of every non-overlapping match, left to right.
+    pub fn captures_iter(&self, text: &str) -> Vec<Captures> {
+        collect_captures(text, |at| self.captures_at(text, at))
+    }
+
+    /// Replaces every match with `replacement`, expanding `$0`, `$1`, `$name`,
+    /// `${name}` and `$$` (a literal dollar sign).
+    pub fn replace_all(&self, text: &str, replacement: &str) -> String {
+        replace_matches(text, &self.captures_iter(text), replacement)
+    }
+
+    /// Splits `text` on every match of the regex.
+    pub fn split<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        split_on_matches(text, &self.captures_iter(text))
+    }
+
+    /// Names of `(?P<name>...)` groups with their ids.
+    pub fn group_names(&self) -> Vec<(String, usize)> {
+        let mut names = Vec::new();
+        collect_group_names(self, &mut names);
+        names
+    }
+
+    fn captures_at(&self, text: &str, at: usize) -> Option<Captures> {
+        let start_positions: Box<dyn Iterator<Item = usize>> = if is_anchored_at_start(self) {
+            Box::new(std::iter::once(0).filter(move |_| at == 0))
         } else {
-            Box::new(char_boundaries(text))
+            Box::new(char_boundaries(text).filter(move |&p| p >= at))
         };
 
+        let template = Captures::new(count_groups(self)).with_names(self.group_names());
         for start in start_positions {
-            let caps = Captures::new(count_groups(self));
-            for (end, caps2) in self.match_from(text, start, caps.clone()) {
-                let full = Match {
+            if let Some((end, mut out)) = self.match_from(text, start, template.clone()).into_iter().next() {
+                out.full_match = Some(Match {
                     start,
                     end,
                     text: text[start..end].to_string(),
-                };
-                let mut out = caps2;
-                out.full_match = Some(full);
+                });
                 return Some(out);
             }
         }
@@ -234,35 +377,25 @@ impl Regex {
                 None => vec![],
             },
 
+            Regex::AnyExceptNewline => match next_char(text, pos) {
+                Some((ch, next)) if ch != '\n' => vec![(next, caps)],
+                _ => vec![],
+            },
+
             Regex::CharClass {
                 ranges,
                 chars,
+                categories,
                 negated,
             } => match next_char(text, pos) {
-                Some((ch, next)) => {
-                    let mut matched = chars.contains(&ch);
-                    if !matched {
-                        matched = ranges
-                            .iter()
-                            .any(|(a, b)| matches!(ch, x if x >= *a && x <= *b));
-                    }
-                    let ok = if *negated { !matched } else { matched };
-                    if ok {
-                        vec![(next, caps)]
-                    } else {
-                        vec![]
-                    }
+                Some((ch, next)) if class_matches(ranges, chars, categories, *negated, ch) => {
+                    vec![(next, caps)]
                 }
-                None => vec![],
+                _ => vec![],
             },
 
             Regex::Anchor(kind) => {
-                let ok = match kind {
-                    AnchorKind::StartOfLine => pos == 0,
-                    AnchorKind::EndOfLine => pos == text.len(),
-                    AnchorKind::WordBoundary => is_word_boundary(text, pos),
-                };
-                if ok {
+                if anchor_matches(*kind, text, pos) {
                     vec![(pos, caps)]
                 } else {
                     vec![]
@@ -293,7 +426,9 @@ impl Regex {
                 out
             }
 
-            Regex::Group { id, expr } => {
+            Regex::NonCapturing(expr) => expr.match_from(text, pos, caps),
+
+            Regex::Group { id, expr, .. } => {
                 let start = pos;
                 let mut out = Vec::new();
                 for (end, mut caps2) in expr.match_from(text, pos, caps) {
@@ -308,7 +443,12 @@ impl Regex {
                 out
             }
 
-            Regex::Repeat { expr, min, max } => {
+            Regex::Repeat {
+                expr,
+                min,
+                max,
+                greedy,
+            } => {
                 // Greedy: generate the most-consumed options first, then backtrack.
                 // Strategy:
                 // 1) Match `min` times (must succeed).
@@ -357,11 +497,11 @@ impl Regex {
                 }
 
                 // Greedy order: largest layer first (most reps), then earlier.
-                let mut out = Vec::new();
-                for layer in layers.into_iter().rev() {
-                    out.extend(layer);
+                // Lazy order is the reverse: fewest reps first.
+                if *greedy {
+                    layers.reverse();
                 }
-                out
+                layers.into_iter().flatten().collect()
             }
         }
     }
@@ -383,6 +523,7 @@ pub struct Match {
 pub struct Captures {
     pub full_match: Option<Match>,
     pub groups: Vec<Option<Match>>,
+    pub names: Vec<(String, usize)>, // (?P<name>...) -> group id
 }
 
 impl Captures {
@@ -390,14 +531,75 @@ impl Captures {
         Self {
             full_match: None,
             groups: vec![None; group_count],
+            names: Vec::new(),
         }
     }
 
+    fn with_names(mut self, names: Vec<(String, usize)>) -> Self {
+        self.names = names;
+        self
+    }
+
     fn set_group(&mut self, id: usize, m: Match) {
         if id < self.groups.len() {
             self.groups[id] = Some(m);
         }
     }
+
+    /// `$n` numbering: 0 is the whole match, 1.. are the groups.
+    pub fn get(&self, n: usize) -> Option<&Match> {
+        match n {
+            0 => self.full_match.as_ref(),
+            n => self.groups.get(n - 1)?.as_ref(),
+        }
+    }
+
+    pub fn name(&self, name: &str) -> Option<&Match> {
+        let (_, id) = self.names.iter().find(|(n, _)| n == name)?;
+        self.groups.get(*id)?.as_ref()
+    }
+
+    /// Appends `template` to `out`, substituting `$n`, `$name`, `${name}` and `$$`.
+    /// Unknown references expand to nothing.
+    pub fn expand(&self, template: &str, out: &mut String) {
+        let mut rest = template;
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                out.push('$');
+                rest = after;
+                continue;
+            }
+            let (reference, after) = if let Some(braced) = rest.strip_prefix('{') {
+                match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => {
+                        out.push('$');
+                        continue;
+                    }
+                }
+            } else {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+            if reference.is_empty() {
+                out.push('$');
+                continue;
+            }
+            let m = match reference.parse::<usize>() {
+                Ok(n) => self.get(n),
+                Err(_) => self.name(reference),
+            };
+            if let Some(m) = m {
+                out.push_str(&m.text);
+            }
+            rest = after;
+        }
+        out.push_str(rest);
+    }
 }
 
 /* ============================================================
@@ -405,10 +607,20 @@ impl Captures {
  * ============================================================
  */
 
+/// Inline flags: `(?i)`, `(?m)`, `(?s)`; scoped to the enclosing group.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Flags {
+    case_insensitive: bool,
+    multi_line: bool,
+    dot_all: bool,
+}
+
 struct Parser<'a> {
     chars: Vec<char>,
     pos: usize,
     group_id: usize,
+    names: Vec<String>,
+    flags: Flags,
     _src: &'a str,
 }
 
@@ -418,6 +630,8 @@ impl<'a> Parser<'a> {
             chars: src.chars().collect(),
             pos: 0,
             group_id: 0,
+            names: Vec::new(),
+            flags: Flags::default(),
             _src: src,
         }
     }
@@ -462,37 +676,32 @@ impl<'a> Parser<'a> {
         Ok(Regex::Sequence(parts))
     }
 
-    // repeat := atom quant?
+    // repeat := atom (quant '?'?)?
     fn parse_repeat(&mut self) -> Result<Regex, ParseError> {
         let atom = self.parse_atom()?;
-        if let Some(q) = self.peek() {
-            match q {
-                '*' => {
-                    self.pos += 1;
-                    return Ok(Regex::zero_or_more(atom));
-                }
-                '+' => {
-                    self.pos += 1;
-                    return Ok(Regex::one_or_more(atom));
-                }
-                '?' => {
-                    self.pos += 1;
-                    return Ok(Regex::optional(atom));
-                }
-                '{' => {
-                    let (min, max) = self.parse_brace_quantifier()?;
-                    validate_quantifier(min, max)?;
-                    self.pos += 1; // consume '}'
-                    return Ok(Regex::Repeat {
-                        expr: Box::new(atom),
-                        min,
-                        max,
-                    });
-                }
-                _ => {}
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let (min, max) = self.parse_brace_quantifier()?;
+                validate_quantifier(min, max)?;
+                (min, max)
             }
+            _ => return Ok(atom),
+        };
+        self.pos += 1; // consume the quantifier (or the closing '}')
+
+        let greedy = self.peek() != Some('?');
+        if !greedy {
+            self.pos += 1;
         }
-        Ok(atom)
+        Ok(Regex::Repeat {
+            expr: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
     }
 
     // atom := group | class | anchor | escaped | '.' | literal_char
@@ -506,30 +715,88 @@ impl<'a> Parser<'a> {
             '[' => self.parse_char_class(),
             '.' => {
                 self.pos += 1;
-                Ok(Regex::Wildcard)
+                Ok(if self.flags.dot_all {
+                    Regex::Wildcard
+                } else {
+                    Regex::AnyExceptNewline
+                })
             }
             '^' => {
                 self.pos += 1;
-                Ok(Regex::Anchor(AnchorKind::StartOfLine))
+                Ok(Regex::Anchor(if self.flags.multi_line {
+                    AnchorKind::MultiLineStart
+                } else {
+                    AnchorKind::StartOfLine
+                }))
             }
             '$' => {
                 self.pos += 1;
-                Ok(Regex::Anchor(AnchorKind::EndOfLine))
+                Ok(Regex::Anchor(if self.flags.multi_line {
+                    AnchorKind::MultiLineEnd
+                } else {
+                    AnchorKind::EndOfLine
+                }))
             }
             '\\' => self.parse_escape(),
             // literal
             _ => {
                 self.pos += 1;
-                Ok(Regex::Char(c))
+                Ok(self.literal(c))
             }
         }
     }
 
+    /// A literal char, expanded to both cases under `(?i)`.
+    fn literal(&self, c: char) -> Regex {
+        if self.flags.case_insensitive {
+            let variants = case_variants(c);
+            if variants.len() > 1 {
+                return Regex::char_class(vec![], variants, false);
+            }
+        }
+        Regex::Char(c)
+    }
+
+    // group := '(' ( '?:' | '?P<name>' | '?<name>' | '?flags:' )? alternation ')'
+    //        | '(?flags)'
     fn parse_group(&mut self) -> Result<Regex, ParseError> {
         // consume '('
         self.expect('(')?;
+        let outer_flags = self.flags;
+
+        let mut capture: Option<Option<String>> = Some(None);
+        if self.peek() == Some('?') {
+            self.pos += 1;
+            match self.peek() {
+                Some(':') => {
+                    self.pos += 1;
+                    capture = None;
+                }
+                Some('P') if self.peek_n(1) == Some('<') => {
+                    self.pos += 2;
+                    capture = Some(Some(self.parse_group_name()?));
+                }
+                Some('<') => {
+                    self.pos += 1;
+                    capture = Some(Some(self.parse_group_name()?));
+                }
+                _ => {
+                    self.parse_flags()?;
+                    if self.peek() == Some(')') {
+                        // (?flags) applies to the rest of the enclosing group
+                        self.pos += 1;
+                        return Ok(Regex::Empty);
+                    }
+                    self.expect(':')?;
+                    capture = None;
+                }
+            }
+        }
+
         let id = self.group_id;
-        self.group_id += 1;
+        if capture.is_some() {
+            self.group_id += 1;
+        }
 
         if self.peek().is_none() {
             return Err(ParseError::UnclosedGroup);
@@ -541,13 +808,59 @@ impl<'a> Parser<'a> {
             return Err(ParseError::UnclosedGroup);
         }
         self.pos += 1; // consume ')'
+        self.flags = outer_flags;
 
-        Ok(Regex::Group {
-            id,
-            expr: Box::new(inner),
+        Ok(match capture {
+            Some(name) => Regex::Group {
+                id,
+                name,
+                expr: Box::new(inner),
+            },
+            None => Regex::NonCapturing(Box::new(inner)),
         })
     }
 
+    fn parse_group_name(&mut self) -> Result<String, ParseError> {
+        let mut name = String::new();
+        loop {
+            match self.peek() {
+                Some('>') => break,
+                Some(c) if c.is_alphanumeric() || c == '_' => {
+                    name.push(c);
+                    self.pos += 1;
+                }
+                Some(_) => return Err(ParseError::InvalidGroupName),
+                None => return Err(ParseError::UnclosedGroup),
+            }
+        }
+        self.pos += 1; // consume '>'
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(ParseError::InvalidGroupName);
+        }
+        if self.names.contains(&name) {
+            return Err(ParseError::DuplicateGroupName(name));
+        }
+        self.names.push(name.clone());
+        Ok(name)
+    }
+
+    // flags := [ims]* ('-' [ims]*)?
+    fn parse_flags(&mut self) -> Result<(), ParseError> {
+        let mut enable = true;
+        while let Some(c) = self.peek() {
+            match c {
+                ':' | ')' => return Ok(()),
+                '-' => enable = false,
+                'i' => self.flags.case_insensitive = enable,
+                'm' => self.flags.multi_line = enable,
+                's' => self.flags.dot_all = enable,
+                other => return Err(ParseError::UnknownFlag(other)),
+            }
+            self.pos += 1;
+        }
+        Err(ParseError::UnclosedGroup)
+    }
+
     fn parse_escape(&mut self) -> Result<Regex, ParseError> {
         self.expect('\\')?;
         let Some(c) = self.peek() else {
@@ -559,6 +872,13 @@ impl<'a> Parser<'a> {
             'w' => Regex::word_char(),
             's' => Regex::whitespace(),
             'b' => Regex::Anchor(AnchorKind::WordBoundary),
+            'B' => Regex::Anchor(AnchorKind::NotWordBoundary),
+            'A' => Regex::Anchor(AnchorKind::StartOfText),
+            'z' => Regex::Anchor(AnchorKind::EndOfText),
+            'p' | 'P' => Regex::category(self.parse_category()?, c == 'P'),
+            'n' => Regex::Char('\n'),
+            't' => Regex::Char('\t'),
+            'r' => Regex::Char('\r'),
             // escape metacharacters to literal
             '\\' | '.' | '[' | ']' | '(' | ')' | '{' | '}' | '*' | '+' | '?' | '|' | '^' | '$' => {
                 Regex::Char(c)
@@ -567,6 +887,32 @@ impl<'a> Parser<'a> {
         })
     }
 
+    // category := '{' name '}' | letter   (after \p or \P)
+    fn parse_category(&mut self) -> Result<UnicodeCategory, ParseError> {
+        let name: String = match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                let mut name = String::new();
+                loop {
+                    match self.peek() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(ParseError::UnexpectedEnd),
+                    }
+                    self.pos += 1;
+                }
+                self.pos += 1; // consume '}'
+                name
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => return Err(ParseError::UnexpectedEnd),
+        };
+        UnicodeCategory::from_name(&name).ok_or(ParseError::UnknownCategory(name))
+    }
+
     fn parse_char_class(&mut self) -> Result<Regex, ParseError> {
         self.expect('[')?;
         let mut negated = false;
@@ -577,20 +923,25 @@ impl<'a> Parser<'a> {
 
         let mut ranges: Vec<(char, char)> = Vec::new();
         let mut chars: Vec<char> = Vec::new();
+        let mut categories: Vec<UnicodeCategory> = Vec::new();
 
         while let Some(c) = self.peek() {
             if c == ']' {
                 self.pos += 1;
+                if self.flags.case_insensitive {
+                    fold_case(&mut ranges, &mut chars);
+                }
                 return Ok(Regex::CharClass {
                     ranges,
                     chars,
+                    categories,
                     negated,
                 });
             }
 
             // parse an element (could be escaped)
             let start = if c == '\\' {
-                // allow \d, \w, \s inside class by expanding to ranges/chars
+                // allow \d, \w, \s, \p{..} inside class by expanding to ranges/chars
                 self.pos += 1;
                 let Some(ec) = self.peek() else {
                     return Err(ParseError::UnexpectedEnd);
@@ -610,6 +961,13 @@ impl<'a> Parser<'a> {
                         chars.extend([' ', '\t', '\n', '\r']);
                         continue;
                     }
+                    'p' => {
+                        categories.push(self.parse_category()?);
+                        continue;
+                    }
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
                     // escaped literal
                     '\\' | '-' | ']' | '^' => ec,
                     other => return Err(ParseError::UnexpectedChar(other)),
@@ -657,26 +1015,20 @@ impl<'a> Parser<'a> {
         self.expect('{')?;
 
         let min = self.parse_number()?;
-        let mut max = None;
 
-        match self.peek() {
+        let max = match self.peek() {
             Some('}') => return Ok((min, Some(min))),
             Some(',') => {
                 self.pos += 1;
                 match self.peek() {
-                    Some('}') => {
-                        max = None; // {n,}
-                    }
-                    Some(_) => {
-                        let m = self.parse_number()?;
-                        max = Some(m);
-                    }
+                    Some('}') => None, // {n,}
+                    Some(_) => Some(self.parse_number()?),
                     None => return Err(ParseError::UnexpectedEnd),
                 }
             }
             Some(c) => return Err(ParseError::UnexpectedChar(c)),
             None => return Err(ParseError::UnexpectedEnd),
-        }
+        };
 
         if self.peek() != Some('}') {
             return Err(ParseError::InvalidQuantifier);
@@ -731,8 +1083,58 @@ impl<'a> Parser<'a> {
  * ============================================================
  */
 
+/// Runs `captures_at` from each position after the previous match.
+fn collect_captures(text: &str, captures_at: impl Fn(usize) -> Option<Captures>) -> Vec<Captures> {
+    let mut out = Vec::new();
+    let mut at = 0;
+    while at <= text.len() {
+        let Some(caps) = captures_at(at) else { break };
+        let Some(m) = caps.full_match.as_ref() else { break };
+        // an empty match must still make progress
+        at = if m.end == m.start {
+            match next_char(text, m.end) {
+                Some((_, next)) => next,
+                None => text.len() + 1,
+            }
+        } else {
+            m.end
+        };
+        out.push(caps);
+    }
+    out
+}
+
+fn replace_matches(text: &str, matches: &[Captures], replacement: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in matches {
+        let Some(m) = caps.full_match.as_ref() else { continue };
+        out.push_str(&text[last..m.start]);
+        caps.expand(replacement, &mut out);
+        last = m.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+fn split_on_matches<'t>(text: &'t str, matches: &[Captures]) -> Vec<&'t str> {
+    let mut out = Vec::new();
+    let mut last = 0;
+    for caps in matches {
+        let Some(m) = caps.full_match.as_ref() else { continue };
+        if m.start == m.end && (m.start == 0 || m.start == text.len()) {
+            continue; // empty matches at the edges don't produce empty pieces
+        }
+        out.push(&text[last..m.start]);
+        last = m.end;
+    }
+    out.push(&text[last..]);
+    out
+}
+
+/// Every char boundary of `s`, including `s.len()` (where only empty matches fit).
 fn char_boundaries(s: &str) -> impl Iterator<Item = usize> + '_ {
-    std::iter::once(0).chain(s.char_indices().skip(1).map(|(i, _)| i))
+    s.char_indices().map(|(i, _)| i).chain(std::iter::once(s.len()))
 }
 
 fn next_char(s: &str, pos: usize) -> Option<(char, usize)> {
@@ -763,6 +1165,85 @@ fn is_word_boundary(text: &str, pos: usize) -> bool {
     before_is ^ after_is
 }
 
+fn anchor_matches(kind: AnchorKind, text: &str, pos: usize) -> bool {
+    match kind {
+        AnchorKind::StartOfLine | AnchorKind::StartOfText => pos == 0,
+        AnchorKind::EndOfLine | AnchorKind::EndOfText => pos == text.len(),
+        AnchorKind::MultiLineStart => pos == 0 || text[..pos].ends_with('\n'),
+        AnchorKind::MultiLineEnd => pos == text.len() || text[pos..].starts_with('\n'),
+        AnchorKind::WordBoundary => is_word_boundary(text, pos),
+        AnchorKind::NotWordBoundary => !is_word_boundary(text, pos),
+    }
+}
+
+fn class_matches(
+    ranges: &[(char, char)],
+    chars: &[char],
+    categories: &[UnicodeCategory],
+    negated: bool,
+    ch: char,
+) -> bool {
+    let matched = chars.contains(&ch)
+        || ranges.iter().any(|&(a, b)| a <= ch && ch <= b)
+        || categories.iter().any(|cat| cat.contains(ch));
+    matched != negated
+}
+
+/// `c` plus its upper/lower case forms (multi-char mappings like `ß` -> `SS` are skipped).
+fn case_variants(c: char) -> Vec<char> {
+    fn single(mut it: impl Iterator<Item = char>) -> Option<char> {
+        match (it.next(), it.next()) {
+            (Some(v), None) => Some(v),
+            _ => None,
+        }
+    }
+    let mut out = vec![c];
+    for v in [single(c.to_lowercase()), single(c.to_uppercase())].into_iter().flatten() {
+        if !out.contains(&v) {
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// Widest range expanded char-by-char under `(?i)`; larger ranges are left as-is.
+const FOLD_RANGE_LIMIT: u32 = 0x800;
+
+/// Adds the other-case counterparts of a class's members for `(?i)`.
+fn fold_case(ranges: &mut [(char, char)], chars: &mut Vec<char>) {
+    let mut extra = Vec::new();
+    for &(a, b) in ranges.iter() {
+        if (b as u32) - (a as u32) > FOLD_RANGE_LIMIT {
+            continue;
+        }
+        extra.extend((a..=b).flat_map(case_variants).filter(|v| !(a..=b).contains(v)));
+    }
+    for &c in chars.iter() {
+        extra.extend(case_variants(c));
+    }
+    for c in extra {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+}
+
+fn collect_group_names(regex: &Regex, out: &mut Vec<(String, usize)>) {
+    match regex {
+        Regex::Group { id, name, expr } => {
+            if let Some(n) = name {
+                out.push((n.clone(), *id));
+            }
+            collect_group_names(expr, out);
+        }
+        Regex::Sequence(xs) | Regex::Alternation(xs) => {
+            xs.iter().for_each(|x| collect_group_names(x, out));
+        }
+        Regex::Repeat { expr, .. } | Regex::NonCapturing(expr) => collect_group_names(expr, out),
+        _ => {}
+    }
+}
+
 pub fn validate_quantifier(min: usize, max: Option<usize>) -> Result<(), ParseError> {
     match (min, max) {
         (_, None) => Ok(()),
@@ -787,7 +1268,7 @@ pub fn count_groups(regex: &Regex) -> usize {
     match regex {
         Regex::Group { expr, .. } => 1 + count_groups(expr),
         Regex::Sequence(xs) | Regex::Alternation(xs) => xs.iter().map(count_groups).sum(),
-        Regex::Repeat { expr, .. } => count_groups(expr),
+        Regex::Repeat { expr, .. } | Regex::NonCapturing(expr) => count_groups(expr),
         _ => 0,
     }
 }
@@ -796,7 +1277,7 @@ pub fn has_alternation(regex: &Regex) -> bool {
     match regex {
         Regex::Alternation(_) => true,
         Regex::Sequence(xs) => xs.iter().any(has_alternation),
-        Regex::Group { expr, .. } => has_alternation(expr),
+        Regex::Group { expr, .. } | Regex::NonCapturing(expr) => has_alternation(expr),
         Regex::Repeat { expr, .. } => has_alternation(expr),
         _ => false,
     }
@@ -804,9 +1285,9 @@ pub fn has_alternation(regex: &Regex) -> bool {
 
 pub fn is_anchored_at_start(regex: &Regex) -> bool {
     match regex {
-        Regex::Anchor(AnchorKind::StartOfLine) => true,
-        Regex::Sequence(xs) => xs.first().is_some_and(|r| matches!(r, Regex::Anchor(AnchorKind::StartOfLine))),
-        Regex::Group { expr, .. } => is_anchored_at_start(expr),
+        Regex::Anchor(AnchorKind::StartOfLine | AnchorKind::StartOfText) => true,
+        Regex::Sequence(xs) => xs.first().is_some_and(is_anchored_at_start),
+        Regex::Group { expr, .. } | Regex::NonCapturing(expr) => is_anchored_at_start(expr),
         _ => false,
     }
 }
@@ -815,7 +1296,7 @@ pub fn is_literal_only(regex: &Regex) -> bool {
     match regex {
         Regex::Empty | Regex::Literal(_) | Regex::Char(_) => true,
         Regex::Sequence(xs) => xs.iter().all(is_literal_only),
-        Regex::Group { expr, .. } => is_literal_only(expr),
+        Regex::Group { expr, .. } | Regex::NonCapturing(expr) => is_literal_only(expr),
         _ => false,
     }
 }
@@ -894,7 +1375,12 @@ pub fn optimize(regex: Regex) -> Regex {
             }
         }
 
-        Regex::Repeat { expr, min, max } => {
+        Regex::Repeat {
+            expr,
+            min,
+            max,
+            greedy,
+        } => {
             let inner = optimize(*expr);
             match (min, max) {
                 (0, Some(0)) => Regex::Empty,
@@ -903,15 +1389,23 @@ pub fn optimize(regex: Regex) -> Regex {
                     expr: Box::new(inner),
                     min,
                     max,
+                    greedy,
                 },
             }
         }
 
-        Regex::Group { id, expr } => Regex::Group {
+        Regex::Group { id, name, expr } => Regex::Group {
             id,
+            name,
             expr: Box::new(optimize(*expr)),
         },
 
+        // a non-capturing group only matters for grouping; a single atom doesn't need it
+        Regex::NonCapturing(expr) => match optimize(*expr) {
+            inner @ (Regex::Sequence(_) | Regex::Alternation(_)) => Regex::NonCapturing(Box::new(inner)),
+            inner => inner,
+        },
+
         other => other,
     }
 }
@@ -923,8 +1417,13 @@ impl fmt::Display for Regex {
             Regex::Empty => Ok(()),
             Regex::Literal(s) => write!(f, "{s}"),
             Regex::Char(c) => write!(f, "{c}"),
-            Regex::Wildcard => write!(f, "."),
-            Regex::CharClass { ranges, chars, negated } => {
+            Regex::Wildcard => write!(f, "(?s:.)"),
+            Regex::AnyExceptNewline => write!(f, "."),
+            Regex::CharClass { ranges, chars, categories, negated } => {
+                if let ([], [], [cat]) = (ranges.as_slice(), chars.as_slice(), categories.as_slice()) {
+                    let p = if *negated { 'P' } else { 'p' };
+                    return write!(f, "\\{p}{{{}}}", cat.name());
+                }
                 write!(f, "[")?;
                 if *negated { write!(f, "^")?; }
                 for (a, b) in ranges {
@@ -933,13 +1432,16 @@ impl fmt::Display for Regex {
                 for c in chars {
                     write!(f, "{c}")?;
                 }
+                for cat in categories {
+                    write!(f, "\\p{{{}}}", cat.name())?;
+                }
                 write!(f, "]")
             }
             Regex::Sequence(xs) => {
                 for x in xs { write!(f, "{x}")?; }
                 Ok(())
             }
-            Regex::Repeat { expr, min, max } => {
+            Regex::Repeat { expr, min, max, greedy } => {
                 let need_parens = matches!(**expr, Regex::Alternation(_)) 
                     || matches!(**expr, Regex::Sequence(ref xs) if xs.len() > 1);
                 if need_parens { write!(f, "({expr})")?; } else { write!(f, "{expr}")?; }
@@ -950,7 +1452,8 @@ impl fmt::Display for Regex {
                     (m, Some(mx)) if m == mx => write!(f, "{{{m}}}"),
                     (m, Some(mx)) => write!(f, "{{{m},{mx}}}"),
                     (m, None) => write!(f, "{{{m},}}"),
-                }
+                }?;
+                if *greedy { Ok(()) } else { write!(f, "?") }
             }
             Regex::Alternation(alts) => {
                 for (i, a) in alts.iter().enumerate() {
@@ -959,16 +1462,737 @@ impl fmt::Display for Regex {
                 }
                 Ok(())
             }
-            Regex::Group { expr, .. } => write!(f, "({expr})"),
+            Regex::Group { name: None, expr, .. } => write!(f, "({expr})"),
+            Regex::Group { name: Some(n), expr, .. } => write!(f, "(?P<{n}>{expr})"),
+            Regex::NonCapturing(expr) => write!(f, "(?:{expr})"),
             Regex::Anchor(kind) => match kind {
                 StartOfLine => write!(f, "^"),
                 EndOfLine => write!(f, "$"),
+                MultiLineStart => write!(f, "(?m:^)"),
+                MultiLineEnd => write!(f, "(?m:$)"),
+                StartOfText => write!(f, "\\A"),
+                EndOfText => write!(f, "\\z"),
                 WordBoundary => write!(f, "\\b"),
+                NotWordBoundary => write!(f, "\\B"),
             },
         }
     }
 }
 
+/* ============================================================
+ * Compiled matching: Thompson NFA (Pike VM) + lazy DFA
+ * ============================================================
+ */
+
+/// Which matching engine a `Matcher` uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// The recursive backtracker (`Regex::match_from`). Exponential worst case.
+    Backtrack,
+    /// Pike VM only: O(n·m) for every query, including captures.
+    PikeVm,
+    /// Lazy DFA for `is_match`/`find`, Pike VM for captures and `\b`.
+    #[default]
+    Automaton,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Inst {
+    Char(char),
+    Any,
+    AnyExceptNewline,
+    Class {
+        ranges: Vec<(char, char)>,
+        chars: Vec<char>,
+        categories: Vec<UnicodeCategory>,
+        negated: bool,
+    },
+    Assert(AnchorKind),
+    Save(usize),
+    Split(usize, usize), // prefer first, then second
+    Jmp(usize),
+    Match,
+}
+
+impl Inst {
+    /// For consuming instructions: does `ch` get through?
+    fn accepts(&self, ch: char) -> bool {
+        match self {
+            Inst::Char(c) => *c == ch,
+            Inst::Any => true,
+            Inst::AnyExceptNewline => ch != '\n',
+            Inst::Class {
+                ranges,
+                chars,
+                categories,
+                negated,
+            } => class_matches(ranges, chars, categories, *negated, ch),
+            _ => false,
+        }
+    }
+}
+
+/// A regex compiled to Thompson NFA instructions.
+///
+/// Slots `0`/`1` hold the overall match; group `id` uses `2 + 2*id` and `3 + 2*id`.
+#[derive(Debug, Clone)]
+pub struct Program {
+    insts: Vec<Inst>,
+    slots: usize,
+}
+
+impl Program {
+    pub fn compile(regex: &Regex) -> Self {
+        let mut c = Compiler { insts: Vec::new() };
+        c.emit(Inst::Save(0));
+        c.compile(regex);
+        c.emit(Inst::Save(1));
+        c.emit(Inst::Match);
+        Program {
+            insts: c.insts,
+            slots: 2 + 2 * count_groups(regex),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.insts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insts.is_empty()
+    }
+
+    /// The lazy DFA only tracks text and line edges, not word chars.
+    fn dfa_supported(&self) -> bool {
+        !self.insts.iter().any(|i| {
+            matches!(
+                i,
+                Inst::Assert(AnchorKind::WordBoundary | AnchorKind::NotWordBoundary)
+            )
+        })
+    }
+}
+
+struct Compiler {
+    insts: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> usize {
+        self.insts.push(inst);
+        self.insts.len() - 1
+    }
+
+    /// `prefer_first = false` swaps the branch priority (lazy quantifiers).
+    fn patch_split(&mut self, at: usize, first: usize, second: usize, prefer_first: bool) {
+        self.insts[at] = if prefer_first {
+            Inst::Split(first, second)
+        } else {
+            Inst::Split(second, first)
+        };
+    }
+
+    fn compile(&mut self, regex: &Regex) {
+        match regex {
+            Regex::Empty => {}
+            Regex::Literal(s) => {
+                for c in s.chars() {
+                    self.emit(Inst::Char(c));
+                }
+            }
+            Regex::Char(c) => {
+                self.emit(Inst::Char(*c));
+            }
+            Regex::Wildcard => {
+                self.emit(Inst::Any);
+            }
+            Regex::AnyExceptNewline => {
+                self.emit(Inst::AnyExceptNewline);
+            }
+            Regex::CharClass {
+                ranges,
+                chars,
+                categories,
+                negated,
+            } => {
+                self.emit(Inst::Class {
+                    ranges: ranges.clone(),
+                    chars: chars.clone(),
+                    categories: categories.clone(),
+                    negated: *negated,
+                });
+            }
+            Regex::Anchor(kind) => {
+                self.emit(Inst::Assert(*kind));
+            }
+            Regex::Sequence(xs) => {
+                for x in xs {
+                    self.compile(x);
+                }
+            }
+            Regex::Alternation(alts) => {
+                let mut exits = Vec::new();
+                for (i, alt) in alts.iter().enumerate() {
+                    if i + 1 == alts.len() {
+                        self.compile(alt);
+                        break;
+                    }
+                    let split = self.emit(Inst::Split(0, 0));
+                    self.compile(alt);
+                    exits.push(self.emit(Inst::Jmp(0)));
+                    let next = self.insts.len();
+                    self.patch_split(split, split + 1, next, true);
+                }
+                let end = self.insts.len();
+                for j in exits {
+                    self.insts[j] = Inst::Jmp(end);
+                }
+            }
+            Regex::Group { id, expr, .. } => {
+                self.emit(Inst::Save(2 + 2 * id));
+                self.compile(expr);
+                self.emit(Inst::Save(3 + 2 * id));
+            }
+            Regex::NonCapturing(expr) => self.compile(expr),
+            Regex::Repeat {
+                expr,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.compile(expr);
+                }
+                match *max {
+                    None => {
+                        let split = self.emit(Inst::Split(0, 0));
+                        self.compile(expr);
+                        self.emit(Inst::Jmp(split));
+                        let end = self.insts.len();
+                        self.patch_split(split, split + 1, end, *greedy);
+                    }
+                    Some(mx) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..mx {
+                            splits.push(self.emit(Inst::Split(0, 0)));
+                            self.compile(expr);
+                        }
+                        let end = self.insts.len();
+                        for s in splits {
+                            self.patch_split(s, s + 1, end, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reverses a regex so it matches the mirrored text (used to recover match starts).
+/// Anchors keep their absolute meaning: `^` is still "position 0".
+fn reverse_regex(regex: &Regex) -> Regex {
+    match regex {
+        Regex::Literal(s) => Regex::Literal(s.chars().rev().collect()),
+        Regex::Sequence(xs) => Regex::Sequence(xs.iter().rev().map(reverse_regex).collect()),
+        Regex::Alternation(alts) => Regex::Alternation(alts.iter().map(reverse_regex).collect()),
+        Regex::Group { id, name, expr } => Regex::Group {
+            id: *id,
+            name: name.clone(),
+            expr: Box::new(reverse_regex(expr)),
+        },
+        Regex::NonCapturing(expr) => Regex::non_capturing(reverse_regex(expr)),
+        Regex::Repeat {
+            expr,
+            min,
+            max,
+            greedy,
+        } => Regex::Repeat {
+            expr: Box::new(reverse_regex(expr)),
+            min: *min,
+            max: *max,
+            greedy: *greedy,
+        },
+        other => other.clone(),
+    }
+}
+
+/// Literal text every match must start with, if any (fed by `optimize`'s
+/// joining of adjacent chars into `Literal` chunks).
+pub fn literal_prefix(regex: &Regex) -> Option<String> {
+    match regex {
+        Regex::Literal(s) if !s.is_empty() => Some(s.clone()),
+        Regex::Char(c) => Some(c.to_string()),
+        Regex::Sequence(xs) => xs.first().and_then(literal_prefix),
+        Regex::Group { expr, .. } | Regex::NonCapturing(expr) => literal_prefix(expr),
+        Regex::Repeat { expr, min, .. } if *min > 0 => literal_prefix(expr),
+        _ => None,
+    }
+}
+
+/* ---------- Pike VM ---------- */
+
+type Slots = Vec<Option<usize>>;
+
+struct ThreadList {
+    threads: Vec<(usize, Slots)>,
+    seen: Vec<bool>,
+}
+
+impl ThreadList {
+    fn new(size: usize) -> Self {
+        Self {
+            threads: Vec::new(),
+            seen: vec![false; size],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.threads.clear();
+        self.seen.iter_mut().for_each(|s| *s = false);
+    }
+}
+
+struct PikeVm<'p> {
+    prog: &'p Program,
+}
+
+impl<'p> PikeVm<'p> {
+    /// Follows epsilon edges from `pc`, appending threads in priority order.
+    fn add_thread(&self, list: &mut ThreadList, pc: usize, text: &str, pos: usize, slots: Slots) {
+        if list.seen[pc] {
+            return;
+        }
+        list.seen[pc] = true;
+        match &self.prog.insts[pc] {
+            Inst::Jmp(target) => self.add_thread(list, *target, text, pos, slots),
+            Inst::Split(a, b) => {
+                self.add_thread(list, *a, text, pos, slots.clone());
+                self.add_thread(list, *b, text, pos, slots);
+            }
+            Inst::Save(n) => {
+                let mut slots = slots;
+                if *n < slots.len() {
+                    slots[*n] = Some(pos);
+                }
+                self.add_thread(list, pc + 1, text, pos, slots);
+            }
+            Inst::Assert(kind) => {
+                if anchor_matches(*kind, text, pos) {
+                    self.add_thread(list, pc + 1, text, pos, slots);
+                }
+            }
+            _ => list.threads.push((pc, slots)),
+        }
+    }
+
+    /// Leftmost-first search; returns the capture slots of the winning thread.
+    fn search(&self, text: &str, start: usize, anchored: bool, prefix: Option<&str>) -> Option<Slots> {
+        let n = self.prog.insts.len();
+        let mut clist = ThreadList::new(n);
+        let mut nlist = ThreadList::new(n);
+        let mut matched: Option<Slots> = None;
+        let mut pos = start;
+
+        loop {
+            if matched.is_none() && (!anchored || pos == start) {
+                if clist.threads.is_empty() {
+                    if let Some(p) = prefix {
+                        match text[pos..].find(p) {
+                            Some(skip) => pos += skip,
+                            None => break,
+                        }
+                    }
+                }
+                self.add_thread(&mut clist, 0, text, pos, vec![None; self.prog.slots]);
+            }
+            // no threads left and none will be injected later
+            if clist.threads.is_empty() && (matched.is_some() || anchored) {
+                break;
+            }
+
+            let step = next_char(text, pos);
+            for (pc, slots) in std::mem::take(&mut clist.threads) {
+                match &self.prog.insts[pc] {
+                    Inst::Match => {
+                        matched = Some(slots);
+                        break; // cut lower-priority threads
+                    }
+                    inst => {
+                        if let Some((ch, next)) = step {
+                            if inst.accepts(ch) {
+                                self.add_thread(&mut nlist, pc + 1, text, next, slots);
+                            }
+                        }
+                    }
+                }
+            }
+
+            let Some((_, next)) = step else { break };
+            pos = next;
+            std::mem::swap(&mut clist, &mut nlist);
+            nlist.clear();
+        }
+        matched
+    }
+}
+
+/* ---------- Lazy DFA ---------- */
+
+/// What the DFA knows about a position: text edges and adjacent newlines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Look {
+    at_start: bool,
+    at_end: bool,
+    after_newline: bool,
+    before_newline: bool,
+}
+
+impl Look {
+    fn at(text: &str, pos: usize) -> Self {
+        Look {
+            at_start: pos == 0,
+            at_end: pos == text.len(),
+            after_newline: text[..pos].ends_with('\n'),
+            before_newline: text[pos..].starts_with('\n'),
+        }
+    }
+
+    fn allows(self, kind: AnchorKind) -> bool {
+        match kind {
+            AnchorKind::StartOfLine | AnchorKind::StartOfText => self.at_start,
+            AnchorKind::EndOfLine | AnchorKind::EndOfText => self.at_end,
+            AnchorKind::MultiLineStart => self.at_start || self.after_newline,
+            AnchorKind::MultiLineEnd => self.at_end || self.before_newline,
+            AnchorKind::WordBoundary | AnchorKind::NotWordBoundary => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DfaState {
+    pcs: Vec<usize>, // NFA threads in priority order
+    is_match: bool,
+}
+
+/// Cap on cached states; the cache is flushed when it is reached.
+const DFA_STATE_LIMIT: usize = 4096;
+
+/// A DFA built on demand from NFA state sets and cached between searches.
+///
+/// With `leftmost_first` the closure stops at `Match` (like the Pike VM's cut);
+/// without it every thread is kept, which gives longest-match semantics.
+#[derive(Debug)]
+struct LazyDfa {
+    prog: Program,
+    leftmost_first: bool,
+    states: Vec<DfaState>,
+    index: HashMap<Vec<usize>, usize>,
+    starts: HashMap<Look, usize>,
+    transitions: HashMap<(usize, char, Look, bool), usize>,
+    flushes: usize,
+}
+
+impl LazyDfa {
+    fn new(prog: Program, leftmost_first: bool) -> Self {
+        Self {
+            prog,
+            leftmost_first,
+            states: Vec::new(),
+            index: HashMap::new(),
+            starts: HashMap::new(),
+            transitions: HashMap::new(),
+            flushes: 0,
+        }
+    }
+
+    fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    fn closure(&self, seeds: &[usize], look: Look) -> DfaState {
+        let mut seen = vec![false; self.prog.insts.len()];
+        let mut pcs = Vec::new();
+        let mut stack = Vec::new();
+        for &seed in seeds {
+            stack.push(seed);
+            while let Some(pc) = stack.pop() {
+                if seen[pc] {
+                    continue;
+                }
+                seen[pc] = true;
+                match &self.prog.insts[pc] {
+                    Inst::Jmp(target) => stack.push(*target),
+                    Inst::Split(a, b) => {
+                        stack.push(*b);
+                        stack.push(*a);
+                    }
+                    Inst::Save(_) => stack.push(pc + 1),
+                    Inst::Assert(kind) => {
+                        if look.allows(*kind) {
+                            stack.push(pc + 1);
+                        }
+                    }
+                    Inst::Match => {
+                        pcs.push(pc);
+                        if self.leftmost_first {
+                            return DfaState { pcs, is_match: true };
+                        }
+                    }
+                    _ => pcs.push(pc),
+                }
+            }
+        }
+        let is_match = pcs.iter().any(|&pc| self.prog.insts[pc] == Inst::Match);
+        DfaState { pcs, is_match }
+    }
+
+    fn intern(&mut self, state: DfaState) -> usize {
+        if let Some(&id) = self.index.get(&state.pcs) {
+            return id;
+        }
+        if self.states.len() >= DFA_STATE_LIMIT {
+            self.states.clear();
+            self.index.clear();
+            self.starts.clear();
+            self.transitions.clear();
+            self.flushes += 1;
+        }
+        self.index.insert(state.pcs.clone(), self.states.len());
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    fn start(&mut self, look: Look) -> usize {
+        if let Some(&id) = self.starts.get(&look) {
+            return id;
+        }
+        let state = self.closure(&[0], look);
+        let id = self.intern(state);
+        self.starts.insert(look, id);
+        id
+    }
+
+    /// `restart` re-seeds the start state at the new position (unanchored search).
+    fn next(&mut self, from: usize, ch: char, look: Look, restart: bool) -> usize {
+        let key = (from, ch, look, restart);
+        if let Some(&id) = self.transitions.get(&key) {
+            return id;
+        }
+        let mut seeds: Vec<usize> = self.states[from]
+            .pcs
+            .iter()
+            .filter(|&&pc| self.prog.insts[pc].accepts(ch))
+            .map(|&pc| pc + 1)
+            .collect();
+        if restart {
+            seeds.push(0);
+        }
+        let flushes = self.flushes;
+        let state = self.closure(&seeds, look);
+        let id = self.intern(state);
+        // A flush inside `intern` leaves `from` stale, so don't cache that edge.
+        if self.flushes == flushes {
+            self.transitions.insert(key, id);
+        }
+        id
+    }
+
+    /// Forward scan: end of the leftmost-first match, or of the first match seen if `earliest`.
+    fn find_end(&mut self, text: &str, anchored: bool, prefix: Option<&str>, earliest: bool) -> Option<usize> {
+        let mut pos = 0;
+        let mut state = self.start(Look::at(text, 0));
+        let mut last = self.states[state].is_match.then_some(0);
+        let mut restart = !anchored && last.is_none();
+
+        while last.is_none() || !earliest {
+            if !restart && self.states[state].pcs.is_empty() {
+                break;
+            }
+            // Back in the start state nothing is in progress, so the next
+            // match can only begin where the literal prefix occurs.
+            let idle = self.states[state].pcs.is_empty()
+                || self.starts.get(&Look::at(text, pos)) == Some(&state);
+            if let (true, true, Some(p)) = (restart, idle, prefix) {
+                match text[pos..].find(p) {
+                    Some(skip) if skip > 0 => {
+                        pos += skip;
+                        state = self.start(Look::at(text, pos));
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            let Some((ch, next)) = next_char(text, pos) else { break };
+            state = self.next(state, ch, Look::at(text, next), restart);
+            pos = next;
+            if self.states[state].is_match {
+                last = Some(pos);
+                restart = false;
+            }
+        }
+        last
+    }
+
+    /// Backward scan from `end` over the reversed program: furthest-back start.
+    fn find_start(&mut self, text: &str, end: usize) -> Option<usize> {
+        let mut pos = end;
+        let mut state = self.start(Look::at(text, end));
+        let mut last = self.states[state].is_match.then_some(end);
+
+        while !self.states[state].pcs.is_empty() {
+            let Some(ch) = text[..pos].chars().next_back() else { break };
+            pos -= ch.len_utf8();
+            state = self.next(state, ch, Look::at(text, pos), false);
+            if self.states[state].is_match {
+                last = Some(pos);
+            }
+        }
+        last
+    }
+}
+
+/* ---------- Matcher: engine selection ---------- */
+
+/// A compiled regex with a selectable engine.
+#[derive(Debug)]
+pub struct Matcher {
+    regex: Regex,
+    engine: Engine,
+    program: Program,
+    prefix: Option<String>,
+    anchored: bool,
+    dfa: Option<RefCell<(LazyDfa, LazyDfa)>>, // (forward, reverse)
+}
+
+impl Matcher {
+    pub fn new(regex: Regex, engine: Engine) -> Self {
+        let regex = regex.optimize();
+        let program = Program::compile(&regex);
+        let dfa = program.dfa_supported().then(|| {
+            let reverse = Program::compile(&reverse_regex(&regex));
+            RefCell::new((LazyDfa::new(program.clone(), true), LazyDfa::new(reverse, false)))
+        });
+        Self {
+            prefix: literal_prefix(&regex),
+            anchored: is_anchored_at_start(&regex),
+            regex,
+            engine,
+            program,
+            dfa,
+        }
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    /// Number of DFA states currently cached (0 if the DFA is unused).
+    pub fn dfa_states(&self) -> usize {
+        self.dfa.as_ref().map_or(0, |d| d.borrow().0.state_count())
+    }
+
+    fn use_dfa(&self) -> Option<&RefCell<(LazyDfa, LazyDfa)>> {
+        match self.engine {
+            Engine::Automaton => self.dfa.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn pike(&self, text: &str, start: usize) -> Option<Slots> {
+        PikeVm { prog: &self.program }.search(text, start, self.anchored, self.prefix.as_deref())
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        if self.engine == Engine::Backtrack {
+            return self.regex.is_match(text);
+        }
+        match self.use_dfa() {
+            Some(dfa) => dfa
+                .borrow_mut()
+                .0
+                .find_end(text, self.anchored, self.prefix.as_deref(), true)
+                .is_some(),
+            None => self.pike(text, 0).is_some(),
+        }
+    }
+
+    /// Returns (start, len) of the leftmost-first match.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        if self.engine == Engine::Backtrack {
+            return self.regex.find(text);
+        }
+        match self.use_dfa() {
+            Some(dfa) => {
+                let (forward, reverse) = &mut *dfa.borrow_mut();
+                let end = forward.find_end(text, self.anchored, self.prefix.as_deref(), false)?;
+                let start = reverse.find_start(text, end)?;
+                Some((start, end - start))
+            }
+            None => {
+                let slots = self.pike(text, 0)?;
+                let (start, end) = (slots[0]?, slots[1]?);
+                Some((start, end - start))
+            }
+        }
+    }
+
+    /// Captures always come from the Pike VM (or the backtracker, if selected).
+    pub fn captures(&self, text: &str) -> Option<Captures> {
+        self.captures_at(text, 0)
+    }
+
+    /// Captures of every non-overlapping match, left to right.
+    pub fn captures_iter(&self, text: &str) -> Vec<Captures> {
+        collect_captures(text, |at| self.captures_at(text, at))
+    }
+
+    /// Replaces every match with `replacement`; see `Regex::replace_all`.
+    pub fn replace_all(&self, text: &str, replacement: &str) -> String {
+        replace_matches(text, &self.captures_iter(text), replacement)
+    }
+
+    /// Splits `text` on every match of the regex.
+    pub fn split<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        split_on_matches(text, &self.captures_iter(text))
+    }
+
+    fn captures_at(&self, text: &str, at: usize) -> Option<Captures> {
+        if self.engine == Engine::Backtrack {
+            return self.regex.captures_at(text, at);
+        }
+        if self.anchored && at > 0 {
+            return None;
+        }
+        let slots = self.pike(text, at)?;
+        let span = |i: usize| match (slots[2 * i], slots[2 * i + 1]) {
+            (Some(start), Some(end)) => Some(Match {
+                start,
+                end,
+                text: text[start..end].to_string(),
+            }),
+            _ => None,
+        };
+        let mut caps = Captures::new(self.program.slots / 2 - 1).with_names(self.regex.group_names());
+        caps.full_match = span(0);
+        for (id, group) in caps.groups.iter_mut().enumerate() {
+            *group = span(id + 1);
+        }
+        Some(caps)
+    }
+}
+
 /* ============================================================
  * Demo (cargo run)
  * ============================================================
@@ -987,6 +2211,16 @@ fn main() {
     } else {
         println!("No match");
     }
+
+    let nested = Regex::parse("(a*)*b").unwrap().compile();
+    let haystack = "a".repeat(10_000);
+    println!(
+        "(a*)*b on {} a's: {} ({} NFA insts, {} DFA states)",
+        haystack.len(),
+        nested.is_match(&haystack),
+        nested.program().len(),
+        nested.dfa_states()
+    );
 }
 
 /* ============================================================
@@ -1135,4 +2369,165 @@ mod tests {
         assert!(is_literal_only(&lit));
         assert!(!is_literal_only(&re));
     }
+
+    #[test]
+    fn milestone6_pike_vm_is_linear_on_nested_stars() {
+        let m = Regex::parse("(a*)*b").unwrap().compile();
+        let text = "a".repeat(5_000);
+        assert!(!m.is_match(&text));
+        assert_eq!(m.find(&format!("{text}b")), Some((0, 5_001)));
+
+        let pike = Matcher::new(Regex::parse("(a*)*b").unwrap(), Engine::PikeVm);
+        assert!(!pike.is_match(&text));
+        let caps = pike.captures("xaab").unwrap();
+        assert_eq!(caps.full_match.unwrap().text, "aab");
+    }
+
+    #[test]
+    fn milestone6_captures_and_prefilter() {
+        let m = Regex::parse(r"(\w+)@(\w+)\.com").unwrap().compile();
+        let caps = m.captures("mail bob@example.com now").unwrap();
+        assert_eq!(caps.full_match.as_ref().unwrap().text, "bob@example.com");
+        assert_eq!(caps.groups[0].as_ref().unwrap().text, "bob");
+        assert_eq!(caps.groups[1].as_ref().unwrap().text, "example");
+
+        let m2 = Regex::parse(r"error: (\d+)").unwrap().compile();
+        assert_eq!(m2.prefix(), Some("error: "));
+        assert_eq!(m2.find("ok ok error: 42"), Some((6, 9)));
+        assert_eq!(m2.find("error 42"), None);
+        assert!(m2.dfa_states() > 0);
+        assert_eq!(literal_prefix(&Regex::parse("[ab]c").unwrap()), None);
+    }
+
+    #[test]
+    fn milestone7_non_capturing_named_groups_and_lazy_quantifiers() {
+        let re = Regex::parse(r"(?:ab)+(?P<num>\d+)").unwrap();
+        assert_eq!(count_groups(&re), 1);
+        let caps = re.captures("x abab42").unwrap();
+        assert_eq!(caps.full_match.as_ref().unwrap().text, "abab42");
+        assert_eq!(caps.name("num").unwrap().text, "42");
+        assert_eq!(caps.get(1), caps.name("num"));
+        assert!(caps.name("missing").is_none());
+
+        let lazy = Regex::parse("<.+?>").unwrap();
+        assert_eq!(lazy.find("<a><b>"), Some((0, 3)));
+        let greedy = Regex::parse("<.+>").unwrap();
+        assert_eq!(greedy.find("<a><b>"), Some((0, 6)));
+        assert_eq!(Regex::parse("a{2,}?").unwrap().find("aaaa"), Some((0, 2)));
+        assert_eq!(Regex::parse("ab??").unwrap().find("ab"), Some((0, 1)));
+        assert_eq!(lazy.to_string(), "<.+?>");
+
+        assert_eq!(
+            Regex::parse("(?P<a>x)(?P<a>y)"),
+            Err(ParseError::DuplicateGroupName("a".into()))
+        );
+        assert_eq!(Regex::parse("(?P<1x>y)"), Err(ParseError::InvalidGroupName));
+    }
+
+    #[test]
+    fn milestone7_flags_anchors_and_unicode_categories() {
+        assert!(Regex::parse("(?i)hello").unwrap().is_match("say HeLLo"));
+        let scoped = Regex::parse("(?i:a)b").unwrap();
+        assert!(scoped.is_match("Ab"));
+        assert!(!scoped.is_match("AB"));
+        assert!(Regex::parse("(?i)[a-c]+").unwrap().is_match("BCA"));
+
+        assert!(!Regex::parse("a.c").unwrap().is_match("a\nc"));
+        assert!(Regex::parse("(?s)a.c").unwrap().is_match("a\nc"));
+
+        let lines = Regex::parse("(?m)^b$").unwrap();
+        assert_eq!(lines.find("a\nb\nc"), Some((2, 1)));
+        assert!(!Regex::parse("^b$").unwrap().is_match("a\nb\nc"));
+        assert!(Regex::parse(r"\Aab").unwrap().is_match("abc"));
+        assert!(!Regex::parse(r"\Aab").unwrap().is_match("cab"));
+        assert!(Regex::parse(r"b\z").unwrap().is_match("ab"));
+        assert_eq!(Regex::parse(r"\Bor\B").unwrap().find("or word"), Some((4, 2)));
+
+        let letters = Regex::parse(r"\p{L}+").unwrap();
+        assert_eq!(letters.find("123 héllo"), Some((4, 6)));
+        let title = Regex::parse(r"\p{Lu}\p{Ll}+").unwrap();
+        assert!(title.is_match("Élan"));
+        assert!(!title.is_match("élan"));
+        assert!(Regex::parse(r"^[\p{N}_]+$").unwrap().is_match("12_٣"));
+        assert!(Regex::parse(r"\P{L}").unwrap().is_match("abc!"));
+
+        assert_eq!(Regex::parse("(?x)a"), Err(ParseError::UnknownFlag('x')));
+        assert_eq!(
+            Regex::parse(r"\p{Klingon}"),
+            Err(ParseError::UnknownCategory("Klingon".into()))
+        );
+    }
+
+    #[test]
+    fn milestone7_replace_all_and_split() {
+        let email = Regex::parse(r"(?P<user>\w+)@(?P<domain>\w+)\.com").unwrap();
+        assert_eq!(
+            email.replace_all("mail bob@example.com or amy@test.com", "<${user} at $domain>"),
+            "mail <bob at example> or <amy at test>"
+        );
+        assert_eq!(email.replace_all("bob@x.com", "$1 ($0) $$5"), "bob (bob@x.com) $5");
+
+        // a typical redaction rule: mask digits of card-like numbers
+        let card = Regex::parse(r"(?i)card:\s*\d{4}(?:-?\d{4}){2}-?(?P<last>\d{4})").unwrap();
+        assert_eq!(
+            card.replace_all("CARD: 1234-5678-9012-3456 ok", "card: ****$last"),
+            "card: ****3456 ok"
+        );
+
+        let sep = Regex::parse(r"\s*,\s*").unwrap();
+        assert_eq!(sep.split("a, b ,c,,d"), vec!["a", "b", "c", "", "d"]);
+        assert_eq!(sep.split(""), vec![""]);
+        for engine in [Engine::Backtrack, Engine::PikeVm, Engine::Automaton] {
+            let m = Matcher::new(card.clone(), engine);
+            assert_eq!(m.replace_all("CARD: 1234-5678-9012-3456 ok", "card: ****$last"), "card: ****3456 ok");
+            assert_eq!(Matcher::new(sep.clone(), engine).split("a, b ,c,,d"), vec!["a", "b", "c", "", "d"]);
+        }
+        assert_eq!(Regex::parse("x*").unwrap().split("abc"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn dfa_prefilter_skips_to_literal_prefix() {
+        let m = Matcher::new(Regex::parse(r"error: \d+").unwrap(), Engine::Automaton);
+        assert_eq!(m.find("zzzz error: 42"), Some((5, 9)));
+        assert_eq!(m.find("error: error: 7"), Some((7, 8)));
+        let dfa = m.dfa.as_ref().unwrap().borrow();
+        assert!(dfa.0.transitions.keys().all(|&(_, ch, _, _)| ch != 'z'));
+    }
+
+    #[test]
+    fn differential_backtracker_vs_automata() {
+        let patterns = [
+            "abc", "a.c", "a*ab", "a{2,4}b", "[a-c]+d?", "[^a-z]+", r"\d{3}-\d{4}",
+            "cat|dog|bird", "(a+)(b+)", "(ab|cd)*e", "^hello", "bye$", "^$", "x*",
+            r"\bword\b", r"\s+\w", "(a|b)*abb", "colou?r", "h(e|a)llo+", "é+",
+            "<.+?>", "(?:ab)+c?", "(?i)hello", "a*$", "error: x", "(?m)^b$", "a.c", "(?s)a.c", r"\Bor", r"\Aab",
+            r"b\z", r"\p{L}+", "a+?b", "(?P<x>a|b)+?c", r"(?i)[a-c]+\P{N}",
+        ];
+        let texts = [
+            "", "abc", "aaab", "xaaaabz", "abcd cccd", "HELLO 123", "555-1234",
+            "hotdog", "aaabbb", "ababcde", "hello world", "say hello", "goodbye",
+            "a word here", "swords", "  x", "babaabb", "color colour", "haaallooo", "café éé",
+            "<a><b>", "a\nb\nc", "HeLLo", "a\nc", "word for", "abab", "ÉTÉ été",
+            "b", "errerror: x", "error: error: x",
+        ];
+        let engines = [Engine::PikeVm, Engine::Automaton];
+        for pat in patterns {
+            let re = Regex::parse(pat).unwrap();
+            let compiled = engines.map(|e| Matcher::new(re.clone(), e));
+            for text in texts {
+                let expected = (re.is_match(text), re.find(text));
+                for m in &compiled {
+                    let got = (m.is_match(text), m.find(text));
+                    assert_eq!(got, expected, "{pat:?} on {text:?} with {:?}", m.engine());
+                }
+                let caps = re.captures(text).map(|c| (c.full_match, c.groups));
+                let vm = compiled[0].captures(text).map(|c| (c.full_match, c.groups));
+                assert_eq!(vm, caps, "captures of {pat:?} on {text:?}");
+                for m in &compiled {
+                    assert_eq!(m.split(text), re.split(text), "split {pat:?} on {text:?}");
+                    assert_eq!(m.replace_all(text, "<$0>"), re.replace_all(text, "<$0>"), "replace {pat:?} on {text:?}");
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
 use std::hash::Hash;
-use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -20,6 +20,7 @@ pub enum ParseError {
     UnterminatedQuote,
     InvalidEscape,
     EmptyLine,
+    InvalidUtf8,
 }
 
 impl CsvRecord {
@@ -102,26 +103,32 @@ impl CsvRecord {
 // Milestone 2: Streaming iterator over CSV files
 // =============================================================================
 
+/// Line-oriented view over a CSV file: no header row, every record yielded
+/// as-is. Backed by [`CsvReader`], so quoted fields may span lines.
 pub struct CsvFileIterator {
-    reader: BufReader<File>,
-    delimiter: char,
-    line_number: usize,
+    reader: CsvReader<File>,
 }
 
 #[derive(Debug)]
 pub enum Error {
     Io(std::io::Error),
+    /// Parse failure, with the line the offending record started on.
     Parse(ParseError, usize),
+    /// Typed-record conversion failure, with the record's starting line.
+    Conversion(ConversionError, usize),
+    /// Malformed NDJSON input, with the offending line.
+    Json(String, usize),
+    /// Malformed or truncated columnar file.
+    Columnar(String),
 }
 
 impl CsvFileIterator {
     pub fn new(path: &Path, delimiter: char) -> Result<Self, std::io::Error> {
-        let file = File::open(path)?;
-        Ok(Self {
-            reader: BufReader::new(file),
-            delimiter,
-            line_number: 0,
-        })
+        let reader = CsvReaderBuilder::new()
+            .delimiter(ascii_byte(delimiter)?)
+            .has_headers(false)
+            .open(path)?;
+        Ok(Self { reader })
     }
 }
 
@@ -129,29 +136,19 @@ impl Iterator for CsvFileIterator {
     type Item = Result<CsvRecord, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut line = String::new();
-
-        loop {
-            line.clear();
-            match self.reader.read_line(&mut line) {
-                Ok(0) => return None,
-                Ok(_) => {
-                    self.line_number += 1;
-                    match CsvRecord::parse_csv_line(
-                        line.trim_end_matches(|c| c == '\n' || c == '\r'),
-                        self.delimiter,
-                    ) {
-                        Ok(record) => return Some(Ok(record)),
-                        Err(ParseError::EmptyLine) => continue,
-                        Err(err) => return Some(Err(Error::Parse(err, self.line_number))),
-                    }
-                }
-                Err(err) => return Some(Err(Error::Io(err))),
-            }
-        }
+        self.reader.next()
     }
 }
 
+fn ascii_byte(c: char) -> io::Result<u8> {
+    u8::try_from(c).ok().filter(u8::is_ascii).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("delimiter {:?} is not a single ASCII byte", c),
+        )
+    })
+}
+
 // =============================================================================
 // Milestone 3: Type-safe column extraction
 // =============================================================================
@@ -166,6 +163,7 @@ pub enum ConversionError {
     ParseFloat(std::num::ParseFloatError),
     InvalidValue(String),
     MissingField,
+    UnknownColumn(String),
 }
 
 impl FromCsvField for String {
@@ -202,6 +200,17 @@ impl FromCsvField for bool {
     }
 }
 
+/// Empty fields become `None`; anything else must parse as `T`.
+impl<T: FromCsvField> FromCsvField for Option<T> {
+    fn from_csv_field(field: &str) -> Result<Self, ConversionError> {
+        if field.trim().is_empty() {
+            Ok(None)
+        } else {
+            T::from_csv_field(field).map(Some)
+        }
+    }
+}
+
 impl CsvRecord {
     pub fn get_typed<T: FromCsvField>(&self, index: usize) -> Result<T, ConversionError> {
         let field = self.get_field(index).ok_or(ConversionError::MissingField)?;
@@ -464,7 +473,16 @@ impl<K: Eq + Hash + Clone> GroupedAggregator<K> {
 // Milestone 6: Parallel CSV processing
 // =============================================================================
 
-fn find_chunk_boundaries(file: &mut File, num_chunks: usize) -> io::Result<Vec<u64>> {
+/// Splits the file at record boundaries, never inside a quoted field.
+///
+/// Whether a newline ends a record depends on every quote before it, so the
+/// boundaries come from one sequential pass. The pass only tracks quote
+/// parity, which is far cheaper than the full parse the workers do.
+fn find_chunk_boundaries(
+    file: &mut File,
+    num_chunks: usize,
+    config: &CsvReaderBuilder,
+) -> io::Result<Vec<u64>> {
     let file_size = file.metadata()?.len();
     let num_chunks = num_chunks.max(1);
     if file_size == 0 {
@@ -475,41 +493,36 @@ fn find_chunk_boundaries(file: &mut File, num_chunks: usize) -> io::Result<Vec<u
     let mut boundaries = vec![0];
     let mut next = chunk_size;
 
-    while next < file_size {
-        file.seek(SeekFrom::Start(next))?;
-        let mut buf = [0u8; 1];
-        let mut pos = next;
-        loop {
-            match file.read(&mut buf) {
-                Ok(0) => {
-                    pos = file_size;
-                    break;
-                }
-                Ok(1) => {
-                    pos += 1;
-                    if buf[0] == b'\n' {
-                        break;
-                    }
-                }
-                Ok(_) => unreachable!(),
-                Err(err) => return Err(err),
-            }
-            if pos >= file_size {
-                pos = file_size;
-                break;
-            }
-        }
-        if pos >= file_size {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let mut pos = 0u64;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
             break;
         }
-        boundaries.push(pos);
-        next = pos + chunk_size;
-    }
-
-    if *boundaries.last().unwrap() != file_size {
-        boundaries.push(file_size);
+        for &byte in buf {
+            pos += 1;
+            if escaped {
+                escaped = false;
+            } else if in_quotes && config.escape == Some(byte) && byte != config.quote {
+                escaped = true;
+            } else if byte == config.quote {
+                // a doubled quote toggles twice, which leaves us inside the field
+                in_quotes = !in_quotes;
+            } else if byte == b'\n' && !in_quotes && pos >= next && pos < file_size {
+                boundaries.push(pos);
+                next = pos + chunk_size;
+            }
+        }
+        let consumed = buf.len();
+        reader.consume(consumed);
     }
 
+    boundaries.push(file_size);
     Ok(boundaries)
 }
 
@@ -519,6 +532,24 @@ pub fn parallel_process_csv<F, R>(
     num_workers: usize,
     process_chunk: F,
 ) -> io::Result<Vec<R>>
+where
+    F: Fn(Vec<CsvRecord>) -> R + Send + Sync + 'static,
+    R: Send,
+{
+    let config = CsvReaderBuilder::new()
+        .delimiter(ascii_byte(delimiter)?)
+        .has_headers(false);
+    parallel_process_with(path, &config, num_workers, process_chunk)
+}
+
+/// Like [`parallel_process_csv`], but with a full reader configuration.
+/// With `has_headers`, the header row is dropped from the first chunk.
+pub fn parallel_process_with<F, R>(
+    path: &Path,
+    config: &CsvReaderBuilder,
+    num_workers: usize,
+    process_chunk: F,
+) -> io::Result<Vec<R>>
 where
     F: Fn(Vec<CsvRecord>) -> R + Send + Sync + 'static,
     R: Send,
@@ -526,7 +557,7 @@ where
     let num_workers = num_workers.max(1);
     let path_buf = Arc::new(path.to_path_buf());
     let mut file = File::open(&*path_buf)?;
-    let boundaries = find_chunk_boundaries(&mut file, num_workers)?;
+    let boundaries = find_chunk_boundaries(&mut file, num_workers, config)?;
     let chunk_ranges: Vec<(u64, u64)> = boundaries
         .windows(2)
         .map(|window| (window[0], window[1]))
@@ -541,33 +572,17 @@ where
             move |(start, end)| -> io::Result<R> {
                 let mut chunk_file = File::open(&*path_buf)?;
                 chunk_file.seek(SeekFrom::Start(start))?;
-                let mut reader = BufReader::new(chunk_file);
-                let mut consumed = start;
-                let mut records = Vec::new();
-                let mut line = String::new();
-
-                while consumed < end {
-                    line.clear();
-                    let bytes_read = reader.read_line(&mut line)?;
-                    if bytes_read == 0 {
-                        break;
-                    }
-                    consumed += bytes_read as u64;
-                    let trimmed = line.trim_end_matches(|c| c == '\n' || c == '\r');
-                    if trimmed.trim().is_empty() {
-                        continue;
-                    }
-                    match CsvRecord::parse_csv_line(trimmed, delimiter) {
-                        Ok(record) => records.push(record),
-                        Err(ParseError::EmptyLine) => continue,
-                        Err(err) => {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                format!("Failed to parse chunk at byte {}: {:?}", consumed, err),
-                            ))
-                        }
-                    }
-                }
+                let records = config
+                    .clone()
+                    .has_headers(config.has_headers && start == 0)
+                    .build(chunk_file.take(end - start))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Failed to parse chunk at byte {}: {:?}", start, err),
+                        )
+                    })?;
 
                 Ok((*processor)(records))
             }
@@ -630,6 +645,1173 @@ where
     Ok(final_grouped)
 }
 
+// =============================================================================
+// Milestone 7: RFC 4180 streaming reader, headers and typed records
+// =============================================================================
+
+/// Dialect and header settings for [`CsvReader`].
+#[derive(Debug, Clone)]
+pub struct CsvReaderBuilder {
+    delimiter: u8,
+    quote: u8,
+    escape: Option<u8>,
+    double_quote: bool,
+    has_headers: bool,
+}
+
+impl Default for CsvReaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvReaderBuilder {
+    /// RFC 4180 defaults: `,` delimiter, `"` quote, `""` as an escaped quote,
+    /// first record treated as the header row.
+    pub fn new() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            double_quote: true,
+            has_headers: true,
+        }
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Escape byte inside quoted fields, e.g. `b'\\'` for `"say \"hi\""`.
+    pub fn escape(mut self, escape: Option<u8>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Whether a doubled quote inside a quoted field stands for one quote.
+    pub fn double_quote(mut self, double_quote: bool) -> Self {
+        self.double_quote = double_quote;
+        self
+    }
+
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    pub fn build<R: Read>(&self, input: R) -> CsvReader<R> {
+        CsvReader {
+            input: BufReader::new(input),
+            config: self.clone(),
+            headers: None,
+            headers_read: false,
+            bom_checked: false,
+            done: false,
+            line: 1,
+            record_line: 0,
+        }
+    }
+
+    pub fn open(&self, path: &Path) -> io::Result<CsvReader<File>> {
+        Ok(self.build(File::open(path)?))
+    }
+}
+
+/// Column names from the header row, with name → index lookup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Headers {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Headers {
+    pub fn new(names: Vec<String>) -> Self {
+        let mut index = HashMap::new();
+        for (i, name) in names.iter().enumerate() {
+            // duplicate names resolve to the first column
+            index.entry(name.clone()).or_insert(i);
+        }
+        Self { names, index }
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReadState {
+    StartField,
+    Unquoted,
+    Quoted,
+    QuoteInQuoted,
+    Escaped,
+}
+
+/// Byte-level CSV reader. Unlike [`CsvRecord::parse_csv_line`] it tracks
+/// quoting across physical lines, so quoted fields may contain delimiters,
+/// CR/LF and newlines. A leading UTF-8 BOM is skipped and blank lines between
+/// records are ignored.
+pub struct CsvReader<R> {
+    input: BufReader<R>,
+    config: CsvReaderBuilder,
+    headers: Option<Arc<Headers>>,
+    headers_read: bool,
+    bom_checked: bool,
+    done: bool,
+    line: usize,
+    record_line: usize,
+}
+
+impl<R: Read> CsvReader<R> {
+    /// The header row, read on first use. `None` without `has_headers`.
+    pub fn headers(&mut self) -> Result<Option<Arc<Headers>>, Error> {
+        self.ensure_headers()?;
+        Ok(self.headers.clone())
+    }
+
+    /// Line on which the most recently returned record started.
+    pub fn record_line(&self) -> usize {
+        self.record_line
+    }
+
+    pub fn read_record(&mut self) -> Result<Option<CsvRecord>, Error> {
+        self.ensure_headers()?;
+        self.read_raw()
+    }
+
+    /// Records paired with the header row for by-name access.
+    pub fn rows(self) -> Rows<R> {
+        Rows { reader: self }
+    }
+
+    /// Records converted through [`FromCsvRecord`].
+    pub fn deserialize<T: FromCsvRecord>(self) -> DeserializeRecords<R, T> {
+        DeserializeRecords {
+            rows: self.rows(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn ensure_headers(&mut self) -> Result<(), Error> {
+        if self.config.has_headers && !self.headers_read {
+            self.headers_read = true;
+            let names = self.read_raw()?.map(|r| r.fields).unwrap_or_default();
+            self.headers = Some(Arc::new(Headers::new(names)));
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Error> {
+        Ok(self.input.fill_buf().map_err(Error::Io)?.first().copied())
+    }
+
+    fn bump(&mut self) {
+        self.input.consume(1);
+    }
+
+    fn skip_bom(&mut self) -> Result<(), Error> {
+        self.bom_checked = true;
+        let buf = self.input.fill_buf().map_err(Error::Io)?;
+        if buf.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            self.input.consume(3);
+        }
+        Ok(())
+    }
+
+    /// Consumes the rest of a line ending that started with `byte`.
+    fn end_line(&mut self, byte: u8) -> Result<(), Error> {
+        if byte == b'\r' && self.peek()? == Some(b'\n') {
+            self.bump();
+        }
+        self.line += 1;
+        Ok(())
+    }
+
+    /// Resynchronises after a malformed record by dropping the rest of its line.
+    fn skip_line(&mut self) -> Result<(), Error> {
+        while let Some(byte) = self.peek()? {
+            self.bump();
+            if byte == b'\n' || byte == b'\r' {
+                return self.end_line(byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_raw(&mut self) -> Result<Option<CsvRecord>, Error> {
+        // a line of only unquoted whitespace is as blank as an empty one
+        while let Some((record, quoted)) = self.read_physical_record()? {
+            if quoted || record.fields.len() != 1 || !record.fields[0].trim().is_empty() {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Next record as written, and whether any of its fields was quoted.
+    fn read_physical_record(&mut self) -> Result<Option<(CsvRecord, bool)>, Error> {
+        if !self.bom_checked {
+            self.skip_bom()?;
+        }
+        if self.done {
+            return Ok(None);
+        }
+
+        // blank lines between records carry no data
+        while let Some(byte @ (b'\n' | b'\r')) = self.peek()? {
+            self.bump();
+            self.end_line(byte)?;
+        }
+        if self.peek()?.is_none() {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let CsvReaderBuilder {
+            delimiter,
+            quote,
+            escape,
+            double_quote,
+            ..
+        } = self.config;
+        let start_line = self.line;
+        self.record_line = start_line;
+        let to_string = |bytes: Vec<u8>| {
+            String::from_utf8(bytes).map_err(|_| Error::Parse(ParseError::InvalidUtf8, start_line))
+        };
+
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut state = ReadState::StartField;
+        let mut quoted = false;
+
+        loop {
+            let Some(byte) = self.peek()? else {
+                if matches!(state, ReadState::Quoted | ReadState::Escaped) {
+                    self.done = true;
+                    return Err(Error::Parse(ParseError::UnterminatedQuote, start_line));
+                }
+                fields.push(to_string(field)?);
+                break;
+            };
+            self.bump();
+
+            match state {
+                ReadState::Quoted => {
+                    if byte == quote {
+                        state = ReadState::QuoteInQuoted;
+                    } else if escape == Some(byte) {
+                        state = ReadState::Escaped;
+                    } else {
+                        if byte == b'\n' {
+                            self.line += 1;
+                        }
+                        field.push(byte);
+                    }
+                }
+                ReadState::Escaped => {
+                    if byte == b'\n' {
+                        self.line += 1;
+                    }
+                    field.push(byte);
+                    state = ReadState::Quoted;
+                }
+                ReadState::QuoteInQuoted if byte == quote && double_quote => {
+                    field.push(byte);
+                    state = ReadState::Quoted;
+                }
+                _ if byte == delimiter => {
+                    fields.push(to_string(std::mem::take(&mut field))?);
+                    state = ReadState::StartField;
+                }
+                _ if byte == b'\n' || byte == b'\r' => {
+                    self.end_line(byte)?;
+                    fields.push(to_string(field)?);
+                    break;
+                }
+                ReadState::StartField if byte == quote => {
+                    quoted = true;
+                    state = ReadState::Quoted;
+                }
+                ReadState::StartField | ReadState::Unquoted if byte != quote => {
+                    field.push(byte);
+                    state = ReadState::Unquoted;
+                }
+                // a quote inside an unquoted field, or data after a closing quote
+                _ => {
+                    self.skip_line()?;
+                    return Err(Error::Parse(ParseError::InvalidEscape, start_line));
+                }
+            }
+        }
+
+        Ok(Some((CsvRecord { fields }, quoted)))
+    }
+}
+
+impl<R: Read> Iterator for CsvReader<R> {
+    type Item = Result<CsvRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// A record together with the header row it belongs to.
+#[derive(Debug, Clone)]
+pub struct Row {
+    headers: Arc<Headers>,
+    record: CsvRecord,
+    line: usize,
+}
+
+impl Row {
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.record.get_field(self.headers.index_of(column)?)
+    }
+
+    pub fn get_typed<T: FromCsvField>(&self, column: &str) -> Result<T, ConversionError> {
+        let index = self
+            .headers
+            .index_of(column)
+            .ok_or_else(|| ConversionError::UnknownColumn(column.to_string()))?;
+        self.record.get_typed(index)
+    }
+
+    pub fn record(&self) -> &CsvRecord {
+        &self.record
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+pub struct Rows<R> {
+    reader: CsvReader<R>,
+}
+
+impl<R: Read> Iterator for Rows<R> {
+    type Item = Result<Row, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.reader.read_record() {
+            Ok(record) => record?,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(Ok(Row {
+            headers: self.reader.headers.clone().unwrap_or_default(),
+            record,
+            line: self.reader.record_line,
+        }))
+    }
+}
+
+/// A type built from a header-addressed row.
+pub trait FromCsvRecord: Sized {
+    fn from_row(row: &Row) -> Result<Self, ConversionError>;
+}
+
+pub struct DeserializeRecords<R, T> {
+    rows: Rows<R>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<R: Read, T: FromCsvRecord> Iterator for DeserializeRecords<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.rows
+                .next()?
+                .and_then(|row| T::from_row(&row).map_err(|err| Error::Conversion(err, row.line))),
+        )
+    }
+}
+
+// =============================================================================
+// Milestone 8: Writing and format conversion
+// =============================================================================
+
+/// When [`CsvWriter`] wraps a field in quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotePolicy {
+    /// Only fields containing the delimiter, a quote or a line break.
+    #[default]
+    Necessary,
+    Always,
+    /// Every field that does not parse as a number.
+    NonNumeric,
+    /// Fields are written verbatim; the caller guarantees they are safe.
+    Never,
+}
+
+pub struct CsvWriter<W: Write> {
+    output: W,
+    delimiter: u8,
+    quote: u8,
+    policy: QuotePolicy,
+    terminator: &'static [u8],
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            delimiter: b',',
+            quote: b'"',
+            policy: QuotePolicy::Necessary,
+            terminator: b"\n",
+        }
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn quote_policy(mut self, policy: QuotePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Terminate records with `\r\n` as RFC 4180 specifies, instead of `\n`.
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.terminator = if crlf { b"\r\n" } else { b"\n" };
+        self
+    }
+
+    pub fn write_record<I, S>(&mut self, fields: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let fields: Vec<S> = fields.into_iter().collect();
+        for (i, field) in fields.iter().enumerate() {
+            let field = field.as_ref();
+            if i > 0 {
+                self.output.write_all(&[self.delimiter])?;
+            }
+            // a lone empty field would otherwise read back as a blank line
+            let lone_empty = fields.len() == 1 && field.is_empty();
+            if self.needs_quotes(field) || (lone_empty && self.policy != QuotePolicy::Never) {
+                self.write_quoted(field)?;
+            } else {
+                self.output.write_all(field.as_bytes())?;
+            }
+        }
+        self.output.write_all(self.terminator)
+    }
+
+    pub fn write_csv_record(&mut self, record: &CsvRecord) -> io::Result<()> {
+        self.write_record(&record.fields)
+    }
+
+    pub fn write_headers(&mut self, headers: &Headers) -> io::Result<()> {
+        self.write_record(headers.names())
+    }
+
+    /// Drains a record stream, e.g. the output of `filter_valid().map_column(..)`.
+    pub fn write_all<I>(&mut self, records: I) -> io::Result<usize>
+    where
+        I: IntoIterator<Item = CsvRecord>,
+    {
+        let mut written = 0;
+        for record in records {
+            self.write_csv_record(&record)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+
+    fn needs_quotes(&self, field: &str) -> bool {
+        let special = || {
+            field
+                .bytes()
+                .any(|b| b == self.delimiter || b == self.quote || b == b'\n' || b == b'\r')
+        };
+        match self.policy {
+            QuotePolicy::Always => true,
+            QuotePolicy::Never => false,
+            QuotePolicy::Necessary => special(),
+            QuotePolicy::NonNumeric => field.trim().parse::<f64>().is_err() || special(),
+        }
+    }
+
+    fn write_quoted(&mut self, field: &str) -> io::Result<()> {
+        let quote = [self.quote];
+        self.output.write_all(&quote)?;
+        for (i, part) in field.split(self.quote as char).enumerate() {
+            if i > 0 {
+                self.output.write_all(&[self.quote, self.quote])?;
+            }
+            self.output.write_all(part.as_bytes())?;
+        }
+        self.output.write_all(&quote)
+    }
+}
+
+/// An in-memory table: the common ground between CSV, NDJSON and columnar.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    headers: Headers,
+    records: Vec<CsvRecord>,
+}
+
+impl Table {
+    /// Builds a table, padding every record to a common width. Columns
+    /// without a header are named `column_<index>`.
+    pub fn new(headers: Headers, records: Vec<CsvRecord>) -> Self {
+        let width = records
+            .iter()
+            .map(CsvRecord::field_count)
+            .max()
+            .unwrap_or(0)
+            .max(headers.len());
+        let headers = if headers.len() < width {
+            let mut names = headers.names().to_vec();
+            names.extend((names.len()..width).map(|i| format!("column_{}", i)));
+            Headers::new(names)
+        } else {
+            headers
+        };
+        let records = records
+            .into_iter()
+            .map(|mut record| {
+                record.fields.resize(width, String::new());
+                record
+            })
+            .collect();
+        Self { headers, records }
+    }
+
+    pub fn from_csv<R: Read>(mut reader: CsvReader<R>) -> Result<Self, Error> {
+        let headers = reader.headers()?.map(|h| (*h).clone()).unwrap_or_default();
+        let records = reader.collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(headers, records))
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn records(&self) -> &[CsvRecord] {
+        &self.records
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut CsvWriter<W>) -> io::Result<()> {
+        writer.write_headers(&self.headers)?;
+        writer.write_all(self.records.iter().cloned())?;
+        Ok(())
+    }
+
+    /// Reads NDJSON objects. Keys become columns in first-seen order; nested
+    /// arrays and objects are kept as compact JSON text.
+    pub fn from_ndjson<R: BufRead>(input: R) -> Result<Self, Error> {
+        let mut names: Vec<String> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut records = Vec::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.map_err(Error::Io)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let object: JsonObject = serde_json::from_str(&line)
+                .map_err(|err| Error::Json(err.to_string(), line_number))?;
+
+            let mut fields = vec![String::new(); names.len()];
+            for (key, value) in object.0 {
+                let column = *index.entry(key.clone()).or_insert_with(|| {
+                    names.push(key);
+                    names.len() - 1
+                });
+                if column >= fields.len() {
+                    fields.resize(column + 1, String::new());
+                }
+                fields[column] = json_to_field(value);
+            }
+            records.push(CsvRecord { fields });
+        }
+
+        Ok(Self::new(Headers::new(names), records))
+    }
+
+    pub fn write_ndjson<W: Write>(&self, mut output: W) -> io::Result<()> {
+        for record in &self.records {
+            write_ndjson_line(&mut output, &self.headers, record)?;
+        }
+        output.flush()
+    }
+}
+
+/// Streams CSV rows to NDJSON without materialising a [`Table`].
+pub fn csv_to_ndjson<R: Read, W: Write>(
+    reader: CsvReader<R>,
+    mut output: W,
+) -> Result<usize, Error> {
+    let mut written = 0;
+    for row in reader.rows() {
+        let row = row?;
+        write_ndjson_line(&mut output, &row.headers, &row.record).map_err(Error::Io)?;
+        written += 1;
+    }
+    output.flush().map_err(Error::Io)?;
+    Ok(written)
+}
+
+fn write_ndjson_line<W: Write>(
+    output: &mut W,
+    headers: &Headers,
+    record: &CsvRecord,
+) -> io::Result<()> {
+    // written by hand so keys keep the header order
+    output.write_all(b"{")?;
+    for (i, field) in record.fields.iter().enumerate() {
+        if i > 0 {
+            output.write_all(b",")?;
+        }
+        let key = headers
+            .names()
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| format!("column_{}", i));
+        serde_json::to_writer(&mut *output, &key)?;
+        output.write_all(b":")?;
+        serde_json::to_writer(&mut *output, &field_to_json(field))?;
+    }
+    output.write_all(b"}\n")
+}
+
+/// Empty fields become `null`; numbers and booleans are only unquoted when
+/// that loses nothing, so `007` and `1.50` stay strings.
+fn field_to_json(field: &str) -> serde_json::Value {
+    match ColumnType::of(field) {
+        None => serde_json::Value::Null,
+        Some(ColumnType::Int) => field.parse::<i64>().map(Into::into).unwrap_or_default(),
+        Some(ColumnType::Float) => field.parse::<f64>().map(Into::into).unwrap_or_default(),
+        Some(ColumnType::Bool) => (field == "true").into(),
+        Some(ColumnType::Text) => field.into(),
+    }
+}
+
+/// A JSON object with its keys in document order, which `serde_json::Map`
+/// does not keep by default.
+struct JsonObject(Vec<(String, serde_json::Value)>);
+
+impl<'de> serde::Deserialize<'de> for JsonObject {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ObjectVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ObjectVisitor {
+            type Value = JsonObject;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<JsonObject, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(JsonObject(entries))
+            }
+        }
+
+        deserializer.deserialize_map(ObjectVisitor)
+    }
+}
+
+fn json_to_field(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// Physical type of a column in the columnar format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int,
+    Float,
+    Bool,
+    Text,
+}
+
+impl ColumnType {
+    /// Narrowest type that reproduces `field` exactly, or `None` for empty.
+    fn of(field: &str) -> Option<Self> {
+        if field.is_empty() {
+            None
+        } else if field.parse::<i64>().is_ok_and(|v| v.to_string() == field) {
+            Some(ColumnType::Int)
+        } else if field
+            .parse::<f64>()
+            .is_ok_and(|v| v.is_finite() && v.to_string() == field)
+        {
+            Some(ColumnType::Float)
+        } else if field == "true" || field == "false" {
+            Some(ColumnType::Bool)
+        } else {
+            Some(ColumnType::Text)
+        }
+    }
+
+    /// Narrowest type covering every non-empty value; all-empty columns are text.
+    /// Ints and floats only share a `Float` column if every int renders back
+    /// unchanged as an `f64`; otherwise the column stays text.
+    pub fn infer<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Self {
+        let mut inferred: Option<ColumnType> = None;
+        let mut ints_exact_as_float = true;
+        for value in values {
+            let Some(ty) = ColumnType::of(value) else {
+                continue;
+            };
+            if ty == ColumnType::Int {
+                ints_exact_as_float &= value
+                    .parse::<f64>()
+                    .is_ok_and(|v| v.to_string() == value);
+            }
+            inferred = Some(match (inferred, ty) {
+                (None, ty) => ty,
+                (Some(a), b) if a == b => a,
+                (Some(ColumnType::Int), ColumnType::Float)
+                | (Some(ColumnType::Float), ColumnType::Int) => ColumnType::Float,
+                _ => ColumnType::Text,
+            });
+            if inferred == Some(ColumnType::Text) {
+                break;
+            }
+        }
+        match inferred {
+            Some(ColumnType::Float) if !ints_exact_as_float => ColumnType::Text,
+            inferred => inferred.unwrap_or(ColumnType::Text),
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            ColumnType::Int => 0,
+            ColumnType::Float => 1,
+            ColumnType::Bool => 2,
+            ColumnType::Text => 3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Some(match tag {
+            0 => ColumnType::Int,
+            1 => ColumnType::Float,
+            2 => ColumnType::Bool,
+            3 => ColumnType::Text,
+            _ => return None,
+        })
+    }
+}
+
+const COLUMNAR_MAGIC: &[u8; 4] = b"CCOL";
+const COLUMNAR_VERSION: u8 = 1;
+const ENCODING_PLAIN: u8 = 0;
+const ENCODING_DICTIONARY: u8 = 1;
+
+/// Decoded values of one column; `None` marks an empty field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValues {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+    Text(Vec<Option<String>>),
+}
+
+impl ColumnValues {
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnValues::Int(_) => ColumnType::Int,
+            ColumnValues::Float(_) => ColumnType::Float,
+            ColumnValues::Bool(_) => ColumnType::Bool,
+            ColumnValues::Text(_) => ColumnType::Text,
+        }
+    }
+
+    /// The same numeric reading `get_typed::<f64>` would give the CSV field.
+    pub fn as_f64(&self, row: usize) -> Option<f64> {
+        match self {
+            ColumnValues::Int(v) => v[row].map(|x| x as f64),
+            ColumnValues::Float(v) => v[row],
+            ColumnValues::Bool(_) => None,
+            ColumnValues::Text(v) => v[row].as_deref().and_then(|s| s.trim().parse().ok()),
+        }
+    }
+
+    fn render(&self, row: usize) -> String {
+        match self {
+            ColumnValues::Int(v) => v[row].map(|x| x.to_string()),
+            ColumnValues::Float(v) => v[row].map(|x| x.to_string()),
+            ColumnValues::Bool(v) => v[row].map(|x| x.to_string()),
+            ColumnValues::Text(v) => v[row].clone(),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// Writes `table` in the columnar format:
+///
+/// ```text
+/// "CCOL" version:u8 columns:varint rows:varint
+/// per column: name:str type:u8 encoding:u8 body_len:varint body
+/// body: presence bitmap, then the non-empty values only
+/// ```
+///
+/// Ints are zig-zag varints, floats little-endian `f64`, booleans a bitmap.
+/// Text columns whose distinct values are at most half of their non-empty
+/// values are dictionary encoded. Types are inferred so that every field
+/// reads back byte-for-byte identical.
+pub fn write_columnar<W: Write>(table: &Table, mut output: W) -> io::Result<()> {
+    let rows = table.records.len();
+    let mut buf = Vec::new();
+    buf.extend_from_slice(COLUMNAR_MAGIC);
+    buf.push(COLUMNAR_VERSION);
+    put_varint(&mut buf, table.headers.len() as u64);
+    put_varint(&mut buf, rows as u64);
+
+    for (column, name) in table.headers.names().iter().enumerate() {
+        let values: Vec<&str> = table
+            .records
+            .iter()
+            .map(|r| r.get_field(column).unwrap_or(""))
+            .collect();
+        let ty = ColumnType::infer(values.iter().copied());
+        let present: Vec<&str> = values.iter().copied().filter(|v| !v.is_empty()).collect();
+
+        let mut body = bitmap(values.iter().map(|v| !v.is_empty()));
+        let mut encoding = ENCODING_PLAIN;
+        match ty {
+            ColumnType::Int => {
+                for v in &present {
+                    let v: i64 = v.parse().unwrap_or_default();
+                    put_varint(&mut body, ((v << 1) ^ (v >> 63)) as u64);
+                }
+            }
+            ColumnType::Float => {
+                for v in &present {
+                    let v: f64 = v.parse().unwrap_or_default();
+                    body.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+            ColumnType::Bool => body.extend(bitmap(present.iter().map(|v| *v == "true"))),
+            ColumnType::Text => {
+                let mut dictionary: Vec<&str> = Vec::new();
+                let mut codes: HashMap<&str, usize> = HashMap::new();
+                for v in &present {
+                    codes.entry(v).or_insert_with(|| {
+                        dictionary.push(v);
+                        dictionary.len() - 1
+                    });
+                }
+                if !present.is_empty() && dictionary.len() * 2 <= present.len() {
+                    encoding = ENCODING_DICTIONARY;
+                    put_varint(&mut body, dictionary.len() as u64);
+                    for entry in &dictionary {
+                        put_str(&mut body, entry);
+                    }
+                    for v in &present {
+                        put_varint(&mut body, codes[v] as u64);
+                    }
+                } else {
+                    for v in &present {
+                        put_str(&mut body, v);
+                    }
+                }
+            }
+        }
+
+        put_str(&mut buf, name);
+        buf.push(ty.tag());
+        buf.push(encoding);
+        put_varint(&mut buf, body.len() as u64);
+        buf.extend_from_slice(&body);
+    }
+
+    output.write_all(&buf)?;
+    output.flush()
+}
+
+pub fn read_columnar<R: Read>(input: R) -> Result<Table, Error> {
+    let (names, columns, rows) = decode_columnar(input, None)?;
+    let records = (0..rows)
+        .map(|row| CsvRecord {
+            fields: columns.iter().map(|c| c.render(row)).collect(),
+        })
+        .collect();
+    Ok(Table::new(Headers::new(names), records))
+}
+
+/// Decodes a single column, skipping the bodies of all others.
+pub fn read_columnar_column<R: Read>(input: R, name: &str) -> Result<ColumnValues, Error> {
+    let (_, mut columns, _) = decode_columnar(input, Some(name))?;
+    columns
+        .pop()
+        .ok_or_else(|| Error::Columnar(format!("no column named {:?}", name)))
+}
+
+/// Columnar counterpart of [`parallel_aggregate_column`]: only the requested
+/// column is decoded, then its values are folded in parallel.
+pub fn parallel_aggregate_columnar(
+    path: &Path,
+    column: &str,
+    num_workers: usize,
+) -> Result<CsvAggregator, Error> {
+    let file = File::open(path).map_err(Error::Io)?;
+    let values = read_columnar_column(BufReader::new(file), column)?;
+    let rows = match &values {
+        ColumnValues::Int(v) => v.len(),
+        ColumnValues::Float(v) => v.len(),
+        ColumnValues::Bool(v) => v.len(),
+        ColumnValues::Text(v) => v.len(),
+    };
+    let numbers: Vec<f64> = (0..rows).filter_map(|row| values.as_f64(row)).collect();
+    let chunk_size = numbers.len().div_ceil(num_workers.max(1)).max(1);
+
+    Ok(numbers
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            chunk.iter().fold(CsvAggregator::new(), |mut agg, &value| {
+                agg.update(value);
+                agg
+            })
+        })
+        .reduce(CsvAggregator::new, |mut a, b| {
+            a.merge(b);
+            a
+        }))
+}
+
+fn decode_columnar<R: Read>(
+    mut input: R,
+    only: Option<&str>,
+) -> Result<(Vec<String>, Vec<ColumnValues>, usize), Error> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes).map_err(Error::Io)?;
+    let mut src = ByteCursor {
+        bytes: &bytes,
+        pos: 0,
+    };
+
+    if src.take(4)? != COLUMNAR_MAGIC {
+        return Err(Error::Columnar("not a columnar file".into()));
+    }
+    let version = src.byte()?;
+    if version != COLUMNAR_VERSION {
+        return Err(Error::Columnar(format!("unsupported version {}", version)));
+    }
+    let column_count = src.varint()? as usize;
+    let rows = src.varint()? as usize;
+
+    let mut names = Vec::new();
+    let mut columns = Vec::new();
+    for _ in 0..column_count {
+        let name = src.string()?;
+        let ty = ColumnType::from_tag(src.byte()?)
+            .ok_or_else(|| Error::Columnar(format!("column {:?}: unknown type", name)))?;
+        let encoding = src.byte()?;
+        let body_len = src.varint()? as usize;
+        let body = src.take(body_len)?;
+        if only.is_some_and(|wanted| wanted != name) {
+            continue;
+        }
+        let values = decode_column(body, ty, encoding, rows)
+            .map_err(|err| Error::Columnar(format!("column {:?}: {}", name, err)))?;
+        names.push(name);
+        columns.push(values);
+    }
+    Ok((names, columns, rows))
+}
+
+fn decode_column(
+    body: &[u8],
+    ty: ColumnType,
+    encoding: u8,
+    rows: usize,
+) -> Result<ColumnValues, String> {
+    let mut src = ByteCursor {
+        bytes: body,
+        pos: 0,
+    };
+    let present = read_bitmap(&mut src, rows).map_err(|_| "truncated bitmap")?;
+    let count = present.iter().filter(|p| **p).count();
+    let err = |_| "truncated body".to_string();
+
+    // spreads `count` decoded values over the rows marked present
+    fn spread<T>(present: &[bool], values: Vec<T>) -> Vec<Option<T>> {
+        let mut values = values.into_iter();
+        present
+            .iter()
+            .map(|&p| if p { values.next() } else { None })
+            .collect()
+    }
+
+    Ok(match (ty, encoding) {
+        (ColumnType::Int, ENCODING_PLAIN) => {
+            let values = (0..count)
+                .map(|_| src.varint().map(|z| ((z >> 1) as i64) ^ -((z & 1) as i64)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(err)?;
+            ColumnValues::Int(spread(&present, values))
+        }
+        (ColumnType::Float, ENCODING_PLAIN) => {
+            let values = (0..count)
+                .map(|_| {
+                    src.take(8)
+                        .map(|b| f64::from_le_bytes(b.try_into().expect("8 bytes")))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(err)?;
+            ColumnValues::Float(spread(&present, values))
+        }
+        (ColumnType::Bool, ENCODING_PLAIN) => {
+            let values = read_bitmap(&mut src, count).map_err(err)?;
+            ColumnValues::Bool(spread(&present, values))
+        }
+        (ColumnType::Text, ENCODING_PLAIN) => {
+            let values = (0..count)
+                .map(|_| src.string())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(err)?;
+            ColumnValues::Text(spread(&present, values))
+        }
+        (ColumnType::Text, ENCODING_DICTIONARY) => {
+            let size = src.varint().map_err(err)? as usize;
+            let dictionary = (0..size)
+                .map(|_| src.string())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(err)?;
+            let values = (0..count)
+                .map(|_| {
+                    let code = src.varint().map_err(err)? as usize;
+                    dictionary
+                        .get(code)
+                        .cloned()
+                        .ok_or_else(|| format!("dictionary code {} out of range", code))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            ColumnValues::Text(spread(&present, values))
+        }
+        (ty, encoding) => return Err(format!("encoding {} is invalid for {:?}", encoding, ty)),
+    })
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    put_varint(buf, s.len() as u64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn bitmap<I: IntoIterator<Item = bool>>(bits: I) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (i, bit) in bits.into_iter().enumerate() {
+        if i % 8 == 0 {
+            out.push(0);
+        }
+        if bit {
+            *out.last_mut().expect("pushed above") |= 1 << (i % 8);
+        }
+    }
+    out
+}
+
+fn read_bitmap(src: &mut ByteCursor, len: usize) -> Result<Vec<bool>, Error> {
+    let bytes = src.take(len.div_ceil(8))?;
+    Ok((0..len)
+        .map(|i| bytes[i / 8] & (1 << (i % 8)) != 0)
+        .collect())
+}
+
+struct ByteCursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| Error::Columnar("unexpected end of data".into()))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Columnar("varint overflow".into()))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.varint()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| Error::Columnar("invalid UTF-8 in string".into()))
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -723,6 +1905,21 @@ mod tests {
         assert_eq!(records.len(), 2);
     }
 
+    #[test]
+    fn test_skip_whitespace_only_lines() {
+        let file = create_test_csv("a,b\n   \n\t\r\n1,2\n\" \"\n");
+        let records: Vec<_> = CsvFileIterator::new(file.path(), ',')
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        // a quoted blank field is data, not a blank line
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].fields, vec![" "]);
+
+        let counts = parallel_process_csv(file.path(), ',', 2, |records| records.len()).unwrap();
+        assert_eq!(counts.iter().sum::<usize>(), 3);
+    }
+
     #[test]
     fn test_error_reporting_with_line_numbers() {
         let file = create_test_csv("a,b\n\"unterminated\n3,4");
@@ -904,6 +2101,331 @@ mod tests {
         assert_eq!(grouped.get(&"C".to_string()).unwrap().count, 300);
     }
 
+    #[test]
+    fn test_reader_multiline_quoted_fields_and_crlf() {
+        let input = "id,note\r\n1,\"line one\r\nline two\"\r\n2,\"a, \"\"quoted\"\" b\"\r\n";
+        let mut reader = CsvReaderBuilder::new().build(input.as_bytes());
+
+        let first = reader.read_record().unwrap().unwrap();
+        assert_eq!(first.get_field(1), Some("line one\r\nline two"));
+        assert_eq!(reader.record_line(), 2);
+
+        let second = reader.read_record().unwrap().unwrap();
+        assert_eq!(second.get_field(1), Some(r#"a, "quoted" b"#));
+        assert_eq!(reader.record_line(), 4);
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reader_bom_headers_and_named_access() {
+        let input = "\u{feff}name,age\nalice,30\n\nbob,25";
+        let rows: Vec<Row> = CsvReaderBuilder::new()
+            .build(input.as_bytes())
+            .rows()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("name"), Some("alice"));
+        assert_eq!(rows[1].get_typed::<i64>("age").unwrap(), 25);
+        assert_eq!(rows[1].line(), 4);
+        assert!(matches!(
+            rows[0].get_typed::<i64>("height"),
+            Err(ConversionError::UnknownColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_reader_custom_quote_and_escape() {
+        let input = "'it\\'s,plain';x\n";
+        let record = CsvReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(b';')
+            .quote(b'\'')
+            .escape(Some(b'\\'))
+            .double_quote(false)
+            .build(input.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(record.get_field(0), Some("it's,plain"));
+        assert_eq!(record.get_field(1), Some("x"));
+    }
+
+    #[test]
+    fn test_reader_recovers_after_malformed_record() {
+        let input = "a,b\nba\"d,1\nok,2\n";
+        let results: Vec<_> = CsvReaderBuilder::new()
+            .has_headers(false)
+            .build(input.as_bytes())
+            .collect();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            results[1],
+            Err(Error::Parse(ParseError::InvalidEscape, 2))
+        ));
+        assert_eq!(results[2].as_ref().unwrap().get_field(0), Some("ok"));
+    }
+
+    /// Implements [`FromCsvRecord`] for a struct by reading each listed field
+    /// from the column of the same name (or `field => "column"`) through
+    /// [`FromCsvField`].
+    macro_rules! impl_from_csv_record {
+        ($ty:ident { $($field:ident $(=> $column:literal)?),* $(,)? }) => {
+            impl FromCsvRecord for $ty {
+                fn from_row(row: &Row) -> Result<Self, ConversionError> {
+                    Ok($ty {
+                        $($field: row.get_typed(impl_from_csv_record!(@column $field $($column)?))?,)*
+                    })
+                }
+            }
+        };
+        (@column $field:ident $column:literal) => {
+            $column
+        };
+        (@column $field:ident) => {
+            stringify!($field)
+        };
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Trade {
+        symbol: String,
+        price: f64,
+        qty: Option<i64>,
+        settled: bool,
+    }
+
+    impl_from_csv_record!(Trade {
+        symbol,
+        price,
+        qty => "quantity",
+        settled,
+    });
+
+    #[test]
+    fn test_typed_records() {
+        let input =
+            "symbol,price,quantity,settled\nABC,10.5,3,yes\n\"X\nY\",2,,no\nBAD,oops,1,no\n";
+        let trades: Vec<Result<Trade, Error>> = CsvReaderBuilder::new()
+            .build(input.as_bytes())
+            .deserialize()
+            .collect();
+
+        assert_eq!(
+            trades[0].as_ref().unwrap(),
+            &Trade {
+                symbol: "ABC".into(),
+                price: 10.5,
+                qty: Some(3),
+                settled: true
+            }
+        );
+        let second = trades[1].as_ref().unwrap();
+        assert_eq!(second.symbol, "X\nY");
+        assert_eq!(second.qty, None);
+        assert!(matches!(
+            trades[2],
+            Err(Error::Conversion(ConversionError::ParseFloat(_), 5))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_boundaries_respect_quoted_newlines() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,text,value").unwrap();
+        for i in 0..500 {
+            writeln!(file, "{},\"multi\nline, \"\"{}\"\"\n\",{}", i, i, i).unwrap();
+        }
+
+        let config = CsvReaderBuilder::new();
+        let mut handle = File::open(file.path()).unwrap();
+        let boundaries = find_chunk_boundaries(&mut handle, 8, &config).unwrap();
+        assert!(boundaries.len() > 2);
+
+        let counts = parallel_process_with(file.path(), &config, 8, |records| {
+            assert!(records.iter().all(|r| r.field_count() == 3));
+            records.len()
+        })
+        .unwrap();
+        assert_eq!(counts.iter().sum::<usize>(), 500);
+    }
+
+    #[test]
+    fn test_writer_quote_policies_round_trip() {
+        let fields = ["plain", "a,b", "say \"hi\"", "two\nlines", "42", ""];
+        for policy in [
+            QuotePolicy::Necessary,
+            QuotePolicy::Always,
+            QuotePolicy::NonNumeric,
+        ] {
+            let mut writer = CsvWriter::new(Vec::new()).quote_policy(policy).crlf(true);
+            writer.write_record(fields).unwrap();
+            writer.write_record([""]).unwrap();
+            let bytes = writer.into_inner().unwrap();
+
+            let records: Vec<CsvRecord> = CsvReaderBuilder::new()
+                .has_headers(false)
+                .build(bytes.as_slice())
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(records.len(), 2, "{:?}", policy);
+            assert_eq!(records[0].fields, fields);
+            assert_eq!(records[1].fields, [""]);
+        }
+
+        let mut writer = CsvWriter::new(Vec::new()).quote_policy(QuotePolicy::NonNumeric);
+        writer.write_record(["x", "1.5", ""]).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"\"x\",1.5,\"\"\n");
+    }
+
+    #[test]
+    fn test_csv_ndjson_round_trip() {
+        let input = "id,name,score,zip,active\n1,\"Smith, J\",9.5,007,true\n2,Lee,,12,false\n";
+        let mut json = Vec::new();
+        let written =
+            csv_to_ndjson(CsvReaderBuilder::new().build(input.as_bytes()), &mut json).unwrap();
+        assert_eq!(written, 2);
+
+        let text = String::from_utf8(json.clone()).unwrap();
+        assert_eq!(
+            text.lines().next().unwrap(),
+            r#"{"id":1,"name":"Smith, J","score":9.5,"zip":"007","active":true}"#
+        );
+
+        let table = Table::from_ndjson(json.as_slice()).unwrap();
+        let mut writer = CsvWriter::new(Vec::new());
+        table.write_csv(&mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_ndjson_import_unions_keys_and_reports_lines() {
+        let input = "{\"a\":1}\n\n{\"b\":[1,2],\"a\":null}\n";
+        let table = Table::from_ndjson(input.as_bytes()).unwrap();
+        assert_eq!(table.headers().names(), ["a", "b"]);
+        assert_eq!(table.records()[0].fields, ["1", ""]);
+        assert_eq!(table.records()[1].fields, ["", "[1,2]"]);
+
+        let err = Table::from_ndjson("{\"a\":1}\n[1]\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::Json(_, 2)));
+    }
+
+    #[test]
+    fn test_columnar_round_trip_and_type_inference() {
+        let mut csv = String::from("city,temp,count,ok,note\n");
+        for i in 0..200 {
+            let city = ["Oslo", "Lima", "Pune"][i % 3];
+            let note = if i % 7 == 0 {
+                String::new()
+            } else {
+                format!("n{}", i)
+            };
+            csv.push_str(&format!(
+                "{},{}.5,{},{},{}\n",
+                city,
+                i,
+                i * 3,
+                i % 2 == 0,
+                note
+            ));
+        }
+        let table = Table::from_csv(CsvReaderBuilder::new().build(csv.as_bytes())).unwrap();
+
+        let mut encoded = Vec::new();
+        write_columnar(&table, &mut encoded).unwrap();
+        assert!(
+            encoded.len() < csv.len() * 3 / 4,
+            "{} vs {}",
+            encoded.len(),
+            csv.len()
+        );
+        assert_eq!(read_columnar(encoded.as_slice()).unwrap(), table);
+
+        let types: Vec<ColumnType> = ["city", "temp", "count", "ok", "note"]
+            .iter()
+            .map(|c| {
+                read_columnar_column(encoded.as_slice(), c)
+                    .unwrap()
+                    .column_type()
+            })
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ColumnType::Text,
+                ColumnType::Float,
+                ColumnType::Int,
+                ColumnType::Bool,
+                ColumnType::Text
+            ]
+        );
+        assert_eq!(ColumnType::infer(["1", "", "007"]), ColumnType::Text);
+        assert_eq!(ColumnType::infer(["1", "2.5"]), ColumnType::Float);
+        // 2^53 + 1 has no exact f64, so widening would rewrite it
+        assert_eq!(ColumnType::infer(["9007199254740993", "2.5"]), ColumnType::Text);
+        assert_eq!(ColumnType::infer(["9007199254740993"]), ColumnType::Int);
+
+        let mixed = Table::new(
+            Headers::new(vec!["n".into()]),
+            ["1", "2.5", "-0", "9007199254740993", ""]
+                .iter()
+                .map(|v| CsvRecord {
+                    fields: vec![v.to_string()],
+                })
+                .collect(),
+        );
+        let mut encoded = Vec::new();
+        write_columnar(&mixed, &mut encoded).unwrap();
+        assert_eq!(read_columnar(encoded.as_slice()).unwrap(), mixed);
+    }
+
+    #[test]
+    fn test_columnar_rejects_corrupt_input() {
+        let table = Table::new(
+            Headers::new(vec!["a".into()]),
+            vec![CsvRecord {
+                fields: vec!["x".into()],
+            }],
+        );
+        let mut encoded = Vec::new();
+        write_columnar(&table, &mut encoded).unwrap();
+
+        assert!(matches!(
+            read_columnar(&b"nope"[..]),
+            Err(Error::Columnar(_))
+        ));
+        assert!(matches!(
+            read_columnar(&encoded[..encoded.len() - 1]),
+            Err(Error::Columnar(_))
+        ));
+        assert!(matches!(
+            read_columnar_column(encoded.as_slice(), "missing"),
+            Err(Error::Columnar(_))
+        ));
+    }
+
+    #[test]
+    fn test_columnar_aggregate_matches_csv() {
+        let file = create_large_test_csv(1000);
+        let csv_agg = parallel_aggregate_column(file.path(), ',', 1, 4).unwrap();
+
+        let table = Table::from_csv(CsvReaderBuilder::new().open(file.path()).unwrap()).unwrap();
+        let columnar = NamedTempFile::new().unwrap();
+        write_columnar(&table, File::create(columnar.path()).unwrap()).unwrap();
+        let col_agg = parallel_aggregate_columnar(columnar.path(), "value", 4).unwrap();
+
+        assert_eq!(csv_agg.count, col_agg.count);
+        assert_eq!(csv_agg.sum, col_agg.sum);
+        assert_eq!(csv_agg.min, col_agg.min);
+        assert_eq!(csv_agg.max, col_agg.max);
+    }
+
     #[test]
     #[ignore]
     fn benchmark_parallel_speedup() {
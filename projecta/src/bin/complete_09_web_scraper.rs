@@ -1,16 +1,22 @@
 use futures::future::join_all;
 use rand::Rng;
 use reqwest::Client;
+use scraper::{Html, Selector};
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
     future::Future,
+    io::Write,
+    path::Path,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 use thiserror::Error;
 use tokio::{
-    sync::{OwnedSemaphorePermit, Semaphore},
+    sync::{Notify, OwnedSemaphorePermit, Semaphore},
     time::{sleep, timeout},
 };
+use url::Url;
 
 // =============================================================================
 // Milestone 1: Basic Async HTTP Client with Error Types
@@ -35,6 +41,12 @@ pub enum ScraperError {
 
     #[error("Resource limit exceeded: {0}")]
     ResourceLimitExceeded(String),
+
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Storage error: {0}")]
+    StorageError(String),
 }
 
 impl From<reqwest::Error> for ScraperError {
@@ -599,6 +611,1073 @@ pub async fn fetch_all_managed(
     join_all(futures).await
 }
 
+// =============================================================================
+// Milestone 7: Polite Crawling (frontier, robots.txt, link extraction)
+// =============================================================================
+
+/// Resolve `raw` against `base` and normalize it so that equivalent URLs
+/// compare equal: the fragment is dropped, query pairs are sorted and an
+/// empty query is removed. Scheme/host case, default ports and dot segments
+/// are already normalized by the `url` parser. Only http(s) URLs are accepted.
+pub fn canonicalize_url(raw: &str, base: Option<&Url>) -> Result<Url, ScraperError> {
+    let parsed = match base {
+        Some(base) => base.join(raw.trim()),
+        None => Url::parse(raw.trim()),
+    };
+    let mut url = parsed.map_err(|e| ScraperError::InvalidUrl(format!("{}: {}", raw, e)))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(ScraperError::InvalidUrl(format!(
+            "{}: not an http(s) URL",
+            raw
+        )));
+    }
+
+    url.set_fragment(None);
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        pairs.sort();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    Ok(url)
+}
+
+fn host_key(url: &Url) -> String {
+    match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RobotsRule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RobotsGroup {
+    agents: Vec<String>,
+    rules: Vec<RobotsRule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// A parsed robots.txt file (RFC 9309, plus the common `Crawl-delay`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsTxt {
+    groups: Vec<RobotsGroup>,
+    pub sitemaps: Vec<String>,
+}
+
+impl RobotsTxt {
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    pub fn disallow_all() -> Self {
+        Self::parse("User-agent: *\nDisallow: /")
+    }
+
+    pub fn parse(body: &str) -> Self {
+        let mut robots = RobotsTxt::default();
+        let mut current: Option<RobotsGroup> = None;
+        let mut in_agent_lines = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive user-agent lines share one group.
+                    if !in_agent_lines {
+                        robots.groups.extend(current.take());
+                        current = Some(RobotsGroup::default());
+                    }
+                    if let Some(group) = current.as_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                    in_agent_lines = true;
+                }
+                "allow" | "disallow" => {
+                    in_agent_lines = false;
+                    // An empty Disallow means "allow everything".
+                    if let (Some(group), false) = (current.as_mut(), value.is_empty()) {
+                        group.rules.push(RobotsRule {
+                            allow: key.trim().eq_ignore_ascii_case("allow"),
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_agent_lines = false;
+                    if let (Some(group), Ok(seconds)) = (current.as_mut(), value.parse::<f64>()) {
+                        if seconds.is_finite() && seconds >= 0.0 {
+                            group.crawl_delay = Some(Duration::from_secs_f64(seconds));
+                        }
+                    }
+                }
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+        robots.groups.extend(current);
+        robots
+    }
+
+    /// The groups that apply to `user_agent`: every group naming its product
+    /// token, or the `*` groups when none does.
+    fn groups_for(&self, user_agent: &str) -> Vec<&RobotsGroup> {
+        let token = user_agent
+            .split('/')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        let specific: Vec<&RobotsGroup> = self
+            .groups
+            .iter()
+            .filter(|g| g.agents.iter().any(|a| a != "*" && token == *a))
+            .collect();
+        if !specific.is_empty() {
+            return specific;
+        }
+        self.groups
+            .iter()
+            .filter(|g| g.agents.iter().any(|a| a == "*"))
+            .collect()
+    }
+
+    /// Whether `path` (path plus query) may be fetched. The longest matching
+    /// rule wins and `Allow` wins a tie.
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        self.groups_for(user_agent)
+            .into_iter()
+            .flat_map(|g| g.rules.iter())
+            .filter(|rule| robots_pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .map(|rule| rule.allow)
+            .unwrap_or(true)
+    }
+
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent)
+            .into_iter()
+            .filter_map(|g| g.crawl_delay)
+            .max()
+    }
+}
+
+/// Match a robots.txt path pattern, where `*` matches any run of characters
+/// and a trailing `$` anchors the pattern to the end of the path.
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(stripped) => (stripped, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+/// robots.txt files by origin, refetched after `ttl`.
+pub struct RobotsCache {
+    entries: HashMap<String, (Arc<RobotsTxt>, Instant)>,
+    ttl: Duration,
+}
+
+impl RobotsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            ttl,
+        }
+    }
+
+    pub async fn get(&mut self, client: &Client, url: &Url) -> Arc<RobotsTxt> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((robots, fetched_at)) = self.entries.get(&origin) {
+            if fetched_at.elapsed() < self.ttl {
+                return robots.clone();
+            }
+        }
+        let robots = Arc::new(fetch_robots(client, &origin).await);
+        self.entries
+            .insert(origin, (robots.clone(), Instant::now()));
+        robots
+    }
+}
+
+/// Per RFC 9309: a missing file (4xx) allows everything, while an
+/// unreachable one (5xx, network error) is treated as a full disallow.
+async fn fetch_robots(client: &Client, origin: &str) -> RobotsTxt {
+    match client.get(format!("{}/robots.txt", origin)).send().await {
+        Ok(response) if response.status().is_success() => match response.text().await {
+            Ok(body) => RobotsTxt::parse(&body),
+            Err(_) => RobotsTxt::disallow_all(),
+        },
+        Ok(response) if response.status().is_client_error() => RobotsTxt::allow_all(),
+        _ => RobotsTxt::disallow_all(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlRequest {
+    pub url: Url,
+    pub depth: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FrontierPoll {
+    Ready(CrawlRequest),
+    Wait(Duration),
+    Empty,
+}
+
+/// URLs waiting to be fetched, queued per host. A host is not handed out
+/// again until its politeness delay has passed since its last fetch, so
+/// other hosts are served in the meantime.
+#[derive(Default)]
+pub struct Frontier {
+    queues: HashMap<String, VecDeque<CrawlRequest>>,
+    next_allowed: HashMap<String, Instant>,
+}
+
+impl Frontier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, request: CrawlRequest) {
+        self.queues
+            .entry(host_key(&request.url))
+            .or_default()
+            .push_back(request);
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.values().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The next request whose host is ready at `now`, or how long to wait
+    /// until one is.
+    pub fn poll(&mut self, now: Instant) -> FrontierPoll {
+        let next = self
+            .queues
+            .iter()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(host, _)| {
+                let ready_at = self.next_allowed.get(host).copied().unwrap_or(now);
+                (ready_at, host.clone())
+            })
+            .min();
+        match next {
+            None => FrontierPoll::Empty,
+            Some((ready_at, _)) if ready_at > now => FrontierPoll::Wait(ready_at - now),
+            Some((_, host)) => {
+                let queue = self.queues.get_mut(&host).expect("host queue");
+                let request = queue.pop_front().expect("non-empty queue");
+                if queue.is_empty() {
+                    self.queues.remove(&host);
+                }
+                FrontierPoll::Ready(request)
+            }
+        }
+    }
+
+    /// Record a fetch from `url`'s host that finished at `now`.
+    pub fn record_fetch(&mut self, url: &Url, now: Instant, delay: Duration) {
+        self.next_allowed.insert(host_key(url), now + delay);
+    }
+}
+
+/// Which discovered links are followed.
+#[derive(Debug, Clone)]
+pub struct CrawlScope {
+    pub max_depth: usize,
+    pub allowed_domains: Vec<String>,
+}
+
+impl CrawlScope {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            allowed_domains: Vec::new(),
+        }
+    }
+
+    /// Restrict the crawl to `domain` and its subdomains. With no domains
+    /// configured every host is in scope.
+    pub fn allow_domain(mut self, domain: &str) -> Self {
+        self.allowed_domains.push(domain.to_ascii_lowercase());
+        self
+    }
+
+    pub fn allows(&self, url: &Url, depth: usize) -> bool {
+        if depth > self.max_depth {
+            return false;
+        }
+        let host = url.host_str().unwrap_or_default();
+        self.allowed_domains.is_empty()
+            || self
+                .allowed_domains
+                .iter()
+                .any(|d| host == d || host.ends_with(&format!(".{}", d)))
+    }
+}
+
+/// Every URL the crawl has discovered and fetched, optionally journaled to a
+/// file so that an interrupted crawl can resume. Each line is either
+/// `discovered\t<depth>\t<url>` or `fetched\t<url>`; lines that do not parse
+/// (such as a line torn by a crash) are ignored on load.
+pub struct VisitedStore {
+    journal: Option<fs::File>,
+    discovered: HashMap<String, usize>,
+    fetched: HashSet<String>,
+}
+
+impl VisitedStore {
+    pub fn in_memory() -> Self {
+        Self {
+            journal: None,
+            discovered: HashMap::new(),
+            fetched: HashSet::new(),
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ScraperError> {
+        let path = path.as_ref();
+        let storage_error =
+            |e: std::io::Error| ScraperError::StorageError(format!("{}: {}", path.display(), e));
+        let mut store = Self::in_memory();
+        if path.exists() {
+            let contents = fs::read_to_string(path).map_err(storage_error)?;
+            for line in contents.lines() {
+                let fields: Vec<&str> = line.split('\t').collect();
+                match fields.as_slice() {
+                    ["discovered", depth, url] => {
+                        if let Ok(depth) = depth.parse() {
+                            store.discovered.entry(url.to_string()).or_insert(depth);
+                        }
+                    }
+                    ["fetched", url] => {
+                        store.fetched.insert(url.to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let journal = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(storage_error)?;
+        store.journal = Some(journal);
+        Ok(store)
+    }
+
+    fn append(&mut self, line: String) -> Result<(), ScraperError> {
+        if let Some(journal) = self.journal.as_mut() {
+            writeln!(journal, "{}", line)
+                .and_then(|_| journal.flush())
+                .map_err(|e| ScraperError::StorageError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Record a newly found URL. Returns false if it was already known.
+    pub fn discover(&mut self, request: &CrawlRequest) -> Result<bool, ScraperError> {
+        let key = request.url.as_str();
+        if self.discovered.contains_key(key) {
+            return Ok(false);
+        }
+        self.append(format!("discovered\t{}\t{}", request.depth, key))?;
+        self.discovered.insert(key.to_string(), request.depth);
+        Ok(true)
+    }
+
+    pub fn mark_fetched(&mut self, url: &Url) -> Result<(), ScraperError> {
+        if self.fetched.insert(url.to_string()) {
+            self.append(format!("fetched\t{}", url))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_fetched(&self, url: &Url) -> bool {
+        self.fetched.contains(url.as_str())
+    }
+
+    pub fn fetched_count(&self) -> usize {
+        self.fetched.len()
+    }
+
+    /// URLs discovered but not yet fetched, shallowest first.
+    pub fn pending(&self) -> Vec<CrawlRequest> {
+        let mut pending: Vec<CrawlRequest> = self
+            .discovered
+            .iter()
+            .filter(|(url, _)| !self.fetched.contains(*url))
+            .filter_map(|(url, &depth)| Url::parse(url).ok().map(|url| CrawlRequest { url, depth }))
+            .collect();
+        pending.sort_by(|a, b| (a.depth, a.url.as_str()).cmp(&(b.depth, b.url.as_str())));
+        pending
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub canonical: Option<Url>,
+    pub links: Vec<Url>,
+    pub noindex: bool,
+    pub nofollow: bool,
+}
+
+/// Extract metadata and followable links from an HTML page. Links are
+/// resolved against `<base href>` when present, canonicalized and
+/// deduplicated; `rel="nofollow"` links and non-http(s) targets are skipped.
+pub fn extract_page(page_url: &Url, html: &str) -> PageMetadata {
+    let document = Html::parse_document(html);
+    let selector = |css: &str| Selector::parse(css).expect("valid selector");
+    let first_attr = |css: &str, attr: &str| {
+        document
+            .select(&selector(css))
+            .find_map(|el| el.value().attr(attr).map(|v| v.trim().to_string()))
+    };
+
+    let base = first_attr("base[href]", "href")
+        .and_then(|href| page_url.join(&href).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let title = document
+        .select(&selector("title"))
+        .next()
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .filter(|t| !t.is_empty());
+    let description = first_attr("meta[name=description]", "content");
+    let canonical = first_attr("link[rel=canonical]", "href")
+        .and_then(|href| canonicalize_url(&href, Some(&base)).ok());
+    let robots = first_attr("meta[name=robots]", "content")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let mut seen = HashSet::new();
+    let links = document
+        .select(&selector("a[href]"))
+        .filter(|el| {
+            !el.value().attr("rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("nofollow"))
+            })
+        })
+        .filter_map(|el| canonicalize_url(el.value().attr("href")?, Some(&base)).ok())
+        .filter(|url| seen.insert(url.to_string()))
+        .collect();
+
+    PageMetadata {
+        title,
+        description,
+        canonical,
+        links,
+        noindex: robots.contains("noindex"),
+        nofollow: robots.contains("nofollow"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CrawlConfig {
+    pub user_agent: String,
+    pub scope: CrawlScope,
+    pub default_delay: Duration,
+    pub max_pages: usize,
+    pub timeout_ms: u64,
+    pub retry: RetryConfig,
+    pub robots_ttl: Duration,
+    /// Breaker, rate and concurrency limits applied to each crawled host.
+    pub host_policy: HostPolicy,
+}
+
+impl CrawlConfig {
+    pub fn new(user_agent: &str, scope: CrawlScope) -> Self {
+        Self {
+            user_agent: user_agent.to_string(),
+            scope,
+            default_delay: Duration::from_secs(1),
+            max_pages: 100,
+            timeout_ms: 10_000,
+            retry: RetryConfig::default(),
+            robots_ttl: Duration::from_secs(24 * 60 * 60),
+            host_policy: HostPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CrawledPage {
+    pub url: Url,
+    pub depth: usize,
+    pub result: Result<PageMetadata, ScraperError>,
+}
+
+#[derive(Debug, Default)]
+pub struct CrawlReport {
+    pub pages: Vec<CrawledPage>,
+    pub blocked: Vec<Url>,
+}
+
+/// A breadth-first crawler that honours robots.txt and per-host delays, and
+/// fetches through a [`HostPool`] so each host's breaker and limits apply.
+pub struct Crawler {
+    client: Client,
+    config: CrawlConfig,
+    robots: RobotsCache,
+    frontier: Frontier,
+    visited: VisitedStore,
+    hosts: Arc<HostPool>,
+}
+
+impl Crawler {
+    /// Create a crawler, re-queueing whatever `visited` recorded as
+    /// discovered but not yet fetched.
+    pub fn new(config: CrawlConfig, visited: VisitedStore) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .user_agent(config.user_agent.clone())
+            .build()
+            .expect("HTTP client");
+        let mut frontier = Frontier::new();
+        for request in visited.pending() {
+            frontier.push(request);
+        }
+        Self {
+            client,
+            robots: RobotsCache::new(config.robots_ttl),
+            hosts: HostPool::new(config.host_policy.clone()),
+            config,
+            frontier,
+            visited,
+        }
+    }
+
+    /// Share limits with other crawlers or fetchers instead of using a pool
+    /// built from `config.host_policy`.
+    pub fn with_host_pool(mut self, hosts: Arc<HostPool>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    pub fn seed(&mut self, url: &str) -> Result<(), ScraperError> {
+        let request = CrawlRequest {
+            url: canonicalize_url(url, None)?,
+            depth: 0,
+        };
+        if self.visited.discover(&request)? {
+            self.frontier.push(request);
+        }
+        Ok(())
+    }
+
+    pub fn visited(&self) -> &VisitedStore {
+        &self.visited
+    }
+
+    pub fn host_pool(&self) -> &Arc<HostPool> {
+        &self.hosts
+    }
+
+    /// Crawl until the frontier is exhausted or `max_pages` pages have been
+    /// fetched in this run.
+    pub async fn run(&mut self) -> Result<CrawlReport, ScraperError> {
+        let mut report = CrawlReport::default();
+        while report.pages.len() < self.config.max_pages {
+            let request = match self.frontier.poll(Instant::now()) {
+                FrontierPoll::Empty => break,
+                FrontierPoll::Wait(delay) => {
+                    sleep(delay).await;
+                    continue;
+                }
+                FrontierPoll::Ready(request) => request,
+            };
+            if self.visited.is_fetched(&request.url) {
+                continue;
+            }
+
+            let robots = self.robots.get(&self.client, &request.url).await;
+            let target = match request.url.query() {
+                Some(query) => format!("{}?{}", request.url.path(), query),
+                None => request.url.path().to_string(),
+            };
+            if !robots.is_allowed(&self.config.user_agent, &target) {
+                self.visited.mark_fetched(&request.url)?;
+                report.blocked.push(request.url);
+                continue;
+            }
+
+            let fetched = match self.hosts.acquire(request.url.as_str()).await {
+                Ok(permit) => {
+                    let outcome = fetch_with_retry_internal(
+                        &self.client,
+                        request.url.as_str(),
+                        self.config.timeout_ms,
+                        &self.config.retry,
+                    )
+                    .await;
+                    permit.finish(&outcome.result);
+                    outcome.result
+                }
+                Err(err) => Err(err),
+            };
+            let delay = robots
+                .crawl_delay(&self.config.user_agent)
+                .map_or(self.config.default_delay, |d| {
+                    d.max(self.config.default_delay)
+                });
+            self.frontier
+                .record_fetch(&request.url, Instant::now(), delay);
+            self.visited.mark_fetched(&request.url)?;
+
+            let result = fetched.map(|body| extract_page(&request.url, &body));
+            if let Ok(page) = &result {
+                if !page.nofollow {
+                    self.enqueue_links(&page.links, request.depth + 1)?;
+                }
+            }
+            report.pages.push(CrawledPage {
+                url: request.url,
+                depth: request.depth,
+                result,
+            });
+        }
+        Ok(report)
+    }
+
+    fn enqueue_links(&mut self, links: &[Url], depth: usize) -> Result<(), ScraperError> {
+        for url in links {
+            if !self.config.scope.allows(url, depth) {
+                continue;
+            }
+            let request = CrawlRequest {
+                url: url.clone(),
+                depth,
+            };
+            if self.visited.discover(&request)? {
+                self.frontier.push(request);
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Milestone 8: Per-Host Circuit Breakers and Adaptive Concurrency
+// =============================================================================
+
+/// A token bucket holding up to `capacity` tokens and refilled continuously at
+/// `refill_per_second`.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(refill_per_second: f64, capacity: usize) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_second: refill_per_second.max(0.1),
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token, or report how long until one is available.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_second,
+            ))
+        }
+    }
+}
+
+/// Additive-increase/multiplicative-decrease concurrency limit. Each fast
+/// success adds `1 / limit` (about +1 per window of `limit` requests); a
+/// failure or a response slower than `latency_target` multiplies the limit
+/// by `backoff`.
+#[derive(Debug, Clone)]
+pub struct AimdController {
+    limit: f64,
+    min_limit: f64,
+    max_limit: f64,
+    backoff: f64,
+    latency_target: Duration,
+}
+
+impl AimdController {
+    pub fn new(initial: usize, min: usize, max: usize, latency_target: Duration) -> Self {
+        let min_limit = min.max(1) as f64;
+        let max_limit = (max as f64).max(min_limit);
+        Self {
+            limit: (initial as f64).clamp(min_limit, max_limit),
+            min_limit,
+            max_limit,
+            backoff: 0.5,
+            latency_target,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.floor() as usize
+    }
+
+    pub fn on_success(&mut self, latency: Duration) {
+        if latency > self.latency_target {
+            self.decrease();
+        } else {
+            self.limit = (self.limit + 1.0 / self.limit).min(self.max_limit);
+        }
+    }
+
+    pub fn on_failure(&mut self) {
+        self.decrease();
+    }
+
+    fn decrease(&mut self) {
+        self.limit = (self.limit * self.backoff).max(self.min_limit);
+    }
+}
+
+/// Limits applied to every host; each host gets its own breaker, token
+/// bucket and concurrency controller built from these.
+#[derive(Debug, Clone)]
+pub struct HostPolicy {
+    pub failure_threshold: usize,
+    pub breaker_timeout: Duration,
+    pub rate_per_second: f64,
+    pub burst: usize,
+    pub initial_concurrency: usize,
+    pub min_concurrency: usize,
+    pub max_concurrency: usize,
+    pub latency_target: Duration,
+}
+
+impl Default for HostPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            breaker_timeout: Duration::from_secs(30),
+            rate_per_second: 10.0,
+            burst: 10,
+            initial_concurrency: 4,
+            min_concurrency: 1,
+            max_concurrency: 32,
+            latency_target: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct HostMetrics {
+    total: usize,
+    success: usize,
+    failed: usize,
+    rejected: usize,
+    total_duration_ms: u64,
+    max_duration_ms: u64,
+}
+
+struct HostState {
+    breaker: CircuitBreaker,
+    bucket: TokenBucket,
+    aimd: AimdController,
+    in_flight: usize,
+    metrics: HostMetrics,
+    /// Woken whenever a permit on this host is released.
+    slot_freed: Arc<Notify>,
+}
+
+impl HostState {
+    fn new(policy: &HostPolicy) -> Self {
+        Self {
+            breaker: CircuitBreaker::new(policy.failure_threshold, policy.breaker_timeout),
+            bucket: TokenBucket::new(policy.rate_per_second, policy.burst),
+            aimd: AimdController::new(
+                policy.initial_concurrency,
+                policy.min_concurrency,
+                policy.max_concurrency,
+                policy.latency_target,
+            ),
+            in_flight: 0,
+            metrics: HostMetrics::default(),
+            slot_freed: Arc::new(Notify::new()),
+        }
+    }
+
+    fn release(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.slot_freed.notify_waiters();
+    }
+}
+
+/// `FetchSummary` for a single host, plus its current limits.
+#[derive(Debug, Clone)]
+pub struct HostSummary {
+    pub host: String,
+    pub total: usize,
+    pub success: usize,
+    pub failed: usize,
+    pub rejected: usize,
+    pub avg_duration_ms: u64,
+    pub max_duration_ms: u64,
+    pub concurrency_limit: usize,
+    pub circuit: CircuitState,
+}
+
+impl HostSummary {
+    pub fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.success as f64 / self.total as f64) * 100.0
+        }
+    }
+}
+
+/// Breakers, token buckets and adaptive concurrency limits keyed by host, so
+/// that one struggling site neither trips nor throttles the others.
+pub struct HostPool {
+    policy: HostPolicy,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl HostPool {
+    pub fn new(policy: HostPolicy) -> Arc<Self> {
+        Arc::new(Self {
+            policy,
+            hosts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Wait for a slot on `url`'s host. Fails immediately with
+    /// `CircuitBreakerOpen` while that host's breaker is open.
+    pub async fn acquire(self: &Arc<Self>, url: &str) -> Result<HostPermit, ScraperError> {
+        let host = Url::parse(url)
+            .ok()
+            .filter(|u| u.host_str().is_some())
+            .map(|u| host_key(&u))
+            .ok_or_else(|| ScraperError::InvalidUrl(url.to_string()))?;
+
+        // Host states are never removed, so this handle stays valid.
+        let slot_freed = self
+            .hosts
+            .lock()
+            .unwrap()
+            .entry(host.clone())
+            .or_insert_with(|| HostState::new(&self.policy))
+            .slot_freed
+            .clone();
+
+        loop {
+            // Registered before the lock is released, so a permit dropped in
+            // between still wakes us.
+            let freed = slot_freed.notified();
+            tokio::pin!(freed);
+            let wait = {
+                let mut hosts = self.hosts.lock().unwrap();
+                let state = hosts
+                    .get_mut(&host)
+                    .expect("host state is created above");
+                if !state.breaker.should_attempt() {
+                    state.metrics.rejected += 1;
+                    return Err(ScraperError::CircuitBreakerOpen);
+                }
+                if state.in_flight >= state.aimd.limit() {
+                    freed.as_mut().enable();
+                    None
+                } else {
+                    match state.bucket.try_acquire(Instant::now()) {
+                        Ok(()) => {
+                            state.in_flight += 1;
+                            return Ok(HostPermit {
+                                pool: self.clone(),
+                                host,
+                                started: Instant::now(),
+                                finished: false,
+                            });
+                        }
+                        Err(wait) => Some(wait),
+                    }
+                }
+            };
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => freed.await,
+            }
+        }
+    }
+
+    pub fn summary(&self) -> Vec<HostSummary> {
+        let hosts = self.hosts.lock().unwrap();
+        let mut summaries: Vec<HostSummary> = hosts
+            .iter()
+            .map(|(host, state)| {
+                let m = &state.metrics;
+                HostSummary {
+                    host: host.clone(),
+                    total: m.total,
+                    success: m.success,
+                    failed: m.failed,
+                    rejected: m.rejected,
+                    avg_duration_ms: if m.total > 0 {
+                        m.total_duration_ms / m.total as u64
+                    } else {
+                        0
+                    },
+                    max_duration_ms: m.max_duration_ms,
+                    concurrency_limit: state.aimd.limit(),
+                    circuit: state.breaker.state(),
+                }
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.host.cmp(&b.host));
+        summaries
+    }
+}
+
+/// A claimed slot on one host. Report the outcome with `finish`; dropping
+/// the permit without it just frees the slot.
+pub struct HostPermit {
+    pool: Arc<HostPool>,
+    host: String,
+    started: Instant,
+    finished: bool,
+}
+
+impl HostPermit {
+    /// Feed the outcome back into the host's metrics, breaker and
+    /// concurrency limit. Only retryable errors (network, timeout, 5xx)
+    /// count against the host; a 404 says nothing about its health.
+    pub fn finish<T>(mut self, result: &Result<T, ScraperError>) {
+        let latency = self.started.elapsed();
+        let mut hosts = self.pool.hosts.lock().unwrap();
+        if let Some(state) = hosts.get_mut(&self.host) {
+            state.release();
+            let millis = latency.as_millis() as u64;
+            state.metrics.total += 1;
+            state.metrics.total_duration_ms += millis;
+            state.metrics.max_duration_ms = state.metrics.max_duration_ms.max(millis);
+            match result {
+                Ok(_) => {
+                    state.metrics.success += 1;
+                    state.breaker.on_success();
+                    state.aimd.on_success(latency);
+                }
+                Err(err) => {
+                    state.metrics.failed += 1;
+                    if err.is_retryable() {
+                        state.breaker.on_failure();
+                        state.aimd.on_failure();
+                    }
+                }
+            }
+        }
+        self.finished = true;
+    }
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Ok(mut hosts) = self.pool.hosts.lock() {
+            if let Some(state) = hosts.get_mut(&self.host) {
+                state.release();
+            }
+        }
+    }
+}
+
+pub async fn fetch_all_per_host(
+    urls: Vec<String>,
+    timeout_ms: u64,
+    retry_config: &RetryConfig,
+    pool: &Arc<HostPool>,
+) -> Vec<FetchResult> {
+    let client = create_client(timeout_ms);
+
+    let futures = urls.into_iter().map(|url| {
+        let client = client.clone();
+        let retry = retry_config.clone();
+        let pool = pool.clone();
+
+        async move {
+            let start = Instant::now();
+            let permit = match pool.acquire(&url).await {
+                Ok(permit) => permit,
+                Err(err) => {
+                    return FetchResult {
+                        url,
+                        result: Err(err),
+                        duration_ms: 0,
+                        attempt_count: 0,
+                    }
+                }
+            };
+
+            let outcome = fetch_with_retry_internal(&client, &url, timeout_ms, &retry).await;
+            permit.finish(&outcome.result);
+
+            FetchResult {
+                url,
+                result: outcome.result,
+                duration_ms: start.elapsed().as_millis() as u64,
+                attempt_count: outcome.attempt_count,
+            }
+        }
+    });
+
+    join_all(futures).await
+}
+
 // =============================================================================
 // Tests for All Milestones
 // =============================================================================
@@ -839,6 +1918,473 @@ mod tests {
         assert_eq!(results.len(), 3);
         assert!(results.iter().all(|r| r.is_success()));
     }
+
+    #[test]
+    fn test_canonicalize_url() {
+        let base = Url::parse("http://Example.COM:80/dir/page.html").unwrap();
+        let url = canonicalize_url("../a/./b?z=1&a=2#frag", Some(&base)).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/a/b?a=2&z=1");
+
+        let url = canonicalize_url("https://example.com/x?", None).unwrap();
+        assert_eq!(url.as_str(), "https://example.com/x");
+
+        assert!(matches!(
+            canonicalize_url("mailto:someone@example.com", None),
+            Err(ScraperError::InvalidUrl(_))
+        ));
+        assert!(canonicalize_url("not a url", None).is_err());
+    }
+
+    #[test]
+    fn test_robots_txt_rules() {
+        let robots = RobotsTxt::parse(
+            "# comment\n\
+             User-agent: *\n\
+             Disallow: /\n\
+             \n\
+             User-agent: TestBot\n\
+             User-agent: otherbot\n\
+             Disallow: /private\n\
+             Allow: /private/open$\n\
+             Disallow: /*.pdf$\n\
+             Allow: /docs/\n\
+             Disallow: /docs\n\
+             Crawl-delay: 2.5\n\
+             Sitemap: http://example.com/sitemap.xml\n",
+        );
+
+        assert!(robots.is_allowed("testbot/1.0", "/"));
+        assert!(!robots.is_allowed("testbot/1.0", "/private/x"));
+        assert!(robots.is_allowed("testbot/1.0", "/private/open"));
+        assert!(!robots.is_allowed("testbot/1.0", "/private/open/x"));
+        assert!(!robots.is_allowed("testbot/1.0", "/files/report.pdf"));
+        assert!(robots.is_allowed("testbot/1.0", "/files/report.pdf?download=1"));
+        assert!(robots.is_allowed("testbot/1.0", "/docs/"));
+        assert!(!robots.is_allowed("testbot/1.0", "/docs"));
+        assert!(!robots.is_allowed("somebot", "/anything"));
+
+        assert_eq!(
+            robots.crawl_delay("TestBot"),
+            Some(Duration::from_millis(2_500))
+        );
+        assert_eq!(robots.crawl_delay("somebot"), None);
+        assert_eq!(robots.sitemaps, vec!["http://example.com/sitemap.xml"]);
+
+        assert!(RobotsTxt::allow_all().is_allowed("any", "/x"));
+        assert!(!RobotsTxt::disallow_all().is_allowed("any", "/x"));
+    }
+
+    #[test]
+    fn test_frontier_politeness() {
+        let mut frontier = Frontier::new();
+        let request = |url: &str| CrawlRequest {
+            url: Url::parse(url).unwrap(),
+            depth: 0,
+        };
+        frontier.push(request("http://a.test/1"));
+        frontier.push(request("http://a.test/2"));
+        frontier.push(request("http://b.test/1"));
+
+        let now = Instant::now();
+        let FrontierPoll::Ready(first) = frontier.poll(now) else {
+            panic!("expected a ready request");
+        };
+        frontier.record_fetch(&first.url, now, Duration::from_millis(100));
+
+        // The other host is served while the first one cools down.
+        let FrontierPoll::Ready(second) = frontier.poll(now) else {
+            panic!("expected a ready request");
+        };
+        assert_ne!(host_key(&first.url), host_key(&second.url));
+        frontier.record_fetch(&second.url, now, Duration::from_millis(200));
+
+        assert_eq!(
+            frontier.poll(now + Duration::from_millis(40)),
+            FrontierPoll::Wait(Duration::from_millis(60))
+        );
+        let later = now + Duration::from_millis(100);
+        assert_eq!(
+            frontier.poll(later),
+            FrontierPoll::Ready(request("http://a.test/2"))
+        );
+        assert_eq!(frontier.poll(later), FrontierPoll::Empty);
+    }
+
+    #[test]
+    fn test_crawl_scope() {
+        let scope = CrawlScope::new(2).allow_domain("example.com");
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(scope.allows(&url("http://example.com/"), 2));
+        assert!(scope.allows(&url("http://docs.example.com/"), 1));
+        assert!(!scope.allows(&url("http://notexample.com/"), 1));
+        assert!(!scope.allows(&url("http://example.com/"), 3));
+        assert!(CrawlScope::new(0).allows(&url("http://anything.test/"), 0));
+    }
+
+    #[test]
+    fn test_extract_page() {
+        let page = Url::parse("http://example.com/blog/post").unwrap();
+        let html = r#"
+            <html><head>
+              <title> Hello </title>
+              <meta name="description" content="A post">
+              <meta name="robots" content="NOINDEX">
+              <link rel="canonical" href="/blog/post?ref=x#top">
+              <base href="/root/">
+            </head><body>
+              <a href="a.html">A</a>
+              <a href="a.html#section">A again</a>
+              <a href="http://other.test/x">Other</a>
+              <a href="/paid" rel="sponsored nofollow">Ad</a>
+              <a href="javascript:void(0)">JS</a>
+              <a href="mailto:me@example.com">Mail</a>
+            </body></html>"#;
+
+        let meta = extract_page(&page, html);
+        assert_eq!(meta.title.as_deref(), Some("Hello"));
+        assert_eq!(meta.description.as_deref(), Some("A post"));
+        assert_eq!(
+            meta.canonical.as_ref().map(Url::as_str),
+            Some("http://example.com/blog/post?ref=x")
+        );
+        assert!(meta.noindex);
+        assert!(!meta.nofollow);
+        let links: Vec<&str> = meta.links.iter().map(Url::as_str).collect();
+        assert_eq!(
+            links,
+            vec!["http://example.com/root/a.html", "http://other.test/x"]
+        );
+    }
+
+    async fn mount_page(server: &MockServer, route: &str, body: &str, expected_hits: u64) {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html")
+                    .set_body_string(body),
+            )
+            .expect(expected_hits)
+            .mount(server)
+            .await;
+    }
+
+    /// Each crawler fetches robots.txt once, so the expected count is the
+    /// number of crawlers run against the site.
+    async fn test_site(robots_fetches: u64) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    "User-agent: testbot\nDisallow: /private\nAllow: /private/open\n",
+                ),
+            )
+            .expect(robots_fetches)
+            .mount(&server)
+            .await;
+        mount_page(
+            &server,
+            "/",
+            r#"<a href="/a">a</a> <a href="/a#top">a</a> <a href="/b?y=2&x=1">b</a>
+               <a href="/private/x">x</a> <a href="/private/open">open</a>
+               <a href="http://external.invalid/">ext</a>"#,
+            1,
+        )
+        .await;
+        mount_page(
+            &server,
+            "/a",
+            r#"<a href="/b?x=1&y=2">b</a> <a href="c">c</a>"#,
+            1,
+        )
+        .await;
+        mount_page(
+            &server,
+            "/b",
+            r#"<title>B</title><a href="/deep">deep</a>"#,
+            1,
+        )
+        .await;
+        mount_page(&server, "/c", "<title>C</title>", 1).await;
+        mount_page(&server, "/private/open", "<title>open</title>", 1).await;
+        mount_page(&server, "/deep", r#"<a href="/deeper">deeper</a>"#, 1).await;
+        mount_page(&server, "/deeper", "", 0).await;
+        mount_page(&server, "/private/x", "", 0).await;
+        server
+    }
+
+    fn test_config(server: &MockServer) -> CrawlConfig {
+        let host = Url::parse(&server.uri()).unwrap();
+        let mut config = CrawlConfig::new(
+            "TestBot/1.0",
+            CrawlScope::new(2).allow_domain(host.host_str().unwrap()),
+        );
+        config.default_delay = Duration::from_millis(0);
+        config.retry = RetryConfig {
+            max_attempts: 1,
+            ..RetryConfig::default()
+        };
+        config
+    }
+
+    fn crawled_paths(report: &CrawlReport) -> Vec<String> {
+        let mut paths: Vec<String> = report
+            .pages
+            .iter()
+            .map(|p| match p.url.query() {
+                Some(q) => format!("{}?{}", p.url.path(), q),
+                None => p.url.path().to_string(),
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn test_crawler_respects_robots_scope_and_dedup() {
+        let server = test_site(1).await;
+        let mut crawler = Crawler::new(test_config(&server), VisitedStore::in_memory());
+        crawler.seed(&server.uri()).unwrap();
+
+        let report = crawler.run().await.unwrap();
+        assert_eq!(
+            crawled_paths(&report),
+            vec!["/", "/a", "/b?x=1&y=2", "/c", "/deep", "/private/open"]
+        );
+        assert!(report.pages.iter().all(|p| p.result.is_ok()));
+        let blocked: Vec<&str> = report.blocked.iter().map(|u| u.path()).collect();
+        assert_eq!(blocked, vec!["/private/x"]);
+
+        let b = report.pages.iter().find(|p| p.url.path() == "/b").unwrap();
+        assert_eq!(b.depth, 1);
+        assert_eq!(b.result.as_ref().unwrap().title.as_deref(), Some("B"));
+        // Mock expectations (each page once, nothing past depth 2) are
+        // verified when the server is dropped.
+    }
+
+    #[tokio::test]
+    async fn test_crawler_resumes_from_visited_store() {
+        let server = test_site(2).await;
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("visited.log");
+
+        let mut config = test_config(&server);
+        config.max_pages = 2;
+        let mut crawler = Crawler::new(config, VisitedStore::open(&journal).unwrap());
+        crawler.seed(&server.uri()).unwrap();
+        let first = crawler.run().await.unwrap();
+        assert_eq!(first.pages.len(), 2);
+        drop(crawler);
+
+        // A fresh process: re-seeding is a no-op and the pending URLs resume.
+        let mut crawler = Crawler::new(test_config(&server), VisitedStore::open(&journal).unwrap());
+        crawler.seed(&server.uri()).unwrap();
+        let second = crawler.run().await.unwrap();
+
+        let mut all = crawled_paths(&first);
+        all.extend(crawled_paths(&second));
+        all.sort();
+        assert_eq!(
+            all,
+            vec!["/", "/a", "/b?x=1&y=2", "/c", "/deep", "/private/open"]
+        );
+        assert_eq!(crawler.visited().fetched_count(), 7);
+        assert!(crawler.visited().pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crawler_applies_host_breaker() {
+        let server = MockServer::start().await;
+        mount_page(
+            &server,
+            "/",
+            r#"<a href="/a">a</a> <a href="/b">b</a> <a href="/c">c</a>"#,
+            1,
+        )
+        .await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let mut config = test_config(&server);
+        config.host_policy = HostPolicy {
+            failure_threshold: 2,
+            rate_per_second: 1_000.0,
+            ..HostPolicy::default()
+        };
+        let mut crawler = Crawler::new(config, VisitedStore::in_memory());
+        crawler.seed(&server.uri()).unwrap();
+        let report = crawler.run().await.unwrap();
+
+        let outcomes: Vec<(&str, bool)> = report
+            .pages
+            .iter()
+            .map(|p| {
+                let open = matches!(p.result, Err(ScraperError::CircuitBreakerOpen));
+                (p.url.path(), open)
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![("/", false), ("/a", false), ("/b", false), ("/c", true)]
+        );
+        let summary = &crawler.host_pool().summary()[0];
+        assert_eq!((summary.success, summary.failed, summary.rejected), (1, 2, 1));
+        assert!(matches!(summary.circuit, CircuitState::Open { .. }));
+    }
+
+    #[test]
+    fn test_token_bucket_refills() {
+        let mut bucket = TokenBucket::new(10.0, 2);
+        let start = bucket.last_refill;
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert_eq!(wait.as_millis(), 100);
+
+        assert!(bucket
+            .try_acquire(start + Duration::from_millis(100))
+            .is_ok());
+        // Refill is capped at the bucket's capacity.
+        let later = start + Duration::from_secs(10);
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_err());
+    }
+
+    #[test]
+    fn test_aimd_controller() {
+        let mut aimd = AimdController::new(4, 1, 6, Duration::from_millis(100));
+        let fast = Duration::from_millis(10);
+
+        // Roughly one window of fast successes adds one slot.
+        for _ in 0..5 {
+            aimd.on_success(fast);
+        }
+        assert_eq!(aimd.limit(), 5);
+
+        aimd.on_failure();
+        assert_eq!(aimd.limit(), 2);
+        aimd.on_success(Duration::from_millis(500));
+        assert_eq!(aimd.limit(), 1);
+        aimd.on_failure();
+        assert_eq!(aimd.limit(), 1);
+
+        for _ in 0..100 {
+            aimd.on_success(fast);
+        }
+        assert_eq!(aimd.limit(), 6);
+    }
+
+    fn summary_for<'a>(summaries: &'a [HostSummary], server: &MockServer) -> &'a HostSummary {
+        let host = host_key(&Url::parse(&server.uri()).unwrap());
+        summaries.iter().find(|s| s.host == host).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_per_host_breaker_isolates_failing_host() {
+        let healthy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&healthy)
+            .await;
+        let failing = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&failing)
+            .await;
+
+        let policy = HostPolicy {
+            failure_threshold: 2,
+            initial_concurrency: 1,
+            rate_per_second: 1_000.0,
+            ..HostPolicy::default()
+        };
+        let pool = HostPool::new(policy);
+        let cfg = RetryConfig {
+            max_attempts: 1,
+            ..RetryConfig::default()
+        };
+        let urls: Vec<String> = (0..5)
+            .flat_map(|i| {
+                [
+                    format!("{}/{}", healthy.uri(), i),
+                    format!("{}/{}", failing.uri(), i),
+                ]
+            })
+            .collect();
+
+        let results = fetch_all_per_host(urls, 1_000, &cfg, &pool).await;
+        let healthy_results: Vec<_> = results
+            .iter()
+            .filter(|r| r.url.starts_with(&healthy.uri()))
+            .collect();
+        assert!(healthy_results.iter().all(|r| r.is_success()));
+        assert!(results
+            .iter()
+            .any(|r| matches!(r.result, Err(ScraperError::CircuitBreakerOpen))));
+
+        let summaries = pool.summary();
+        let good = summary_for(&summaries, &healthy);
+        assert_eq!((good.total, good.success, good.rejected), (5, 5, 0));
+        assert_eq!(good.circuit, CircuitState::Closed);
+        assert!((good.success_rate() - 100.0).abs() < f64::EPSILON);
+
+        let bad = summary_for(&summaries, &failing);
+        assert_eq!((bad.total, bad.failed, bad.rejected), (2, 2, 3));
+        assert!(matches!(bad.circuit, CircuitState::Open { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_concurrency_follows_latency() {
+        let fast = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&fast)
+            .await;
+        let slow = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+            .mount(&slow)
+            .await;
+
+        let policy = HostPolicy {
+            initial_concurrency: 2,
+            min_concurrency: 1,
+            max_concurrency: 8,
+            rate_per_second: 1_000.0,
+            burst: 100,
+            latency_target: Duration::from_millis(50),
+            ..HostPolicy::default()
+        };
+        let pool = HostPool::new(policy);
+        let cfg = RetryConfig::default();
+        let urls: Vec<String> = (0..12)
+            .flat_map(|i| {
+                [
+                    format!("{}/{}", fast.uri(), i),
+                    format!("{}/{}", slow.uri(), i),
+                ]
+            })
+            .collect();
+
+        let start = Instant::now();
+        let results = fetch_all_per_host(urls, 1_000, &cfg, &pool).await;
+        assert!(results.iter().all(|r| r.is_success()));
+
+        let summaries = pool.summary();
+        assert!(summary_for(&summaries, &fast).concurrency_limit > 2);
+        assert_eq!(summary_for(&summaries, &slow).concurrency_limit, 1);
+        // With at most two slow requests in flight, twelve take >= 600ms.
+        assert!(start.elapsed() >= Duration::from_millis(600));
+    }
 }
 
 fn main() {}
@@ -1,6 +1,6 @@
 use csv::{Reader, ReaderBuilder, StringRecord};
 use rayon::prelude::*;
-use rusqlite::{Connection, Transaction};
+use rusqlite::{params, params_from_iter, types::Value, Connection, Transaction};
 use std::{
     cmp::Ordering,
     collections::HashSet,
@@ -313,6 +313,12 @@ pub struct ImportStats {
     pub records_failed: usize,
     pub batches_processed: usize,
     pub duration_ms: u64,
+    pub records_inserted: usize,
+    pub records_updated: usize,
+    pub records_rejected: usize,
+    /// Rows whose key already existed and that `ON CONFLICT DO NOTHING` left as-is
+    pub records_skipped: usize,
+    pub migrations: Vec<String>,
 }
 
 /// Import with detailed statistics
@@ -463,6 +469,531 @@ pub fn benchmark_parallel(path: &str, chunk_size: usize) {
     println!("Parallel: {:?}", par_time);
 }
 
+// =============================================================================
+// Milestone 7: Schema Inference, Upserts and Migrations
+// =============================================================================
+
+/// SQLite column type inferred from sampled values. Variants are ordered by
+/// how much they accept, so a column's type is the maximum over its samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnType {
+    /// Type of a single non-empty value
+    /// Role: Numbers with leading zeros ("007", zip codes) stay TEXT so the zeros survive
+    /// Role: Only values with a `.` or an exponent are REAL; integers too big for i64
+    /// (20-digit IDs) stay TEXT instead of losing digits as floats
+    pub fn of(value: &str) -> Self {
+        let digits = value.strip_prefix(['+', '-']).unwrap_or(value).as_bytes();
+        let fractional = value.contains(['.', 'e', 'E']);
+        if digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit() {
+            ColumnType::Text
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if fractional && value.parse::<f64>().is_ok_and(f64::is_finite) {
+            ColumnType::Real
+        } else {
+            ColumnType::Text
+        }
+    }
+
+    pub fn sql(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Text => "TEXT",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// Table layout inferred from a CSV header and a sample of its rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub table: String,
+    pub columns: Vec<ColumnSchema>,
+    pub primary_key: Vec<String>,
+    pub unique_keys: Vec<Vec<String>>,
+}
+
+/// Quote an SQL identifier
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_list(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| quote_ident(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl TableSchema {
+    /// Infer column types from sampled rows
+    /// Role: Empty cells make a column nullable; a column with no values at all is TEXT
+    pub fn infer(table: &str, headers: &StringRecord, sample: &[StringRecord]) -> Self {
+        let columns = headers
+            .iter()
+            .enumerate()
+            .map(|(idx, header)| {
+                let name = match header.trim() {
+                    "" => format!("column_{}", idx + 1),
+                    trimmed => trimmed.to_string(),
+                };
+                let values: Vec<&str> = sample
+                    .iter()
+                    .map(|row| row.get(idx).unwrap_or("").trim())
+                    .collect();
+                let column_type = values
+                    .iter()
+                    .filter(|value| !value.is_empty())
+                    .map(|value| ColumnType::of(value))
+                    .max()
+                    .unwrap_or(ColumnType::Text);
+                ColumnSchema {
+                    name,
+                    column_type,
+                    nullable: values.iter().any(|value| value.is_empty()),
+                }
+            })
+            .collect();
+
+        Self {
+            table: table.to_string(),
+            columns,
+            primary_key: Vec::new(),
+            unique_keys: Vec::new(),
+        }
+    }
+
+    /// Set the primary key
+    /// Role: Key columns must always be present, so they become NOT NULL
+    pub fn with_primary_key(mut self, columns: &[&str]) -> Result<Self, ParseError> {
+        self.primary_key = self.key_columns(columns)?;
+        for column in &mut self.columns {
+            if self.primary_key.contains(&column.name) {
+                column.nullable = false;
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn with_unique_key(mut self, columns: &[&str]) -> Result<Self, ParseError> {
+        let key = self.key_columns(columns)?;
+        self.unique_keys.push(key);
+        Ok(self)
+    }
+
+    fn key_columns(&self, columns: &[&str]) -> Result<Vec<String>, ParseError> {
+        columns
+            .iter()
+            .map(|name| {
+                self.column(name)
+                    .map(|column| column.name.clone())
+                    .ok_or_else(|| ParseError::MissingField(name.to_string()))
+            })
+            .collect()
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Key used as the `ON CONFLICT` target: the primary key, else the first unique key
+    pub fn conflict_key(&self) -> Option<&[String]> {
+        if !self.primary_key.is_empty() {
+            Some(&self.primary_key)
+        } else {
+            self.unique_keys.first().map(|key| key.as_slice())
+        }
+    }
+
+    pub fn create_table_sql(&self) -> String {
+        let mut lines: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "    {} {}{}",
+                    quote_ident(&column.name),
+                    column.column_type.sql(),
+                    if column.nullable { "" } else { " NOT NULL" }
+                )
+            })
+            .collect();
+        if !self.primary_key.is_empty() {
+            lines.push(format!(
+                "    PRIMARY KEY ({})",
+                quote_list(&self.primary_key)
+            ));
+        }
+        for key in &self.unique_keys {
+            lines.push(format!("    UNIQUE ({})", quote_list(key)));
+        }
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n{}\n)",
+            quote_ident(&self.table),
+            lines.join(",\n")
+        )
+    }
+
+    /// INSERT that updates the existing row when the conflict key matches
+    pub fn upsert_sql(&self) -> String {
+        let names: Vec<String> = self.columns.iter().map(|c| c.name.clone()).collect();
+        let placeholders: Vec<String> = (1..=names.len()).map(|i| format!("?{}", i)).collect();
+        let mut sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_ident(&self.table),
+            quote_list(&names),
+            placeholders.join(", ")
+        );
+        if let Some(key) = self.conflict_key() {
+            let updates: Vec<String> = names
+                .iter()
+                .filter(|name| !key.contains(name))
+                .map(|name| format!("{0} = excluded.{0}", quote_ident(name)))
+                .collect();
+            let action = if updates.is_empty() {
+                "DO NOTHING".to_string()
+            } else {
+                format!("DO UPDATE SET {}", updates.join(", "))
+            };
+            sql.push_str(&format!(" ON CONFLICT ({}) {}", quote_list(key), action));
+        }
+        sql
+    }
+
+    /// Convert a CSV row into typed SQL values in column order
+    pub fn convert_row(&self, row: &StringRecord) -> Result<Vec<Value>, ParseError> {
+        if row.len() != self.columns.len() {
+            return Err(ParseError::InvalidFormat(format!(
+                "expected {} fields, found {}",
+                self.columns.len(),
+                row.len()
+            )));
+        }
+
+        self.columns
+            .iter()
+            .zip(row.iter())
+            .map(|(column, raw)| {
+                let raw = raw.trim();
+                if raw.is_empty() {
+                    return if column.nullable {
+                        Ok(Value::Null)
+                    } else {
+                        Err(ParseError::MissingField(column.name.clone()))
+                    };
+                }
+                let invalid = || ParseError::InvalidType {
+                    field: column.name.clone(),
+                    value: raw.to_string(),
+                };
+                match column.column_type {
+                    ColumnType::Integer => raw.parse().map(Value::Integer).map_err(|_| invalid()),
+                    ColumnType::Real => raw.parse().map(Value::Real).map_err(|_| invalid()),
+                    ColumnType::Text => Ok(Value::Text(raw.to_string())),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Bring the database in line with `schema`
+/// Role: Create the table if missing, otherwise ADD COLUMN for every new CSV column
+/// and CREATE UNIQUE INDEX for every key the table does not enforce yet
+/// Returns the statements that were executed. Columns that disappeared from the
+/// CSV are left in place, and added columns are nullable because SQLite cannot
+/// add a NOT NULL column without a default.
+pub fn migrate_schema(
+    conn: &Connection,
+    schema: &TableSchema,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "PRAGMA table_info({})",
+        quote_ident(&schema.table)
+    ))?;
+    // (name, position in the primary key, 0 if not part of it)
+    let existing: Vec<(String, i64)> = stmt
+        .query_map([], |row| Ok((row.get(1)?, row.get(5)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let statements: Vec<String> = if existing.is_empty() {
+        vec![schema.create_table_sql()]
+    } else {
+        let mut primary_key: Vec<String> = existing
+            .iter()
+            .filter(|(_, pk)| *pk > 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        primary_key.sort_unstable();
+        let unique_keys = unique_indexes(conn, &schema.table)?;
+
+        let add_columns = schema
+            .columns
+            .iter()
+            .filter(|column| !existing.iter().any(|(name, _)| name == &column.name))
+            .map(|column| {
+                format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    quote_ident(&schema.table),
+                    quote_ident(&column.name),
+                    column.column_type.sql()
+                )
+            });
+        // ON CONFLICT needs a unique index on exactly the key columns
+        let add_keys = Some(&schema.primary_key)
+            .filter(|key| !key.is_empty())
+            .into_iter()
+            .chain(&schema.unique_keys)
+            .filter(|key| {
+                let mut key = key.to_vec();
+                key.sort_unstable();
+                key != primary_key && !unique_keys.contains(&key)
+            })
+            .map(|key| {
+                format!(
+                    "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {} ({})",
+                    quote_ident(&format!("{}_{}_key", schema.table, key.join("_"))),
+                    quote_ident(&schema.table),
+                    quote_list(key)
+                )
+            });
+        add_columns.chain(add_keys).collect()
+    };
+
+    for sql in &statements {
+        conn.execute(sql, [])?;
+    }
+    Ok(statements)
+}
+
+/// Column sets (sorted) of the unique indexes on `table`
+fn unique_indexes(conn: &Connection, table: &str) -> Result<Vec<Vec<String>>, rusqlite::Error> {
+    let mut list = conn.prepare(&format!("PRAGMA index_list({})", quote_ident(table)))?;
+    let names: Vec<String> = list
+        .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, bool>(2)?)))?
+        .filter_map(|row| match row {
+            Ok((name, true)) => Some(Ok(name)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    names
+        .iter()
+        .map(|name| {
+            let mut info = conn.prepare(&format!("PRAGMA index_info({})", quote_ident(name)))?;
+            let mut columns: Vec<String> = info
+                .query_map([], |row| row.get(2))?
+                .collect::<Result<_, _>>()?;
+            columns.sort_unstable();
+            Ok(columns)
+        })
+        .collect()
+}
+
+/// Create the side table that holds rejected rows
+pub fn create_quarantine_table(conn: &Connection, table: &str) -> Result<String, rusqlite::Error> {
+    let name = format!("{}_rejects", table);
+    conn.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                line INTEGER NOT NULL,
+                raw TEXT NOT NULL,
+                reason TEXT NOT NULL
+            )",
+            quote_ident(&name)
+        ),
+        [],
+    )?;
+    Ok(name)
+}
+
+/// Options for schema-inferring imports
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub table: String,
+    pub primary_key: Vec<String>,
+    pub unique_keys: Vec<Vec<String>>,
+    pub sample_size: usize,
+    pub batch_size: usize,
+}
+
+impl ImportOptions {
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            primary_key: Vec::new(),
+            unique_keys: Vec::new(),
+            sample_size: 1_000,
+            batch_size: 500,
+        }
+    }
+
+    pub fn primary_key(mut self, columns: &[&str]) -> Self {
+        self.primary_key = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn unique_key(mut self, columns: &[&str]) -> Self {
+        self.unique_keys
+            .push(columns.iter().map(|c| c.to_string()).collect());
+        self
+    }
+}
+
+fn raw_row(row: &StringRecord) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    let _ = writer.write_record(row);
+    let bytes = writer.into_inner().unwrap_or_default();
+    String::from_utf8_lossy(&bytes).trim_end().to_string()
+}
+
+/// Import any CSV into SQLite
+/// Role: Infer the table from a sample, migrate, then upsert in batched transactions
+/// Rows that fail to convert or violate a constraint go to `<table>_rejects`
+/// instead of aborting their batch.
+pub fn import_csv_with_schema(
+    path: &str,
+    db_path: &str,
+    options: &ImportOptions,
+) -> Result<ImportStats, Box<dyn Error>> {
+    let start = Instant::now();
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)?;
+    let headers = reader.headers()?.clone();
+
+    let mut rows = reader.records();
+    let mut sample: Vec<Result<StringRecord, csv::Error>> = Vec::new();
+    for row in rows.by_ref().take(options.sample_size.max(1)) {
+        sample.push(row);
+    }
+    let valid_sample: Vec<StringRecord> = sample
+        .iter()
+        .filter_map(|row| row.as_ref().ok())
+        .filter(|row| row.len() == headers.len())
+        .cloned()
+        .collect();
+
+    let mut schema = TableSchema::infer(&options.table, &headers, &valid_sample);
+    if !options.primary_key.is_empty() {
+        let key: Vec<&str> = options.primary_key.iter().map(String::as_str).collect();
+        schema = schema.with_primary_key(&key)?;
+    }
+    for unique in &options.unique_keys {
+        let key: Vec<&str> = unique.iter().map(String::as_str).collect();
+        schema = schema.with_unique_key(&key)?;
+    }
+
+    let mut conn = Connection::open(db_path)?;
+    let mut stats = ImportStats {
+        migrations: migrate_schema(&conn, &schema)?,
+        ..ImportStats::default()
+    };
+    let rejects_table = create_quarantine_table(&conn, &schema.table)?;
+
+    let upsert_sql = schema.upsert_sql();
+    let reject_sql = format!(
+        "INSERT INTO {} (line, raw, reason) VALUES (?1, ?2, ?3)",
+        quote_ident(&rejects_table)
+    );
+    let key_indexes: Vec<usize> = schema
+        .conflict_key()
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|key| schema.columns.iter().position(|c| &c.name == key))
+        .collect();
+    let exists_sql = format!(
+        "SELECT 1 FROM {} WHERE {} LIMIT 1",
+        quote_ident(&schema.table),
+        key_indexes
+            .iter()
+            .enumerate()
+            .map(|(i, &idx)| format!("{} = ?{}", quote_ident(&schema.columns[idx].name), i + 1))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    );
+
+    let mut all_rows = sample.into_iter().chain(rows).peekable();
+    while all_rows.peek().is_some() {
+        let tx = conn.transaction()?;
+        {
+            let mut upsert = tx.prepare(&upsert_sql)?;
+            let mut reject = tx.prepare(&reject_sql)?;
+            let mut exists = if key_indexes.is_empty() {
+                None
+            } else {
+                Some(tx.prepare(&exists_sql)?)
+            };
+
+            for row in all_rows.by_ref().take(options.batch_size.max(1)) {
+                let (line, raw, converted) = match &row {
+                    Ok(row) => (
+                        row.position().map_or(0, |p| p.line()),
+                        raw_row(row),
+                        schema.convert_row(row),
+                    ),
+                    Err(err) => (
+                        err.position().map_or(0, |p| p.line()),
+                        String::new(),
+                        Err(ParseError::InvalidFormat(err.to_string())),
+                    ),
+                };
+                let values = match converted {
+                    Ok(values) => values,
+                    Err(err) => {
+                        reject.execute(params![line as i64, raw, err.to_string()])?;
+                        stats.records_rejected += 1;
+                        continue;
+                    }
+                };
+
+                let existed = match exists.as_mut() {
+                    Some(stmt) => {
+                        let key: Vec<&Value> = key_indexes.iter().map(|&i| &values[i]).collect();
+                        stmt.exists(params_from_iter(key))?
+                    }
+                    None => false,
+                };
+                // changes() is 0 when DO NOTHING kept the existing row
+                match upsert.execute(params_from_iter(values.iter())) {
+                    Ok(0) => stats.records_skipped += 1,
+                    Ok(_) if existed => stats.records_updated += 1,
+                    Ok(_) => stats.records_inserted += 1,
+                    Err(err) => {
+                        reject.execute(params![line as i64, raw, err.to_string()])?;
+                        stats.records_rejected += 1;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        stats.batches_processed += 1;
+    }
+
+    stats.records_imported = stats.records_inserted + stats.records_updated;
+    stats.records_failed = stats.records_rejected;
+    stats.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(stats)
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -997,6 +1528,343 @@ id,name,email,age,country
         let records = process_csv_parallel(file.path().to_str().unwrap(), 5).unwrap();
         assert_eq!(records.len(), 20);
     }
+
+    // ----- Milestone 7 tests -----
+    fn rows(lines: &[&[&str]]) -> Vec<StringRecord> {
+        lines
+            .iter()
+            .map(|line| StringRecord::from(line.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_column_types() {
+        let headers = StringRecord::from(vec!["id", "score", "name", "note", " "]);
+        let sample = rows(&[
+            &["1", "2", "Alice", "", "x"],
+            &["2", "2.5", "Bob", "", "y"],
+            &["3", "-1e3", "42", "", "z"],
+        ]);
+
+        let schema = TableSchema::infer("people", &headers, &sample);
+        let types: Vec<(&str, ColumnType, bool)> = schema
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.column_type, c.nullable))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("id", ColumnType::Integer, false),
+                ("score", ColumnType::Real, false),
+                ("name", ColumnType::Text, false),
+                ("note", ColumnType::Text, true),
+                ("column_5", ColumnType::Text, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_type_keeps_leading_zeros() {
+        assert_eq!(ColumnType::of("007"), ColumnType::Text);
+        assert_eq!(ColumnType::of("-01"), ColumnType::Text);
+        assert_eq!(ColumnType::of("00.5"), ColumnType::Text);
+        assert_eq!(ColumnType::of("0"), ColumnType::Integer);
+        assert_eq!(ColumnType::of("0.5"), ColumnType::Real);
+        assert_eq!(ColumnType::of("-0.25"), ColumnType::Real);
+
+        let headers = StringRecord::from(vec!["zip"]);
+        let schema = TableSchema::infer("t", &headers, &rows(&[&["94105"], &["02134"]]));
+        assert_eq!(schema.columns[0].column_type, ColumnType::Text);
+        assert_eq!(
+            schema.convert_row(&StringRecord::from(vec!["02134"])).unwrap(),
+            vec![Value::Text("02134".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_column_type_keeps_wide_integers_as_text() {
+        assert_eq!(ColumnType::of("12345678901234567890"), ColumnType::Text);
+        assert_eq!(ColumnType::of("9223372036854775807"), ColumnType::Integer);
+        assert_eq!(ColumnType::of("1.5E3"), ColumnType::Real);
+        assert_eq!(ColumnType::of("inf"), ColumnType::Text);
+
+        let headers = StringRecord::from(vec!["id", "amount"]);
+        let sample = rows(&[&["12345678901234567890", "1.25"], &["98765432109876543210", "2"]]);
+        let schema = TableSchema::infer("orders", &headers, &sample);
+        assert_eq!(schema.columns[0].column_type, ColumnType::Text);
+        assert_eq!(schema.columns[1].column_type, ColumnType::Real);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(&schema.create_table_sql(), []).unwrap();
+        let values = schema.convert_row(&sample[0]).unwrap();
+        conn.execute(
+            "INSERT INTO orders (id, amount) VALUES (?1, ?2)",
+            params_from_iter(values),
+        )
+        .unwrap();
+        let id: String = conn.query_row("SELECT id FROM orders", [], |row| row.get(0)).unwrap();
+        assert_eq!(id, "12345678901234567890");
+    }
+
+    #[test]
+    fn test_create_table_and_upsert_sql() {
+        let headers = StringRecord::from(vec!["id", "email", "age"]);
+        let sample = rows(&[&["1", "a@test.com", ""]]);
+        let schema = TableSchema::infer("users", &headers, &sample)
+            .with_primary_key(&["id"])
+            .unwrap()
+            .with_unique_key(&["email"])
+            .unwrap();
+
+        assert_eq!(
+            schema.create_table_sql(),
+            "CREATE TABLE IF NOT EXISTS \"users\" (\n    \"id\" INTEGER NOT NULL,\n    \"email\" TEXT NOT NULL,\n    \"age\" TEXT,\n    PRIMARY KEY (\"id\"),\n    UNIQUE (\"email\")\n)"
+        );
+        assert_eq!(
+            schema.upsert_sql(),
+            "INSERT INTO \"users\" (\"id\", \"email\", \"age\") VALUES (?1, ?2, ?3) \
+             ON CONFLICT (\"id\") DO UPDATE SET \"email\" = excluded.\"email\", \"age\" = excluded.\"age\""
+        );
+        assert!(matches!(
+            schema.clone().with_primary_key(&["missing"]),
+            Err(ParseError::MissingField(field)) if field == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_convert_row_errors() {
+        let headers = StringRecord::from(vec!["id", "age"]);
+        let schema = TableSchema::infer("t", &headers, &rows(&[&["1", "30"]]));
+
+        assert_eq!(
+            schema
+                .convert_row(&StringRecord::from(vec!["2", "41"]))
+                .unwrap(),
+            vec![Value::Integer(2), Value::Integer(41)]
+        );
+        let err = schema
+            .convert_row(&StringRecord::from(vec!["2", "old"]))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid type for field 'age': 'old'");
+        assert!(matches!(
+            schema.convert_row(&StringRecord::from(vec!["2", ""])),
+            Err(ParseError::MissingField(field)) if field == "age"
+        ));
+        assert!(matches!(
+            schema.convert_row(&StringRecord::from(vec!["2"])),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    fn rejects(conn: &Connection, table: &str) -> Vec<(i64, String, String)> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT line, raw, reason FROM {}_rejects ORDER BY id",
+                table
+            ))
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn test_import_with_schema_upserts_on_reimport() {
+        let db = NamedTempFile::new().unwrap();
+        let db_path = db.path().to_str().unwrap();
+        let options = ImportOptions::new("users").primary_key(&["id"]);
+
+        let first = create_test_csv("id,name,age\n1,Alice,30\n2,Bob,25\n3,Charlie,35\n");
+        let stats =
+            import_csv_with_schema(first.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            (
+                stats.records_inserted,
+                stats.records_updated,
+                stats.records_rejected
+            ),
+            (3, 0, 0)
+        );
+        assert_eq!(stats.records_imported, 3);
+        assert_eq!(stats.migrations.len(), 1);
+        assert!(stats.migrations[0].starts_with("CREATE TABLE"));
+
+        let second = create_test_csv("id,name,age\n2,Bobby,26\n4,Diana,28\n");
+        let stats =
+            import_csv_with_schema(second.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            (
+                stats.records_inserted,
+                stats.records_updated,
+                stats.records_rejected
+            ),
+            (1, 1, 0)
+        );
+        assert!(stats.migrations.is_empty());
+
+        let conn = Connection::open(db_path).unwrap();
+        let (name, age): (String, i64) = conn
+            .query_row("SELECT name, age FROM users WHERE id = 2", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((name.as_str(), age), ("Bobby", 26));
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_import_with_schema_migrates_new_columns() {
+        let db = NamedTempFile::new().unwrap();
+        let db_path = db.path().to_str().unwrap();
+        let options = ImportOptions::new("users").unique_key(&["email"]);
+
+        let first = create_test_csv("email,name\na@test.com,Alice\n");
+        import_csv_with_schema(first.path().to_str().unwrap(), db_path, &options).unwrap();
+
+        let second = create_test_csv("email,name,score\na@test.com,Alice,9.5\nb@test.com,Bob,7\n");
+        let stats =
+            import_csv_with_schema(second.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            stats.migrations,
+            vec!["ALTER TABLE \"users\" ADD COLUMN \"score\" REAL"]
+        );
+        assert_eq!((stats.records_inserted, stats.records_updated), (1, 1));
+
+        let conn = Connection::open(db_path).unwrap();
+        let score: f64 = conn
+            .query_row(
+                "SELECT score FROM users WHERE email = 'a@test.com'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(score, 9.5);
+    }
+
+    #[test]
+    fn test_import_with_schema_adds_key_to_existing_table() {
+        let db = NamedTempFile::new().unwrap();
+        let db_path = db.path().to_str().unwrap();
+
+        let first = create_test_csv("email,name
+a@test.com,Alice
+");
+        let stats = import_csv_with_schema(
+            first.path().to_str().unwrap(),
+            db_path,
+            &ImportOptions::new("users"),
+        )
+        .unwrap();
+        assert_eq!(stats.records_inserted, 1);
+
+        let options = ImportOptions::new("users").unique_key(&["email"]);
+        let second = create_test_csv("email,name
+a@test.com,Alicia
+b@test.com,Bob
+");
+        let stats =
+            import_csv_with_schema(second.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            stats.migrations,
+            vec![
+                "CREATE UNIQUE INDEX IF NOT EXISTS \"users_email_key\" ON \"users\" (\"email\")"
+            ]
+        );
+        assert_eq!((stats.records_inserted, stats.records_updated), (1, 1));
+
+        // the index now exists, so a third run migrates nothing
+        let stats =
+            import_csv_with_schema(second.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert!(stats.migrations.is_empty());
+        assert_eq!((stats.records_inserted, stats.records_updated), (0, 2));
+    }
+
+    #[test]
+    fn test_import_with_schema_counts_do_nothing_as_skipped() {
+        let db = NamedTempFile::new().unwrap();
+        let db_path = db.path().to_str().unwrap();
+        let options = ImportOptions::new("tags").primary_key(&["tag"]);
+
+        let first = create_test_csv("tag
+rust
+sql
+");
+        import_csv_with_schema(first.path().to_str().unwrap(), db_path, &options).unwrap();
+
+        let second = create_test_csv("tag
+rust
+csv
+");
+        let stats =
+            import_csv_with_schema(second.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            (
+                stats.records_inserted,
+                stats.records_updated,
+                stats.records_skipped
+            ),
+            (1, 0, 1)
+        );
+        assert_eq!(stats.records_imported, 1);
+    }
+
+    #[test]
+    fn test_import_with_schema_quarantines_rejects() {
+        let db = NamedTempFile::new().unwrap();
+        let db_path = db.path().to_str().unwrap();
+        let mut options = ImportOptions::new("users")
+            .primary_key(&["id"])
+            .unique_key(&["email"]);
+        options.sample_size = 2;
+        options.batch_size = 2;
+
+        let csv = create_test_csv(
+            "id,email,age\n\
+             1,a@test.com,30\n\
+             2,b@test.com,25\n\
+             3,c@test.com,unknown\n\
+             ,d@test.com,40\n\
+             5,a@test.com,50\n\
+             6,\"f,x@test.com\"\n\
+             7,g@test.com,22\n",
+        );
+        let stats =
+            import_csv_with_schema(csv.path().to_str().unwrap(), db_path, &options).unwrap();
+        assert_eq!(
+            (
+                stats.records_inserted,
+                stats.records_updated,
+                stats.records_rejected
+            ),
+            (3, 0, 4)
+        );
+        assert_eq!(stats.records_failed, 4);
+        assert_eq!(stats.batches_processed, 4);
+
+        let conn = Connection::open(db_path).unwrap();
+        let rejected = rejects(&conn, "users");
+        assert_eq!(rejected.len(), 4);
+        assert_eq!(
+            rejected[0],
+            (
+                4,
+                "3,c@test.com,unknown".to_string(),
+                "Invalid type for field 'age': 'unknown'".to_string()
+            )
+        );
+        assert_eq!(rejected[1].2, "Missing required field: id");
+        assert!(rejected[2].2.contains("UNIQUE constraint failed"));
+        assert_eq!(rejected[3].1, "6,\"f,x@test.com\"");
+        assert_eq!(
+            rejected[3].2,
+            "Invalid CSV format: expected 3 fields, found 2"
+        );
+    }
 }
 
 fn main() {}
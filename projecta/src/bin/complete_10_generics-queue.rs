@@ -321,7 +321,252 @@ impl<T: Ord, Order: HeapOrder> PriorityQueue<T, Order> {
 }
 
 // =============================================================================
-// Tests covering milestones 1-6
+// Milestone 7: Addressable d-ary heap (handles, change_priority, remove)
+// =============================================================================
+
+/// Stable reference to an element of an `AddressableQueue`. It stays valid
+/// while the element is queued; once the element is popped or removed the
+/// handle is rejected, even if its slot has been reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    generation: u32,
+    position: Option<usize>,
+}
+
+/// Priority queue whose elements can be re-prioritized or cancelled through
+/// the `Handle` returned by `push`. `D` is the arity of the heap: wider nodes
+/// make the tree shallower (cheaper `push`/`change_priority` towards the
+/// root) and keep siblings on one cache line, at the cost of more
+/// comparisons per level on the way down.
+pub struct AddressableQueue<T, Order = MinHeap, const D: usize = 2> {
+    heap: Vec<(T, usize)>,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    _order: PhantomData<Order>,
+}
+
+pub type BinaryQueue<T, Order = MinHeap> = AddressableQueue<T, Order, 2>;
+pub type QuaternaryQueue<T, Order = MinHeap> = AddressableQueue<T, Order, 4>;
+pub type OctonaryQueue<T, Order = MinHeap> = AddressableQueue<T, Order, 8>;
+
+impl<T: Ord, Order: HeapOrder, const D: usize> Default for AddressableQueue<T, Order, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, Order: HeapOrder, const D: usize> AddressableQueue<T, Order, D> {
+    const VALID_ARITY: () = assert!(D >= 2, "heap arity must be at least 2");
+
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_ARITY;
+        AddressableQueue {
+            heap: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            _order: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.first().map(|(item, _)| item)
+    }
+
+    pub fn peek_handle(&self) -> Option<Handle> {
+        self.heap.first().map(|&(_, slot)| self.handle_for(slot))
+    }
+
+    /// O(log_D n). Returns the element's handle.
+    pub fn push(&mut self, item: T) -> Handle {
+        let position = self.heap.len();
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].position = Some(position);
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    position: Some(position),
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.heap.push((item, slot));
+        self.sift_up(position);
+        self.handle_for(slot)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|pos| &self.heap[pos].0)
+    }
+
+    /// Replace the element behind `handle` and restore heap order in
+    /// O(log_D n), moving it up or down as needed. Returns the old value, or
+    /// `None` if the handle is no longer valid.
+    pub fn change_priority(&mut self, handle: Handle, item: T) -> Option<T> {
+        let position = self.position(handle)?;
+        let old = std::mem::replace(&mut self.heap[position].0, item);
+        if Order::should_swap(&old, &self.heap[position].0) {
+            self.sift_up(position);
+        } else {
+            self.sift_down(position);
+        }
+        Some(old)
+    }
+
+    /// Cancel the element behind `handle` in O(log_D n).
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let position = self.position(handle)?;
+        Some(self.remove_at(position))
+    }
+
+    /// Move every element of `other` into this queue, rebuilding the heap
+    /// in O(n + m). Returns each of `other`'s handles paired with the
+    /// handle of the same element here.
+    pub fn merge(&mut self, other: Self) -> Vec<(Handle, Handle)> {
+        let mut remap = Vec::with_capacity(other.len());
+        for (item, other_slot) in other.heap {
+            let old = Handle {
+                slot: other_slot,
+                generation: other.slots[other_slot].generation,
+            };
+            let position = self.heap.len();
+            let slot = match self.free.pop() {
+                Some(slot) => slot,
+                None => {
+                    self.slots.push(Slot {
+                        generation: 0,
+                        position: None,
+                    });
+                    self.slots.len() - 1
+                }
+            };
+            self.slots[slot].position = Some(position);
+            self.heap.push((item, slot));
+            remap.push((old, self.handle_for(slot)));
+        }
+        for position in (0..self.heap.len() / D + 1).rev() {
+            self.sift_down(position);
+        }
+        remap
+    }
+
+    /// Elements with their handles, in heap (not priority) order.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+        self.heap
+            .iter()
+            .map(move |(item, slot)| (self.handle_for(*slot), item))
+    }
+
+    pub fn clear(&mut self) {
+        while let Some((_, slot)) = self.heap.pop() {
+            self.release(slot);
+        }
+    }
+
+    fn handle_for(&self, slot: usize) -> Handle {
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    fn release(&mut self, slot: usize) {
+        let entry = &mut self.slots[slot];
+        entry.position = None;
+        entry.generation = entry.generation.wrapping_add(1);
+        self.free.push(slot);
+    }
+
+    fn remove_at(&mut self, position: usize) -> T {
+        let last = self.heap.len() - 1;
+        self.swap(position, last);
+        let (item, slot) = self.heap.pop().expect("non-empty heap");
+        self.release(slot);
+        if position < self.heap.len() {
+            self.sift_up(position);
+            self.sift_down(position);
+        }
+        item
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.slots[self.heap[a].1].position = Some(a);
+        self.slots[self.heap[b].1].position = Some(b);
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / D;
+            if !Order::should_swap(&self.heap[parent].0, &self.heap[i].0) {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let first_child = D * i + 1;
+            let end = (first_child + D).min(self.heap.len());
+            let mut best = i;
+            for child in first_child..end {
+                if Order::should_swap(&self.heap[best].0, &self.heap[child].0) {
+                    best = child;
+                }
+            }
+            if best == i {
+                break;
+            }
+            self.swap(i, best);
+            i = best;
+        }
+    }
+}
+
+// =============================================================================
+// Tests covering milestones 1-7
 // =============================================================================
 
 #[cfg(test)]
@@ -1016,6 +1261,192 @@ mod tests {
         let iter = pq.into_iter();
         assert_eq!(iter.len(), 3);
     }
+
+    // ----- Milestone 7 tests -----
+    fn verify_d_ary<T: Ord, O: HeapOrder, const D: usize>(pq: &AddressableQueue<T, O, D>) {
+        for i in 1..pq.heap.len() {
+            let parent = (i - 1) / D;
+            assert!(!O::should_swap(&pq.heap[parent].0, &pq.heap[i].0));
+        }
+        for (pos, &(_, slot)) in pq.heap.iter().enumerate() {
+            assert_eq!(pq.slots[slot].position, Some(pos));
+        }
+    }
+
+    #[test]
+    fn test_addressable_change_priority_and_remove() {
+        let mut pq: BinaryQueue<i32> = AddressableQueue::new();
+        let a = pq.push(50);
+        let b = pq.push(30);
+        let c = pq.push(40);
+        let d = pq.push(10);
+
+        assert_eq!(pq.peek(), Some(&10));
+        assert_eq!(pq.peek_handle(), Some(d));
+
+        // Decrease-key moves an element up, increase-key moves it down.
+        assert_eq!(pq.change_priority(a, 5), Some(50));
+        assert_eq!(pq.peek_handle(), Some(a));
+        assert_eq!(pq.change_priority(a, 45), Some(5));
+        assert_eq!(pq.peek_handle(), Some(d));
+        verify_d_ary(&pq);
+
+        assert_eq!(pq.remove(b), Some(30));
+        assert!(!pq.contains(b));
+        assert_eq!(pq.get(c), Some(&40));
+        verify_d_ary(&pq);
+
+        assert_eq!(pq.pop(), Some(10));
+        assert_eq!(pq.pop(), Some(40));
+        assert_eq!(pq.pop(), Some(45));
+        assert!(pq.is_empty());
+    }
+
+    #[test]
+    fn test_addressable_max_heap_order() {
+        let mut pq: OctonaryQueue<i32, MaxHeap> = AddressableQueue::new();
+        let handles: Vec<Handle> = (0..20).map(|i| pq.push(i)).collect();
+        pq.change_priority(handles[3], 100);
+        pq.change_priority(handles[19], -1);
+        pq.remove(handles[18]);
+        verify_d_ary(&pq);
+
+        assert_eq!(pq.pop(), Some(100));
+        assert_eq!(pq.pop(), Some(17));
+        let rest: Vec<i32> = std::iter::from_fn(|| pq.pop()).collect();
+        assert_eq!(rest.last(), Some(&-1));
+        assert_eq!(rest.len(), 17);
+    }
+
+    #[test]
+    fn test_addressable_stale_handles() {
+        let mut pq: BinaryQueue<i32> = AddressableQueue::new();
+        let first = pq.push(1);
+        assert_eq!(pq.pop(), Some(1));
+
+        // The freed slot is reused, but the old handle must not alias it.
+        let second = pq.push(2);
+        assert_ne!(first, second);
+        assert_eq!(pq.get(first), None);
+        assert_eq!(pq.change_priority(first, 0), None);
+        assert_eq!(pq.remove(first), None);
+        assert_eq!(pq.get(second), Some(&2));
+
+        pq.clear();
+        assert!(!pq.contains(second));
+        assert_eq!(pq.pop(), None);
+    }
+
+    fn check_against_sorted_model<const D: usize>(seed: u64) {
+        let mut state = seed;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        let mut pq: AddressableQueue<u64, MinHeap, D> = AddressableQueue::new();
+        let mut live: Vec<(Handle, u64)> = Vec::new();
+        for _ in 0..2_000 {
+            match next(4) {
+                0 | 1 => {
+                    let value = next(1_000);
+                    live.push((pq.push(value), value));
+                }
+                2 if !live.is_empty() => {
+                    let idx = next(live.len() as u64) as usize;
+                    let value = next(1_000);
+                    assert_eq!(pq.change_priority(live[idx].0, value), Some(live[idx].1));
+                    live[idx].1 = value;
+                }
+                _ if !live.is_empty() => {
+                    let idx = next(live.len() as u64) as usize;
+                    let (handle, value) = live.swap_remove(idx);
+                    assert_eq!(pq.remove(handle), Some(value));
+                }
+                _ => {}
+            }
+            assert_eq!(pq.peek(), live.iter().map(|(_, v)| v).min());
+        }
+        verify_d_ary(&pq);
+
+        let mut expected: Vec<u64> = live.iter().map(|&(_, v)| v).collect();
+        expected.sort();
+        let drained: Vec<u64> = std::iter::from_fn(|| pq.pop()).collect();
+        assert_eq!(drained, expected);
+    }
+
+    #[test]
+    fn test_addressable_arities_match_model() {
+        check_against_sorted_model::<2>(0x9e37_79b9_7f4a_7c15);
+        check_against_sorted_model::<4>(0x2545_f491_4f6c_dd1d);
+        check_against_sorted_model::<8>(0x1234_5678_9abc_def1);
+    }
+
+    #[test]
+    fn test_addressable_dijkstra_decrease_key() {
+        // (to, weight) adjacency list
+        let graph: Vec<Vec<(usize, u32)>> = vec![
+            vec![(1, 7), (2, 9), (5, 14)],
+            vec![(0, 7), (2, 10), (3, 15)],
+            vec![(0, 9), (1, 10), (3, 11), (5, 2)],
+            vec![(1, 15), (2, 11), (4, 6)],
+            vec![(3, 6), (5, 9)],
+            vec![(0, 14), (2, 2), (4, 9)],
+        ];
+        let mut dist = vec![u32::MAX; graph.len()];
+        let mut handles: Vec<Option<Handle>> = vec![None; graph.len()];
+        let mut pq: QuaternaryQueue<(u32, usize)> = AddressableQueue::new();
+
+        dist[0] = 0;
+        handles[0] = Some(pq.push((0, 0)));
+        while let Some((d, node)) = pq.pop() {
+            for &(to, weight) in &graph[node] {
+                let candidate = d + weight;
+                if candidate < dist[to] {
+                    dist[to] = candidate;
+                    match handles[to].filter(|&h| pq.contains(h)) {
+                        Some(h) => {
+                            pq.change_priority(h, (candidate, to));
+                        }
+                        None => handles[to] = Some(pq.push((candidate, to))),
+                    }
+                }
+            }
+        }
+        assert_eq!(dist, vec![0, 7, 9, 20, 20, 11]);
+    }
+
+    #[test]
+    fn test_addressable_merge_remaps_handles() {
+        let mut left: QuaternaryQueue<i32> = AddressableQueue::new();
+        let mut right: QuaternaryQueue<i32> = AddressableQueue::new();
+        for i in (0..30).step_by(2) {
+            left.push(i);
+        }
+        let popped = left.push(-5);
+        left.pop();
+        let right_handles: Vec<(Handle, i32)> =
+            (1..30).step_by(2).map(|i| (right.push(i), i)).collect();
+
+        let remap = left.merge(right);
+        assert_eq!(remap.len(), right_handles.len());
+        assert_eq!(left.len(), 30);
+        verify_d_ary(&left);
+        assert!(!left.contains(popped));
+
+        for (old, value) in right_handles {
+            let (_, new) = remap.iter().find(|(o, _)| *o == old).unwrap();
+            assert_eq!(left.get(*new), Some(&value));
+        }
+
+        let (_, handle_of_29) = remap[remap.len() - 1];
+        left.change_priority(handle_of_29, -1);
+        let drained: Vec<i32> = std::iter::from_fn(|| left.pop()).collect();
+        assert_eq!(drained[0], -1);
+        assert_eq!(drained[1..], (0..29).collect::<Vec<_>>()[..]);
+    }
 }
 
 fn main() {}
@@ -356,6 +356,43 @@ impl CollectionBenchmark {
         }
     }
 
+    pub fn benchmark_static_lookups(&self) {
+        use std::time::Instant;
+        for &size in &self.sizes {
+            let data: Vec<i32> = (0..size as i32).map(|i| i * 2).collect();
+            let eytzinger = EytzingerLayout::from_sorted(&data);
+            let btree = StaticBTree::from_sorted(&data);
+            let queries: Vec<i32> = (0..size as i32)
+                .map(|i| (i * 7919) % (size as i32 * 2))
+                .collect();
+
+            let timed = |name: &str, search: &dyn Fn(&i32) -> usize| {
+                let start = Instant::now();
+                let checksum: usize = queries.iter().map(search).sum();
+                println!(
+                    "{} lookups on {} elements: {:?}",
+                    name,
+                    size,
+                    start.elapsed()
+                );
+                checksum
+            };
+            let expected = timed("lower_bound", &|q| binary_search_lower_bound(&data, q));
+            assert_eq!(
+                timed("EytzingerLayout", &|q| eytzinger.lower_bound(q)),
+                expected
+            );
+            assert_eq!(timed("StaticBTree", &|q| btree.lower_bound(q)), expected);
+            assert_eq!(
+                timed(
+                    "InterpolationSearch",
+                    &|q| interpolation_search_lower_bound(&data, q)
+                ),
+                expected
+            );
+        }
+    }
+
     pub fn measure_memory(&self) {
         for &size in &self.sizes {
             println!(
@@ -376,15 +413,44 @@ impl CollectionBenchmark {
     }
 }
 
-pub fn recommend_collection(
-    size: usize,
-    needs_ordering: bool,
-    needs_ranges: bool,
-    write_heavy: bool,
-) -> &'static str {
+/// What a lookup table will be used for, for `recommend_collection`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Workload {
+    pub size: usize,
+    pub needs_ordering: bool,
+    pub needs_ranges: bool,
+    pub write_heavy: bool,
+    /// Built once and never modified afterwards.
+    pub read_only: bool,
+    /// Keys are numeric and spread roughly evenly over their range.
+    pub uniform_keys: bool,
+}
+
+/// Read-only data can use the static layouts from Milestone 7. Small tables
+/// fit in cache, so a `SortedVec` is already as fast as those there.
+pub fn recommend_collection(workload: &Workload) -> &'static str {
+    let Workload {
+        size,
+        needs_ordering,
+        needs_ranges,
+        write_heavy,
+        read_only,
+        uniform_keys,
+    } = *workload;
     if !needs_ordering && !needs_ranges {
         return "HashSet";
     }
+    if read_only {
+        return if size <= 1000 {
+            "SortedVec"
+        } else if uniform_keys {
+            "InterpolationSearch"
+        } else if needs_ranges {
+            "StaticBTree"
+        } else {
+            "EytzingerLayout"
+        };
+    }
     if size > 1000 || write_heavy {
         return "BTreeSet";
     }
@@ -395,6 +461,234 @@ pub fn recommend_collection(
     }
 }
 
+// =============================================================================
+// Milestone 7: Cache-Optimized Static Search Layouts
+// =============================================================================
+
+/// Hint the CPU to pull `slice[index]` into cache. A no-op off x86_64.
+#[inline]
+fn prefetch<T>(slice: &[T], index: usize) {
+    #[cfg(target_arch = "x86_64")]
+    if index < slice.len() {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        // SAFETY: the pointer is in bounds, and prefetching never faults.
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(slice.as_ptr().add(index) as *const i8) }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = (slice, index);
+}
+
+/// Sorted data stored in breadth-first (Eytzinger) order: node `k` has
+/// children `2k` and `2k + 1` (1-based). The first levels of the tree share
+/// a few cache lines, and the next levels can be prefetched before they are
+/// needed, which a plain binary search over a sorted slice cannot do.
+#[derive(Debug, Clone)]
+pub struct EytzingerLayout<T> {
+    layout: Vec<T>,
+    // Position in the original sorted slice of each layout slot.
+    ranks: Vec<usize>,
+}
+
+impl<T: Ord + Clone> EytzingerLayout<T> {
+    pub fn from_sorted(sorted: &[T]) -> Self {
+        debug_assert!(is_sorted(sorted));
+        let n = sorted.len();
+        let mut ranks = vec![0; n];
+        let mut next = 0;
+        let mut stack = Vec::new();
+        let mut k = 1;
+        // In-order walk of the implicit tree assigns sorted positions.
+        loop {
+            while k <= n {
+                stack.push(k);
+                k *= 2;
+            }
+            match stack.pop() {
+                Some(node) => {
+                    ranks[node - 1] = next;
+                    next += 1;
+                    k = 2 * node + 1;
+                }
+                None => break,
+            }
+        }
+        let layout = ranks.iter().map(|&rank| sorted[rank].clone()).collect();
+        Self { layout, ranks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.is_empty()
+    }
+
+    /// Same result as `binary_search_lower_bound` on the original slice.
+    pub fn lower_bound(&self, target: &T) -> usize {
+        let n = self.layout.len();
+        let mut k = 1;
+        while k <= n {
+            // Four levels down (16 descendants) is roughly one memory
+            // latency ahead of the comparisons.
+            prefetch(&self.layout, 16 * k - 1);
+            k = 2 * k + usize::from(self.layout[k - 1] < *target);
+        }
+        // Undo the trailing right turns plus the final left turn.
+        k >>= k.trailing_ones() + 1;
+        if k == 0 {
+            n
+        } else {
+            self.ranks[k - 1]
+        }
+    }
+
+    pub fn contains(&self, target: &T) -> bool {
+        let idx = self.lower_bound(target);
+        idx < self.len() && self.get(idx) == Some(target)
+    }
+
+    /// Element at `rank` in sorted order. O(n); meant for tests and
+    /// debugging, not lookups.
+    pub fn get(&self, rank: usize) -> Option<&T> {
+        self.ranks
+            .iter()
+            .position(|&r| r == rank)
+            .map(|slot| &self.layout[slot])
+    }
+}
+
+/// Keys per node of `StaticBTree`: 16 four-byte keys fill one cache line.
+pub const STATIC_BTREE_NODE: usize = 16;
+
+/// Read-only B+-tree over a sorted slice. The leaves are the sorted data
+/// itself, so ranges are contiguous; each internal level holds the largest
+/// key of every node below it. Nodes are scanned by counting keys less than
+/// the target rather than stopping at the first match, which has no
+/// data-dependent branches and vectorizes for primitive keys.
+#[derive(Debug, Clone)]
+pub struct StaticBTree<T> {
+    data: Vec<T>,
+    // levels[0] sits directly above the leaves; the last level is the root.
+    levels: Vec<Vec<T>>,
+}
+
+impl<T: Ord + Clone> StaticBTree<T> {
+    pub fn from_sorted(sorted: &[T]) -> Self {
+        debug_assert!(is_sorted(sorted));
+        let data = sorted.to_vec();
+        let mut levels: Vec<Vec<T>> = Vec::new();
+        let mut below = &data;
+        while below.len() > STATIC_BTREE_NODE {
+            let level: Vec<T> = below
+                .chunks(STATIC_BTREE_NODE)
+                .map(|node| node[node.len() - 1].clone())
+                .collect();
+            levels.push(level);
+            below = levels.last().unwrap();
+        }
+        Self { data, levels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    fn count_less(keys: &[T], target: &T) -> usize {
+        keys.iter().map(|key| usize::from(key < target)).sum()
+    }
+
+    /// Same result as `binary_search_lower_bound` on the original slice.
+    pub fn lower_bound(&self, target: &T) -> usize {
+        let mut node = 0;
+        for (depth, level) in self.levels.iter().enumerate().rev() {
+            let start = node * STATIC_BTREE_NODE;
+            let keys = &level[start..(start + STATIC_BTREE_NODE).min(level.len())];
+            let i = Self::count_less(keys, target);
+            if i == keys.len() {
+                // Only possible at the root: every key is smaller.
+                debug_assert_eq!(depth, self.levels.len() - 1);
+                return self.data.len();
+            }
+            node = start + i;
+        }
+        let start = node * STATIC_BTREE_NODE;
+        let keys = &self.data[start..(start + STATIC_BTREE_NODE).min(self.data.len())];
+        start + Self::count_less(keys, target)
+    }
+
+    pub fn contains(&self, target: &T) -> bool {
+        self.data.get(self.lower_bound(target)) == Some(target)
+    }
+
+    /// Elements in `[start, end]`, like `range_query`.
+    pub fn range(&self, start: &T, end: &T) -> &[T] {
+        let lo = self.lower_bound(start);
+        let hi = lo + binary_search_upper_bound(&self.data[lo..], end);
+        &self.data[lo..hi]
+    }
+}
+
+/// Keys that interpolation search can place on a number line.
+pub trait NumericKey: Ord + Copy {
+    fn as_f64(self) -> f64;
+}
+
+macro_rules! impl_numeric_key {
+    ($($t:ty),*) => {
+        $(impl NumericKey for $t {
+            fn as_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+impl_numeric_key!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Lower bound by interpolation: about O(log log n) probes when keys are
+/// roughly uniform. Skewed data would degrade it to O(n), so after
+/// `log2(n) + 1` probes it finishes with a binary search over what is left.
+pub fn interpolation_search_lower_bound<T: NumericKey>(arr: &[T], target: &T) -> usize {
+    // Invariant: arr[..lo] < target <= arr[hi..]
+    let mut lo = 0;
+    let mut hi = arr.len();
+    let mut probes_left = arr.len().max(1).ilog2() + 1;
+
+    while lo < hi {
+        let (first, last) = (arr[lo], arr[hi - 1]);
+        if *target <= first {
+            return lo;
+        }
+        if *target > last {
+            return hi;
+        }
+        if probes_left == 0 {
+            return lo + binary_search_lower_bound(&arr[lo..hi], target);
+        }
+        probes_left -= 1;
+
+        // first < target <= last, so the span is non-zero.
+        let fraction = (target.as_f64() - first.as_f64()) / (last.as_f64() - first.as_f64());
+        let offset = (fraction * (hi - 1 - lo) as f64) as usize;
+        let mid = (lo + offset).min(hi - 1);
+        if arr[mid] < *target {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -604,9 +898,16 @@ mod tests {
     // Milestone 6 tests
     #[test]
     fn test_recommendations() {
-        assert_eq!(recommend_collection(100, true, true, false), "SortedVec");
-        assert_eq!(recommend_collection(10000, true, false, false), "BTreeSet");
-        assert_eq!(recommend_collection(1000, false, false, true), "HashSet");
+        let workload = |size, needs_ordering, needs_ranges, write_heavy| Workload {
+            size,
+            needs_ordering,
+            needs_ranges,
+            write_heavy,
+            ..Workload::default()
+        };
+        assert_eq!(recommend_collection(&workload(100, true, true, false)), "SortedVec");
+        assert_eq!(recommend_collection(&workload(10000, true, false, false)), "BTreeSet");
+        assert_eq!(recommend_collection(&workload(1000, false, false, true)), "HashSet");
     }
 
     #[test]
@@ -634,6 +935,152 @@ mod tests {
         assert_eq!(sum_seq, sum_rand);
         println!("Sequential: {:?}, Random: {:?}", seq_time, rand_time);
     }
+
+    // Milestone 7 tests
+    fn lower_bound_fixtures() -> Vec<Vec<i32>> {
+        let mut fixtures = vec![vec![], vec![5], vec![1, 1, 1, 1], vec![1, 2, 2, 2, 3, 9]];
+        for n in [2i32, 15, 16, 17, 31, 255, 256, 257, 1000, 4099] {
+            fixtures.push((0..n).map(|i| i * 3 - (i % 7)).collect());
+            fixtures.push((0..n).map(|i| i / 5).collect());
+        }
+        for fixture in &mut fixtures {
+            fixture.sort();
+        }
+        fixtures
+    }
+
+    fn targets_for(data: &[i32]) -> Vec<i32> {
+        let mut targets = vec![i32::MIN, i32::MAX, -1, 0];
+        for &v in data {
+            targets.extend([v - 1, v, v + 1]);
+        }
+        targets
+    }
+
+    #[test]
+    fn test_eytzinger_matches_lower_bound() {
+        for data in lower_bound_fixtures() {
+            let layout = EytzingerLayout::from_sorted(&data);
+            assert_eq!(layout.len(), data.len());
+            for target in targets_for(&data) {
+                assert_eq!(
+                    layout.lower_bound(&target),
+                    binary_search_lower_bound(&data, &target),
+                    "n={} target={}",
+                    data.len(),
+                    target
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_eytzinger_layout_order() {
+        let layout = EytzingerLayout::from_sorted(&[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(layout.layout, vec![4, 2, 6, 1, 3, 5, 7]);
+        assert!(layout.contains(&5));
+        assert!(!layout.contains(&8));
+        assert_eq!(layout.get(0), Some(&1));
+        assert!(EytzingerLayout::<i32>::from_sorted(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_static_btree_matches_lower_bound() {
+        for data in lower_bound_fixtures() {
+            let tree = StaticBTree::from_sorted(&data);
+            for target in targets_for(&data) {
+                assert_eq!(
+                    tree.lower_bound(&target),
+                    binary_search_lower_bound(&data, &target),
+                    "n={} target={}",
+                    data.len(),
+                    target
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_static_btree_levels_and_range() {
+        let data: Vec<i32> = (0..1000).collect();
+        let tree = StaticBTree::from_sorted(&data);
+        let sizes: Vec<usize> = tree.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![63, 4]);
+        assert_eq!(tree.range(&100, &110), range_query(&data, &100, &110));
+        assert_eq!(tree.range(&995, &2000), &[995, 996, 997, 998, 999]);
+        assert!(tree.range(&2000, &3000).is_empty());
+        assert!(tree.contains(&999));
+        assert!(!tree.contains(&1000));
+    }
+
+    #[test]
+    fn test_interpolation_matches_lower_bound() {
+        for data in lower_bound_fixtures() {
+            for target in targets_for(&data) {
+                assert_eq!(
+                    interpolation_search_lower_bound(&data, &target),
+                    binary_search_lower_bound(&data, &target),
+                    "n={} target={}",
+                    data.len(),
+                    target
+                );
+            }
+        }
+
+        // Heavily skewed keys fall back to binary search but stay exact.
+        let skewed: Vec<u64> = (0..64).map(|i| 1u64 << i).collect();
+        for target in [0, 1, 3, 1 << 20, (1 << 40) + 1, u64::MAX] {
+            assert_eq!(
+                interpolation_search_lower_bound(&skewed, &target),
+                binary_search_lower_bound(&skewed, &target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_benchmark_static_lookups() {
+        CollectionBenchmark::new(vec![100, 5000]).benchmark_static_lookups();
+    }
+
+    #[test]
+    fn test_recommend_static_workloads() {
+        let read_only = Workload {
+            size: 1_000_000,
+            needs_ordering: true,
+            read_only: true,
+            ..Workload::default()
+        };
+        assert_eq!(recommend_collection(&read_only), "EytzingerLayout");
+        assert_eq!(
+            recommend_collection(&Workload {
+                needs_ranges: true,
+                ..read_only
+            }),
+            "StaticBTree"
+        );
+        assert_eq!(
+            recommend_collection(&Workload {
+                uniform_keys: true,
+                ..read_only
+            }),
+            "InterpolationSearch"
+        );
+        assert_eq!(
+            recommend_collection(&Workload {
+                size: 100,
+                ..read_only
+            }),
+            "SortedVec"
+        );
+        // Mutable workloads keep the original advice.
+        assert_eq!(
+            recommend_collection(&Workload {
+                read_only: false,
+                ..read_only
+            }),
+            "BTreeSet"
+        );
+    }
 }
 
 fn main() {}
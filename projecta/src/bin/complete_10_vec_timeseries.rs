@@ -1,4 +1,7 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
 
 // =============================================================================
 // Milestone 1: Basic Sliding Window with VecDeque
@@ -79,34 +82,72 @@ pub struct IncrementalWindow {
     capacity: usize,
     running_sum: f64,
     running_sum_sq: f64,
+    // Non-NaN values of the window in sorted order (Milestone 7).
+    ordered: IndexableSkipList,
+    nan_policy: NanPolicy,
+    nan_count: usize,
 }
 
 impl IncrementalWindow {
     pub fn new(capacity: usize) -> Self {
+        Self::with_nan_policy(capacity, NanPolicy::default())
+    }
+
+    pub fn with_nan_policy(capacity: usize, nan_policy: NanPolicy) -> Self {
         Self {
             window: VecDeque::with_capacity(capacity),
             capacity,
             running_sum: 0.0,
             running_sum_sq: 0.0,
+            ordered: IndexableSkipList::new(),
+            nan_policy,
+            nan_count: 0,
         }
     }
 
+    /// Like `try_push`, but silently drops a NaN refused by
+    /// `NanPolicy::Reject`.
     pub fn push(&mut self, value: f64) {
+        let _ = self.try_push(value);
+    }
+
+    pub fn try_push(&mut self, value: f64) -> Result<(), NanRejected> {
+        if value.is_nan() {
+            match self.nan_policy {
+                NanPolicy::Propagate => {}
+                NanPolicy::Ignore => return Ok(()),
+                NanPolicy::Reject => return Err(NanRejected),
+            }
+        }
         if self.window.len() == self.capacity {
             if let Some(old) = self.window.pop_front() {
-                self.running_sum -= old;
-                self.running_sum_sq -= old * old;
+                if old.is_nan() {
+                    self.nan_count -= 1;
+                } else {
+                    self.running_sum -= old;
+                    self.running_sum_sq -= old * old;
+                    self.ordered.remove(old);
+                }
             }
         }
         self.window.push_back(value);
-        self.running_sum += value;
-        self.running_sum_sq += value * value;
+        if value.is_nan() {
+            self.nan_count += 1;
+        } else {
+            self.running_sum += value;
+            self.running_sum_sq += value * value;
+            self.ordered.insert(value);
+        }
+        Ok(())
     }
 
     pub fn average(&self) -> Option<f64> {
         if self.window.is_empty() {
             return None;
         }
+        if self.nan_count > 0 {
+            return Some(f64::NAN);
+        }
         Some(self.running_sum / self.window.len() as f64)
     }
 
@@ -114,6 +155,9 @@ impl IncrementalWindow {
         if self.window.len() < 2 {
             return None;
         }
+        if self.nan_count > 0 {
+            return Some(f64::NAN);
+        }
         let len = self.window.len() as f64;
         let mean = self.running_sum / len;
         let mean_sq = self.running_sum_sq / len;
@@ -209,52 +253,45 @@ impl MinMaxWindow {
 }
 
 // =============================================================================
-// Milestone 4: Median and Percentiles with select_nth_unstable
+// Milestone 4: Median and Percentiles by Rank
 // =============================================================================
 
+// Each query is one or two O(log n) rank lookups in the window's sorted
+// index instead of copying the window and running select_nth_unstable.
 impl IncrementalWindow {
     pub fn median(&self) -> Option<f64> {
         if self.window.is_empty() {
             return None;
         }
-        let mut values: Vec<f64> = self.window.iter().copied().collect();
-        let len = values.len();
+        if self.nan_count > 0 {
+            return Some(f64::NAN);
+        }
+        let len = self.ordered.len();
         if len % 2 == 1 {
-            let mid = len / 2;
-            values.select_nth_unstable_by(mid, |a, b| a.partial_cmp(b).unwrap());
-            Some(values[mid])
+            self.ordered.select(len / 2)
         } else {
-            let mid2 = len / 2;
-            values.select_nth_unstable_by(mid2, |a, b| a.partial_cmp(b).unwrap());
-            let val2 = values[mid2];
-            let val1 = values[..mid2]
-                .iter()
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .copied()
-                .unwrap_or(val2);
+            let val1 = self.ordered.select(len / 2 - 1)?;
+            let val2 = self.ordered.select(len / 2)?;
             Some((val1 + val2) / 2.0)
         }
     }
 
     pub fn percentile(&self, p: f64) -> Option<f64> {
-        if self.window.is_empty() || p < 0.0 || p > 100.0 {
+        if self.window.is_empty() || !(0.0..=100.0).contains(&p) {
             return None;
         }
-        let mut values: Vec<f64> = self.window.iter().copied().collect();
-        let len = values.len();
+        if self.nan_count > 0 {
+            return Some(f64::NAN);
+        }
+        let len = self.ordered.len();
         let pos = (p / 100.0) * (len as f64 - 1.0);
         let low = pos.floor() as usize;
         let high = pos.ceil() as usize;
-        values.select_nth_unstable_by(high, |a, b| a.partial_cmp(b).unwrap());
-        let high_val = values[high];
+        let high_val = self.ordered.select(high)?;
         if high == low {
             Some(high_val)
         } else {
-            let low_val = values[..high]
-                .iter()
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .copied()
-                .unwrap_or(high_val);
+            let low_val = self.ordered.select(low)?;
             let frac = pos - low as f64;
             Some(low_val * (1.0 - frac) + high_val * frac)
         }
@@ -264,20 +301,28 @@ impl IncrementalWindow {
         if self.window.is_empty() {
             return ps.iter().map(|_| None).collect();
         }
-        let mut values: Vec<f64> = self.window.iter().copied().collect();
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let len = values.len();
+        let len = self.ordered.len();
         ps.iter()
             .map(|p| {
-                if *p < 0.0 || *p > 100.0 {
+                if !(0.0..=100.0).contains(p) {
                     None
+                } else if self.nan_count > 0 {
+                    Some(f64::NAN)
                 } else {
                     let rank = ((p / 100.0) * (len as f64 - 1.0)).round() as usize;
-                    Some(values[rank])
+                    self.ordered.select(rank)
                 }
             })
             .collect()
     }
+
+    pub fn min(&self) -> Option<f64> {
+        self.percentile(0.0)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.percentile(100.0)
+    }
 }
 
 // =============================================================================
@@ -312,112 +357,1752 @@ impl MultiWindowAnalyzer {
         }
     }
 
-    pub fn push(&mut self, value: f64) {
-        for window in &mut self.windows {
-            window.push(value);
+    pub fn push(&mut self, value: f64) {
+        for window in &mut self.windows {
+            window.push(value);
+        }
+    }
+
+    pub fn get_stats(&self, window_index: usize) -> Option<WindowStats> {
+        self.windows.get(window_index).map(|window| WindowStats {
+            average: window.average(),
+            std_dev: window.std_dev(),
+            median: window.median(),
+            min: window.min(),
+            max: window.max(),
+            window_size: self.window_sizes[window_index],
+        })
+    }
+
+    pub fn all_stats(&self) -> Vec<WindowStats> {
+        (0..self.windows.len())
+            .filter_map(|idx| self.get_stats(idx))
+            .collect()
+    }
+
+    pub fn window_count(&self) -> usize {
+        self.windows.len()
+    }
+}
+
+// =============================================================================
+// Milestone 6: Anomaly Detection with Z-Score
+// =============================================================================
+
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    analyzer: MultiWindowAnalyzer,
+    threshold: f64,
+    anomalies: Vec<Anomaly>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    pub value: f64,
+    /// Signed deviation in the detector's own units of spread.
+    pub z_score: f64,
+    pub timestamp: Timestamp,
+    /// Set by the window-based `AnomalyDetector` only.
+    pub window_stats: Option<WindowStats>,
+    /// `Detector::name` of whoever flagged the point.
+    pub detector: &'static str,
+    pub expected: f64,
+    /// Confidence band the value fell outside of.
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl AnomalyDetector {
+    pub fn new(window_sizes: Vec<usize>, threshold: f64) -> Self {
+        Self {
+            analyzer: MultiWindowAnalyzer::new(window_sizes),
+            threshold,
+            anomalies: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: f64, timestamp: Timestamp) -> Option<Anomaly> {
+        self.analyzer.push(value);
+        if let Some(last_stats) = self
+            .analyzer
+            .get_stats(self.analyzer.window_count().saturating_sub(1))
+        {
+            if let (Some(mean), Some(std_dev)) = (last_stats.average, last_stats.std_dev) {
+                if std_dev > 0.0 {
+                    let z = (value - mean) / std_dev;
+                    if z.abs() >= self.threshold {
+                        let anomaly = Anomaly {
+                            value,
+                            z_score: z,
+                            timestamp,
+                            window_stats: Some(last_stats.clone()),
+                            detector: "zscore",
+                            expected: mean,
+                            lower: mean - self.threshold * std_dev,
+                            upper: mean + self.threshold * std_dev,
+                        };
+                        self.anomalies.push(anomaly.clone());
+                        return Some(anomaly);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Push every point of a range in order, returning the anomalies found.
+    pub fn scan(&mut self, points: &[DataPoint]) -> Vec<Anomaly> {
+        points
+            .iter()
+            .filter_map(|p| self.push(p.value, p.timestamp))
+            .collect()
+    }
+
+    pub fn anomaly_rate(&self, total_points: usize) -> f64 {
+        if total_points == 0 {
+            return 0.0;
+        }
+        self.anomalies.len() as f64 / total_points as f64
+    }
+
+    pub fn get_anomalies(&self) -> &[Anomaly] {
+        &self.anomalies
+    }
+
+    pub fn clear_anomalies(&mut self) {
+        self.anomalies.clear();
+    }
+}
+
+// =============================================================================
+// Milestone 7: Order-Statistic Windows and Quantile Sketches
+// =============================================================================
+
+/// What an `IncrementalWindow` does with NaN samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NanPolicy {
+    /// NaN takes a slot in the window, and every statistic is NaN until it
+    /// has been evicted.
+    #[default]
+    Propagate,
+    /// NaN is dropped before it reaches the window.
+    Ignore,
+    /// `try_push` refuses NaN with `NanRejected`.
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NanRejected;
+
+impl std::fmt::Display for NanRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NaN rejected by window policy")
+    }
+}
+
+impl std::error::Error for NanRejected {}
+
+const SKIP_MAX_LEVEL: usize = 32;
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct SkipNode {
+    value: f64,
+    next: Vec<usize>,
+    // Number of level-0 steps each link jumps over.
+    width: Vec<usize>,
+}
+
+/// Sorted multiset of `f64` (ordered by `total_cmp`) with O(log n) expected
+/// insert, remove and select-by-rank. A skip list whose links also record
+/// how many elements they skip, so a rank can be found by walking down the
+/// levels the same way a value is.
+#[derive(Debug, Clone)]
+pub struct IndexableSkipList {
+    // nodes[0] is the head; removed nodes are recycled through `free`.
+    nodes: Vec<SkipNode>,
+    free: Vec<usize>,
+    height: usize,
+    len: usize,
+    rng: u64,
+}
+
+impl Default for IndexableSkipList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IndexableSkipList {
+    pub fn new() -> Self {
+        let head = SkipNode {
+            value: f64::NEG_INFINITY,
+            next: vec![NIL; SKIP_MAX_LEVEL],
+            width: vec![1; SKIP_MAX_LEVEL],
+        };
+        Self {
+            nodes: vec![head],
+            free: Vec::new(),
+            height: 1,
+            len: 0,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (1 + self.rng.trailing_ones() as usize).min(SKIP_MAX_LEVEL)
+    }
+
+    fn next_is(&self, node: usize, level: usize, value: f64, accept: Ordering) -> bool {
+        let next = self.nodes[node].next[level];
+        next != NIL && self.nodes[next].value.total_cmp(&value) <= accept
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        let level = self.random_level();
+        if level > self.height {
+            // New levels start as one link from the head past every element.
+            for lvl in self.height..level {
+                self.nodes[0].width[lvl] = self.len + 1;
+            }
+            self.height = level;
+        }
+
+        let mut chain = [0; SKIP_MAX_LEVEL];
+        let mut steps = [0; SKIP_MAX_LEVEL];
+        let mut node = 0;
+        for lvl in (0..self.height).rev() {
+            while self.next_is(node, lvl, value, Ordering::Equal) {
+                steps[lvl] += self.nodes[node].width[lvl];
+                node = self.nodes[node].next[lvl];
+            }
+            chain[lvl] = node;
+        }
+
+        let new_node = SkipNode {
+            value,
+            next: vec![NIL; level],
+            width: vec![0; level],
+        };
+        let new = match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = new_node;
+                slot
+            }
+            None => {
+                self.nodes.push(new_node);
+                self.nodes.len() - 1
+            }
+        };
+
+        // `distance` is how far chain[lvl] is from the new node's
+        // predecessor on level 0.
+        let mut distance = 0;
+        for (lvl, &prev) in chain.iter().enumerate().take(level) {
+            self.nodes[new].next[lvl] = self.nodes[prev].next[lvl];
+            self.nodes[prev].next[lvl] = new;
+            self.nodes[new].width[lvl] = self.nodes[prev].width[lvl] - distance;
+            self.nodes[prev].width[lvl] = distance + 1;
+            distance += steps[lvl];
+        }
+        for (lvl, &prev) in chain.iter().enumerate().take(self.height).skip(level) {
+            self.nodes[prev].width[lvl] += 1;
+        }
+        self.len += 1;
+    }
+
+    /// Remove one occurrence of `value`. Returns false if it is absent.
+    pub fn remove(&mut self, value: f64) -> bool {
+        let mut chain = [0; SKIP_MAX_LEVEL];
+        let mut node = 0;
+        for lvl in (0..self.height).rev() {
+            while self.next_is(node, lvl, value, Ordering::Less) {
+                node = self.nodes[node].next[lvl];
+            }
+            chain[lvl] = node;
+        }
+
+        let target = self.nodes[chain[0]].next[0];
+        if target == NIL || self.nodes[target].value.total_cmp(&value) != Ordering::Equal {
+            return false;
+        }
+        let level = self.nodes[target].next.len();
+        for (lvl, &prev) in chain.iter().enumerate().take(level) {
+            self.nodes[prev].width[lvl] += self.nodes[target].width[lvl] - 1;
+            self.nodes[prev].next[lvl] = self.nodes[target].next[lvl];
+        }
+        for (lvl, &prev) in chain.iter().enumerate().take(self.height).skip(level) {
+            self.nodes[prev].width[lvl] -= 1;
+        }
+        self.free.push(target);
+        self.len -= 1;
+        true
+    }
+
+    /// The value at `rank` (0-based) in sorted order.
+    pub fn select(&self, rank: usize) -> Option<f64> {
+        if rank >= self.len {
+            return None;
+        }
+        let mut remaining = rank + 1;
+        let mut node = 0;
+        for lvl in (0..self.height).rev() {
+            while self.nodes[node].next[lvl] != NIL && self.nodes[node].width[lvl] <= remaining {
+                remaining -= self.nodes[node].width[lvl];
+                node = self.nodes[node].next[lvl];
+            }
+        }
+        Some(self.nodes[node].value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        let mut node = self.nodes[0].next[0];
+        std::iter::from_fn(move || {
+            if node == NIL {
+                return None;
+            }
+            let value = self.nodes[node].value;
+            node = self.nodes[node].next[0];
+            Some(value)
+        })
+    }
+}
+
+/// KLL quantile sketch: a stack of compactors in which an item at level `h`
+/// stands for `2^h` inputs. When the sketch is full, the first level over
+/// its capacity is sorted and every other item is promoted, starting from a
+/// random offset. Rank error is about `1/k` whatever the distribution, and
+/// two sketches merge by concatenating their levels.
+#[derive(Debug, Clone)]
+pub struct KllSketch {
+    k: usize,
+    compactors: Vec<Vec<f64>>,
+    count: u64,
+    rng: u64,
+}
+
+impl KllSketch {
+    pub fn new(k: usize) -> Self {
+        Self::with_seed(k, 0x9e37_79b9_7f4a_7c15)
+    }
+
+    pub fn with_seed(k: usize, seed: u64) -> Self {
+        Self {
+            k: k.max(8),
+            compactors: vec![Vec::new()],
+            count: 0,
+            rng: seed | 1,
+        }
+    }
+
+    /// Number of values summarized (NaN is skipped).
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of values actually stored.
+    pub fn retained(&self) -> usize {
+        self.compactors.iter().map(Vec::len).sum()
+    }
+
+    // Lower levels get geometrically smaller capacities (factor 2/3).
+    fn capacity(&self, level: usize) -> usize {
+        let depth = (self.compactors.len() - level - 1) as i32;
+        ((self.k as f64) * (2.0f64 / 3.0).powi(depth)).ceil() as usize + 1
+    }
+
+    fn max_retained(&self) -> usize {
+        (0..self.compactors.len()).map(|h| self.capacity(h)).sum()
+    }
+
+    pub fn update(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.compactors[0].push(value);
+        self.count += 1;
+        self.compress();
+    }
+
+    pub fn merge(&mut self, other: &KllSketch) {
+        while self.compactors.len() < other.compactors.len() {
+            self.compactors.push(Vec::new());
+        }
+        for (level, items) in other.compactors.iter().enumerate() {
+            self.compactors[level].extend_from_slice(items);
+        }
+        self.count += other.count;
+        self.compress();
+    }
+
+    fn compress(&mut self) {
+        while self.retained() >= self.max_retained() {
+            let level = (0..self.compactors.len())
+                .find(|&h| self.compactors[h].len() >= self.capacity(h))
+                .expect("a full sketch has a full level");
+            if level + 1 == self.compactors.len() {
+                self.compactors.push(Vec::new());
+            }
+
+            self.rng ^= self.rng << 13;
+            self.rng ^= self.rng >> 7;
+            self.rng ^= self.rng << 17;
+            let offset = (self.rng & 1) as usize;
+
+            let items = &mut self.compactors[level];
+            items.sort_by(f64::total_cmp);
+            // An odd item out stays behind so the total weight is exact.
+            let leftover = if items.len() % 2 == 1 {
+                items.pop()
+            } else {
+                None
+            };
+            let promoted: Vec<f64> = items.iter().skip(offset).step_by(2).copied().collect();
+            items.clear();
+            items.extend(leftover);
+            self.compactors[level + 1].extend(promoted);
+        }
+    }
+
+    fn weighted(&self) -> Vec<(f64, u64)> {
+        let mut items: Vec<(f64, u64)> = self
+            .compactors
+            .iter()
+            .enumerate()
+            .flat_map(|(level, items)| items.iter().map(move |&v| (v, 1u64 << level)))
+            .collect();
+        items.sort_by(|a, b| a.0.total_cmp(&b.0));
+        items
+    }
+
+    /// Approximate value at quantile `q` in `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let target = ((q * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (value, weight) in self.weighted() {
+            seen += weight;
+            if seen >= target {
+                return Some(value);
+            }
+        }
+        self.weighted().last().map(|&(value, _)| value)
+    }
+
+    /// Approximate fraction of values less than or equal to `value`.
+    pub fn rank(&self, value: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let below: u64 = self
+            .weighted()
+            .iter()
+            .take_while(|(v, _)| v.total_cmp(&value) != Ordering::Greater)
+            .map(|(_, w)| w)
+            .sum();
+        Some(below as f64 / self.count as f64)
+    }
+}
+
+/// Approximate quantiles over a window too large to keep exactly: one
+/// `KllSketch` per block of `block_size` values, merged when queried. The
+/// window slides a whole block at a time, so it covers the last `window`
+/// values rounded up to whole blocks, plus the block being filled.
+#[derive(Debug, Clone)]
+pub struct SketchWindow {
+    k: usize,
+    block_size: usize,
+    max_blocks: usize,
+    blocks: VecDeque<KllSketch>,
+    blocks_created: u64,
+}
+
+impl SketchWindow {
+    pub fn new(window: usize, block_size: usize, k: usize) -> Self {
+        let block_size = block_size.max(1);
+        Self {
+            k,
+            block_size,
+            max_blocks: window.div_ceil(block_size).max(1),
+            blocks: VecDeque::new(),
+            blocks_created: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let full = self
+            .blocks
+            .back()
+            .is_none_or(|block| block.len() >= self.block_size as u64);
+        if full {
+            self.blocks_created += 1;
+            let seed = self.blocks_created.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            self.blocks.push_back(KllSketch::with_seed(self.k, seed));
+            while self.blocks.len() > self.max_blocks + 1 {
+                self.blocks.pop_front();
+            }
+        }
+        if let Some(block) = self.blocks.back_mut() {
+            block.update(value);
+        }
+    }
+
+    /// Number of values currently covered.
+    pub fn len(&self) -> u64 {
+        self.blocks.iter().map(KllSketch::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All blocks merged into one sketch.
+    pub fn snapshot(&self) -> KllSketch {
+        let mut merged = KllSketch::new(self.k);
+        for block in &self.blocks {
+            merged.merge(block);
+        }
+        merged
+    }
+
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.snapshot().quantile(q)
+    }
+}
+
+// =============================================================================
+// Milestone 8: Timestamped Series Store with Gorilla Compression
+// =============================================================================
+
+/// Milliseconds since the Unix epoch.
+pub type Timestamp = i64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataPoint {
+    pub timestamp: Timestamp,
+    pub value: f64,
+}
+
+impl DataPoint {
+    pub fn new(timestamp: Timestamp, value: f64) -> Self {
+        Self { timestamp, value }
+    }
+}
+
+/// Time-based counterpart of `SlidingWindow`: keeps the points from the last
+/// `duration_ms` milliseconds, measured from the newest point. Points are
+/// expected in timestamp order.
+#[derive(Debug, Clone)]
+pub struct TimeWindow {
+    duration_ms: i64,
+    points: VecDeque<DataPoint>,
+    running_sum: f64,
+}
+
+impl TimeWindow {
+    pub fn new(duration_ms: i64) -> Self {
+        Self {
+            duration_ms,
+            points: VecDeque::new(),
+            running_sum: 0.0,
+        }
+    }
+
+    /// Add a point and return how many expired points were evicted.
+    pub fn push(&mut self, point: DataPoint) -> usize {
+        self.points.push_back(point);
+        self.running_sum += point.value;
+        let horizon = point.timestamp - self.duration_ms;
+        let mut evicted = 0;
+        while let Some(front) = self.points.front() {
+            if front.timestamp > horizon {
+                break;
+            }
+            self.running_sum -= front.value;
+            self.points.pop_front();
+            evicted += 1;
+        }
+        evicted
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        if self.points.is_empty() {
+            return None;
+        }
+        Some(self.running_sum / self.points.len() as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.points.iter().map(|p| p.value).min_by(f64::total_cmp)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.points.iter().map(|p| p.value).max_by(f64::total_cmp)
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &DataPoint> {
+        self.points.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.bits.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 0x80 >> (self.bits % 8);
+        }
+        self.bits += 1;
+    }
+
+    /// Write the low `count` bits of `value`, most significant first.
+    fn write_bits(&mut self, value: u64, count: u32) {
+        for i in (0..count).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.pos / 8)?;
+        let bit = byte & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Some(value)
+    }
+
+    fn read_signed(&mut self, count: u32) -> Option<i64> {
+        let raw = self.read_bits(count)?;
+        // Sign-extend from `count` bits.
+        let shift = 64 - count;
+        Some(((raw << shift) as i64) >> shift)
+    }
+}
+
+// Delta-of-delta buckets: (prefix, prefix length, payload bits). The final
+// bucket carries the full 64 bits for arbitrary jumps.
+const DOD_BUCKETS: [(u64, u32, u32); 5] = [
+    (0b10, 2, 7),
+    (0b110, 3, 9),
+    (0b1110, 4, 12),
+    (0b11110, 5, 32),
+    (0b11111, 5, 64),
+];
+
+fn write_delta_of_delta(writer: &mut BitWriter, dod: i64) {
+    if dod == 0 {
+        writer.write_bit(false);
+        return;
+    }
+    for &(prefix, prefix_len, bits) in &DOD_BUCKETS {
+        let fits = bits == 64 || (-(1i64 << (bits - 1))..(1i64 << (bits - 1))).contains(&dod);
+        if fits {
+            writer.write_bits(prefix, prefix_len);
+            writer.write_bits(dod as u64, bits);
+            return;
+        }
+    }
+}
+
+fn read_delta_of_delta(reader: &mut BitReader) -> Option<i64> {
+    let mut ones = 0;
+    while ones < 5 && reader.read_bit()? {
+        ones += 1;
+    }
+    if ones == 0 {
+        return Some(0);
+    }
+    let (_, _, bits) = DOD_BUCKETS[ones - 1];
+    reader.read_signed(bits)
+}
+
+/// Points compressed with Facebook's Gorilla scheme: timestamps as
+/// delta-of-deltas (regular intervals cost one bit), values as the XOR with
+/// the previous value, storing only the bits that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedBlock {
+    start: Timestamp,
+    end: Timestamp,
+    count: usize,
+    bytes: Vec<u8>,
+}
+
+impl CompressedBlock {
+    /// Encode points sorted by timestamp.
+    pub fn encode(points: &[DataPoint]) -> Self {
+        let mut writer = BitWriter::default();
+        let mut prev_ts = 0i64;
+        let mut prev_delta = 0i64;
+        let mut prev_bits = 0u64;
+        // (leading zeros, trailing zeros) of the last stored XOR window.
+        let mut window: Option<(u32, u32)> = None;
+
+        for (i, point) in points.iter().enumerate() {
+            let bits = point.value.to_bits();
+            if i == 0 {
+                writer.write_bits(point.timestamp as u64, 64);
+                writer.write_bits(bits, 64);
+            } else {
+                let delta = point.timestamp.wrapping_sub(prev_ts);
+                write_delta_of_delta(&mut writer, delta.wrapping_sub(prev_delta));
+                prev_delta = delta;
+
+                let xor = bits ^ prev_bits;
+                if xor == 0 {
+                    writer.write_bit(false);
+                } else {
+                    writer.write_bit(true);
+                    let leading = xor.leading_zeros().min(31);
+                    let trailing = xor.trailing_zeros();
+                    match window {
+                        Some((lead, trail)) if leading >= lead && trailing >= trail => {
+                            writer.write_bit(false);
+                            writer.write_bits(xor >> trail, 64 - lead - trail);
+                        }
+                        _ => {
+                            let significant = 64 - leading - trailing;
+                            writer.write_bit(true);
+                            writer.write_bits(u64::from(leading), 5);
+                            writer.write_bits(u64::from(significant - 1), 6);
+                            writer.write_bits(xor >> trailing, significant);
+                            window = Some((leading, trailing));
+                        }
+                    }
+                }
+            }
+            prev_ts = point.timestamp;
+            prev_bits = bits;
+        }
+
+        Self {
+            start: points.first().map_or(0, |p| p.timestamp),
+            end: points.last().map_or(0, |p| p.timestamp),
+            count: points.len(),
+            bytes: writer.bytes,
+        }
+    }
+
+    fn try_decode(&self) -> Option<Vec<DataPoint>> {
+        let mut reader = BitReader::new(&self.bytes);
+        // A corrupt count must not trigger a huge allocation.
+        let mut points = Vec::with_capacity(self.count.min(self.bytes.len() * 8));
+        let mut ts = 0i64;
+        let mut delta = 0i64;
+        let mut bits = 0u64;
+        let mut window = (0u32, 0u32);
+
+        for i in 0..self.count {
+            if i == 0 {
+                ts = reader.read_bits(64)? as i64;
+                bits = reader.read_bits(64)?;
+            } else {
+                delta = delta.wrapping_add(read_delta_of_delta(&mut reader)?);
+                ts = ts.wrapping_add(delta);
+                if reader.read_bit()? {
+                    if reader.read_bit()? {
+                        let leading = reader.read_bits(5)? as u32;
+                        let significant = reader.read_bits(6)? as u32 + 1;
+                        if leading + significant > 64 {
+                            return None;
+                        }
+                        window = (leading, 64 - leading - significant);
+                    }
+                    let (lead, trail) = window;
+                    bits ^= reader.read_bits(64 - lead - trail)? << trail;
+                }
+            }
+            points.push(DataPoint::new(ts, f64::from_bits(bits)));
+        }
+        Some(points)
+    }
+
+    pub fn decode(&self) -> Vec<DataPoint> {
+        self.try_decode()
+            .expect("blocks are validated when built or loaded")
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn compressed_size(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The point is older than the series' grace period allows.
+    TooLate {
+        metric: String,
+        timestamp: Timestamp,
+        watermark: Timestamp,
+    },
+    Io(std::io::Error),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::TooLate {
+                metric,
+                timestamp,
+                watermark,
+            } => write!(
+                f,
+                "point at {} for '{}' is older than watermark {}",
+                timestamp, metric, watermark
+            ),
+            StoreError::Io(err) => write!(f, "I/O error: {}", err),
+            StoreError::Corrupt(msg) => write!(f, "corrupt store file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<std::io::Error> for StoreError {
+    fn from(err: std::io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    /// How far behind the newest point a late point may still arrive.
+    pub grace_period_ms: i64,
+    /// Points per compressed block.
+    pub block_points: usize,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            grace_period_ms: 60_000,
+            block_points: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Min,
+    Max,
+    Avg,
+    Last,
+}
+
+/// Summary of one downsampling bucket `[bucket_start, bucket_start + width)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rollup {
+    pub bucket_start: Timestamp,
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub last: f64,
+}
+
+impl Rollup {
+    pub fn get(&self, aggregation: Aggregation) -> f64 {
+        match aggregation {
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Avg => self.avg,
+            Aggregation::Last => self.last,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Series {
+    // Sealed, time-ordered and non-overlapping.
+    blocks: Vec<CompressedBlock>,
+    // Points that may still receive late neighbours.
+    head: BTreeMap<Timestamp, f64>,
+    sealed_until: Option<Timestamp>,
+    latest: Option<Timestamp>,
+}
+
+impl Series {
+    fn watermark(&self, grace_period_ms: i64) -> Option<Timestamp> {
+        let late = self.latest.map(|t| t.saturating_sub(grace_period_ms));
+        let sealed = self.sealed_until.map(|t| t.saturating_add(1));
+        late.max(sealed)
+    }
+
+    /// Compress head points that can no longer change. With `partial`,
+    /// a final block may hold fewer than `block_points` points.
+    fn seal(&mut self, config: &StoreConfig, partial: bool) {
+        let Some(latest) = self.latest else {
+            return;
+        };
+        let cutoff = latest.saturating_sub(config.grace_period_ms);
+        let block_points = config.block_points.max(1);
+        loop {
+            let ready: Vec<DataPoint> = self
+                .head
+                .range(..cutoff)
+                .take(block_points)
+                .map(|(&t, &v)| DataPoint::new(t, v))
+                .collect();
+            if ready.is_empty() || (ready.len() < block_points && !partial) {
+                return;
+            }
+            for point in &ready {
+                self.head.remove(&point.timestamp);
+            }
+            self.sealed_until = ready.last().map(|p| p.timestamp);
+            self.blocks.push(CompressedBlock::encode(&ready));
+        }
+    }
+
+    fn range(&self, start: Timestamp, end: Timestamp) -> Vec<DataPoint> {
+        let mut points = Vec::new();
+        for block in &self.blocks {
+            if block.end < start || block.start >= end {
+                continue;
+            }
+            points.extend(
+                block
+                    .decode()
+                    .into_iter()
+                    .filter(|p| p.timestamp >= start && p.timestamp < end),
+            );
+        }
+        if start < end {
+            points.extend(
+                self.head
+                    .range(start..end)
+                    .map(|(&t, &v)| DataPoint::new(t, v)),
+            );
+        }
+        points
+    }
+
+    fn len(&self) -> usize {
+        self.blocks.iter().map(CompressedBlock::len).sum::<usize>() + self.head.len()
+    }
+}
+
+const STORE_MAGIC: &[u8; 8] = b"GORILLA1";
+
+/// Time series keyed by metric name. Recent points live in an ordered
+/// buffer so that late arrivals within `grace_period_ms` can still be
+/// inserted; anything older is sealed into Gorilla-compressed blocks.
+#[derive(Debug, Clone, Default)]
+pub struct SeriesStore {
+    config: StoreConfig,
+    series: BTreeMap<String, Series>,
+}
+
+impl SeriesStore {
+    pub fn new(config: StoreConfig) -> Self {
+        Self {
+            config,
+            series: BTreeMap::new(),
+        }
+    }
+
+    /// Insert or overwrite a point. Fails with `TooLate` if it falls
+    /// behind the grace period or into an already sealed block.
+    pub fn insert(&mut self, metric: &str, point: DataPoint) -> Result<(), StoreError> {
+        let series = self.series.entry(metric.to_string()).or_default();
+        if let Some(watermark) = series.watermark(self.config.grace_period_ms) {
+            if point.timestamp < watermark {
+                return Err(StoreError::TooLate {
+                    metric: metric.to_string(),
+                    timestamp: point.timestamp,
+                    watermark,
+                });
+            }
+        }
+        series.head.insert(point.timestamp, point.value);
+        series.latest = series.latest.max(Some(point.timestamp));
+        series.seal(&self.config, false);
+        Ok(())
+    }
+
+    /// Seal every point outside the grace period, even into short blocks.
+    pub fn flush(&mut self) {
+        for series in self.series.values_mut() {
+            series.seal(&self.config, true);
+        }
+    }
+
+    pub fn metrics(&self) -> impl Iterator<Item = &str> {
+        self.series.keys().map(String::as_str)
+    }
+
+    pub fn len(&self, metric: &str) -> usize {
+        self.series.get(metric).map_or(0, Series::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.values().all(|s| s.len() == 0)
+    }
+
+    pub fn block_count(&self, metric: &str) -> usize {
+        self.series.get(metric).map_or(0, |s| s.blocks.len())
+    }
+
+    /// Bytes used by sealed blocks of `metric`.
+    pub fn compressed_bytes(&self, metric: &str) -> usize {
+        self.series.get(metric).map_or(0, |s| {
+            s.blocks.iter().map(CompressedBlock::compressed_size).sum()
+        })
+    }
+
+    /// Points with `start <= timestamp < end`, in timestamp order.
+    pub fn range(&self, metric: &str, start: Timestamp, end: Timestamp) -> Vec<DataPoint> {
+        self.series
+            .get(metric)
+            .map_or_else(Vec::new, |s| s.range(start, end))
+    }
+
+    pub fn latest(&self, metric: &str) -> Option<DataPoint> {
+        self.last_n(metric, 1).pop()
+    }
+
+    /// The newest `n` points (a count-based window).
+    pub fn last_n(&self, metric: &str, n: usize) -> Vec<DataPoint> {
+        let Some(series) = self.series.get(metric) else {
+            return Vec::new();
+        };
+        let mut newest_first: Vec<DataPoint> = series
+            .head
+            .iter()
+            .rev()
+            .take(n)
+            .map(|(&t, &v)| DataPoint::new(t, v))
+            .collect();
+        for block in series.blocks.iter().rev() {
+            if newest_first.len() >= n {
+                break;
+            }
+            let needed = n - newest_first.len();
+            newest_first.extend(block.decode().into_iter().rev().take(needed));
+        }
+        newest_first.reverse();
+        newest_first
+    }
+
+    /// Points from the last `duration_ms` before the newest point (a
+    /// time-based window, e.g. "last 5 minutes").
+    pub fn last_duration(&self, metric: &str, duration_ms: i64) -> Vec<DataPoint> {
+        let Some(latest) = self.latest(metric).filter(|_| duration_ms > 0) else {
+            return Vec::new();
+        };
+        // `range` excludes its end, which cannot go past i64::MAX, so the
+        // newest point is appended rather than covered by `latest + 1`.
+        let start = latest.timestamp.saturating_sub(duration_ms - 1);
+        let mut points = self.range(metric, start, latest.timestamp);
+        points.push(latest);
+        points
+    }
+
+    /// Min/max/avg/last for each `bucket_ms`-wide bucket in `[start, end)`
+    /// that contains at least one point.
+    pub fn rollup(
+        &self,
+        metric: &str,
+        start: Timestamp,
+        end: Timestamp,
+        bucket_ms: i64,
+    ) -> Vec<Rollup> {
+        let bucket_ms = bucket_ms.max(1);
+        let mut rollups: Vec<Rollup> = Vec::new();
+        let mut sum = 0.0;
+        for point in self.range(metric, start, end) {
+            let bucket_start = point.timestamp - point.timestamp.rem_euclid(bucket_ms);
+            match rollups.last_mut() {
+                Some(rollup) if rollup.bucket_start == bucket_start => {
+                    rollup.count += 1;
+                    rollup.min = rollup.min.min(point.value);
+                    rollup.max = rollup.max.max(point.value);
+                    rollup.last = point.value;
+                    sum += point.value;
+                    rollup.avg = sum / rollup.count as f64;
+                }
+                _ => {
+                    sum = point.value;
+                    rollups.push(Rollup {
+                        bucket_start,
+                        count: 1,
+                        min: point.value,
+                        max: point.value,
+                        avg: point.value,
+                        last: point.value,
+                    });
+                }
+            }
+        }
+        rollups
+    }
+
+    /// One point per bucket, stamped with the bucket start.
+    pub fn downsample(
+        &self,
+        metric: &str,
+        start: Timestamp,
+        end: Timestamp,
+        bucket_ms: i64,
+        aggregation: Aggregation,
+    ) -> Vec<DataPoint> {
+        self.rollup(metric, start, end, bucket_ms)
+            .iter()
+            .map(|r| DataPoint::new(r.bucket_start, r.get(aggregation)))
+            .collect()
+    }
+
+    /// Feed a stored range through `detector`, returning what it flags.
+    pub fn detect_anomalies(
+        &self,
+        metric: &str,
+        start: Timestamp,
+        end: Timestamp,
+        detector: &mut dyn Detector,
+    ) -> Vec<Anomaly> {
+        detector.scan(&self.range(metric, start, end))
+    }
+
+    /// Write every series to `path`. The unsealed buffer is stored as one
+    /// more block, so a reloaded series only accepts points newer than
+    /// everything that was saved.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StoreError> {
+        let mut out = Vec::new();
+        out.extend_from_slice(STORE_MAGIC);
+        out.extend_from_slice(&(self.series.len() as u32).to_le_bytes());
+        for (name, series) in &self.series {
+            let head: Vec<DataPoint> = series
+                .head
+                .iter()
+                .map(|(&t, &v)| DataPoint::new(t, v))
+                .collect();
+            let head_block = (!head.is_empty()).then(|| CompressedBlock::encode(&head));
+            let blocks: Vec<&CompressedBlock> =
+                series.blocks.iter().chain(head_block.as_ref()).collect();
+
+            out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+            for block in blocks {
+                out.extend_from_slice(&(block.count as u64).to_le_bytes());
+                out.extend_from_slice(&block.start.to_le_bytes());
+                out.extend_from_slice(&block.end.to_le_bytes());
+                out.extend_from_slice(&(block.bytes.len() as u32).to_le_bytes());
+                out.extend_from_slice(&block.bytes);
+            }
+        }
+
+        // Write to a sibling file first so a crash never leaves half a store.
+        // The suffix is appended, so `a.db` and `a.log` get distinct temp files.
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, &out)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>, config: StoreConfig) -> Result<Self, StoreError> {
+        let data = fs::read(path)?;
+        let mut input = data.as_slice();
+        let mut take = |n: usize| -> Result<&[u8], StoreError> {
+            if input.len() < n {
+                return Err(StoreError::Corrupt("unexpected end of file".to_string()));
+            }
+            let (head, rest) = input.split_at(n);
+            input = rest;
+            Ok(head)
+        };
+
+        if take(STORE_MAGIC.len())? != STORE_MAGIC {
+            return Err(StoreError::Corrupt("bad magic".to_string()));
+        }
+        let mut store = Self::new(config);
+        let series_count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        for _ in 0..series_count {
+            let name_len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+            let name = String::from_utf8(take(name_len)?.to_vec())
+                .map_err(|_| StoreError::Corrupt("metric name is not UTF-8".to_string()))?;
+            let block_count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+            let mut series = Series::default();
+            for _ in 0..block_count {
+                let count = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+                let start = i64::from_le_bytes(take(8)?.try_into().unwrap());
+                let end = i64::from_le_bytes(take(8)?.try_into().unwrap());
+                let len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+                let block = CompressedBlock {
+                    start,
+                    end,
+                    count,
+                    bytes: take(len)?.to_vec(),
+                };
+                let points = block
+                    .try_decode()
+                    .filter(|p| {
+                        !p.is_empty()
+                            && p[0].timestamp == start
+                            && p[p.len() - 1].timestamp == end
+                            && p.windows(2).all(|w| w[0].timestamp < w[1].timestamp)
+                            && series.sealed_until.is_none_or(|t| t < start)
+                    })
+                    .ok_or_else(|| {
+                        StoreError::Corrupt(format!("invalid block in series '{}'", name))
+                    })?;
+                series.sealed_until = points.last().map(|p| p.timestamp);
+                series.blocks.push(block);
+            }
+            series.latest = series.sealed_until;
+            store.series.insert(name, series);
+        }
+        if !input.is_empty() {
+            return Err(StoreError::Corrupt("trailing bytes".to_string()));
+        }
+        Ok(store)
+    }
+}
+
+// =============================================================================
+// Milestone 9: Pluggable, Seasonal and Ensemble Anomaly Detectors
+// =============================================================================
+
+/// A streaming anomaly detector. Points must be fed in timestamp order;
+/// seasonal detectors also assume they are evenly spaced.
+pub trait Detector {
+    fn name(&self) -> &'static str;
+
+    /// Feed the next point; returns an anomaly if it is flagged.
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly>;
+
+    fn scan(&mut self, points: &[DataPoint]) -> Vec<Anomaly> {
+        points.iter().filter_map(|&p| self.observe(p)).collect()
+    }
+}
+
+impl Detector for AnomalyDetector {
+    fn name(&self) -> &'static str {
+        "zscore"
+    }
+
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        self.push(point.value, point.timestamp)
+    }
+}
+
+/// Band detectors clamp flagged points to `expected ± width * sigma`, which
+/// needs `lower <= upper`.
+fn assert_band_width(width: f64) {
+    assert!(
+        width.is_finite() && width >= 0.0,
+        "band width must be finite and non-negative, got {width}"
+    );
+}
+
+/// Build an anomaly for `point` if it falls outside `expected ± width * sigma`.
+fn check_band(
+    detector: &'static str,
+    point: DataPoint,
+    expected: f64,
+    sigma: f64,
+    width: f64,
+) -> Option<Anomaly> {
+    let deviation = point.value - expected;
+    let z_score = if sigma > 0.0 {
+        deviation / sigma
+    } else if deviation == 0.0 {
+        0.0
+    } else {
+        deviation.signum() * f64::INFINITY
+    };
+    (z_score.abs() > width).then_some(Anomaly {
+        value: point.value,
+        z_score,
+        timestamp: point.timestamp,
+        window_stats: None,
+        detector,
+        expected,
+        lower: expected - width * sigma,
+        upper: expected + width * sigma,
+    })
+}
+
+/// Exponentially weighted moving average with control limits at
+/// `expected ± width * sigma`, where sigma is the EWMA of squared
+/// deviations. Flagged points are clamped to the band before updating, so a
+/// single spike does not drag the baseline after it.
+#[derive(Debug, Clone)]
+pub struct EwmaDetector {
+    alpha: f64,
+    width: f64,
+    warmup: usize,
+    seen: usize,
+    mean: f64,
+    variance: f64,
+}
+
+impl EwmaDetector {
+    /// Panics if `width` is negative or not finite.
+    pub fn new(alpha: f64, width: f64, warmup: usize) -> Self {
+        assert_band_width(width);
+        Self {
+            alpha: alpha.clamp(f64::EPSILON, 1.0),
+            width,
+            warmup: warmup.max(2),
+            seen: 0,
+            mean: 0.0,
+            variance: 0.0,
+        }
+    }
+}
+
+impl Detector for EwmaDetector {
+    fn name(&self) -> &'static str {
+        "ewma"
+    }
+
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        if point.value.is_nan() {
+            return None;
+        }
+        self.seen += 1;
+        if self.seen == 1 {
+            self.mean = point.value;
+            return None;
+        }
+        let sigma = self.variance.sqrt();
+        let anomaly = if self.seen > self.warmup {
+            check_band(self.name(), point, self.mean, sigma, self.width)
+        } else {
+            None
+        };
+        let value = match &anomaly {
+            Some(a) => point.value.clamp(a.lower, a.upper),
+            None => point.value,
+        };
+        let deviation = value - self.mean;
+        self.mean += self.alpha * deviation;
+        self.variance = (1.0 - self.alpha) * (self.variance + self.alpha * deviation * deviation);
+        anomaly
+    }
+}
+
+/// Median and median absolute deviation over the last `window` points.
+/// One large outlier barely moves either statistic, unlike mean and
+/// standard deviation. Sigma is estimated as `1.4826 * MAD`.
+#[derive(Debug, Clone)]
+pub struct MadDetector {
+    window: VecDeque<f64>,
+    capacity: usize,
+    width: f64,
+}
+
+impl MadDetector {
+    /// Panics if `width` is negative or not finite.
+    pub fn new(window: usize, width: f64) -> Self {
+        assert_band_width(width);
+        Self {
+            window: VecDeque::with_capacity(window),
+            capacity: window.max(3),
+            width,
+        }
+    }
+
+    fn median(values: &mut [f64]) -> f64 {
+        let mid = values.len() / 2;
+        let (_, upper, _) = values.select_nth_unstable_by(mid, f64::total_cmp);
+        let upper = *upper;
+        if values.len() % 2 == 1 {
+            upper
+        } else {
+            let lower = values[..mid].iter().copied().fold(f64::MIN, f64::max);
+            (lower + upper) / 2.0
+        }
+    }
+}
+
+impl Detector for MadDetector {
+    fn name(&self) -> &'static str {
+        "mad"
+    }
+
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        if point.value.is_nan() {
+            return None;
+        }
+        let anomaly = if self.window.len() == self.capacity {
+            let mut values: Vec<f64> = self.window.iter().copied().collect();
+            let median = Self::median(&mut values);
+            let mut deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+            let mad = Self::median(&mut deviations);
+            check_band(self.name(), point, median, 1.4826 * mad, self.width)
+        } else {
+            None
+        };
+        if self.window.len() == self.capacity {
+            self.window.pop_front();
+        }
+        self.window.push_back(point.value);
+        anomaly
+    }
+}
+
+/// Additive Holt-Winters (level, trend and a seasonal profile of `period`
+/// points). The first two periods initialise the model, so a point is
+/// judged against what is normal for its position in the cycle rather
+/// than against the overall mean. Residual spread is an EWMA with the
+/// seasonal smoothing factor; flagged points are clamped to the band
+/// before the model learns from them.
+#[derive(Debug, Clone)]
+pub struct HoltWintersDetector {
+    period: usize,
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    width: f64,
+    warmup: Vec<f64>,
+    level: f64,
+    trend: f64,
+    seasonal: Vec<f64>,
+    residual_variance: f64,
+    fitted: usize,
+}
+
+impl HoltWintersDetector {
+    /// Smoothing factors are clamped to `[0, 1]`. Panics if `width` is
+    /// negative or not finite.
+    pub fn new(period: usize, alpha: f64, beta: f64, gamma: f64, width: f64) -> Self {
+        assert_band_width(width);
+        let period = period.max(1);
+        Self {
+            period,
+            alpha: alpha.clamp(0.0, 1.0),
+            beta: beta.clamp(0.0, 1.0),
+            gamma: gamma.clamp(0.0, 1.0),
+            width,
+            warmup: Vec::with_capacity(2 * period),
+            level: 0.0,
+            trend: 0.0,
+            seasonal: vec![0.0; period],
+            residual_variance: 0.0,
+            fitted: 0,
         }
     }
 
-    pub fn get_stats(&self, window_index: usize) -> Option<WindowStats> {
-        self.windows.get(window_index).map(|window| {
-            let min = window
-                .window
-                .iter()
-                .min_by(|a, b| a.partial_cmp(b).unwrap())
-                .copied();
-            let max = window
-                .window
-                .iter()
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .copied();
-            WindowStats {
-                average: window.average(),
-                std_dev: window.std_dev(),
-                median: window.median(),
-                min,
-                max,
-                window_size: self.window_sizes[window_index],
-            }
-        })
+    fn initialise(&mut self) {
+        let p = self.period as f64;
+        let first: f64 = self.warmup[..self.period].iter().sum::<f64>() / p;
+        let second: f64 = self.warmup[self.period..].iter().sum::<f64>() / p;
+        self.trend = (second - first) / p;
+        for i in 0..self.period {
+            self.seasonal[i] =
+                (self.warmup[i] - first + self.warmup[i + self.period] - second) / 2.0;
+        }
+        // Level at the end of the second period.
+        self.level = second + self.trend * (p - 1.0) / 2.0;
+        let variance = self
+            .warmup
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let base = if i < self.period { first } else { second };
+                let r = x - base - self.seasonal[i % self.period];
+                r * r
+            })
+            .sum::<f64>()
+            / (2.0 * p);
+        self.residual_variance = variance;
     }
 
-    pub fn all_stats(&self) -> Vec<WindowStats> {
-        (0..self.windows.len())
-            .filter_map(|idx| self.get_stats(idx))
-            .collect()
+    /// Forecast for the next point.
+    pub fn forecast(&self) -> Option<f64> {
+        if self.warmup.len() < 2 * self.period {
+            return None;
+        }
+        let season = self.seasonal[self.fitted % self.period];
+        Some(self.level + self.trend + season)
     }
+}
 
-    pub fn window_count(&self) -> usize {
-        self.windows.len()
+impl Detector for HoltWintersDetector {
+    fn name(&self) -> &'static str {
+        "holt-winters"
     }
-}
 
-// =============================================================================
-// Milestone 6: Anomaly Detection with Z-Score
-// =============================================================================
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        if point.value.is_nan() {
+            return None;
+        }
+        let Some(expected) = self.forecast() else {
+            self.warmup.push(point.value);
+            if self.warmup.len() == 2 * self.period {
+                self.initialise();
+            }
+            return None;
+        };
 
-#[derive(Debug)]
-pub struct AnomalyDetector {
-    analyzer: MultiWindowAnalyzer,
-    threshold: f64,
-    anomalies: Vec<Anomaly>,
+        let sigma = self.residual_variance.sqrt();
+        // Let the residual estimate settle for one period before judging.
+        let anomaly = if self.fitted >= self.period {
+            check_band(self.name(), point, expected, sigma, self.width)
+        } else {
+            None
+        };
+        let value = match &anomaly {
+            Some(a) => point.value.clamp(a.lower, a.upper),
+            None => point.value,
+        };
+
+        let idx = self.fitted % self.period;
+        let season = self.seasonal[idx];
+        let previous_level = self.level;
+        self.level = self.alpha * (value - season) + (1.0 - self.alpha) * (self.level + self.trend);
+        self.trend = self.beta * (self.level - previous_level) + (1.0 - self.beta) * self.trend;
+        self.seasonal[idx] = self.gamma * (value - self.level) + (1.0 - self.gamma) * season;
+        let residual = value - expected;
+        self.residual_variance += self.gamma * (residual * residual - self.residual_variance);
+        self.fitted += 1;
+        anomaly
+    }
 }
 
+/// Two-sided tabular CUSUM. A baseline mean and sigma are learned from the
+/// first `warmup` points. Standardised deviations beyond `slack` accumulate
+/// until one side exceeds `threshold`, which catches small sustained shifts
+/// that never trip a per-point limit. After an alarm the baseline is
+/// re-learned from the points that follow. The band reported is the slack
+/// region around the baseline.
 #[derive(Debug, Clone)]
-pub struct Anomaly {
-    pub value: f64,
-    pub z_score: f64,
-    pub timestamp: usize,
-    pub window_stats: WindowStats,
+pub struct CusumDetector {
+    warmup: usize,
+    slack: f64,
+    threshold: f64,
+    baseline: Vec<f64>,
+    mean: f64,
+    sigma: f64,
+    upper_sum: f64,
+    lower_sum: f64,
 }
 
-impl AnomalyDetector {
-    pub fn new(window_sizes: Vec<usize>, threshold: f64) -> Self {
+impl CusumDetector {
+    /// Panics if `slack` is negative, `threshold` is not positive, or either
+    /// is not finite.
+    pub fn new(warmup: usize, slack: f64, threshold: f64) -> Self {
+        assert!(
+            slack.is_finite() && slack >= 0.0,
+            "CUSUM slack must be finite and non-negative, got {slack}"
+        );
+        assert!(
+            threshold.is_finite() && threshold > 0.0,
+            "CUSUM threshold must be finite and positive, got {threshold}"
+        );
         Self {
-            analyzer: MultiWindowAnalyzer::new(window_sizes),
+            warmup: warmup.max(2),
+            slack,
             threshold,
-            anomalies: Vec::new(),
+            baseline: Vec::new(),
+            mean: 0.0,
+            sigma: 0.0,
+            upper_sum: 0.0,
+            lower_sum: 0.0,
         }
     }
 
-    pub fn push(&mut self, value: f64, timestamp: usize) -> Option<Anomaly> {
-        self.analyzer.push(value);
-        if let Some(last_stats) = self
-            .analyzer
-            .get_stats(self.analyzer.window_count().saturating_sub(1))
-        {
-            if let (Some(mean), Some(std_dev)) = (last_stats.average, last_stats.std_dev) {
-                if std_dev > 0.0 {
-                    let z = (value - mean) / std_dev;
-                    if z.abs() >= self.threshold {
-                        let anomaly = Anomaly {
-                            value,
-                            z_score: z,
-                            timestamp,
-                            window_stats: last_stats.clone(),
-                        };
-                        self.anomalies.push(anomaly.clone());
-                        return Some(anomaly);
-                    }
-                }
+    fn learning(&self) -> bool {
+        self.baseline.len() < self.warmup
+    }
+}
+
+impl Detector for CusumDetector {
+    fn name(&self) -> &'static str {
+        "cusum"
+    }
+
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        if point.value.is_nan() {
+            return None;
+        }
+        if self.learning() {
+            self.baseline.push(point.value);
+            if !self.learning() {
+                let n = self.baseline.len() as f64;
+                self.mean = self.baseline.iter().sum::<f64>() / n;
+                let variance = self
+                    .baseline
+                    .iter()
+                    .map(|v| (v - self.mean).powi(2))
+                    .sum::<f64>()
+                    / (n - 1.0);
+                self.sigma = variance.sqrt().max(f64::EPSILON);
             }
+            return None;
         }
-        None
+
+        let z = (point.value - self.mean) / self.sigma;
+        self.upper_sum = (self.upper_sum + z - self.slack).max(0.0);
+        self.lower_sum = (self.lower_sum - z - self.slack).max(0.0);
+        let score = if self.upper_sum > self.threshold {
+            self.upper_sum
+        } else if self.lower_sum > self.threshold {
+            -self.lower_sum
+        } else {
+            return None;
+        };
+
+        let anomaly = Anomaly {
+            value: point.value,
+            z_score: score,
+            timestamp: point.timestamp,
+            window_stats: None,
+            detector: self.name(),
+            expected: self.mean,
+            lower: self.mean - self.slack * self.sigma,
+            upper: self.mean + self.slack * self.sigma,
+        };
+        self.baseline.clear();
+        self.upper_sum = 0.0;
+        self.lower_sum = 0.0;
+        Some(anomaly)
     }
+}
 
-    pub fn anomaly_rate(&self, total_points: usize) -> f64 {
-        if total_points == 0 {
-            return 0.0;
+/// Runs every detector on each point and flags it when at least
+/// `min_votes` of them agree. The reported expected value and band are
+/// the medians of the voters', and the score is the most extreme one.
+pub struct Ensemble {
+    detectors: Vec<Box<dyn Detector>>,
+    min_votes: usize,
+    last_votes: Vec<Anomaly>,
+}
+
+impl Ensemble {
+    pub fn new(min_votes: usize) -> Self {
+        Self {
+            detectors: Vec::new(),
+            min_votes: min_votes.max(1),
+            last_votes: Vec::new(),
         }
-        self.anomalies.len() as f64 / total_points as f64
     }
 
-    pub fn get_anomalies(&self) -> &[Anomaly] {
-        &self.anomalies
+    pub fn with(mut self, detector: impl Detector + 'static) -> Self {
+        self.detectors.push(Box::new(detector));
+        self
     }
 
-    pub fn clear_anomalies(&mut self) {
-        self.anomalies.clear();
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+
+    /// The individual detectors that flagged the last observed point.
+    pub fn last_votes(&self) -> &[Anomaly] {
+        &self.last_votes
+    }
+}
+
+impl Detector for Ensemble {
+    fn name(&self) -> &'static str {
+        "ensemble"
+    }
+
+    fn observe(&mut self, point: DataPoint) -> Option<Anomaly> {
+        self.last_votes = self
+            .detectors
+            .iter_mut()
+            .filter_map(|d| d.observe(point))
+            .collect();
+        if self.last_votes.len() < self.min_votes {
+            return None;
+        }
+
+        let median_of = |field: fn(&Anomaly) -> f64| {
+            let mut values: Vec<f64> = self.last_votes.iter().map(field).collect();
+            values.sort_by(f64::total_cmp);
+            let mid = values.len() / 2;
+            if values.len() % 2 == 1 {
+                values[mid]
+            } else {
+                (values[mid - 1] + values[mid]) / 2.0
+            }
+        };
+        let z_score = self
+            .last_votes
+            .iter()
+            .map(|a| a.z_score)
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.0);
+        Some(Anomaly {
+            value: point.value,
+            z_score,
+            timestamp: point.timestamp,
+            window_stats: None,
+            detector: self.name(),
+            expected: median_of(|a| a.expected),
+            lower: median_of(|a| a.lower),
+            upper: median_of(|a| a.upper),
+        })
     }
 }
 
@@ -791,7 +2476,7 @@ mod tests {
         }
         let anomaly = detector.push(200.0, 30).unwrap();
         assert_eq!(anomaly.timestamp, 30);
-        assert!(anomaly.window_stats.average.is_some());
+        assert!(anomaly.window_stats.unwrap().average.is_some());
     }
 
     #[test]
@@ -799,11 +2484,11 @@ mod tests {
         let data: Vec<f64> = (0..100).map(|i| 50.0 + (i % 20) as f64).collect();
         let mut strict = AnomalyDetector::new(vec![50], 2.0);
         for (i, &v) in data.iter().enumerate() {
-            strict.push(v, i);
+            strict.push(v, i as Timestamp);
         }
         let mut lenient = AnomalyDetector::new(vec![50], 4.0);
         for (i, &v) in data.iter().enumerate() {
-            lenient.push(v, i);
+            lenient.push(v, i as Timestamp);
         }
         assert!(strict.get_anomalies().len() >= lenient.get_anomalies().len());
     }
@@ -819,6 +2504,605 @@ mod tests {
         detector.clear_anomalies();
         assert_eq!(detector.get_anomalies().len(), 0);
     }
+
+    // ----- Milestone 7 -----
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn test_skip_list_matches_sorted_model() {
+        let mut list = IndexableSkipList::new();
+        let mut model: Vec<f64> = Vec::new();
+        let mut state = 0x1234_5678_9abc_def1;
+        for step in 0..3000 {
+            let value = (xorshift(&mut state) % 200) as f64 - 100.0;
+            if step % 3 == 2 && !model.is_empty() {
+                let victim = model[(xorshift(&mut state) as usize) % model.len()];
+                assert!(list.remove(victim));
+                let idx = model.iter().position(|&v| v == victim).unwrap();
+                model.remove(idx);
+            } else {
+                list.insert(value);
+                let idx = model.partition_point(|&v| v < value);
+                model.insert(idx, value);
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        for (rank, &expected) in model.iter().enumerate() {
+            assert_eq!(list.select(rank), Some(expected));
+        }
+        assert_eq!(list.select(model.len()), None);
+        assert_eq!(list.iter().collect::<Vec<_>>(), model);
+        assert!(!list.remove(1000.0));
+    }
+
+    #[test]
+    fn test_window_order_statistics_after_eviction() {
+        let mut window = IncrementalWindow::new(101);
+        let mut state = 0xdead_beef_cafe_f00d;
+        for _ in 0..5000 {
+            window.push((xorshift(&mut state) % 10_000) as f64 / 7.0);
+        }
+
+        let mut sorted: Vec<f64> = window.window.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        assert_eq!(window.median(), Some(sorted[50]));
+        assert_eq!(window.min(), Some(sorted[0]));
+        assert_eq!(window.max(), Some(sorted[100]));
+        assert_eq!(window.percentile(25.0), Some(sorted[25]));
+        let p = window.percentile(33.3).unwrap();
+        assert!(p >= sorted[33] && p <= sorted[34]);
+        assert_eq!(
+            window.percentiles(&[10.0, 90.0]),
+            vec![Some(sorted[10]), Some(sorted[90])]
+        );
+    }
+
+    #[test]
+    fn test_nan_policy_propagate() {
+        let mut window = IncrementalWindow::new(3);
+        window.push(1.0);
+        window.push(f64::NAN);
+        window.push(3.0);
+        assert!(window.median().unwrap().is_nan());
+        assert!(window.average().unwrap().is_nan());
+        assert!(window.percentile(90.0).unwrap().is_nan());
+
+        // Once the NaN slides out, the window recovers.
+        window.push(5.0);
+        window.push(7.0);
+        assert_eq!(window.median(), Some(5.0));
+        assert_eq!(window.average(), Some(5.0));
+    }
+
+    #[test]
+    fn test_nan_policy_ignore_and_reject() {
+        let mut ignore = IncrementalWindow::with_nan_policy(3, NanPolicy::Ignore);
+        for v in [1.0, f64::NAN, 2.0, 3.0] {
+            ignore.push(v);
+        }
+        assert_eq!(ignore.len(), 3);
+        assert_eq!(ignore.median(), Some(2.0));
+
+        let mut reject = IncrementalWindow::with_nan_policy(3, NanPolicy::Reject);
+        assert_eq!(reject.try_push(1.0), Ok(()));
+        assert_eq!(reject.try_push(f64::NAN), Err(NanRejected));
+        assert_eq!(reject.len(), 1);
+        assert_eq!(reject.median(), Some(1.0));
+    }
+
+    #[test]
+    fn test_multi_window_stats_with_nan_do_not_panic() {
+        let mut analyzer = MultiWindowAnalyzer::new(vec![2, 4]);
+        for v in [1.0, f64::NAN, 3.0, 4.0] {
+            analyzer.push(v);
+        }
+        let short = analyzer.get_stats(0).unwrap();
+        assert_eq!(
+            (short.min, short.max, short.median),
+            (Some(3.0), Some(4.0), Some(3.5))
+        );
+        assert!(analyzer.get_stats(1).unwrap().max.unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_kll_sketch_rank_error() {
+        let n = 100_000u64;
+        let mut sketch = KllSketch::new(200);
+        for i in 0..n {
+            sketch.update(((i * 7919) % n) as f64);
+        }
+        sketch.update(f64::NAN);
+        assert_eq!(sketch.len(), n);
+        assert!(sketch.retained() < 1000, "retained {}", sketch.retained());
+
+        for q in [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99] {
+            let value = sketch.quantile(q).unwrap();
+            let true_rank = (value + 1.0) / n as f64;
+            assert!(
+                (true_rank - q).abs() < 0.02,
+                "q={} got rank {}",
+                q,
+                true_rank
+            );
+        }
+        let rank = sketch.rank(50_000.0).unwrap();
+        assert!((rank - 0.5).abs() < 0.02);
+        assert_eq!(sketch.quantile(1.5), None);
+        assert_eq!(KllSketch::new(200).quantile(0.5), None);
+    }
+
+    #[test]
+    fn test_kll_sketch_merge() {
+        let mut low = KllSketch::with_seed(200, 1);
+        let mut high = KllSketch::with_seed(200, 2);
+        for i in 0..50_000 {
+            low.update(i as f64);
+            high.update((i + 50_000) as f64);
+        }
+        low.merge(&high);
+        assert_eq!(low.len(), 100_000);
+        let median = low.quantile(0.5).unwrap();
+        assert!((median - 50_000.0).abs() < 2_000.0, "median {}", median);
+        let p90 = low.quantile(0.9).unwrap();
+        assert!((p90 - 90_000.0).abs() < 2_000.0, "p90 {}", p90);
+    }
+
+    #[test]
+    fn test_sketch_window_slides() {
+        let mut window = SketchWindow::new(10_000, 1_000, 200);
+        for i in 0..50_000 {
+            window.push(i as f64);
+        }
+        // Covers the last 10k values plus at most one partial block.
+        assert!(window.len() >= 10_000 && window.len() <= 11_000);
+        let median = window.quantile(0.5).unwrap();
+        assert!((median - 44_500.0).abs() < 1_000.0, "median {}", median);
+        assert!(window.quantile(0.0).unwrap() >= 39_000.0);
+    }
+
+    // ----- Milestone 8 -----
+    fn minutes(n: i64) -> Timestamp {
+        n * 60_000
+    }
+
+    #[test]
+    fn test_time_window_evicts_by_age() {
+        let mut window = TimeWindow::new(minutes(5));
+        for i in 0..10 {
+            window.push(DataPoint::new(minutes(i), i as f64));
+        }
+        // Only points newer than 9 - 5 minutes remain.
+        let kept: Vec<Timestamp> = window.points().map(|p| p.timestamp).collect();
+        assert_eq!(kept, (5..10).map(minutes).collect::<Vec<_>>());
+        assert_eq!(window.average(), Some(7.0));
+        assert_eq!((window.min(), window.max()), (Some(5.0), Some(9.0)));
+        assert_eq!(window.push(DataPoint::new(minutes(30), 1.0)), 5);
+        assert_eq!(window.len(), 1);
+    }
+
+    #[test]
+    fn test_gorilla_round_trip() {
+        let mut state = 0x0123_4567_89ab_cdef;
+        let mut ts = 1_700_000_000_000i64;
+        let mut points = Vec::new();
+        for i in 0..2000 {
+            ts += match i % 500 {
+                0 => 1 << 40,
+                1 => -5,
+                _ => 1000 + (xorshift(&mut state) % 3) as i64 - 1,
+            };
+            let value = match i % 7 {
+                0 => f64::NAN,
+                1 => f64::NEG_INFINITY,
+                2 => -0.0,
+                3 => (xorshift(&mut state) % 1000) as f64 / 3.0,
+                _ => 42.5,
+            };
+            points.push(DataPoint::new(ts, value));
+        }
+        let block = CompressedBlock::encode(&points);
+        let decoded = block.decode();
+        assert_eq!(decoded.len(), points.len());
+        for (a, b) in points.iter().zip(&decoded) {
+            assert_eq!(a.timestamp, b.timestamp);
+            assert_eq!(a.value.to_bits(), b.value.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_gorilla_compresses_regular_series() {
+        let points: Vec<DataPoint> = (0..1000)
+            .map(|i| DataPoint::new(1_700_000_000_000 + i * 10_000, 20.0 + (i % 4) as f64 * 0.5))
+            .collect();
+        let block = CompressedBlock::encode(&points);
+        assert_eq!(block.decode(), points);
+        // Raw storage would be 16 bytes per point.
+        assert!(
+            block.compressed_size() < 2 * points.len(),
+            "{}",
+            block.compressed_size()
+        );
+    }
+
+    #[test]
+    fn test_store_out_of_order_within_grace() {
+        let mut store = SeriesStore::new(StoreConfig {
+            grace_period_ms: minutes(2),
+            block_points: 4,
+        });
+        for &m in &[0, 1, 3, 2, 5] {
+            store
+                .insert("cpu", DataPoint::new(minutes(m), m as f64))
+                .unwrap();
+        }
+        // Overwrite within the grace period.
+        store
+            .insert("cpu", DataPoint::new(minutes(3), 30.0))
+            .unwrap();
+
+        let err = store
+            .insert("cpu", DataPoint::new(minutes(2), 0.0))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            StoreError::TooLate { watermark, .. } if watermark == minutes(3)
+        ));
+
+        let values: Vec<f64> = store
+            .range("cpu", 0, minutes(10))
+            .iter()
+            .map(|p| p.value)
+            .collect();
+        assert_eq!(values, vec![0.0, 1.0, 2.0, 30.0, 5.0]);
+        assert!(store.range("mem", 0, minutes(10)).is_empty());
+    }
+
+    #[test]
+    fn test_store_seals_blocks_and_queries_across_them() {
+        let mut store = SeriesStore::new(StoreConfig {
+            grace_period_ms: 5_000,
+            block_points: 100,
+        });
+        for i in 0..1000 {
+            store
+                .insert("temp", DataPoint::new(i * 1000, i as f64))
+                .unwrap();
+        }
+        assert_eq!(store.len("temp"), 1000);
+        assert_eq!(store.block_count("temp"), 9);
+        assert!(store.compressed_bytes("temp") > 0);
+
+        // Points older than the sealed frontier are refused.
+        assert!(store.insert("temp", DataPoint::new(500, 1.0)).is_err());
+
+        let range = store.range("temp", 250_000, 260_000);
+        assert_eq!(range.len(), 10);
+        assert_eq!(range[0], DataPoint::new(250_000, 250.0));
+
+        let last = store.last_n("temp", 150);
+        assert_eq!(last.len(), 150);
+        assert_eq!(last[0].timestamp, 850_000);
+        assert_eq!(last[149].timestamp, 999_000);
+
+        let recent = store.last_duration("temp", 5_000);
+        assert_eq!(recent.len(), 5);
+        assert_eq!(store.latest("temp"), Some(DataPoint::new(999_000, 999.0)));
+
+        store.flush();
+        assert_eq!(store.block_count("temp"), 10);
+        assert_eq!(store.range("temp", 0, i64::MAX).len(), 1000);
+    }
+
+    #[test]
+    fn test_store_rollup_and_downsample() {
+        let mut store = SeriesStore::default();
+        for (m, v) in [(0, 4.0), (1, 2.0), (4, 6.0), (5, 1.0), (9, 3.0), (10, 8.0)] {
+            store.insert("load", DataPoint::new(minutes(m), v)).unwrap();
+        }
+        let rollups = store.rollup("load", 0, minutes(10), minutes(5));
+        assert_eq!(
+            rollups,
+            vec![
+                Rollup {
+                    bucket_start: 0,
+                    count: 3,
+                    min: 2.0,
+                    max: 6.0,
+                    avg: 4.0,
+                    last: 6.0,
+                },
+                Rollup {
+                    bucket_start: minutes(5),
+                    count: 2,
+                    min: 1.0,
+                    max: 3.0,
+                    avg: 2.0,
+                    last: 3.0,
+                },
+            ]
+        );
+        let maxes = store.downsample("load", 0, minutes(15), minutes(5), Aggregation::Max);
+        let values: Vec<f64> = maxes.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![6.0, 3.0, 8.0]);
+        assert_eq!(maxes[2].timestamp, minutes(10));
+    }
+
+    #[test]
+    fn test_store_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("series.gor");
+        let mut store = SeriesStore::new(StoreConfig {
+            grace_period_ms: 10,
+            block_points: 16,
+        });
+        for i in 0..100 {
+            store
+                .insert("a", DataPoint::new(i * 7, (i as f64).sin()))
+                .unwrap();
+            store
+                .insert("b.c", DataPoint::new(i * 3, i as f64))
+                .unwrap();
+        }
+        store.save(&path).unwrap();
+
+        let mut loaded = SeriesStore::load(&path, StoreConfig::default()).unwrap();
+        assert_eq!(loaded.metrics().collect::<Vec<_>>(), vec!["a", "b.c"]);
+        for metric in ["a", "b.c"] {
+            assert_eq!(
+                loaded.range(metric, 0, i64::MAX),
+                store.range(metric, 0, i64::MAX)
+            );
+        }
+        assert!(loaded.insert("a", DataPoint::new(693, 0.0)).is_err());
+        loaded.insert("a", DataPoint::new(700, 0.0)).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes.truncate(last);
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            SeriesStore::load(&path, StoreConfig::default()),
+            Err(StoreError::Corrupt(_))
+        ));
+        assert!(matches!(
+            SeriesStore::load(dir.path().join("missing"), StoreConfig::default()),
+            Err(StoreError::Io(_))
+        ));
+
+        // the temp file is `series.gor.tmp`, not a clobbered `series.tmp`
+        let neighbour = dir.path().join("series.tmp");
+        fs::write(&neighbour, b"keep").unwrap();
+        store.save(&path).unwrap();
+        assert_eq!(fs::read(&neighbour).unwrap(), b"keep");
+        assert!(!dir.path().join("series.gor.tmp").exists());
+    }
+
+    #[test]
+    fn test_store_windows_at_max_timestamp() {
+        let mut store = SeriesStore::new(StoreConfig {
+            grace_period_ms: 0,
+            block_points: 2,
+        });
+        for offset in (0..4).rev() {
+            store
+                .insert("t", DataPoint::new(i64::MAX - offset, offset as f64))
+                .unwrap();
+        }
+        store.flush();
+
+        assert_eq!(store.latest("t"), Some(DataPoint::new(i64::MAX, 0.0)));
+        let recent = store.last_duration("t", 2);
+        assert_eq!(
+            recent,
+            vec![
+                DataPoint::new(i64::MAX - 1, 1.0),
+                DataPoint::new(i64::MAX, 0.0)
+            ]
+        );
+        assert_eq!(store.last_duration("t", i64::MAX).len(), 4);
+        assert!(store.last_duration("t", 0).is_empty());
+        assert!(store.insert("t", DataPoint::new(i64::MAX - 3, 9.0)).is_err());
+    }
+
+    #[test]
+    fn test_detect_anomalies_over_stored_range() {
+        let mut store = SeriesStore::default();
+        let base = 1_700_000_000_000;
+        for i in 0..200 {
+            let value = if i == 150 {
+                500.0
+            } else {
+                50.0 + (i % 5) as f64
+            };
+            store
+                .insert("latency", DataPoint::new(base + i * 1000, value))
+                .unwrap();
+        }
+        let mut detector = AnomalyDetector::new(vec![50], 3.0);
+        let anomalies = store.detect_anomalies("latency", base, base + 200_000, &mut detector);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].timestamp, base + 150_000);
+        assert_eq!(anomalies[0].value, 500.0);
+    }
+
+    // ----- Milestone 9 -----
+    /// Hourly points following a daily cycle, with a little noise.
+    fn daily_traffic(days: usize, seed: u64) -> Vec<DataPoint> {
+        let mut state = seed;
+        (0..days * 24)
+            .map(|i| {
+                let hour = (i % 24) as f64;
+                let cycle = 100.0 + 50.0 * (hour / 24.0 * std::f64::consts::TAU).sin();
+                let noise = (xorshift(&mut state) % 1000) as f64 / 500.0 - 1.0;
+                DataPoint::new(i as Timestamp * 3_600_000, cycle + noise)
+            })
+            .collect()
+    }
+
+    fn flagged_indices(detector: &mut dyn Detector, points: &[DataPoint]) -> Vec<usize> {
+        detector
+            .scan(points)
+            .iter()
+            .map(|a| {
+                points
+                    .iter()
+                    .position(|p| p.timestamp == a.timestamp)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zscore_anomaly_carries_band() {
+        let mut detector = AnomalyDetector::new(vec![20], 3.0);
+        for i in 0..20 {
+            detector.push(10.0 + (i % 2) as f64, i);
+        }
+        let anomaly = detector.push(30.0, 20).unwrap();
+        assert_eq!(anomaly.detector, "zscore");
+        assert!(anomaly.window_stats.is_some());
+        assert!(anomaly.lower < anomaly.expected && anomaly.expected < anomaly.upper);
+        assert!(anomaly.value > anomaly.upper);
+    }
+
+    #[test]
+    fn test_holt_winters_handles_daily_seasonality() {
+        let mut points = daily_traffic(14, 7);
+        // 06:00 on day 10 carries a value that is normal at midnight but
+        // far too low for the morning peak.
+        let spike = 10 * 24 + 6;
+        points[spike].value = 100.0;
+
+        let mut hw = HoltWintersDetector::new(24, 0.2, 0.01, 0.3, 4.0);
+        assert_eq!(flagged_indices(&mut hw, &points), vec![spike]);
+
+        // The global z-score stays blind to it: the value is well inside the
+        // overall range of the series.
+        let mut zscore = AnomalyDetector::new(vec![72], 3.0);
+        assert!(!flagged_indices(&mut zscore, &points).contains(&spike));
+
+        let mut hw = HoltWintersDetector::new(24, 0.2, 0.01, 0.3, 4.0);
+        let anomaly = hw.scan(&points).remove(0);
+        assert_eq!(anomaly.detector, "holt-winters");
+        assert!(anomaly.expected > 140.0, "expected {}", anomaly.expected);
+        assert!(anomaly.upper - anomaly.lower < 30.0);
+    }
+
+    #[test]
+    fn test_ewma_and_mad_flag_spikes() {
+        let mut points: Vec<DataPoint> = (0..200)
+            .map(|i| DataPoint::new(i, 50.0 + (i % 5) as f64))
+            .collect();
+        points[120].value = 80.0;
+        points[121].value = 120.0;
+
+        let mut ewma = EwmaDetector::new(0.1, 4.0, 30);
+        assert_eq!(flagged_indices(&mut ewma, &points), vec![120, 121]);
+
+        let mut mad = MadDetector::new(31, 5.0);
+        let anomalies = mad.scan(&points);
+        assert_eq!(anomalies.len(), 2);
+        assert_eq!(anomalies[0].detector, "mad");
+        assert_eq!(anomalies[0].expected, 52.0);
+        // The first outlier in the window does not mask the second.
+        assert_eq!(anomalies[1].expected, 52.0);
+    }
+
+    #[test]
+    fn test_band_detectors_reject_bad_widths() {
+        for width in [-1.0, f64::NAN, f64::INFINITY] {
+            let results = [
+                std::panic::catch_unwind(|| EwmaDetector::new(0.1, width, 30)).is_err(),
+                std::panic::catch_unwind(|| MadDetector::new(31, width)).is_err(),
+                std::panic::catch_unwind(|| HoltWintersDetector::new(24, 0.2, 0.01, 0.3, width))
+                    .is_err(),
+            ];
+            assert_eq!(results, [true; 3], "width {width}");
+        }
+        // zero is a valid (if strict) band
+        EwmaDetector::new(0.1, 0.0, 30);
+
+        let bad = [(-0.5, 5.0), (f64::NAN, 5.0), (0.5, 0.0), (0.5, -1.0), (0.5, f64::INFINITY)];
+        for (slack, threshold) in bad {
+            let result = std::panic::catch_unwind(|| CusumDetector::new(50, slack, threshold));
+            assert!(result.is_err(), "slack {slack}, threshold {threshold}");
+        }
+        CusumDetector::new(50, 0.0, 5.0);
+
+        let hw = HoltWintersDetector::new(24, 1.5, -0.2, 2.0, 3.0);
+        assert_eq!((hw.alpha, hw.beta, hw.gamma), (1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_cusum_detects_small_level_shift() {
+        let mut state = 99;
+        let points: Vec<DataPoint> = (0..300)
+            .map(|i| {
+                let noise = (xorshift(&mut state) % 1000) as f64 / 500.0 - 1.0;
+                let shift = if i >= 150 { 0.8 } else { 0.0 };
+                DataPoint::new(i, 10.0 + noise + shift)
+            })
+            .collect();
+
+        let mut cusum = CusumDetector::new(50, 0.5, 5.0);
+        let alarms = flagged_indices(&mut cusum, &points);
+        assert!(!alarms.is_empty());
+        assert!(
+            alarms[0] >= 150 && alarms[0] < 190,
+            "alarm at {}",
+            alarms[0]
+        );
+        assert!(cusum.scan(&[]).is_empty());
+
+        // A 3-sigma point limit never fires on the same data.
+        let mut zscore = AnomalyDetector::new(vec![50], 3.0);
+        assert!(zscore.scan(&points).is_empty());
+    }
+
+    #[test]
+    fn test_ensemble_requires_votes() {
+        let mut points: Vec<DataPoint> = (0..100)
+            .map(|i| DataPoint::new(i, 20.0 + (i % 3) as f64))
+            .collect();
+        points[60].value = 60.0;
+        points[80].value = 25.0;
+
+        let mut ensemble = Ensemble::new(2)
+            .with(EwmaDetector::new(0.1, 4.0, 20))
+            .with(MadDetector::new(21, 3.0))
+            .with(AnomalyDetector::new(vec![30], 3.0));
+        assert_eq!(ensemble.len(), 3);
+
+        let anomalies = ensemble.scan(&points);
+        assert_eq!(anomalies.len(), 1);
+        let anomaly = &anomalies[0];
+        assert_eq!((anomaly.detector, anomaly.timestamp), ("ensemble", 60));
+        assert!(anomaly.expected > 19.0 && anomaly.expected < 23.0);
+        assert!(anomaly.value > anomaly.upper);
+
+        let mut single = Ensemble::new(1).with(EwmaDetector::new(0.1, 4.0, 20));
+        assert_eq!(flagged_indices(&mut single, &points), vec![60, 80]);
+        assert_eq!(single.last_votes().len(), 0);
+    }
+
+    #[test]
+    fn test_store_runs_any_detector() {
+        let mut store = SeriesStore::default();
+        let mut points = daily_traffic(10, 3);
+        points[200].value += 40.0;
+        for p in &points {
+            store.insert("requests", *p).unwrap();
+        }
+        let mut hw = HoltWintersDetector::new(24, 0.2, 0.01, 0.3, 4.0);
+        let anomalies = store.detect_anomalies("requests", 0, i64::MAX, &mut hw);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].timestamp, points[200].timestamp);
+    }
 }
 
 fn main() {}
@@ -2,14 +2,16 @@
 // Mutation Testing Framework - Complete Implementation
 //==============================================================================
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 use std::time::{Duration, Instant};
+use quote::ToTokens;
 use rayon::prelude::*;
 use serde::{Serialize, Deserialize};
+use syn::spanned::Spanned;
 
-#[cfg(test)]
 use tempfile::TempDir;
 
 //==============================================================================
@@ -73,91 +75,69 @@ impl MutationOperator {
         }
     }
 
+    /// Mutation points of this operator, located on the syntax tree so text
+    /// inside string literals and comments is never mutated. Operator
+    /// patterns (including literal ones such as `&&`) match binary
+    /// expressions outside test code, even when they span lines; other
+    /// literals match where they start a code token. `source` may be a whole
+    /// file or a run of statements; text that does not parse has no points.
     pub fn find_mutation_points(&self, source: &str) -> Vec<MutationPoint> {
-        let mut points = Vec::new();
-        let lines: Vec<&str> = source.lines().collect();
+        let replacement = match self.transformations.first() {
+            Some(Transformation::Replace(replacement)) => replacement,
+            _ => return Vec::new(),
+        };
 
-        for (line_num, line) in lines.iter().enumerate() {
-            let matches = self.find_pattern_in_line(line);
-            for (col, original, mutated) in matches {
-                points.push(MutationPoint {
-                    line: line_num,
-                    column: col,
-                    original,
-                    mutated,
-                    operator_name: self.name.clone(),
-                });
-            }
-        }
+        let sites = match (self.binary_op(), &self.pattern) {
+            (Some(op), _) => binary_op_sites(source)
+                .into_iter()
+                .filter(|(_, found)| found == op)
+                .map(|(at, _)| at)
+                .collect(),
+            (None, Pattern::Literal(lit)) => literal_sites(source, lit),
+            (None, _) => Vec::new(),
+        };
 
-        points
+        sites
+            .into_iter()
+            .map(|(line, column)| MutationPoint {
+                line,
+                column,
+                original: match &self.pattern {
+                    Pattern::Literal(lit) => lit.clone(),
+                    _ => self.binary_op().unwrap_or_default().to_string(),
+                },
+                mutated: replacement.clone(),
+                operator_name: self.name.clone(),
+            })
+            .collect()
     }
 
-    fn find_pattern_in_line(&self, line: &str) -> Vec<(usize, String, String)> {
-        let mut matches = Vec::new();
-
+    /// The binary operator token this operator mutates, if it mutates one.
+    fn binary_op(&self) -> Option<&str> {
         match &self.pattern {
-            Pattern::BinaryOp(op_type) => {
-                let op_str = match op_type {
-                    OpType::Add => "+",
-                    OpType::Sub => "-",
-                    OpType::Mul => "*",
-                    OpType::Div => "/",
-                    OpType::Rem => "%",
-                };
-
-                for (i, ch) in line.chars().enumerate() {
-                    if ch.to_string() == op_str {
-                        if let Some(Transformation::Replace(replacement)) = self.transformations.first() {
-                            matches.push((i, op_str.to_string(), replacement.clone()));
-                        }
-                    }
-                }
-            }
-            Pattern::Comparison(cmp_type) => {
-                let patterns = match cmp_type {
-                    CmpType::Eq => vec!["=="],
-                    CmpType::Ne => vec!["!="],
-                    CmpType::Lt => vec!["<"],
-                    CmpType::Le => vec!["<="],
-                    CmpType::Gt => vec![">"],
-                    CmpType::Ge => vec![">="],
-                };
-
-                for pattern in patterns {
-                    if let Some(pos) = line.find(pattern) {
-                        if let Some(Transformation::Replace(replacement)) = self.transformations.first() {
-                            matches.push((pos, pattern.to_string(), replacement.clone()));
-                        }
-                    }
-                }
-            }
-            Pattern::Literal(lit) => {
-                if let Some(pos) = line.find(lit.as_str()) {
-                    if let Some(Transformation::Replace(replacement)) = self.transformations.first() {
-                        matches.push((pos, lit.clone(), replacement.clone()));
-                    }
-                }
-            }
+            Pattern::BinaryOp(op_type) => Some(match op_type {
+                OpType::Add => "+",
+                OpType::Sub => "-",
+                OpType::Mul => "*",
+                OpType::Div => "/",
+                OpType::Rem => "%",
+            }),
+            Pattern::Comparison(cmp_type) => Some(match cmp_type {
+                CmpType::Eq => "==",
+                CmpType::Ne => "!=",
+                CmpType::Lt => "<",
+                CmpType::Le => "<=",
+                CmpType::Gt => ">",
+                CmpType::Ge => ">=",
+            }),
+            Pattern::Literal(lit) => syn::parse_str::<syn::BinOp>(lit).ok().map(|_| lit.as_str()),
         }
-
-        matches
     }
 
+    /// Replaces `point.original` at its column, falling back to the first
+    /// occurrence on the line when the column doesn't point at it.
     pub fn apply(&self, source: &str, point: &MutationPoint) -> String {
-        let lines: Vec<&str> = source.lines().collect();
-        let mut result = Vec::new();
-
-        for (i, line) in lines.iter().enumerate() {
-            if i == point.line {
-                let mutated_line = line.replacen(&point.original, &point.mutated, 1);
-                result.push(mutated_line);
-            } else {
-                result.push(line.to_string());
-            }
-        }
-
-        result.join("\n")
+        apply_point(source, point)
     }
 
     pub fn describe(&self) -> String {
@@ -294,6 +274,169 @@ impl MutationOperator {
     }
 }
 
+fn apply_point(source: &str, point: &MutationPoint) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut result = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if i == point.line {
+            let at = line.char_indices().nth(point.column).map(|(at, _)| at);
+            let mutated_line = match at {
+                Some(at) if line[at..].starts_with(&point.original) => format!(
+                    "{}{}{}",
+                    &line[..at],
+                    point.mutated,
+                    &line[at + point.original.len()..]
+                ),
+                _ => line.replacen(&point.original, &point.mutated, 1),
+            };
+            result.push(mutated_line);
+        } else {
+            result.push(line.to_string());
+        }
+    }
+
+    result.join("\n")
+}
+
+/// `source` parsed as a file or, failing that, as the statements of a block.
+enum ParsedSource {
+    File(syn::File),
+    Block(syn::Block),
+}
+
+impl ParsedSource {
+    /// The parse and the number of lines it is shifted by: spans are 1-based
+    /// and a block is wrapped in an extra `{` line.
+    fn parse(source: &str) -> Option<(Self, usize)> {
+        if let Ok(file) = syn::parse_file(source) {
+            return Some((ParsedSource::File(file), 1));
+        }
+        syn::parse_str::<syn::Block>(&format!("{{\n{}\n}}", source))
+            .ok()
+            .map(|block| (ParsedSource::Block(block), 2))
+    }
+}
+
+/// Every binary operator outside test code, as `((line, column), token)`
+/// with a 0-based line.
+fn binary_op_sites(source: &str) -> Vec<((usize, usize), String)> {
+    let (parsed, shift) = match ParsedSource::parse(source) {
+        Some(parsed) => parsed,
+        None => return Vec::new(),
+    };
+    let mut sites = BinaryOpSites(Vec::new());
+    match &parsed {
+        ParsedSource::File(file) => syn::visit::visit_file(&mut sites, file),
+        ParsedSource::Block(block) => syn::visit::visit_block(&mut sites, block),
+    }
+    let mut found: Vec<_> = sites
+        .0
+        .into_iter()
+        .map(|(at, op)| ((at.line - shift, at.column), op))
+        .collect();
+    found.sort();
+    found
+}
+
+struct BinaryOpSites(Vec<(proc_macro2::LineColumn, String)>);
+
+impl<'ast> syn::visit::Visit<'ast> for BinaryOpSites {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_item_impl(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_item_fn(self, item);
+        }
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_impl_item_fn(self, item);
+        }
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        let op = expr.op.to_token_stream().to_string().replace(' ', "");
+        self.0.push((expr.op.span().start(), op));
+        syn::visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        for arg in macro_args(mac).iter().flatten() {
+            self.visit_expr(arg);
+        }
+    }
+}
+
+/// Starts of `lit` in `source` that begin a code token, skipping string and
+/// character literals, comments and attributes (doc comments included).
+fn literal_sites(source: &str, lit: &str) -> Vec<(usize, usize)> {
+    let tokens = match source.parse::<proc_macro2::TokenStream>() {
+        Ok(tokens) => tokens,
+        Err(_) => return Vec::new(),
+    };
+    let mut code = Vec::new();
+    collect_code_spans(tokens, &mut code);
+
+    let mut sites = Vec::new();
+    for (line_num, line) in source.lines().enumerate() {
+        for (at, _) in line.match_indices(lit) {
+            let position = proc_macro2::LineColumn {
+                line: line_num + 1,
+                column: line[..at].chars().count(),
+            };
+            if code.iter().any(|(start, end)| *start <= position && position < *end) {
+                sites.push((line_num, position.column));
+            }
+        }
+    }
+    sites
+}
+
+fn collect_code_spans(tokens: proc_macro2::TokenStream, code: &mut Vec<(proc_macro2::LineColumn, proc_macro2::LineColumn)>) {
+    use proc_macro2::{Delimiter, TokenTree};
+
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '!') {
+                    tokens.next();
+                }
+                if matches!(tokens.peek(), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket) {
+                    tokens.next();
+                } else {
+                    code.push((punct.span().start(), punct.span().end()));
+                }
+            }
+            TokenTree::Group(group) => {
+                code.push((group.span_open().start(), group.span_open().end()));
+                code.push((group.span_close().start(), group.span_close().end()));
+                collect_code_spans(group.stream(), code);
+            }
+            TokenTree::Literal(literal) => {
+                let text = literal.to_string();
+                let is_text = text.contains('"') || text.contains('\'');
+                if !is_text {
+                    code.push((literal.span().start(), literal.span().end()));
+                }
+            }
+            other => code.push((other.span().start(), other.span().end())),
+        }
+    }
+}
+
 //==============================================================================
 // Milestone 2: Test Execution Engine
 //==============================================================================
@@ -315,9 +458,60 @@ pub struct MutationResult {
     pub execution_time: Duration,
 }
 
+/// A mutant the runners can build: a line-level `MutationPoint` or an
+/// `AstMutation` located by its span.
+trait Mutant: Sync {
+    fn mutate(&self, source: &str) -> String;
+
+    /// Reported form of the mutant.
+    fn point(&self) -> MutationPoint;
+
+    /// 0-based lines of `source` the mutant rewrites.
+    fn lines(&self, source: &str) -> std::ops::RangeInclusive<usize>;
+}
+
+impl Mutant for MutationPoint {
+    fn mutate(&self, source: &str) -> String {
+        apply_point(source, self)
+    }
+
+    fn point(&self) -> MutationPoint {
+        self.clone()
+    }
+
+    fn lines(&self, _source: &str) -> std::ops::RangeInclusive<usize> {
+        self.line..=self.line
+    }
+}
+
+impl Mutant for AstMutation {
+    fn mutate(&self, source: &str) -> String {
+        self.apply(source)
+    }
+
+    fn point(&self) -> MutationPoint {
+        self.to_point()
+    }
+
+    fn lines(&self, source: &str) -> std::ops::RangeInclusive<usize> {
+        self.line..=self.line + source[self.start..self.end].matches('\n').count()
+    }
+}
+
+/// The crate a mutant is built in: its root directory and the file, relative
+/// to that root, whose contents the mutated source replaces.
+#[derive(Debug, Clone)]
+pub struct ProjectTarget {
+    pub root: PathBuf,
+    pub file: PathBuf,
+}
+
 pub struct TestRunner {
     test_command: String,
     timeout: Duration,
+    compile_timeout: Duration,
+    project: Option<ProjectTarget>,
+    target_dir: Option<PathBuf>,
 }
 
 impl TestRunner {
@@ -325,65 +519,178 @@ impl TestRunner {
         TestRunner {
             test_command: test_command.to_string(),
             timeout,
+            compile_timeout: Duration::from_secs(300),
+            project: None,
+            target_dir: None,
         }
     }
 
+    /// Runs mutants against `file` inside the crate at `root`. Without a
+    /// project every mutant is reported as `Skipped`.
+    pub fn with_project(mut self, root: impl Into<PathBuf>, file: impl Into<PathBuf>) -> Self {
+        self.project = Some(ProjectTarget {
+            root: root.into(),
+            file: file.into(),
+        });
+        self
+    }
+
+    /// Shares one `CARGO_TARGET_DIR` between mutants so only the mutated
+    /// crate is rebuilt each time.
+    pub fn with_target_dir(mut self, target_dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = Some(target_dir.into());
+        self
+    }
+
+    /// Limit for building a mutant; `timeout` only covers the test run. A
+    /// build that overruns it counts as a compile error, not a timeout.
+    pub fn with_compile_timeout(mut self, compile_timeout: Duration) -> Self {
+        self.compile_timeout = compile_timeout;
+        self
+    }
+
+    pub fn project(&self) -> Option<&ProjectTarget> {
+        self.project.as_ref()
+    }
+
     pub fn test_mutation(&self, original_code: &str, mutation: &MutationPoint) -> MutationResult {
+        self.test_mutation_in(original_code, mutation, self.target_dir.as_deref())
+    }
+
+    /// Like `test_mutation`, but builds into `target_dir` instead of the
+    /// runner's own.
+    pub fn test_mutation_in(
+        &self,
+        original_code: &str,
+        mutation: &MutationPoint,
+        target_dir: Option<&Path>,
+    ) -> MutationResult {
+        self.test_mutant_in(original_code, mutation, target_dir)
+    }
+
+    /// Tests a mutant found by `find_ast_mutations`, applied by its span so
+    /// multi-line expressions are replaced as a whole.
+    pub fn test_ast_mutation(&self, original_code: &str, mutation: &AstMutation) -> MutationResult {
+        self.test_mutant_in(original_code, mutation, self.target_dir.as_deref())
+    }
+
+    fn test_mutant_in(
+        &self,
+        original_code: &str,
+        mutant: &impl Mutant,
+        target_dir: Option<&Path>,
+    ) -> MutationResult {
         let start = Instant::now();
+        let mutant_code = mutant.mutate(original_code);
+        self.evaluate(mutant.point(), original_code, &mutant_code, target_dir, start)
+    }
 
-        let operator = MutationOperator::new(
-            &mutation.operator_name,
-            Pattern::Literal(mutation.original.clone()),
-            vec![Transformation::Replace(mutation.mutated.clone())],
-        );
+    fn evaluate(
+        &self,
+        mutation: MutationPoint,
+        original_code: &str,
+        mutant_code: &str,
+        target_dir: Option<&Path>,
+        start: Instant,
+    ) -> MutationResult {
+        let (status, test_output) = if self.is_equivalent(original_code, mutant_code) {
+            (MutationStatus::Skipped, "equivalent mutant".to_string())
+        } else {
+            match self.compile_and_test(mutant_code, target_dir) {
+                Ok(outcome) => outcome,
+                Err(e) => (MutationStatus::CompileError, e.to_string()),
+            }
+        };
 
-        let mutant_code = operator.apply(original_code, mutation);
+        MutationResult {
+            mutation,
+            status,
+            test_output,
+            execution_time: start.elapsed(),
+        }
+    }
 
-        match self.compile_and_test(&mutant_code) {
-            Ok(test_passed) => {
-                let status = if test_passed {
-                    MutationStatus::Survived
-                } else {
-                    MutationStatus::Killed
-                };
+    /// Builds the mutant with `cargo test --no-run`, then runs the test
+    /// command. A failed or timed-out build is a compile error; once it
+    /// builds, passing tests mean the mutant survived and failing ones (or a
+    /// crash) killed it.
+    fn compile_and_test(
+        &self,
+        code: &str,
+        target_dir: Option<&Path>,
+    ) -> Result<(MutationStatus, String), std::io::Error> {
+        let project = match &self.project {
+            Some(project) => project,
+            None => return Ok((MutationStatus::Skipped, "no project configured".to_string())),
+        };
 
-                MutationResult {
-                    mutation: mutation.clone(),
-                    status,
-                    test_output: String::new(),
-                    execution_time: start.elapsed(),
-                }
+        let workspace = create_mutant_workspace(project, code)?;
+
+        let mut build = Command::new("cargo");
+        build.args(["test", "--no-run"]);
+        if self.test_command.split_whitespace().any(|arg| arg == "--offline") {
+            build.arg("--offline");
+        }
+        let build_output = match self.run_in(&mut build, workspace.path(), target_dir, self.compile_timeout) {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                return Ok((MutationStatus::CompileError, format!("build timed out: {}", e)));
             }
-            Err(_) => {
-                MutationResult {
-                    mutation: mutation.clone(),
-                    status: MutationStatus::CompileError,
-                    test_output: String::new(),
-                    execution_time: start.elapsed(),
-                }
+            Err(e) => return Err(e),
+        };
+        if !build_output.status.success() {
+            let stderr = String::from_utf8_lossy(&build_output.stderr).into_owned();
+            return Ok((MutationStatus::CompileError, stderr));
+        }
+
+        let mut parts = self.test_command.split_whitespace();
+        let program = parts.next().unwrap_or("cargo");
+        let mut test = Command::new(program);
+        test.args(parts);
+        match self.run_in(&mut test, workspace.path(), target_dir, self.timeout) {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let status = classify_test_run(output.status.success(), &stderr);
+                Ok((status, format!("{}{}", stdout, stderr)))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                Ok((MutationStatus::Timeout, e.to_string()))
             }
+            Err(e) => Err(e),
         }
     }
 
-    fn compile_and_test(&self, _code: &str) -> Result<bool, std::io::Error> {
-        // Simplified implementation - in a real implementation this would:
-        // 1. Create a temporary directory
-        // 2. Write the code to a file
-        // 3. Compile and run tests
-        // 4. Return test results
-
-        // For this example, we simulate successful test execution
-        Ok(true)
+    fn run_in(
+        &self,
+        command: &mut Command,
+        dir: &Path,
+        target_dir: Option<&Path>,
+        timeout: Duration,
+    ) -> Result<Output, std::io::Error> {
+        command.current_dir(dir);
+        if let Some(target_dir) = target_dir {
+            command.env("CARGO_TARGET_DIR", target_dir);
+        }
+        self.run_with_timeout(command, timeout)
     }
 
-    fn run_with_timeout(&self, command: &mut Command) -> Result<std::process::Output, std::io::Error> {
+    /// Runs `command` in its own process group and kills the whole group if
+    /// it outlives `timeout`, so test binaries spawned by cargo die with it.
+    fn run_with_timeout(&self, command: &mut Command, timeout: Duration) -> Result<Output, std::io::Error> {
         use std::sync::mpsc::channel;
         use std::thread;
 
-        let (tx, rx) = channel();
-        let timeout = self.timeout;
+        command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
 
-        let mut child = command.spawn()?;
+        let (tx, rx) = channel();
+        let child = command.spawn()?;
+        let pid = child.id();
 
         thread::spawn(move || {
             let result = child.wait_with_output();
@@ -394,13 +701,172 @@ impl TestRunner {
             Ok(Ok(output)) => Ok(output),
             Ok(Err(e)) => Err(e),
             Err(_) => {
+                kill_process_group(pid);
+                // Reap the child so the waiting thread finishes.
+                let _ = rx.recv_timeout(Duration::from_secs(5));
                 Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "Process timeout"))
             }
         }
     }
 
-    pub fn is_equivalent(&self, _original: &str, _mutant: &str) -> bool {
-        false
+    pub fn is_equivalent(&self, original: &str, mutant: &str) -> bool {
+        is_trivially_equivalent(original, mutant)
+    }
+}
+
+/// Status of a mutant whose build succeeded, from the test run's exit status.
+/// A compile error can still surface here when the test command builds
+/// targets `--no-run` skipped (e.g. `--all-targets`).
+pub fn classify_test_run(success: bool, stderr: &str) -> MutationStatus {
+    if success {
+        MutationStatus::Survived
+    } else if stderr.contains("error: could not compile") || stderr.contains("error[E") {
+        MutationStatus::CompileError
+    } else {
+        MutationStatus::Killed
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    let _ = Command::new("kill")
+        .args(["-s", "KILL", "--", &format!("-{}", pid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(windows)]
+fn kill_process_group(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Copy of the project in a temporary directory with the mutated file in
+/// place. Files are copied rather than hard-linked so a build script or test
+/// that writes to a source file cannot reach the original (`fs::copy` still
+/// clones extents on filesystems that support it). When the crate belongs to
+/// a workspace the whole workspace is copied, so `workspace = true` fields
+/// and sibling path dependencies resolve, and relative `path` dependencies
+/// that point outside the copy are made absolute.
+fn create_mutant_workspace(project: &ProjectTarget, code: &str) -> Result<MutantWorkspace, std::io::Error> {
+    let crate_root = fs::canonicalize(&project.root)?;
+    let copy_root = find_workspace_root(&crate_root).unwrap_or_else(|| crate_root.clone());
+    let member = crate_root.strip_prefix(&copy_root).unwrap_or(Path::new("")).to_path_buf();
+
+    let dir = tempfile::tempdir()?;
+    copy_tree(&copy_root, dir.path(), &copy_root, &crate_root.join(&project.file))?;
+
+    let crate_dir = dir.path().join(&member);
+    let mutated = crate_dir.join(&project.file);
+    if let Some(parent) = mutated.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(mutated, code)?;
+    Ok(MutantWorkspace { _dir: dir, crate_dir })
+}
+
+/// Temporary copy made by `create_mutant_workspace`; deleted on drop.
+struct MutantWorkspace {
+    _dir: TempDir,
+    crate_dir: PathBuf,
+}
+
+impl MutantWorkspace {
+    /// Directory of the mutated crate inside the copy, where cargo runs.
+    fn path(&self) -> &Path {
+        &self.crate_dir
+    }
+}
+
+/// Nearest ancestor of `crate_root` whose manifest has a `[workspace]` table,
+/// the same search cargo does. A crate that is its own workspace root needs
+/// nothing beyond itself, so only strict ancestors are returned.
+fn find_workspace_root(crate_root: &Path) -> Option<PathBuf> {
+    crate_root.ancestors().skip(1).find_map(|dir| {
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+        let document = manifest.parse::<toml_edit::DocumentMut>().ok()?;
+        document.contains_table("workspace").then(|| dir.to_path_buf())
+    })
+}
+
+fn copy_tree(src: &Path, dst: &Path, root: &Path, skip: &Path) -> Result<(), std::io::Error> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let target = dst.join(&name);
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            if name == "target" || name == ".git" {
+                continue;
+            }
+            copy_tree(&path, &target, root, skip)?;
+        } else if path == skip {
+            continue;
+        } else if name == "Cargo.toml" {
+            let manifest = fs::read_to_string(&path)?;
+            fs::write(&target, rewrite_manifest_paths(&manifest, src, root))?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites relative dependency `path`s in the manifest found in
+/// `manifest_dir` that leave `root`, the directory being copied, to absolute
+/// ones. Paths inside `root` stay relative so they point into the copy.
+/// Manifests that don't parse are returned unchanged for cargo to report.
+fn rewrite_manifest_paths(manifest: &str, manifest_dir: &Path, root: &Path) -> String {
+    let mut document = match manifest.parse::<toml_edit::DocumentMut>() {
+        Ok(document) => document,
+        Err(_) => return manifest.to_string(),
+    };
+    rewrite_tables(document.as_table_mut(), manifest_dir, root);
+    document.to_string()
+}
+
+fn rewrite_tables(table: &mut dyn toml_edit::TableLike, manifest_dir: &Path, root: &Path) {
+    for (key, item) in table.iter_mut() {
+        let Some(child) = item.as_table_like_mut() else {
+            continue;
+        };
+        if key.get().ends_with("dependencies") || key.get() == "replace" {
+            rewrite_dependencies(child, manifest_dir, root);
+        } else if key.get() == "patch" {
+            for (_, registry) in child.iter_mut() {
+                if let Some(registry) = registry.as_table_like_mut() {
+                    rewrite_dependencies(registry, manifest_dir, root);
+                }
+            }
+        } else {
+            // `target.'cfg(..)'.dependencies`, `workspace.dependencies`.
+            rewrite_tables(child, manifest_dir, root);
+        }
+    }
+}
+
+fn rewrite_dependencies(dependencies: &mut dyn toml_edit::TableLike, manifest_dir: &Path, root: &Path) {
+    for (_, dependency) in dependencies.iter_mut() {
+        let Some(dependency) = dependency.as_table_like_mut() else {
+            continue;
+        };
+        let Some(path) = dependency.get("path").and_then(|item| item.as_str()) else {
+            continue;
+        };
+        if Path::new(path).is_absolute() {
+            continue;
+        }
+        let resolved = path_clean::clean(manifest_dir.join(path));
+        if !resolved.starts_with(root) {
+            dependency.insert("path", toml_edit::value(resolved.to_string_lossy().into_owned()));
+        }
     }
 }
 
@@ -411,6 +877,7 @@ impl TestRunner {
 pub struct ParallelTestRunner {
     max_concurrency: usize,
     runner: TestRunner,
+    target_root: Option<PathBuf>,
 }
 
 impl ParallelTestRunner {
@@ -423,7 +890,37 @@ impl ParallelTestRunner {
         ParallelTestRunner {
             max_concurrency,
             runner: TestRunner::new("cargo test", timeout),
+            target_root: None,
+        }
+    }
+
+    /// Uses `runner` for each mutant. Unless a target root is set, workers
+    /// build under `<project>/target/mutants`.
+    pub fn with_runner(mut self, runner: TestRunner) -> Self {
+        if self.target_root.is_none() {
+            self.target_root = runner.project().map(|p| p.root.join("target").join("mutants"));
         }
+        self.runner = runner;
+        self
+    }
+
+    /// Directory holding one `worker-N` target dir per rayon worker, so
+    /// concurrent builds never wait on each other's cargo lock.
+    pub fn with_target_root(mut self, target_root: impl Into<PathBuf>) -> Self {
+        self.target_root = Some(target_root.into());
+        self
+    }
+
+    fn worker_target_dir(&self) -> Option<PathBuf> {
+        let worker = rayon::current_thread_index().unwrap_or(0);
+        self.target_root
+            .as_ref()
+            .map(|root| root.join(format!("worker-{}", worker)))
+    }
+
+    fn test_one(&self, original_code: &str, mutant: &impl Mutant) -> MutationResult {
+        let target_dir = self.worker_target_dir();
+        self.runner.test_mutant_in(original_code, mutant, target_dir.as_deref())
     }
 
     pub fn test_mutations_parallel(
@@ -431,12 +928,75 @@ impl ParallelTestRunner {
         original_code: &str,
         mutations: Vec<MutationPoint>,
     ) -> Vec<MutationResult> {
+        self.test_all(original_code, &mutations)
+    }
+
+    /// Tests mutants found by `find_ast_mutations` in parallel.
+    pub fn test_ast_mutations_parallel(
+        &self,
+        original_code: &str,
+        mutations: Vec<AstMutation>,
+    ) -> Vec<MutationResult> {
+        self.test_all(original_code, &mutations)
+    }
+
+    fn test_all<M: Mutant>(&self, original_code: &str, mutations: &[M]) -> Vec<MutationResult> {
         mutations
             .par_iter()
-            .map(|mutation| self.runner.test_mutation(original_code, mutation))
+            .map(|mutation| self.test_one(original_code, mutation))
             .collect()
     }
 
+    /// Incremental mode: only mutants on lines changed since the git `base`
+    /// (including uncommitted edits) are run; the rest come back `Skipped`.
+    pub fn test_changed_since(
+        &self,
+        original_code: &str,
+        mutations: Vec<MutationPoint>,
+        base: &str,
+    ) -> Result<Vec<MutationResult>, std::io::Error> {
+        self.test_changed(original_code, &mutations, base)
+    }
+
+    /// Incremental mode for `find_ast_mutations` results: a mutant runs when
+    /// any line it spans changed since `base`.
+    pub fn test_ast_changed_since(
+        &self,
+        original_code: &str,
+        mutations: Vec<AstMutation>,
+        base: &str,
+    ) -> Result<Vec<MutationResult>, std::io::Error> {
+        self.test_changed(original_code, &mutations, base)
+    }
+
+    fn test_changed<M: Mutant>(
+        &self,
+        original_code: &str,
+        mutations: &[M],
+        base: &str,
+    ) -> Result<Vec<MutationResult>, std::io::Error> {
+        let project = self.runner.project().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "no project configured")
+        })?;
+        let changed = git_changed_lines(&project.root, base, &project.file)?;
+
+        Ok(mutations
+            .par_iter()
+            .map(|mutation| {
+                if mutation.lines(original_code).any(|line| changed.contains(&line)) {
+                    self.test_one(original_code, mutation)
+                } else {
+                    MutationResult {
+                        mutation: mutation.point(),
+                        status: MutationStatus::Skipped,
+                        test_output: format!("line unchanged since {}", base),
+                        execution_time: Duration::ZERO,
+                    }
+                }
+            })
+            .collect())
+    }
+
     fn batch_mutations(&self, mutations: Vec<MutationPoint>, batch_size: usize) -> Vec<Vec<MutationPoint>> {
         mutations
             .chunks(batch_size)
@@ -459,7 +1019,7 @@ impl ParallelTestRunner {
         let results: Vec<MutationResult> = mutations
             .par_iter()
             .map(|mutation| {
-                let result = self.runner.test_mutation(original_code, mutation);
+                let result = self.test_one(original_code, mutation);
                 let count = completed.fetch_add(1, Ordering::SeqCst) + 1;
                 progress_callback(count, total);
                 result
@@ -470,6 +1030,45 @@ impl ParallelTestRunner {
     }
 }
 
+/// New-side lines (0-based, like `MutationPoint::line`) touched by the hunks
+/// of a `git diff --unified=0`. Pure deletions add no lines.
+pub fn parse_changed_lines(diff: &str) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+
+    for header in diff.lines().filter(|l| l.starts_with("@@")) {
+        let new_side = match header.split_whitespace().find(|part| part.starts_with('+')) {
+            Some(part) => &part[1..],
+            None => continue,
+        };
+        let (start, count) = match new_side.split_once(',') {
+            Some((start, count)) => (start.parse::<usize>(), count.parse::<usize>()),
+            None => (new_side.parse::<usize>(), Ok(1)),
+        };
+        if let (Ok(start), Ok(count)) = (start, count) {
+            lines.extend((start..start + count).map(|line| line.saturating_sub(1)));
+        }
+    }
+
+    lines
+}
+
+/// Lines of `file` that differ between `base` and the working tree.
+pub fn git_changed_lines(repo: &Path, base: &str, file: &Path) -> Result<HashSet<usize>, std::io::Error> {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["diff", "--unified=0", "--no-color", base, "--"])
+        .arg(file)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(parse_changed_lines(&String::from_utf8_lossy(&output.stdout)))
+}
+
 //==============================================================================
 // Milestone 4: Mutation Score Analysis and Reporting
 //==============================================================================
@@ -808,6 +1407,399 @@ impl AnnotatedSource {
     }
 }
 
+//==============================================================================
+// Milestone 7: Syntax-Tree Mutation Operators
+//==============================================================================
+
+/// Mutation operators that work on the parsed syntax tree, so string
+/// literals, comments and test code are never touched and expressions
+/// spanning several lines are handled like any other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AstOperator {
+    /// `+` <-> `-`, `*` <-> `/`, `&&` <-> `||`, `==` <-> `!=`, ...
+    BinaryOpSwap,
+    /// `<` <-> `<=` and `>` <-> `>=`.
+    Boundary,
+    /// Negates `if`/`while` conditions and flips boolean literals.
+    BooleanNegation,
+    /// Replaces a function body with the default value of its return type.
+    ReturnDefault,
+    /// Deletes a `match` arm when a wildcard arm keeps the match exhaustive.
+    MatchArmDeletion,
+    /// Drops the `?` operator.
+    QuestionMarkRemoval,
+}
+
+impl AstOperator {
+    pub fn all() -> [AstOperator; 6] {
+        [
+            AstOperator::BinaryOpSwap,
+            AstOperator::Boundary,
+            AstOperator::BooleanNegation,
+            AstOperator::ReturnDefault,
+            AstOperator::MatchArmDeletion,
+            AstOperator::QuestionMarkRemoval,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AstOperator::BinaryOpSwap => "BinaryOpSwap",
+            AstOperator::Boundary => "Boundary",
+            AstOperator::BooleanNegation => "BooleanNegation",
+            AstOperator::ReturnDefault => "ReturnDefault",
+            AstOperator::MatchArmDeletion => "MatchArmDeletion",
+            AstOperator::QuestionMarkRemoval => "QuestionMarkRemoval",
+        }
+    }
+}
+
+/// A mutation located by its span in the source: `start..end` is a byte
+/// range, `line`/`column` (0-based) locate its start for reporting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstMutation {
+    pub operator: AstOperator,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub original: String,
+    pub replacement: String,
+}
+
+impl AstMutation {
+    pub fn apply(&self, source: &str) -> String {
+        format!("{}{}{}", &source[..self.start], self.replacement, &source[self.end..])
+    }
+
+    pub fn to_point(&self) -> MutationPoint {
+        MutationPoint {
+            line: self.line,
+            column: self.column,
+            original: self.original.clone(),
+            mutated: self.replacement.clone(),
+            operator_name: self.operator.name().to_string(),
+        }
+    }
+}
+
+/// Parses `source` as a Rust file and returns every mutation the operators
+/// produce, minus those `is_trivially_equivalent` recognises.
+pub fn find_ast_mutations(source: &str) -> Result<Vec<AstMutation>, syn::Error> {
+    let file = syn::parse_file(source)?;
+    let mut collector = MutationCollector {
+        source,
+        line_starts: std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect(),
+        found: Vec::new(),
+    };
+    syn::visit::visit_file(&mut collector, &file);
+
+    let mut mutations = collector.found;
+    mutations.sort_by_key(|m| (m.start, m.end));
+    mutations.retain(|m| !is_trivially_equivalent(source, &m.apply(source)));
+    Ok(mutations)
+}
+
+struct MutationCollector<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    found: Vec<AstMutation>,
+}
+
+impl MutationCollector<'_> {
+    fn offset(&self, at: proc_macro2::LineColumn) -> usize {
+        let line_start = self.line_starts[at.line - 1];
+        self.source[line_start..]
+            .char_indices()
+            .nth(at.column)
+            .map_or(self.source.len(), |(i, _)| line_start + i)
+    }
+
+    fn push(&mut self, operator: AstOperator, span: proc_macro2::Span, replacement: String) {
+        let (start, end) = (self.offset(span.start()), self.offset(span.end()));
+        self.found.push(AstMutation {
+            operator,
+            start,
+            end,
+            line: span.start().line - 1,
+            column: span.start().column,
+            original: self.source[start..end].to_string(),
+            replacement,
+        });
+    }
+
+    fn push_return_default(&mut self, sig: &syn::Signature, block: &syn::Block) {
+        let replacements: &[&str] = match &sig.output {
+            syn::ReturnType::Default => &["{}"],
+            syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+                syn::Type::ImplTrait(_) | syn::Type::Never(_) => &[],
+                syn::Type::Path(p) if p.path.is_ident("bool") => &["{ true }", "{ false }"],
+                _ => &["{ Default::default() }"],
+            },
+        };
+        for replacement in replacements {
+            self.push(AstOperator::ReturnDefault, block.span(), replacement.to_string());
+        }
+    }
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str, arg: Option<&str>) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident(name)
+            && arg.is_none_or(|arg| {
+                attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == arg)
+            })
+    })
+}
+
+fn swapped_op(op: &syn::BinOp) -> Option<(AstOperator, &'static str)> {
+    use syn::BinOp::*;
+    let swap = match op {
+        Add(_) => (AstOperator::BinaryOpSwap, "-"),
+        Sub(_) => (AstOperator::BinaryOpSwap, "+"),
+        Mul(_) => (AstOperator::BinaryOpSwap, "/"),
+        Div(_) => (AstOperator::BinaryOpSwap, "*"),
+        Rem(_) => (AstOperator::BinaryOpSwap, "/"),
+        And(_) => (AstOperator::BinaryOpSwap, "||"),
+        Or(_) => (AstOperator::BinaryOpSwap, "&&"),
+        BitAnd(_) => (AstOperator::BinaryOpSwap, "|"),
+        BitOr(_) => (AstOperator::BinaryOpSwap, "&"),
+        Eq(_) => (AstOperator::BinaryOpSwap, "!="),
+        Ne(_) => (AstOperator::BinaryOpSwap, "=="),
+        AddAssign(_) => (AstOperator::BinaryOpSwap, "-="),
+        SubAssign(_) => (AstOperator::BinaryOpSwap, "+="),
+        MulAssign(_) => (AstOperator::BinaryOpSwap, "/="),
+        DivAssign(_) => (AstOperator::BinaryOpSwap, "*="),
+        Lt(_) => (AstOperator::Boundary, "<="),
+        Le(_) => (AstOperator::Boundary, "<"),
+        Gt(_) => (AstOperator::Boundary, ">="),
+        Ge(_) => (AstOperator::Boundary, ">"),
+        _ => return None,
+    };
+    Some(swap)
+}
+
+/// `#[test]` functions and anything under `#[cfg(test)]`; mutating the
+/// tests themselves says nothing about their quality.
+fn is_test_code(attrs: &[syn::Attribute]) -> bool {
+    has_attr(attrs, "test", None) || has_attr(attrs, "cfg", Some("test"))
+}
+
+/// Arguments of a `format!`-style macro, when its body parses as a list of
+/// expressions.
+fn macro_args(mac: &syn::Macro) -> Option<syn::punctuated::Punctuated<syn::Expr, syn::Token![,]>> {
+    mac.parse_body_with(syn::punctuated::Punctuated::parse_terminated).ok()
+}
+
+impl<'ast> syn::visit::Visit<'ast> for MutationCollector<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if !is_test_code(&item.attrs) {
+            syn::visit::visit_item_impl(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if is_test_code(&item.attrs) {
+            return;
+        }
+        self.push_return_default(&item.sig, &item.block);
+        syn::visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if is_test_code(&item.attrs) {
+            return;
+        }
+        self.push_return_default(&item.sig, &item.block);
+        syn::visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if let Some((operator, replacement)) = swapped_op(&expr.op) {
+            self.push(operator, expr.op.span(), replacement.to_string());
+        }
+        syn::visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_expr_if(&mut self, expr: &'ast syn::ExprIf) {
+        if !matches!(*expr.cond, syn::Expr::Let(_)) {
+            let cond = expr.cond.to_token_stream().to_string();
+            self.push(AstOperator::BooleanNegation, expr.cond.span(), format!("!({})", cond));
+        }
+        syn::visit::visit_expr_if(self, expr);
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        if !matches!(*expr.cond, syn::Expr::Let(_)) {
+            let cond = expr.cond.to_token_stream().to_string();
+            self.push(AstOperator::BooleanNegation, expr.cond.span(), format!("!({})", cond));
+        }
+        syn::visit::visit_expr_while(self, expr);
+    }
+
+    fn visit_lit_bool(&mut self, lit: &'ast syn::LitBool) {
+        self.push(AstOperator::BooleanNegation, lit.span, (!lit.value).to_string());
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast syn::ExprMatch) {
+        let is_catch_all = |arm: &syn::Arm| matches!(arm.pat, syn::Pat::Wild(_)) && arm.guard.is_none();
+        if expr.arms.len() > 1 && expr.arms.iter().any(is_catch_all) {
+            for arm in expr.arms.iter().filter(|arm| !is_catch_all(arm)) {
+                self.push(AstOperator::MatchArmDeletion, arm.span(), String::new());
+            }
+        }
+        syn::visit::visit_expr_match(self, expr);
+    }
+
+    // Macro bodies are opaque token streams; arguments of `format!`-style
+    // macros still parse as expressions, so those are mutated too.
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        for arg in macro_args(mac).iter().flatten() {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        self.push(AstOperator::QuestionMarkRemoval, expr.question_token.span, String::new());
+        syn::visit::visit_expr_try(self, expr);
+    }
+}
+
+/// Detects mutants that obviously behave like the original: the two files
+/// must be token-for-token identical once comments, whitespace, parentheses
+/// that don't change precedence and identities such as `x * 1`, `x / 1`,
+/// `x + 0`, `x - 0`, `!!a`, and `a && a` / `a || a` on a variable or literal
+/// are normalised away. Sources that do not parse are never considered
+/// equivalent.
+pub fn is_trivially_equivalent(original: &str, mutant: &str) -> bool {
+    let normalise = |source: &str| {
+        syn::parse_file(source).ok().map(|mut file| {
+            syn::visit_mut::visit_file_mut(&mut Normaliser, &mut file);
+            file.to_token_stream().to_string()
+        })
+    };
+    match (normalise(original), normalise(mutant)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+struct Normaliser;
+
+impl Normaliser {
+    /// Expressions that bind at least as tightly as anything around them, so
+    /// wrapping them in parentheses never changes how the source parses.
+    fn is_atomic(expr: &syn::Expr) -> bool {
+        matches!(
+            expr,
+            syn::Expr::Path(_)
+                | syn::Expr::Lit(_)
+                | syn::Expr::Paren(_)
+                | syn::Expr::Call(_)
+                | syn::Expr::MethodCall(_)
+                | syn::Expr::Field(_)
+                | syn::Expr::Index(_)
+                | syn::Expr::Macro(_)
+                | syn::Expr::Tuple(_)
+                | syn::Expr::Array(_)
+                | syn::Expr::Repeat(_)
+                | syn::Expr::Struct(_)
+                | syn::Expr::Try(_)
+        )
+    }
+
+    /// Evaluating the expression cannot have side effects, so evaluating it
+    /// twice is the same as evaluating it once.
+    fn is_pure(expr: &syn::Expr) -> bool {
+        matches!(expr, syn::Expr::Path(_) | syn::Expr::Lit(_))
+    }
+
+    /// Drops parentheses around an expression in a position that is
+    /// delimited on both sides (a statement, an argument, an initialiser),
+    /// where no operator can bind to it.
+    fn strip_delimited(expr: &mut syn::Expr) {
+        while let syn::Expr::Paren(paren) = expr {
+            *expr = (*paren.expr).clone();
+        }
+    }
+}
+
+impl syn::visit_mut::VisitMut for Normaliser {
+    fn visit_expr_mut(&mut self, expr: &mut syn::Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+
+        let simplified = match expr {
+            syn::Expr::Paren(paren) if Self::is_atomic(&paren.expr) => Some((*paren.expr).clone()),
+            syn::Expr::Unary(outer) if matches!(outer.op, syn::UnOp::Not(_)) => match &*outer.expr {
+                syn::Expr::Unary(inner) if matches!(inner.op, syn::UnOp::Not(_)) => {
+                    Some((*inner.expr).clone())
+                }
+                _ => None,
+            },
+            syn::Expr::Binary(binary) => {
+                let is_int = |e: &syn::Expr, value: &str| {
+                    matches!(e, syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(i), .. })
+                        if i.base10_digits() == value)
+                };
+                let same_pure_operands = Self::is_pure(&binary.left)
+                    && binary.left.to_token_stream().to_string()
+                        == binary.right.to_token_stream().to_string();
+                let identity = match binary.op {
+                    syn::BinOp::Mul(_) | syn::BinOp::Div(_) => is_int(&binary.right, "1"),
+                    syn::BinOp::Add(_) | syn::BinOp::Sub(_) => is_int(&binary.right, "0"),
+                    syn::BinOp::And(_) | syn::BinOp::Or(_) => same_pure_operands,
+                    _ => false,
+                };
+                identity.then(|| (*binary.left).clone())
+            }
+            _ => None,
+        };
+        if let Some(simplified) = simplified {
+            *expr = simplified;
+        }
+    }
+
+    fn visit_expr_call_mut(&mut self, call: &mut syn::ExprCall) {
+        // `(self.f)(x)` calls a field; without the parentheses it would be a
+        // method call, so a parenthesised field callee is kept as written.
+        match &mut *call.func {
+            syn::Expr::Paren(paren) if matches!(*paren.expr, syn::Expr::Field(_)) => {
+                self.visit_expr_mut(&mut paren.expr);
+            }
+            func => self.visit_expr_mut(func),
+        }
+        for arg in call.args.iter_mut() {
+            self.visit_expr_mut(arg);
+            Self::strip_delimited(arg);
+        }
+    }
+
+    fn visit_expr_method_call_mut(&mut self, call: &mut syn::ExprMethodCall) {
+        syn::visit_mut::visit_expr_method_call_mut(self, call);
+        call.args.iter_mut().for_each(Self::strip_delimited);
+    }
+
+    fn visit_local_init_mut(&mut self, init: &mut syn::LocalInit) {
+        syn::visit_mut::visit_local_init_mut(self, init);
+        Self::strip_delimited(&mut init.expr);
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut syn::Stmt) {
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+        if let syn::Stmt::Expr(expr, _) = stmt {
+            Self::strip_delimited(expr);
+        }
+    }
+}
+
 //==============================================================================
 // Main Example
 //==============================================================================
@@ -824,9 +1816,11 @@ fn main() {
     println!("\nFeatures:");
     println!("  - Multiple mutation operators (arithmetic, comparison, logical)");
     println!("  - Parallel mutation testing");
+    println!("  - Isolated cargo runs per mutant with timeouts and git-based incremental mode");
     println!("  - Detailed reports (text, HTML, JSON)");
     println!("  - Source code annotation");
     println!("  - Advanced mutation strategies");
+    println!("  - Syntax-tree operators with equivalent-mutant filtering");
 }
 
 //==============================================================================
@@ -908,6 +1902,36 @@ if x > 0 {
         assert_eq!(points.len(), 2);
     }
 
+    #[test]
+    fn test_mutation_points_come_from_the_syntax_tree() {
+        let add_to_sub = MutationOperator::arithmetic_mutations().remove(0);
+        let source = r#"pub fn f(a: i32, b: i32) -> i32 {
+    // a + b in a comment
+    let s = "a + b";
+    a
+        + b
+}
+
+#[cfg(test)]
+mod tests {
+    fn g() -> i32 { 1 + 1 }
+}
+"#;
+        let points = add_to_sub.find_mutation_points(source);
+        assert_eq!(points.len(), 1);
+        assert_eq!((points[0].line, points[0].column), (4, 8));
+        assert!(add_to_sub.apply(source, &points[0]).contains("        - b"));
+
+        let zero_to_one = MutationOperator::constant_replacement_mutations().remove(0);
+        let points = zero_to_one.find_mutation_points("let s = \"0\"; let n = 0; // 0\n");
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].column, 21);
+
+        let gt_to_ge = MutationOperator::comparison_mutations().remove(0);
+        assert_eq!(gt_to_ge.find_mutation_points("fn f(a: u8) -> bool { a > 1 }").len(), 1);
+        assert!(gt_to_ge.find_mutation_points("fn broken( {").is_empty());
+    }
+
     #[test]
     fn test_predefined_operators() {
         let arith_ops = MutationOperator::arithmetic_mutations();
@@ -954,6 +1978,179 @@ edition = "2021"
         assert_eq!(runner.test_command, "cargo test");
     }
 
+    const MUTANT_CRATE: &str = r#"pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+pub fn count_to(n: u32) -> u32 {
+    let mut i = 0;
+    while i != n {
+        i += 1;
+    }
+    i
+}
+
+pub fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {
+        assert_eq!(super::add(2, 3), 5);
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(super::count_to(3), 3);
+    }
+}
+"#;
+
+    fn point(line: usize, original: &str, mutated: &str) -> MutationPoint {
+        MutationPoint {
+            line,
+            column: 0,
+            original: original.to_string(),
+            mutated: mutated.to_string(),
+            operator_name: "Test".to_string(),
+        }
+    }
+
+    fn project_runner(dir: &TempDir) -> TestRunner {
+        TestRunner::new("cargo test --offline", Duration::from_secs(5))
+            .with_project(dir.path(), "src/lib.rs")
+            .with_target_dir(dir.path().join("target"))
+    }
+
+    #[test]
+    fn test_classify_test_run() {
+        assert_eq!(classify_test_run(true, ""), MutationStatus::Survived);
+        assert_eq!(
+            classify_test_run(false, "test result: FAILED. 1 passed; 1 failed"),
+            MutationStatus::Killed
+        );
+        assert_eq!(
+            classify_test_run(false, "error[E0308]: mismatched types"),
+            MutationStatus::CompileError
+        );
+    }
+
+    #[test]
+    fn test_without_project_is_skipped() {
+        let runner = TestRunner::new("cargo test", Duration::from_secs(1));
+        let result = runner.test_mutation("a + b", &point(0, "+", "-"));
+        assert_eq!(result.status, MutationStatus::Skipped);
+    }
+
+    #[test]
+    fn test_mutant_workspace_leaves_original_untouched() {
+        let dir = create_test_project(MUTANT_CRATE);
+        fs::create_dir(dir.path().join("target")).unwrap();
+        let project = ProjectTarget {
+            root: dir.path().to_path_buf(),
+            file: PathBuf::from("src/lib.rs"),
+        };
+
+        let workspace = create_mutant_workspace(&project, "pub fn mutated() {}").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("src/lib.rs")).unwrap(),
+            "pub fn mutated() {}"
+        );
+        assert!(workspace.path().join("Cargo.toml").exists());
+        assert!(!workspace.path().join("target").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(), MUTANT_CRATE);
+    }
+
+    #[test]
+    fn test_mutant_workspace_copies_instead_of_linking() {
+        let dir = create_test_project(MUTANT_CRATE);
+        fs::write(dir.path().join("src/data.txt"), "original").unwrap();
+        let project = ProjectTarget {
+            root: dir.path().to_path_buf(),
+            file: PathBuf::from("src/lib.rs"),
+        };
+
+        let workspace = create_mutant_workspace(&project, "pub fn mutated() {}").unwrap();
+        fs::write(workspace.path().join("src/data.txt"), "changed by mutant").unwrap();
+
+        assert_eq!(fs::read_to_string(dir.path().join("src/data.txt")).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_mutant_workspace_keeps_workspace_and_path_dependencies() {
+        let outer = tempfile::tempdir().unwrap();
+        let shared = outer.path().join("shared");
+        fs::create_dir_all(shared.join("src")).unwrap();
+        fs::write(
+            shared.join("Cargo.toml"),
+            "[package]\nname = \"shared\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(shared.join("src/lib.rs"), "pub fn one() -> i32 { 1 }\n").unwrap();
+
+        let root = outer.path().join("ws");
+        fs::create_dir_all(root.join("crates/app/src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/app\"]\nresolver = \"2\"\n\n[workspace.package]\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("crates/app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion.workspace = true\nedition.workspace = true\n\n[dependencies]\nshared = { path = \"../../../shared\" }\n",
+        )
+        .unwrap();
+        let code = "pub fn two() -> i32 { shared::one() + 1 }\n\n#[test]\nfn two_is_two() { assert_eq!(two(), 2); }\n";
+        fs::write(root.join("crates/app/src/lib.rs"), code).unwrap();
+
+        let project = ProjectTarget {
+            root: root.join("crates/app"),
+            file: PathBuf::from("src/lib.rs"),
+        };
+        let workspace = create_mutant_workspace(&project, code).unwrap();
+        let manifest = fs::read_to_string(workspace.path().join("Cargo.toml")).unwrap();
+        let shared = fs::canonicalize(&shared).unwrap();
+        assert!(manifest.contains(&*shared.to_string_lossy()), "{}", manifest);
+        assert!(workspace.path().join("../../Cargo.toml").exists());
+
+        let runner = TestRunner::new("cargo test --offline", Duration::from_secs(60))
+            .with_project(&project.root, &project.file)
+            .with_target_dir(outer.path().join("target"));
+        let result = runner.test_mutation(code, &point(0, "+", "-"));
+        assert_eq!(result.status, MutationStatus::Killed, "{}", result.test_output);
+    }
+
+    #[test]
+    fn test_compile_timeout_is_compile_error() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let runner = project_runner(&dir).with_compile_timeout(Duration::from_millis(1));
+
+        let result = runner.test_mutation(MUTANT_CRATE, &point(1, "+", "-"));
+        assert_eq!(result.status, MutationStatus::CompileError, "{}", result.test_output);
+    }
+
+    #[test]
+    fn test_real_cargo_outcomes() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let runner = project_runner(&dir);
+
+        let cases = [
+            (point(1, "+", "-"), MutationStatus::Killed),
+            (point(13, "*", "/"), MutationStatus::Survived),
+            (point(1, "b", "\"b\""), MutationStatus::CompileError),
+            (point(7, "+= 1", "+= 0"), MutationStatus::Timeout),
+        ];
+        for (mutation, expected) in cases {
+            let result = runner.test_mutation(MUTANT_CRATE, &mutation);
+            assert_eq!(result.status, expected, "{:?}: {}", mutation, result.test_output);
+        }
+
+        assert_eq!(fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(), MUTANT_CRATE);
+    }
+
     // Milestone 3 Tests
     #[test]
     fn test_parallel_runner_creation() {
@@ -979,6 +2176,85 @@ edition = "2021"
         assert!(batches.len() >= 3);
     }
 
+    #[test]
+    fn test_parse_changed_lines() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2 +2 @@ pub fn add(a: i32, b: i32) -> i32 {
+-    a + b
++    a - b
+@@ -10,0 +11,2 @@ pub fn count_to(n: u32) -> u32 {
++// one
++// two
+@@ -20,3 +22,0 @@ mod tests {
+";
+        let lines = parse_changed_lines(diff);
+        let mut sorted: Vec<usize> = lines.into_iter().collect();
+        sorted.sort();
+        assert_eq!(sorted, vec![1, 10, 11]);
+    }
+
+    #[test]
+    fn test_parallel_workers_use_own_target_dirs() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let runner = ParallelTestRunner::new(4, Duration::from_secs(5)).with_runner(
+            TestRunner::new("cargo test --offline", Duration::from_secs(5))
+                .with_project(dir.path(), "src/lib.rs"),
+        );
+
+        let results = runner.test_mutations_parallel(
+            MUTANT_CRATE,
+            vec![point(1, "+", "-"), point(13, "*", "/")],
+        );
+
+        assert_eq!(results[0].status, MutationStatus::Killed);
+        assert_eq!(results[1].status, MutationStatus::Survived);
+        let workers: Vec<_> = fs::read_dir(dir.path().join("target/mutants"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert!(!workers.is_empty());
+        assert!(workers.iter().all(|w| w.starts_with("worker-")));
+    }
+
+    #[test]
+    fn test_incremental_mode_only_runs_changed_lines() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "base"]);
+
+        let changed = MUTANT_CRATE.replace("x * 2", "x * 3");
+        fs::write(dir.path().join("src/lib.rs"), &changed).unwrap();
+
+        let runner = ParallelTestRunner::new(4, Duration::from_secs(5))
+            .with_runner(project_runner(&dir));
+        let results = runner
+            .test_changed_since(
+                &changed,
+                vec![point(1, "+", "-"), point(13, "*", "/")],
+                "HEAD",
+            )
+            .unwrap();
+
+        assert_eq!(results[0].status, MutationStatus::Skipped);
+        assert_eq!(results[1].status, MutationStatus::Survived);
+        assert!(runner.test_changed_since(&changed, vec![], "no-such-ref").is_err());
+    }
+
     // Milestone 4 Tests
     fn create_sample_results() -> Vec<MutationResult> {
         vec![
@@ -1230,4 +2506,234 @@ fn max(a: i32, b: i32) -> i32 {
 
         assert_eq!(annotated.metadata.total_lines, 2);
     }
+
+    // Milestone 7 Tests
+    fn ast_ops(source: &str, operator: AstOperator) -> Vec<AstMutation> {
+        find_ast_mutations(source)
+            .unwrap()
+            .into_iter()
+            .filter(|m| m.operator == operator)
+            .collect()
+    }
+
+    #[test]
+    fn test_ast_skips_strings_comments_and_tests() {
+        let source = r#"
+pub fn label(a: i32, b: i32) -> String {
+    // a - b would be wrong here
+    format!("{} + {}", a, b + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {
+        assert_eq!(1 + 1, 2);
+    }
+}
+"#;
+        let swaps = ast_ops(source, AstOperator::BinaryOpSwap);
+        assert_eq!(swaps.len(), 1);
+        assert_eq!((swaps[0].line, swaps[0].original.as_str()), (3, "+"));
+        assert!(swaps[0].apply(source).contains("b - 1"));
+        assert!(swaps[0].apply(source).contains("\"{} + {}\""));
+    }
+
+    #[test]
+    fn test_ast_multi_line_expression() {
+        let source = "pub fn total(a: u32, b: u32) -> bool {\n    a\n        < b\n}\n";
+        let boundary = ast_ops(source, AstOperator::Boundary);
+        assert_eq!(boundary.len(), 1);
+        assert_eq!((boundary[0].line, boundary[0].column), (2, 8));
+        assert_eq!(
+            boundary[0].apply(source),
+            "pub fn total(a: u32, b: u32) -> bool {\n    a\n        <= b\n}\n"
+        );
+        assert_eq!(boundary[0].to_point().operator_name, "Boundary");
+
+        let defaults: Vec<String> = ast_ops(source, AstOperator::ReturnDefault)
+            .into_iter()
+            .map(|m| m.replacement)
+            .collect();
+        assert_eq!(defaults, vec!["{ true }", "{ false }"]);
+    }
+
+    #[test]
+    fn test_ast_typed_operators() {
+        let source = r#"
+use std::num::ParseIntError;
+
+pub fn parse(s: &str) -> Result<u32, ParseIntError> {
+    let n: u32 = s.parse()?;
+    if n > 10 && true {
+        return Ok(0);
+    }
+    Ok(match n {
+        0 => 1,
+        1 => 2,
+        _ => n,
+    })
+}
+
+pub fn exhaustive(b: bool) -> u8 {
+    match b {
+        true => 1,
+        false => 0,
+    }
+}
+"#;
+        let questions = ast_ops(source, AstOperator::QuestionMarkRemoval);
+        assert_eq!(questions.len(), 1);
+        assert!(questions[0].apply(source).contains("s.parse();"));
+
+        let arms = ast_ops(source, AstOperator::MatchArmDeletion);
+        assert_eq!(arms.len(), 2);
+        assert_eq!(arms[0].original, "0 => 1,");
+        assert!(!arms[1].apply(source).contains("1 => 2"));
+
+        let negations = ast_ops(source, AstOperator::BooleanNegation);
+        assert!(negations.iter().any(|m| m.replacement == "!(n > 10 && true)"));
+        assert!(negations.iter().any(|m| m.original == "true" && m.replacement == "false"));
+
+        let defaults = ast_ops(source, AstOperator::ReturnDefault);
+        assert_eq!(defaults[0].replacement, "{ Default::default() }");
+        assert!(defaults[0].original.starts_with('{') && defaults[0].original.ends_with('}'));
+
+        assert!(ast_ops(source, AstOperator::Boundary)[0].apply(source).contains("n >= 10"));
+        assert!(find_ast_mutations("fn broken( {").is_err());
+    }
+
+    #[test]
+    fn test_trivially_equivalent_mutants() {
+        let original = "fn f(x: i32) -> i32 { x * 1 }";
+        assert!(is_trivially_equivalent(original, "fn f(x: i32) -> i32 { x / 1 }"));
+        assert!(is_trivially_equivalent(
+            "fn f(x: i32) -> i32 { x + 0 }",
+            "fn f(x: i32) -> i32 {\n    // comment\n    (x - 0)\n}"
+        ));
+        assert!(is_trivially_equivalent(
+            "fn f(a: bool) -> bool { a && a }",
+            "fn f(a: bool) -> bool { a || a }"
+        ));
+        assert!(is_trivially_equivalent(
+            "fn f(a: bool) -> bool { a }",
+            "fn f(a: bool) -> bool { !!a }"
+        ));
+        assert!(!is_trivially_equivalent(
+            "fn f(x: i32) -> i32 { x + 1 }",
+            "fn f(x: i32) -> i32 { x - 1 }"
+        ));
+        assert!(!is_trivially_equivalent(original, "not rust"));
+
+        let runner = TestRunner::new("cargo test", Duration::from_secs(1));
+        assert!(runner.is_equivalent(original, "fn f(x: i32) -> i32 { x / 1 }"));
+
+        // The mul/div swap on `x * 1` is filtered out; the return default is not.
+        let mutations = find_ast_mutations(original).unwrap();
+        assert!(mutations.iter().all(|m| m.operator != AstOperator::BinaryOpSwap));
+        assert_eq!(mutations.len(), 1);
+    }
+
+    #[test]
+    fn test_ast_skips_test_methods() {
+        let source = r#"
+pub struct S;
+
+impl S {
+    pub fn f(&self) -> i32 { 1 + 2 }
+
+    #[test]
+    fn t() { assert_eq!(1 + 1, 2); }
+}
+
+#[cfg(test)]
+impl S {
+    fn helper(&self) -> i32 { 3 - 1 }
+}
+"#;
+        let swaps = ast_ops(source, AstOperator::BinaryOpSwap);
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].line, 4);
+    }
+
+    #[test]
+    fn test_equivalence_respects_precedence_and_side_effects() {
+        assert!(!is_trivially_equivalent(
+            "fn f(a: i32, b: i32) -> i32 { (a + b).abs() }",
+            "fn f(a: i32, b: i32) -> i32 { a + b.abs() }"
+        ));
+        assert!(!is_trivially_equivalent(
+            "fn f() -> bool { g() && g() }",
+            "fn f() -> bool { g() || g() }"
+        ));
+        assert!(!is_trivially_equivalent(
+            "fn f(s: &S) -> i32 { (s.f)(1) }",
+            "fn f(s: &S) -> i32 { s.f(1) }"
+        ));
+        assert!(is_trivially_equivalent(
+            "fn f(a: i32) -> i32 { let x = (a + 1); (x).abs() }",
+            "fn f(a: i32) -> i32 { let x = a + 1; x.abs() }"
+        ));
+        assert!(is_trivially_equivalent(
+            "fn f() -> bool { true || true }",
+            "fn f() -> bool { true && true }"
+        ));
+    }
+
+    #[test]
+    fn test_ast_mutations_through_parallel_runner() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "base"]);
+
+        let changed = MUTANT_CRATE.replace("x * 2", "x * 3");
+        fs::write(dir.path().join("src/lib.rs"), &changed).unwrap();
+        let mutations = find_ast_mutations(&changed).unwrap();
+        let runner = ParallelTestRunner::new(4, Duration::from_secs(5))
+            .with_runner(project_runner(&dir));
+
+        let results = runner.test_ast_changed_since(&changed, mutations.clone(), "HEAD").unwrap();
+        assert_eq!(results.len(), mutations.len());
+        for (mutation, result) in mutations.iter().zip(&results) {
+            let touches_change = Mutant::lines(mutation, &changed).contains(&13);
+            assert_eq!(result.status != MutationStatus::Skipped, touches_change, "{:?}", mutation);
+        }
+        assert!(results.iter().any(|r| r.mutation.operator_name == "ReturnDefault"
+            && r.status == MutationStatus::Survived));
+
+        let add = mutations.into_iter().filter(|m| m.original == "+").collect();
+        let results = runner.test_ast_mutations_parallel(&changed, add);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, MutationStatus::Killed);
+    }
+
+    #[test]
+    fn test_ast_mutation_runs_through_cargo() {
+        let dir = create_test_project(MUTANT_CRATE);
+        let runner = project_runner(&dir);
+        let mutation = ast_ops(MUTANT_CRATE, AstOperator::BinaryOpSwap)
+            .into_iter()
+            .find(|m| m.original == "+")
+            .unwrap();
+
+        let result = runner.test_ast_mutation(MUTANT_CRATE, &mutation);
+        assert_eq!(result.status, MutationStatus::Killed);
+        assert_eq!(result.mutation.line, 1);
+
+        let point = point(13, "* 2", "* 2 * 1");
+        let skipped = runner.test_mutation(MUTANT_CRATE, &point);
+        assert_eq!(skipped.status, MutationStatus::Skipped);
+    }
 }
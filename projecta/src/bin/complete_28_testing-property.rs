@@ -2,7 +2,12 @@
 // Property-Based Test Generator - Complete Implementation
 //==============================================================================
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Serialize, Deserialize};
 
 //==============================================================================
@@ -882,8 +887,6 @@ impl ShrinkStrategy {
 // Milestone 6: CLI Tool and Project Integration
 //==============================================================================
 
-use std::path::Path;
-
 pub struct Config {
     pub num_cases: usize,
     pub output_dir: String,
@@ -920,6 +923,989 @@ pub fn generate_for_project(_root: &Path, _config: &Config) -> Result<(), String
     Ok(())
 }
 
+//==============================================================================
+// Milestone 7: Runtime Property Engine
+//==============================================================================
+
+/// A generated argument. Every integer type is carried as `i128` so one
+/// strategy covers them all.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Value {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Vec(Vec<Value>),
+    Option(Option<Box<Value>>),
+    Result(Result<Box<Value>, Box<Value>>),
+    Tuple(Vec<Value>),
+}
+
+impl Value {
+    pub fn length(&self) -> Option<usize> {
+        match self {
+            Value::Str(s) => Some(s.chars().count()),
+            Value::Vec(items) | Value::Tuple(items) => Some(items.len()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, items: &[Value]) -> fmt::Result {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", item)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Char(c) => write!(f, "{:?}", c),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Vec(items) => {
+                write!(f, "[")?;
+                list(f, items)?;
+                write!(f, "]")
+            }
+            Value::Option(None) => write!(f, "None"),
+            Value::Option(Some(inner)) => write!(f, "Some({})", inner),
+            Value::Result(Ok(inner)) => write!(f, "Ok({})", inner),
+            Value::Result(Err(inner)) => write!(f, "Err({})", inner),
+            Value::Tuple(items) => {
+                write!(f, "(")?;
+                list(f, items)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// The 32-byte ChaCha seed of one test case, as stored on `cc` lines of a
+/// proptest regression file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed(pub [u8; 32]);
+
+impl Seed {
+    pub fn random() -> Self {
+        Seed(rand::random())
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Seed(bytes))
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Deterministic source of randomness: the same seed always produces the
+/// same values, on every platform.
+pub struct TestRng {
+    rng: ChaCha20Rng,
+}
+
+impl TestRng {
+    pub fn from_seed(seed: Seed) -> Self {
+        TestRng {
+            rng: ChaCha20Rng::from_seed(seed.0),
+        }
+    }
+
+    /// Seed for the next test case, so any single case can be replayed
+    /// without regenerating the ones before it.
+    pub fn next_seed(&mut self) -> Seed {
+        let mut bytes = [0u8; 32];
+        self.rng.fill(&mut bytes);
+        Seed(bytes)
+    }
+
+    pub fn one_in(&mut self, n: u32) -> bool {
+        self.rng.gen_ratio(1, n)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        self.rng.gen_range(0..n)
+    }
+
+    pub fn int_in(&mut self, min: i128, max: i128) -> i128 {
+        self.rng.gen_range(min..=max)
+    }
+
+    pub fn float_in(&mut self, min: f64, max: f64) -> f64 {
+        self.rng.gen_range(min..=max)
+    }
+}
+
+/// Generates values of one type and proposes simpler versions of a
+/// failing value. Candidates are ordered most aggressive first and carry
+/// the step that produced them.
+pub trait Strategy {
+    fn generate(&self, rng: &mut TestRng) -> Value;
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)>;
+}
+
+pub struct IntStrategy {
+    pub min: i128,
+    pub max: i128,
+    pub non_zero: bool,
+}
+
+impl IntStrategy {
+    fn allows(&self, n: i128) -> bool {
+        n >= self.min && n <= self.max && !(self.non_zero && n == 0)
+    }
+
+    /// The simplest allowed value: zero, or the closest value to it.
+    fn target(&self) -> i128 {
+        let target = 0i128.clamp(self.min, self.max);
+        if self.allows(target) {
+            target
+        } else if self.max >= 1 {
+            1
+        } else {
+            -1
+        }
+    }
+}
+
+impl Strategy for IntStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        let n = if rng.one_in(8) {
+            [self.min, self.max, self.target()][rng.below(3)]
+        } else if rng.one_in(3) {
+            rng.int_in(self.min.max(-100), self.max.min(100).max(self.min.max(-100)))
+        } else {
+            rng.int_in(self.min, self.max)
+        };
+        Value::Int(if self.allows(n) { n } else { self.target() })
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Int(n) = *value else { return Vec::new() };
+        let target = self.target();
+        if n == target {
+            return Vec::new();
+        }
+
+        let mut candidates = vec![(ShrinkStep::TowardsZero, target)];
+        if n < 0 && n.checked_neg().is_some_and(|m| self.allows(m)) {
+            candidates.push((ShrinkStep::TowardsZero, -n));
+        }
+        // Halve the distance to the target, then a quarter of it, and so
+        // on, so a failing boundary is found in logarithmic rounds. The
+        // distance itself overflows when `n` and `target` are at opposite
+        // ends of the `i128` range, so it is halved before subtracting then.
+        let mut delta = n.checked_sub(target).map_or(n / 2 - target / 2, |d| d / 2);
+        while delta != 0 {
+            candidates.push((ShrinkStep::BinarySearch, n - delta));
+            delta /= 2;
+        }
+        candidates.push((ShrinkStep::TowardsZero, if n > target { n - 1 } else { n + 1 }));
+
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|&(_, c)| c != n && self.allows(c) && seen.insert(c))
+            .map(|(step, c)| (step, Value::Int(c)))
+            .collect()
+    }
+}
+
+pub struct FloatStrategy {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Strategy for FloatStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        if rng.one_in(8) {
+            return Value::Float(0.0f64.clamp(self.min, self.max));
+        }
+        Value::Float(rng.float_in(self.min, self.max))
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Float(x) = *value else { return Vec::new() };
+        let candidates = [
+            (ShrinkStep::TowardsZero, 0.0f64.clamp(self.min, self.max)),
+            (ShrinkStep::TowardsZero, x.trunc()),
+            (ShrinkStep::BinarySearch, (x / 2.0).trunc()),
+        ];
+        candidates
+            .into_iter()
+            .filter(|&(_, c)| c != x && c.abs() < x.abs() && c >= self.min && c <= self.max)
+            .map(|(step, c)| (step, Value::Float(c)))
+            .collect()
+    }
+}
+
+pub struct BoolStrategy;
+
+impl Strategy for BoolStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        Value::Bool(rng.one_in(2))
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        match value {
+            Value::Bool(true) => vec![(ShrinkStep::ReplaceWithDefault, Value::Bool(false))],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// How simple a character looks in a counterexample; lower is simpler.
+fn char_rank(c: char) -> u32 {
+    match c {
+        'a' => 0,
+        '0' => 1,
+        _ => 2 + c as u32,
+    }
+}
+
+fn random_char(rng: &mut TestRng) -> char {
+    if rng.one_in(10) {
+        char::from_u32(rng.int_in(0xA0, 0x2FFF) as u32).unwrap_or('?')
+    } else {
+        rng.int_in(0x20, 0x7E) as u8 as char
+    }
+}
+
+pub struct CharStrategy;
+
+impl Strategy for CharStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        Value::Char(random_char(rng))
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Char(c) = *value else { return Vec::new() };
+        ['a', '0']
+            .into_iter()
+            .filter(|&r| char_rank(r) < char_rank(c))
+            .map(|r| (ShrinkStep::ReplaceWithDefault, Value::Char(r)))
+            .collect()
+    }
+}
+
+fn shrink_string(s: &str, min_len: usize) -> Vec<(ShrinkStep, String)> {
+    let chars: Vec<char> = s.chars().collect();
+    let len = chars.len();
+    let mut candidates = Vec::new();
+
+    if len > min_len {
+        candidates.push((ShrinkStep::ShortenString, chars[..min_len].iter().collect()));
+        let half = len / 2;
+        if half > min_len {
+            candidates.push((ShrinkStep::ShortenString, chars[..half].iter().collect()));
+            candidates.push((ShrinkStep::ShortenString, chars[len - half..].iter().collect()));
+        }
+        for i in 0..len {
+            let mut shorter = chars.clone();
+            shorter.remove(i);
+            candidates.push((ShrinkStep::ShortenString, shorter.into_iter().collect()));
+        }
+    }
+    for (i, &c) in chars.iter().enumerate() {
+        for r in ['a', '0'] {
+            if char_rank(r) < char_rank(c) {
+                let mut simpler = chars.clone();
+                simpler[i] = r;
+                candidates.push((ShrinkStep::ReplaceWithDefault, simpler.into_iter().collect()));
+            }
+        }
+    }
+
+    candidates
+}
+
+pub struct StringStrategy {
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+impl Strategy for StringStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        let len = rng.int_in(self.min_len as i128, self.max_len as i128) as usize;
+        Value::Str((0..len).map(|_| random_char(rng)).collect())
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Str(s) = value else { return Vec::new() };
+        shrink_string(s, self.min_len)
+            .into_iter()
+            .map(|(step, s)| (step, Value::Str(s)))
+            .collect()
+    }
+}
+
+/// Strings matching a regular expression. Unbounded repetitions stop after
+/// eight extra iterations; shrunk candidates must still match.
+pub struct PatternStrategy {
+    hir: regex_syntax::hir::Hir,
+    matcher: regex::Regex,
+}
+
+impl PatternStrategy {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let hir = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map_err(|e| format!("invalid pattern {:?}: {}", pattern, e))?;
+        let matcher = regex::Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+        Ok(PatternStrategy { hir, matcher })
+    }
+
+    fn generate_into(hir: &regex_syntax::hir::Hir, rng: &mut TestRng, out: &mut String) {
+        use regex_syntax::hir::{Class, HirKind};
+
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(literal) => out.push_str(&String::from_utf8_lossy(&literal.0)),
+            HirKind::Class(Class::Unicode(class)) => {
+                let ranges = class.ranges();
+                if !ranges.is_empty() {
+                    let range = ranges[rng.below(ranges.len())];
+                    let code = rng.int_in(range.start() as i128, range.end() as i128) as u32;
+                    out.push(char::from_u32(code).unwrap_or(range.start()));
+                }
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                let ranges = class.ranges();
+                if !ranges.is_empty() {
+                    let range = ranges[rng.below(ranges.len())];
+                    out.push(rng.int_in(range.start() as i128, range.end() as i128) as u8 as char);
+                }
+            }
+            HirKind::Repetition(repetition) => {
+                let max = repetition.max.unwrap_or(repetition.min + 8);
+                for _ in 0..rng.int_in(repetition.min as i128, max as i128) {
+                    Self::generate_into(&repetition.sub, rng, out);
+                }
+            }
+            HirKind::Capture(capture) => Self::generate_into(&capture.sub, rng, out),
+            HirKind::Concat(parts) => {
+                for part in parts {
+                    Self::generate_into(part, rng, out);
+                }
+            }
+            HirKind::Alternation(options) => Self::generate_into(&options[rng.below(options.len())], rng, out),
+        }
+    }
+}
+
+impl Strategy for PatternStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        let mut out = String::new();
+        Self::generate_into(&self.hir, rng, &mut out);
+        Value::Str(out)
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Str(s) = value else { return Vec::new() };
+        shrink_string(s, 0)
+            .into_iter()
+            .filter(|(_, candidate)| self.matcher.is_match(candidate))
+            .map(|(step, s)| (step, Value::Str(s)))
+            .collect()
+    }
+}
+
+pub struct VecStrategy {
+    pub element: Box<dyn Strategy>,
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+impl Strategy for VecStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        let len = rng.int_in(self.min_len as i128, self.max_len as i128) as usize;
+        Value::Vec((0..len).map(|_| self.element.generate(rng)).collect())
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Vec(items) = value else { return Vec::new() };
+        let len = items.len();
+        let mut candidates = Vec::new();
+
+        if len > self.min_len {
+            candidates.push((ShrinkStep::RemoveElements, Value::Vec(items[..self.min_len].to_vec())));
+            let half = len / 2;
+            if half > self.min_len {
+                candidates.push((ShrinkStep::RemoveElements, Value::Vec(items[..half].to_vec())));
+                candidates.push((ShrinkStep::RemoveElements, Value::Vec(items[len - half..].to_vec())));
+            }
+            for i in 0..len {
+                let mut shorter = items.clone();
+                shorter.remove(i);
+                candidates.push((ShrinkStep::RemoveElements, Value::Vec(shorter)));
+            }
+        }
+        for (i, item) in items.iter().enumerate() {
+            for (_, simpler) in self.element.shrink(item) {
+                let mut replaced = items.clone();
+                replaced[i] = simpler;
+                candidates.push((ShrinkStep::SimplifyStructure, Value::Vec(replaced)));
+            }
+        }
+
+        candidates
+    }
+}
+
+pub struct OptionStrategy {
+    pub inner: Box<dyn Strategy>,
+}
+
+impl Strategy for OptionStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        if rng.one_in(4) {
+            Value::Option(None)
+        } else {
+            Value::Option(Some(Box::new(self.inner.generate(rng))))
+        }
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Option(Some(inner)) = value else { return Vec::new() };
+        let mut candidates = vec![(ShrinkStep::ReplaceWithDefault, Value::Option(None))];
+        candidates.extend(
+            self.inner
+                .shrink(inner)
+                .into_iter()
+                .map(|(_, simpler)| (ShrinkStep::SimplifyStructure, Value::Option(Some(Box::new(simpler))))),
+        );
+        candidates
+    }
+}
+
+pub struct ResultStrategy {
+    pub ok: Box<dyn Strategy>,
+    pub err: Box<dyn Strategy>,
+}
+
+impl Strategy for ResultStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        if rng.one_in(2) {
+            Value::Result(Err(Box::new(self.err.generate(rng))))
+        } else {
+            Value::Result(Ok(Box::new(self.ok.generate(rng))))
+        }
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let candidates = match value {
+            Value::Result(Ok(inner)) => self
+                .ok
+                .shrink(inner)
+                .into_iter()
+                .map(|(_, simpler)| Value::Result(Ok(Box::new(simpler))))
+                .collect(),
+            Value::Result(Err(inner)) => self
+                .err
+                .shrink(inner)
+                .into_iter()
+                .map(|(_, simpler)| Value::Result(Err(Box::new(simpler))))
+                .collect(),
+            _ => Vec::new(),
+        };
+        candidates.into_iter().map(|c| (ShrinkStep::SimplifyStructure, c)).collect()
+    }
+}
+
+/// A fixed number of independent values; also the shape of an argument list.
+pub struct TupleStrategy {
+    pub parts: Vec<Box<dyn Strategy>>,
+}
+
+impl Strategy for TupleStrategy {
+    fn generate(&self, rng: &mut TestRng) -> Value {
+        Value::Tuple(self.parts.iter().map(|part| part.generate(rng)).collect())
+    }
+
+    fn shrink(&self, value: &Value) -> Vec<(ShrinkStep, Value)> {
+        let Value::Tuple(items) = value else { return Vec::new() };
+        let mut candidates = Vec::new();
+        for (i, (part, item)) in self.parts.iter().zip(items).enumerate() {
+            for (step, simpler) in part.shrink(item) {
+                let mut replaced = items.clone();
+                replaced[i] = simpler;
+                candidates.push((step, Value::Tuple(replaced)));
+            }
+        }
+        candidates
+    }
+}
+
+/// Bounds of the integer type `name`. Values are carried as `i128`, so
+/// `u128` is capped at `i128::MAX` and its upper half is never generated.
+fn integer_bounds(name: &str) -> Option<(i128, i128)> {
+    Some(match name {
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" | "isize" => (i64::MIN as i128, i64::MAX as i128),
+        "i128" => (i128::MIN, i128::MAX),
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" | "usize" => (0, u64::MAX as i128),
+        "u128" => (0, i128::MAX),
+        _ => return None,
+    })
+}
+
+fn length_bounds(constraints: &[Constraint], default_max: usize) -> (usize, usize) {
+    let (mut min, mut max) = (0, default_max);
+    for constraint in constraints {
+        match constraint {
+            Constraint::Length { min: lo, max: hi } => {
+                min = *lo;
+                max = *hi;
+            }
+            Constraint::NonEmpty => min = min.max(1),
+            _ => {}
+        }
+    }
+    (min, max.max(min))
+}
+
+fn primitive_strategy(name: &str, constraints: &[Constraint]) -> Result<Box<dyn Strategy>, String> {
+    if let Some((mut min, mut max)) = integer_bounds(name) {
+        let mut non_zero = false;
+        for constraint in constraints {
+            match constraint {
+                Constraint::Range { min: lo, max: hi } => {
+                    min = min.max(*lo as i128);
+                    max = max.min(*hi as i128);
+                }
+                Constraint::Positive => min = min.max(1),
+                Constraint::NonZero => non_zero = true,
+                _ => {}
+            }
+        }
+        if min > max || (non_zero && min == 0 && max == 0) {
+            return Err(format!("constraints leave no {} values", name));
+        }
+        return Ok(Box::new(IntStrategy { min, max, non_zero }));
+    }
+
+    match name {
+        "f32" | "f64" => {
+            let (mut min, mut max) = (f64::NEG_INFINITY, f64::INFINITY);
+            let mut ranged = false;
+            for constraint in constraints {
+                match constraint {
+                    Constraint::Range { min: lo, max: hi } => {
+                        min = min.max(*lo as f64);
+                        max = max.min(*hi as f64);
+                        ranged = true;
+                    }
+                    Constraint::Positive => min = min.max(f64::EPSILON),
+                    _ => {}
+                }
+            }
+            if !ranged {
+                min = min.max(-1e6);
+                max = max.min(1e6);
+            }
+            if min > max {
+                return Err(format!("constraints leave no {} values", name));
+            }
+            Ok(Box::new(FloatStrategy { min, max }))
+        }
+        "bool" => Ok(Box::new(BoolStrategy)),
+        "char" => Ok(Box::new(CharStrategy)),
+        "String" | "&str" => {
+            if let Some(Constraint::Pattern(pattern)) = constraints.iter().find(|c| matches!(c, Constraint::Pattern(_))) {
+                return Ok(Box::new(PatternStrategy::new(pattern)?));
+            }
+            let (min_len, max_len) = length_bounds(constraints, 32);
+            Ok(Box::new(StringStrategy { min_len, max_len }))
+        }
+        _ => Err(format!("no strategy for primitive {}", name)),
+    }
+}
+
+/// Builds the runtime generator for `typ`. Constraints apply to the outer
+/// type and are ignored where they make no sense; generic parameters are
+/// instantiated with `i32`, as in the generated proptest code.
+pub fn strategy_for(typ: &Type, constraints: &[Constraint]) -> Result<Box<dyn Strategy>, String> {
+    if constraints.iter().any(|c| matches!(c, Constraint::Predicate(_))) {
+        return Err("predicate constraints can only be checked in generated code".to_string());
+    }
+
+    match typ {
+        Type::Primitive(name) => primitive_strategy(name, constraints),
+        Type::Generic(_) => primitive_strategy("i32", constraints),
+        Type::Vec(inner) => {
+            let (min_len, max_len) = length_bounds(constraints, 99);
+            Ok(Box::new(VecStrategy {
+                element: strategy_for(inner, &[])?,
+                min_len,
+                max_len,
+            }))
+        }
+        Type::Option(inner) => Ok(Box::new(OptionStrategy {
+            inner: strategy_for(inner, &[])?,
+        })),
+        Type::Result(ok, err) => Ok(Box::new(ResultStrategy {
+            ok: strategy_for(ok, &[])?,
+            err: strategy_for(err, &[])?,
+        })),
+        Type::Tuple(parts) => Ok(Box::new(TupleStrategy {
+            parts: parts.iter().map(|part| strategy_for(part, &[])).collect::<Result<_, _>>()?,
+        })),
+        Type::Custom(name) => Err(format!("no strategy for custom type {}", name)),
+    }
+}
+
+const REGRESSION_HEADER: &str = "\
+# Seeds for failure cases proptest has generated in the past. It is
+# automatically read and these particular cases re-run before any
+# novel cases are generated.
+#
+# It is recommended to check this file in to source control so that
+# everyone who runs the test benefits from these saved cases.
+";
+
+/// Seeds from a proptest regression file. `cc` lines hold a hex ChaCha
+/// seed; legacy `xs` lines hold four decimal words, which fill the first
+/// 16 bytes. Anything after `#` is a comment.
+pub fn parse_regressions(contents: &str) -> Vec<Seed> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.split('#').next()?.trim();
+            let (kind, rest) = line.split_once(' ')?;
+            match kind {
+                "cc" => Seed::from_hex(rest.trim()),
+                "xs" => {
+                    let words: Vec<u32> = rest.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+                    if words.len() != 4 {
+                        return None;
+                    }
+                    let mut bytes = [0u8; 32];
+                    for (chunk, word) in bytes.chunks_mut(4).zip(words) {
+                        chunk.copy_from_slice(&word.to_le_bytes());
+                    }
+                    Some(Seed(bytes))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// A `proptest-regressions` file: failing seeds are replayed before any
+/// novel case. The engine's generators differ from proptest's, so a seed
+/// only reproduces the same input in the tool that recorded it.
+pub struct RegressionFile {
+    pub path: PathBuf,
+}
+
+impl RegressionFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        RegressionFile { path: path.into() }
+    }
+
+    /// Where proptest keeps the regressions of `source`:
+    /// `src/foo.rs` maps to `proptest-regressions/foo.txt` in the crate.
+    pub fn for_source(crate_root: &Path, source: &Path) -> Self {
+        let relative = source.strip_prefix(crate_root).unwrap_or(source);
+        let relative = relative.strip_prefix("src").unwrap_or(relative);
+        RegressionFile::new(crate_root.join("proptest-regressions").join(relative).with_extension("txt"))
+    }
+
+    pub fn load(&self) -> Result<Vec<Seed>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(parse_regressions(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("{}: {}", self.path.display(), e)),
+        }
+    }
+
+    pub fn append(&self, seed: Seed, description: &str) -> Result<(), String> {
+        if self.load()?.contains(&seed) {
+            return Ok(());
+        }
+
+        let mut contents = std::fs::read_to_string(&self.path).unwrap_or_else(|_| REGRESSION_HEADER.to_string());
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&format!("cc {} # shrinks to {}\n", seed.to_hex(), description));
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&self.path, contents).map_err(|e| format!("{}: {}", self.path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub seed: Seed,
+    pub original: Vec<Value>,
+    pub minimal: Vec<Value>,
+    pub message: String,
+    pub shrink_trail: Vec<ShrinkStep>,
+    pub shrink_iters: usize,
+    pub from_regression: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed { cases: usize },
+    Failed(Failure),
+}
+
+impl TestOutcome {
+    pub fn is_passed(&self) -> bool {
+        matches!(self, TestOutcome::Passed { .. })
+    }
+}
+
+/// Runs properties in-process: replays recorded seeds, generates
+/// `num_cases` novel cases, and shrinks the first failure for at most
+/// `max_shrink_iters` attempts.
+pub struct PropertyRunner {
+    config: GeneratorConfig,
+    seed: Option<Seed>,
+    regressions: Option<RegressionFile>,
+}
+
+impl PropertyRunner {
+    pub fn new(config: GeneratorConfig) -> Self {
+        PropertyRunner {
+            config,
+            seed: None,
+            regressions: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: Seed) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_regression_file(mut self, file: RegressionFile) -> Self {
+        self.regressions = Some(file);
+        self
+    }
+
+    /// Checks `test` against generated arguments. A test fails by
+    /// returning `Err` or by panicking.
+    pub fn check<F>(&self, params: Vec<(String, Box<dyn Strategy>)>, test: F) -> Result<TestOutcome, String>
+    where
+        F: Fn(&[Value]) -> Result<(), String>,
+    {
+        let (names, parts): (Vec<String>, Vec<Box<dyn Strategy>>) = params.into_iter().unzip();
+        let strategy = TupleStrategy { parts };
+
+        if let Some(file) = &self.regressions {
+            for seed in file.load()? {
+                let values = strategy.generate(&mut TestRng::from_seed(seed));
+                if let Some(message) = Self::run_case(&test, &values) {
+                    return Ok(TestOutcome::Failed(self.shrink(&strategy, seed, values, message, &test, true)));
+                }
+            }
+        }
+
+        let mut rng = TestRng::from_seed(self.seed.unwrap_or_else(Seed::random));
+        for _ in 0..self.config.num_cases {
+            let seed = rng.next_seed();
+            let values = strategy.generate(&mut TestRng::from_seed(seed));
+            if let Some(message) = Self::run_case(&test, &values) {
+                let failure = self.shrink(&strategy, seed, values, message, &test, false);
+                if let Some(file) = &self.regressions {
+                    file.append(seed, &describe_arguments(&names, &failure.minimal))?;
+                }
+                return Ok(TestOutcome::Failed(failure));
+            }
+        }
+
+        Ok(TestOutcome::Passed {
+            cases: self.config.num_cases,
+        })
+    }
+
+    fn run_case<F>(test: &F, values: &Value) -> Option<String>
+    where
+        F: Fn(&[Value]) -> Result<(), String>,
+    {
+        let Value::Tuple(args) = values else { return Some("arguments are not a tuple".to_string()) };
+        match panic::catch_unwind(AssertUnwindSafe(|| test(args))) {
+            Ok(Ok(())) => None,
+            Ok(Err(message)) => Some(message),
+            Err(payload) => Some(
+                payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "test panicked".to_string()),
+            ),
+        }
+    }
+
+    fn shrink<F>(
+        &self,
+        strategy: &TupleStrategy,
+        seed: Seed,
+        values: Value,
+        mut message: String,
+        test: &F,
+        from_regression: bool,
+    ) -> Failure
+    where
+        F: Fn(&[Value]) -> Result<(), String>,
+    {
+        let mut current = values.clone();
+        let mut shrink_trail = Vec::new();
+        let mut shrink_iters = 0;
+
+        'shrinking: while shrink_iters < self.config.max_shrink_iters {
+            for (step, candidate) in strategy.shrink(&current) {
+                if shrink_iters >= self.config.max_shrink_iters {
+                    break 'shrinking;
+                }
+                shrink_iters += 1;
+                if let Some(candidate_message) = Self::run_case(test, &candidate) {
+                    current = candidate;
+                    message = candidate_message;
+                    shrink_trail.push(step);
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+
+        let (Value::Tuple(original), Value::Tuple(minimal)) = (values, current) else {
+            unreachable!("argument lists are tuples")
+        };
+        Failure {
+            seed,
+            original,
+            minimal,
+            message,
+            shrink_trail,
+            shrink_iters,
+            from_regression,
+        }
+    }
+
+    /// Executes an inferred property against `func`, which takes its
+    /// arguments in parameter order. Parameter constraints inferred by
+    /// `CustomGenerator` shape the generated inputs.
+    pub fn check_property(
+        &self,
+        signature: &FunctionSignature,
+        property: &Property,
+        func: &dyn Fn(&[Value]) -> Value,
+    ) -> Result<TestOutcome, String> {
+        let param = |i: usize| -> Result<(String, Box<dyn Strategy>), String> {
+            let p = signature
+                .parameters
+                .get(i)
+                .ok_or_else(|| format!("{} has no parameter {}", signature.name, i))?;
+            Ok((p.name.clone(), strategy_for(&p.param_type, &CustomGenerator::infer_constraints(p))?))
+        };
+        let renamed = |i: usize, name: &str| param(i).map(|(_, strategy)| (name.to_string(), strategy));
+
+        match &property.property_type {
+            PropertyType::Commutativity => self.check(vec![param(0)?, param(1)?], |v| {
+                expect_eq(func(&[v[0].clone(), v[1].clone()]), func(&[v[1].clone(), v[0].clone()]))
+            }),
+            PropertyType::Associativity => self.check(
+                vec![renamed(0, "a")?, renamed(0, "b")?, renamed(0, "c")?],
+                |v| {
+                    let left = func(&[func(&[v[0].clone(), v[1].clone()]), v[2].clone()]);
+                    let right = func(&[v[0].clone(), func(&[v[1].clone(), v[2].clone()])]);
+                    expect_eq(left, right)
+                },
+            ),
+            PropertyType::Identity(identity) => {
+                let param_type = &signature.parameters.first().ok_or("no parameters")?.param_type;
+                let identity = literal_value(identity, param_type)
+                    .ok_or_else(|| format!("cannot evaluate identity {}", identity))?;
+                self.check(vec![param(0)?], |v| expect_eq(func(&[v[0].clone(), identity.clone()]), v[0].clone()))
+            }
+            PropertyType::Involution => {
+                self.check(vec![param(0)?], |v| expect_eq(func(&[func(&[v[0].clone()])]), v[0].clone()))
+            }
+            PropertyType::Idempotence => self.check(vec![param(0)?], |v| {
+                let once = func(&[v[0].clone()]);
+                expect_eq(func(std::slice::from_ref(&once)), once)
+            }),
+            PropertyType::LengthPreservation => {
+                self.check(vec![param(0)?], |v| expect_eq(func(&[v[0].clone()]).length(), v[0].length()))
+            }
+            PropertyType::Monotonicity => self.check(vec![renamed(0, "a")?, renamed(0, "b")?], |v| {
+                let (lo, hi) = if v[0] <= v[1] { (&v[0], &v[1]) } else { (&v[1], &v[0]) };
+                let (f_lo, f_hi) = (func(std::slice::from_ref(lo)), func(std::slice::from_ref(hi)));
+                if f_lo <= f_hi {
+                    Ok(())
+                } else {
+                    Err(format!("f({}) = {} > f({}) = {}", lo, f_lo, hi, f_hi))
+                }
+            }),
+            PropertyType::RoundTrip | PropertyType::Invariant(_) => Err(format!(
+                "{} needs a second function and can only be checked in generated code",
+                property.name
+            )),
+        }
+    }
+}
+
+fn expect_eq<T: PartialEq + fmt::Debug>(left: T, right: T) -> Result<(), String> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(format!("assertion failed: left == right\n  left: {:?}\n right: {:?}", left, right))
+    }
+}
+
+/// `a = 0, b = 1`, as on the comment of a regression line.
+fn describe_arguments(names: &[String], values: &[Value]) -> String {
+    names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Evaluates the identity literals `PropertyInference` produces.
+fn literal_value(literal: &str, typ: &Type) -> Option<Value> {
+    if let Some(s) = literal.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Some(Value::Str(s.to_string()));
+    }
+    let n = match literal {
+        "i32::MIN" => i32::MIN as i128,
+        "i32::MAX" => i32::MAX as i128,
+        _ => literal.parse().ok()?,
+    };
+    match typ {
+        Type::Primitive(name) if name.starts_with('f') => Some(Value::Float(n as f64)),
+        _ => Some(Value::Int(n)),
+    }
+}
+
 //==============================================================================
 // Main Example
 //==============================================================================
@@ -939,6 +1925,7 @@ fn main() {
     println!("  - Custom value generators with constraints");
     println!("  - Optimized shrinking for minimal failing cases");
     println!("  - CLI integration for easy use");
+    println!("  - In-process runner with seeded generation, shrinking and regression files");
 }
 
 //==============================================================================
@@ -1306,4 +2293,254 @@ mod tests {
         let result = generate_for_project(Path::new("."), &config);
         assert!(result.is_ok());
     }
+
+    // Milestone 7 Tests
+    fn runner(seed: u8) -> PropertyRunner {
+        PropertyRunner::new(GeneratorConfig::default()).with_seed(Seed([seed; 32]))
+    }
+
+    fn inferred(source: &str) -> (FunctionSignature, Vec<Property>) {
+        let sig = FunctionSignature::parse_function(source).unwrap();
+        let properties = PropertyInference::new(sig.clone()).infer_properties();
+        (sig, properties)
+    }
+
+    fn int_args(args: &[Value]) -> Vec<i32> {
+        args.iter()
+            .map(|v| match v {
+                Value::Int(n) => *n as i32,
+                other => panic!("expected an integer, got {}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seeded_generation_is_reproducible() {
+        let typ = Type::Vec(Box::new(Type::Primitive("i32".to_string())));
+        let strategy = strategy_for(&typ, &[]).unwrap();
+        let seed = Seed([7; 32]);
+
+        let first = strategy.generate(&mut TestRng::from_seed(seed));
+        let second = strategy.generate(&mut TestRng::from_seed(seed));
+        assert_eq!(first, second);
+        assert_eq!(Seed::from_hex(&seed.to_hex()), Some(seed));
+
+        let fails_on_long = |v: &[Value]| match v[0].length() {
+            Some(len) if len >= 5 => Err(format!("len {}", len)),
+            _ => Ok(()),
+        };
+        let params = || vec![("v".to_string(), strategy_for(&typ, &[]).unwrap())];
+        let a = runner(3).check(params(), fails_on_long).unwrap();
+        let b = runner(3).check(params(), fails_on_long).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_constraint_strategies() {
+        let mut rng = TestRng::from_seed(Seed([1; 32]));
+        let param = |name: &str, typ: &str| Parameter {
+            name: name.to_string(),
+            param_type: Type::Primitive(typ.to_string()),
+            is_mutable: false,
+        };
+        let strategy = |p: &Parameter| strategy_for(&p.param_type, &CustomGenerator::infer_constraints(p)).unwrap();
+
+        let age = strategy(&param("age", "i32"));
+        let divisor = strategy(&param("divisor", "i64"));
+        let email = strategy(&param("email", "String"));
+        let matcher = regex::Regex::new("^[a-z0-9.]+@[a-z0-9]+\\.[a-z]{2,}$").unwrap();
+        for _ in 0..500 {
+            assert!(matches!(age.generate(&mut rng), Value::Int(n) if (0..=150).contains(&n)));
+            assert_ne!(divisor.generate(&mut rng), Value::Int(0));
+            let Value::Str(address) = email.generate(&mut rng) else { panic!("not a string") };
+            assert!(matcher.is_match(&address), "{}", address);
+        }
+
+        // Shrinking keeps the constraints: divisors stop at 1, not 0.
+        assert!(divisor.shrink(&Value::Int(-40)).iter().all(|(_, v)| *v != Value::Int(0)));
+        assert_eq!(divisor.shrink(&Value::Int(-40))[0].1, Value::Int(1));
+        let Value::Str(address) = email.generate(&mut rng) else { panic!("not a string") };
+        for (_, candidate) in email.shrink(&Value::Str(address)) {
+            let Value::Str(candidate) = candidate else { panic!("not a string") };
+            assert!(matcher.is_match(&candidate), "{}", candidate);
+        }
+
+        assert!(strategy_for(&Type::Custom("User".to_string()), &[]).is_err());
+        assert!(strategy_for(&Type::Primitive("u8".to_string()), &[Constraint::Range { min: 300, max: 400 }]).is_err());
+    }
+
+    #[test]
+    fn test_shrinking_extreme_ints_does_not_overflow() {
+        let full = IntStrategy { min: i128::MIN, max: i128::MAX, non_zero: true };
+        let candidates = full.shrink(&Value::Int(i128::MIN));
+        assert_eq!(candidates[0].1, Value::Int(1));
+        assert!(candidates.iter().any(|(_, v)| *v == Value::Int(i128::MIN + 1)));
+        assert!(candidates.iter().any(|(_, v)| *v == Value::Int(i128::MIN / 2)));
+
+        let negative = IntStrategy { min: i128::MIN, max: -1, non_zero: false };
+        assert_eq!(negative.shrink(&Value::Int(i128::MIN))[0].1, Value::Int(-1));
+        let positive = IntStrategy { min: 1, max: i128::MAX, non_zero: false };
+        assert!(positive.shrink(&Value::Int(i128::MAX)).iter().any(|(_, v)| *v == Value::Int(i128::MAX - 1)));
+    }
+
+    #[test]
+    fn test_float_constraints_intersect() {
+        let float = Type::Primitive("f64".to_string());
+        let mut rng = TestRng::from_seed(Seed([3; 32]));
+
+        let strategy = strategy_for(
+            &float,
+            &[Constraint::Range { min: -10, max: 10 }, Constraint::Positive, Constraint::Range { min: -5, max: 20 }],
+        )
+        .unwrap();
+        for _ in 0..200 {
+            let Value::Float(x) = strategy.generate(&mut rng) else { panic!("not a float") };
+            assert!((f64::EPSILON..=10.0).contains(&x), "{}", x);
+        }
+
+        assert!(strategy_for(&float, &[Constraint::Positive, Constraint::Range { min: -10, max: -1 }]).is_err());
+        assert!(strategy_for(&float, &[Constraint::Range { min: 0, max: 5 }, Constraint::Range { min: 6, max: 9 }]).is_err());
+    }
+
+    #[test]
+    fn test_inferred_properties_pass() {
+        let (sig, properties) = inferred("fn add(a: i32, b: i32) -> i32");
+        let add = |args: &[Value]| {
+            let v = int_args(args);
+            Value::Int(v[0].wrapping_add(v[1]) as i128)
+        };
+        assert!(properties.len() >= 3);
+        for property in &properties {
+            let outcome = runner(1).check_property(&sig, property, &add).unwrap();
+            assert_eq!(outcome, TestOutcome::Passed { cases: 100 }, "{}", property.name);
+        }
+
+        let (sig, properties) = inferred("fn reverse<T>(vec: Vec<T>) -> Vec<T>");
+        let reverse = |args: &[Value]| match &args[0] {
+            Value::Vec(items) => Value::Vec(items.iter().rev().cloned().collect()),
+            other => panic!("expected a vector, got {}", other),
+        };
+        for property in &properties {
+            assert!(runner(2).check_property(&sig, property, &reverse).unwrap().is_passed());
+        }
+    }
+
+    #[test]
+    fn test_failure_shrinks_to_minimal_case() {
+        let (sig, properties) = inferred("fn sub(a: i32, b: i32) -> i32");
+        let commutativity = properties
+            .iter()
+            .find(|p| p.property_type == PropertyType::Commutativity)
+            .unwrap();
+        let sub = |args: &[Value]| {
+            let v = int_args(args);
+            Value::Int(v[0].wrapping_sub(v[1]) as i128)
+        };
+
+        let TestOutcome::Failed(failure) = runner(4).check_property(&sig, commutativity, &sub).unwrap() else {
+            panic!("subtraction is not commutative")
+        };
+        let mut minimal = int_args(&failure.minimal);
+        minimal.sort();
+        assert_eq!(minimal, vec![0, 1]);
+        assert!(!failure.shrink_trail.is_empty());
+        assert!(failure.shrink_trail.iter().all(|s| matches!(s, ShrinkStep::TowardsZero | ShrinkStep::BinarySearch)));
+        assert!(failure.message.contains("left"));
+
+        // Reversing but losing the last element breaks involution from
+        // three elements on; every element shrinks to zero.
+        let (sig, properties) = inferred("fn reverse<T>(vec: Vec<T>) -> Vec<T>");
+        let involution = properties.iter().find(|p| p.property_type == PropertyType::Involution).unwrap();
+        let lossy_reverse = |args: &[Value]| match &args[0] {
+            Value::Vec(items) => {
+                let mut reversed: Vec<Value> = items.iter().rev().cloned().collect();
+                if reversed.len() > 2 {
+                    reversed.pop();
+                }
+                Value::Vec(reversed)
+            }
+            other => panic!("expected a vector, got {}", other),
+        };
+        let TestOutcome::Failed(failure) = runner(5).check_property(&sig, involution, &lossy_reverse).unwrap() else {
+            panic!("lossy reverse is not an involution")
+        };
+        assert_eq!(failure.minimal, vec![Value::Vec(vec![Value::Int(0); 3])]);
+        assert!(failure.shrink_trail.contains(&ShrinkStep::RemoveElements));
+        assert!(failure.shrink_trail.contains(&ShrinkStep::SimplifyStructure));
+    }
+
+    #[test]
+    fn test_panics_count_as_failures() {
+        let params = vec![("x".to_string(), strategy_for(&Type::Primitive("u8".to_string()), &[]).unwrap())];
+        let outcome = runner(6)
+            .check(params, |v| {
+                assert!(v[0] < Value::Int(10), "too big");
+                Ok(())
+            })
+            .unwrap();
+        let TestOutcome::Failed(failure) = outcome else { panic!("expected a failure") };
+        assert_eq!(failure.minimal, vec![Value::Int(10)]);
+        assert_eq!(failure.message, "too big");
+    }
+
+    #[test]
+    fn test_parse_proptest_regression_file() {
+        let contents = "\
+# Seeds for failure cases proptest has generated in the past. It is
+# automatically read and these particular cases re-run before any
+# novel cases are generated.
+cc 35e443b1059a4077be55128b62d2ba4888241423811832e6ba6991314b1851ad # shrinks to a = {\"\": -483821658}, b = {\"\": -1663661991}
+xs 1 2 3 4 # shrinks to x = 0
+cc not-a-seed
+";
+        let seeds = parse_regressions(contents);
+        assert_eq!(seeds.len(), 2);
+        assert_eq!(seeds[0].to_hex(), "35e443b1059a4077be55128b62d2ba4888241423811832e6ba6991314b1851ad");
+        assert_eq!(&seeds[1].0[..8], &[1, 0, 0, 0, 2, 0, 0, 0]);
+
+        let file = RegressionFile::for_source(Path::new("crate"), Path::new("crate/src/p2_proptest_basics.rs"));
+        assert_eq!(file.path, Path::new("crate/proptest-regressions/p2_proptest_basics.txt"));
+    }
+
+    #[test]
+    fn test_regression_file_roundtrip_and_replay() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = || RegressionFile::new(dir.path().join("proptest-regressions/sub.txt"));
+        let params = || {
+            ["a", "b"]
+                .iter()
+                .map(|name| (name.to_string(), strategy_for(&Type::Primitive("i32".to_string()), &[]).unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let commutes = |v: &[Value]| {
+            let v = int_args(v);
+            expect_eq(v[0].wrapping_sub(v[1]), v[1].wrapping_sub(v[0]))
+        };
+
+        let TestOutcome::Failed(failure) = runner(8).with_regression_file(file()).check(params(), commutes).unwrap() else {
+            panic!("subtraction is not commutative")
+        };
+        assert!(!failure.from_regression);
+        let contents = std::fs::read_to_string(&file().path).unwrap();
+        assert!(contents.starts_with("# Seeds for failure cases proptest has generated in the past."));
+        let line = format!("cc {} # shrinks to ", failure.seed.to_hex());
+        assert!(contents.contains(&line), "{}", contents);
+        assert!(contents.ends_with("a = 0, b = 1\n") || contents.ends_with("a = 1, b = 0\n"));
+        assert_eq!(file().load().unwrap(), vec![failure.seed]);
+
+        // With no novel cases at all, the recorded seed still reproduces it.
+        let config = GeneratorConfig {
+            num_cases: 0,
+            ..GeneratorConfig::default()
+        };
+        let replay = PropertyRunner::new(config).with_regression_file(file()).check(params(), commutes).unwrap();
+        let TestOutcome::Failed(replayed) = replay else { panic!("regression was not replayed") };
+        assert!(replayed.from_regression);
+        assert_eq!(replayed.original, failure.original);
+        assert_eq!(replayed.minimal, failure.minimal);
+
+        // Recording the same seed twice leaves the file unchanged.
+        file().append(failure.seed, "a = 0, b = 1").unwrap();
+        assert_eq!(std::fs::read_to_string(&file().path).unwrap(), contents);
+    }
 }
@@ -1,309 +1,1268 @@
-// use std::marker::PhantomData;
-// use std::time::{SystemTime, UNIX_EPOCH};
-// 
-// /* ============================================================
-//  * Shared domain types
-//  * ============================================================
-//  */
-// 
-// #[derive(Debug, Clone, PartialEq)]
-// struct Item {
-//     product_id: u64,
-//     name: String,
-//     price: f64,
-// }
-// 
-// /* ============================================================
-//  * Milestone 1 + 2: Enum-based runtime state machine
-//  * ============================================================
-//  */
-// 
-// #[derive(Debug, Clone, PartialEq)]
-// enum OrderState {
-//     Pending {
-//         items: Vec<Item>,
-//         customer_id: u64,
-//     },
-//     Paid {
-//         order_id: u64,
-//         payment_id: String,
-//         amount: f64,
-//     },
-//     Shipped {
-//         order_id: u64,
-//         tracking_number: String,
-//     },
-//     Delivered {
-//         order_id: u64,
-//         delivered_at: u64,
-//     },
-//     Cancelled {
-//         order_id: u64,
-//         reason: String,
-//     },
-// }
-// 
-// impl OrderState {
-//     fn new_pending(items: Vec<Item>, customer_id: u64) -> Self {
-//         OrderState::Pending { items, customer_id }
-//     }
-// 
-//     fn status_string(&self) -> &str {
-//         match self {
-//             OrderState::Pending { .. } => "Pending",
-//             OrderState::Paid { .. } => "Paid",
-//             OrderState::Shipped { .. } => "Shipped",
-//             OrderState::Delivered { .. } => "Delivered",
-//             OrderState::Cancelled { .. } => "Cancelled",
-//         }
-//     }
-// 
-//     fn pay(self, payment_id: String) -> Result<Self, String> {
-//         match self {
-//             OrderState::Pending { items, customer_id } => {
-//                 if items.is_empty() {
-//                     return Err("Cannot pay for empty order".into());
-//                 }
-// 
-//                 let amount: f64 = items.iter().map(|i| i.price).sum();
-// 
-//                 Ok(OrderState::Paid {
-//                     order_id: customer_id,
-//                     payment_id,
-//                     amount,
-//                 })
-//             }
-//             _ => Err("Can only pay for pending orders".into()),
-//         }
-//     }
-// 
-//     fn ship(self, tracking_number: String) -> Result<Self, String> {
-//         match self {
-//             OrderState::Paid { order_id, .. } => Ok(OrderState::Shipped {
-//                 order_id,
-//                 tracking_number,
-//             }),
-//             _ => Err("Can only ship paid orders".into()),
-//         }
-//     }
-// 
-//     fn deliver(self) -> Result<Self, String> {
-//         match self {
-//             OrderState::Shipped { order_id, .. } => Ok(OrderState::Delivered {
-//                 order_id,
-//                 delivered_at: SystemTime::now()
-//                     .duration_since(UNIX_EPOCH)
-//                     .unwrap()
-//                     .as_secs(),
-//             }),
-//             _ => Err("Can only deliver shipped orders".into()),
-//         }
-//     }
-// 
-//     fn cancel(self, reason: String) -> Result<Self, String> {
-//         match self {
-//             OrderState::Pending { customer_id, .. }
-//             | OrderState::Paid { order_id: customer_id, .. } => Ok(OrderState::Cancelled {
-//                 order_id: customer_id,
-//                 reason,
-//             }),
-//             _ => Err("Cannot cancel after shipping".into()),
-//         }
-//     }
-// 
-//     fn can_cancel(&self) -> bool {
-//         matches!(
-//             self,
-//             OrderState::Pending { .. } | OrderState::Paid { .. }
-//         )
-//     }
-// }
-// 
-// /* ============================================================
-//  * Milestone 3: Typestate pattern (compile-time safety)
-//  * ============================================================
-//  */
-// 
-// struct Pending;
-// struct Paid;
-// struct Shipped;
-// struct Delivered;
-// struct Cancelled;
-// 
-// struct Order<State> {
-//     id: u64,
-//     customer_id: u64,
-//     items: Vec<Item>,
-//     _state: PhantomData<State>,
-// }
-// 
-// impl Order<Pending> {
-//     fn new(customer_id: u64, items: Vec<Item>) -> Result<Self, String> {
-//         if items.is_empty() {
-//             return Err("Order must contain at least one item".into());
-//         }
-// 
-//         Ok(Self {
-//             id: customer_id,
-//             customer_id,
-//             items,
-//             _state: PhantomData,
-//         })
-//     }
-// 
-//     fn pay(self, _payment_id: String) -> Result<Order<Paid>, String> {
-//         Ok(Order {
-//             id: self.id,
-//             customer_id: self.customer_id,
-//             items: self.items,
-//             _state: PhantomData,
-//         })
-//     }
-// 
-//     fn cancel(self, _reason: String) -> Order<Cancelled> {
-//         Order {
-//             id: self.id,
-//             customer_id: self.customer_id,
-//             items: self.items,
-//             _state: PhantomData,
-//         }
-//     }
-// }
-// 
-// impl Order<Paid> {
-//     fn ship(self, _tracking_number: String) -> Order<Shipped> {
-//         Order {
-//             id: self.id,
-//             customer_id: self.customer_id,
-//             items: self.items,
-//             _state: PhantomData,
-//         }
-//     }
-// 
-//     fn cancel(self, _reason: String) -> Order<Cancelled> {
-//         Order {
-//             id: self.id,
-//             customer_id: self.customer_id,
-//             items: self.items,
-//             _state: PhantomData,
-//         }
-//     }
-// }
-// 
-// impl Order<Shipped> {
-//     fn deliver(self) -> Order<Delivered> {
-//         Order {
-//             id: self.id,
-//             customer_id: self.customer_id,
-//             items: self.items,
-//             _state: PhantomData,
-//         }
-//     }
-// }
-// 
-// impl<State> Order<State> {
-//     fn id(&self) -> u64 {
-//         self.id
-//     }
-// 
-//     fn customer_id(&self) -> u64 {
-//         self.customer_id
-//     }
-// 
-//     fn items(&self) -> &[Item] {
-//         &self.items
-//     }
-// }
-// 
-// /* ============================================================
-//  * Demo (cargo run)
-//  * ============================================================
-//  */
-// 
-// fn main() {
-//     let items = vec![Item {
-//         product_id: 1,
-//         name: "Widget".into(),
-//         price: 9.99,
-//     }];
-// 
-//     println!("== Runtime enum state machine ==");
-//     let order = OrderState::new_pending(items.clone(), 42);
-//     let order = order.pay("PAY123".into()).unwrap();
-//     let order = order.ship("TRACK123".into()).unwrap();
-//     let order = order.deliver().unwrap();
-//     println!("Final state: {}", order.status_string());
-// 
-//     println!("\n== Typestate machine ==");
-//     let order = Order::<Pending>::new(42, items).unwrap();
-//     let order = order.pay("PAY123".into()).unwrap();
-//     let order = order.ship("TRACK123".into());
-//     let order = order.deliver();
-//     println!("Delivered order for customer {}", order.customer_id());
-// }
-// 
-// /* ============================================================
-//  * Tests (cargo test)
-//  * ============================================================
-//  */
-// 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-// 
-//     fn sample_items() -> Vec<Item> {
-//         vec![Item {
-//             product_id: 1,
-//             name: "Widget".into(),
-//             price: 9.99,
-//         }]
-//     }
-// 
-//     #[test]
-//     fn test_enum_valid_transitions() {
-//         let order = OrderState::new_pending(sample_items(), 1);
-//         let order = order.pay("pay".into()).unwrap();
-//         let order = order.ship("track".into()).unwrap();
-//         let order = order.deliver().unwrap();
-// 
-//         assert_eq!(order.status_string(), "Delivered");
-//     }
-// 
-//     #[test]
-//     fn test_enum_invalid_transitions() {
-//         let order = OrderState::new_pending(sample_items(), 1);
-//         assert!(order.clone().ship("track".into()).is_err());
-// 
-//         let order = order.pay("pay".into()).unwrap();
-//         assert!(order.clone().pay("pay2".into()).is_err());
-//     }
-// 
-//     #[test]
-//     fn test_enum_cancellation_rules() {
-//         let order = OrderState::new_pending(sample_items(), 1);
-//         assert!(order.can_cancel());
-// 
-//         let order = order.pay("pay".into()).unwrap();
-//         assert!(order.can_cancel());
-// 
-//         let order = order.ship("track".into()).unwrap();
-//         assert!(!order.can_cancel());
-//     }
-// 
-//     #[test]
-//     fn test_typestate_valid_flow() {
-//         let order = Order::<Pending>::new(1, sample_items()).unwrap();
-//         let order = order.pay("pay".into()).unwrap();
-//         let order = order.ship("track".into());
-//         let order = order.deliver();
-// 
-//         assert_eq!(order.customer_id(), 1);
-//     }
-// 
-//     #[test]
-//     fn test_typestate_common_methods() {
-//         let order = Order::<Pending>::new(1, sample_items()).unwrap();
-//         assert_eq!(order.items().len(), 1);
-//         assert_eq!(order.customer_id(), 1);
-//     }
-// }
\ No newline at end of file
+//! complete_07_order_state_machine.rs
+//!
+//! Order processing modelled with enums and the type system.
+//!
+//! Milestones:
+//! 1) + 2) Enum-based runtime state machine (`OrderState`)
+//! 3) Typestate pattern with `PhantomData` (`Order<State>`)
+//! 4) Persisted workflow engine: declarative transition table with guards
+//!    and hooks, timestamped event log, state rebuilt by replay, partial
+//!    shipments and refunds, idempotent commands keyed by request id, and
+//!    storage behind `EventStore` (in-memory and SQLite)
+//!
+//! Run:
+//!   cargo run --bin complete_07_order_state_machine
+//! Test:
+//!   cargo test --bin complete_07_order_state_machine
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* ============================================================
+ * Shared domain types
+ * ============================================================
+ */
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub product_id: u64,
+    pub name: String,
+    pub price: f64,
+}
+
+/* ============================================================
+ * Milestone 1 + 2: Enum-based runtime state machine
+ * ============================================================
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+enum OrderState {
+    Pending {
+        items: Vec<Item>,
+        customer_id: u64,
+    },
+    Paid {
+        order_id: u64,
+        payment_id: String,
+        amount: f64,
+    },
+    Shipped {
+        order_id: u64,
+        tracking_number: String,
+    },
+    Delivered {
+        order_id: u64,
+        delivered_at: u64,
+    },
+    Cancelled {
+        order_id: u64,
+        reason: String,
+    },
+}
+
+impl OrderState {
+    fn new_pending(items: Vec<Item>, customer_id: u64) -> Self {
+        OrderState::Pending { items, customer_id }
+    }
+
+    fn status_string(&self) -> &str {
+        match self {
+            OrderState::Pending { .. } => "Pending",
+            OrderState::Paid { .. } => "Paid",
+            OrderState::Shipped { .. } => "Shipped",
+            OrderState::Delivered { .. } => "Delivered",
+            OrderState::Cancelled { .. } => "Cancelled",
+        }
+    }
+
+    fn pay(self, payment_id: String) -> Result<Self, String> {
+        match self {
+            OrderState::Pending { items, customer_id } => {
+                if items.is_empty() {
+                    return Err("Cannot pay for empty order".into());
+                }
+
+                let amount: f64 = items.iter().map(|i| i.price).sum();
+
+                Ok(OrderState::Paid {
+                    order_id: customer_id,
+                    payment_id,
+                    amount,
+                })
+            }
+            _ => Err("Can only pay for pending orders".into()),
+        }
+    }
+
+    fn ship(self, tracking_number: String) -> Result<Self, String> {
+        match self {
+            OrderState::Paid { order_id, .. } => Ok(OrderState::Shipped {
+                order_id,
+                tracking_number,
+            }),
+            _ => Err("Can only ship paid orders".into()),
+        }
+    }
+
+    fn deliver(self) -> Result<Self, String> {
+        match self {
+            OrderState::Shipped { order_id, .. } => Ok(OrderState::Delivered {
+                order_id,
+                delivered_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            }),
+            _ => Err("Can only deliver shipped orders".into()),
+        }
+    }
+
+    fn cancel(self, reason: String) -> Result<Self, String> {
+        match self {
+            OrderState::Pending { customer_id, .. }
+            | OrderState::Paid { order_id: customer_id, .. } => Ok(OrderState::Cancelled {
+                order_id: customer_id,
+                reason,
+            }),
+            _ => Err("Cannot cancel after shipping".into()),
+        }
+    }
+
+    fn can_cancel(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Pending { .. } | OrderState::Paid { .. }
+        )
+    }
+}
+
+/* ============================================================
+ * Milestone 3: Typestate pattern (compile-time safety)
+ * ============================================================
+ */
+
+struct Pending;
+struct Paid;
+struct Shipped;
+struct Delivered;
+struct Cancelled;
+
+struct Order<State> {
+    id: u64,
+    customer_id: u64,
+    items: Vec<Item>,
+    _state: PhantomData<State>,
+}
+
+impl Order<Pending> {
+    fn new(customer_id: u64, items: Vec<Item>) -> Result<Self, String> {
+        if items.is_empty() {
+            return Err("Order must contain at least one item".into());
+        }
+
+        Ok(Self {
+            id: customer_id,
+            customer_id,
+            items,
+            _state: PhantomData,
+        })
+    }
+
+    fn pay(self, _payment_id: String) -> Result<Order<Paid>, String> {
+        Ok(Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            items: self.items,
+            _state: PhantomData,
+        })
+    }
+
+    fn cancel(self, _reason: String) -> Order<Cancelled> {
+        Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            items: self.items,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl Order<Paid> {
+    fn ship(self, _tracking_number: String) -> Order<Shipped> {
+        Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            items: self.items,
+            _state: PhantomData,
+        }
+    }
+
+    fn cancel(self, _reason: String) -> Order<Cancelled> {
+        Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            items: self.items,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl Order<Shipped> {
+    fn deliver(self) -> Order<Delivered> {
+        Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            items: self.items,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<State> Order<State> {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn customer_id(&self) -> u64 {
+        self.customer_id
+    }
+
+    fn items(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+/* ============================================================
+ * Milestone 4: Persisted, auditable workflow engine
+ * ============================================================
+ */
+
+/// Flat order status used by the workflow engine.
+/// `New` is the pseudo-state of an order id that has no events yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderStatus {
+    New,
+    Pending,
+    Paid,
+    PartiallyShipped,
+    Shipped,
+    Delivered,
+    Cancelled,
+    PartiallyRefunded,
+    Refunded,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Place { customer_id: u64, items: Vec<Item> },
+    Pay { payment_id: String, amount: f64 },
+    /// `product_ids: None` ships everything still outstanding.
+    Ship {
+        tracking_number: String,
+        product_ids: Option<Vec<u64>>,
+    },
+    Deliver,
+    Cancel { reason: String },
+    Refund { amount: f64, reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandKind {
+    Place,
+    Pay,
+    Ship,
+    Deliver,
+    Cancel,
+    Refund,
+}
+
+impl Command {
+    pub fn kind(&self) -> CommandKind {
+        match self {
+            Command::Place { .. } => CommandKind::Place,
+            Command::Pay { .. } => CommandKind::Pay,
+            Command::Ship { .. } => CommandKind::Ship,
+            Command::Deliver => CommandKind::Deliver,
+            Command::Cancel { .. } => CommandKind::Cancel,
+            Command::Refund { .. } => CommandKind::Refund,
+        }
+    }
+}
+
+/// Facts recorded in the event log. Replaying them rebuilds an order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderEvent {
+    Placed { customer_id: u64, items: Vec<Item> },
+    Paid { payment_id: String, amount: f64 },
+    Shipped { tracking_number: String, product_ids: Vec<u64> },
+    Delivered,
+    Cancelled { reason: String },
+    Refunded { amount: f64, reason: String },
+}
+
+/// One entry of the audit log: which request moved which order from where to where, and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub order_id: u64,
+    pub seq: u64, // 1-based position in the order's log
+    pub request_id: String,
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+    pub event: OrderEvent,
+    pub timestamp: u64,
+}
+
+/// Tolerance for comparing money amounts stored as `f64`.
+const AMOUNT_EPSILON: f64 = 0.005;
+
+/// Current view of an order, derived purely from its events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderAggregate {
+    pub order_id: u64,
+    pub customer_id: u64,
+    pub status: OrderStatus,
+    pub items: Vec<Item>,
+    pub shipped: Vec<u64>,
+    pub tracking_numbers: Vec<String>,
+    pub payment_id: Option<String>,
+    pub paid: f64,
+    pub refunded: f64,
+    pub delivered: bool,
+    pub version: u64,
+    pub updated_at: u64,
+}
+
+impl OrderAggregate {
+    pub fn new(order_id: u64) -> Self {
+        Self {
+            order_id,
+            customer_id: 0,
+            status: OrderStatus::New,
+            items: Vec::new(),
+            shipped: Vec::new(),
+            tracking_numbers: Vec::new(),
+            payment_id: None,
+            paid: 0.0,
+            refunded: 0.0,
+            delivered: false,
+            version: 0,
+            updated_at: 0,
+        }
+    }
+
+    /// Rebuilds an order by applying its log in order.
+    pub fn replay(order_id: u64, records: &[EventRecord]) -> Self {
+        records.iter().fold(Self::new(order_id), |mut order, record| {
+            order.apply(&record.event, record.timestamp);
+            order
+        })
+    }
+
+    pub fn total(&self) -> f64 {
+        self.items.iter().map(|i| i.price).sum()
+    }
+
+    /// Product ids ordered but not shipped yet (one entry per unit).
+    pub fn outstanding(&self) -> Vec<u64> {
+        let mut remaining: Vec<u64> = self.items.iter().map(|i| i.product_id).collect();
+        for id in &self.shipped {
+            if let Some(pos) = remaining.iter().position(|r| r == id) {
+                remaining.remove(pos);
+            }
+        }
+        remaining
+    }
+
+    pub fn apply(&mut self, event: &OrderEvent, timestamp: u64) {
+        self.status = match event {
+            OrderEvent::Placed { customer_id, items } => {
+                self.customer_id = *customer_id;
+                self.items = items.clone();
+                OrderStatus::Pending
+            }
+            OrderEvent::Paid { payment_id, amount } => {
+                self.payment_id = Some(payment_id.clone());
+                self.paid = *amount;
+                OrderStatus::Paid
+            }
+            OrderEvent::Shipped {
+                tracking_number,
+                product_ids,
+            } => {
+                self.shipped.extend(product_ids);
+                self.tracking_numbers.push(tracking_number.clone());
+                if self.outstanding().is_empty() {
+                    OrderStatus::Shipped
+                } else {
+                    OrderStatus::PartiallyShipped
+                }
+            }
+            OrderEvent::Delivered => {
+                self.delivered = true;
+                OrderStatus::Delivered
+            }
+            OrderEvent::Cancelled { .. } => OrderStatus::Cancelled,
+            OrderEvent::Refunded { amount, .. } => {
+                self.refunded += amount;
+                if self.refunded + AMOUNT_EPSILON >= self.paid {
+                    OrderStatus::Refunded
+                } else if self.status == OrderStatus::Cancelled {
+                    // a cancelled order must not become shippable again
+                    OrderStatus::Cancelled
+                } else {
+                    OrderStatus::PartiallyRefunded
+                }
+            }
+        };
+        self.version += 1;
+        self.updated_at = timestamp;
+    }
+
+    /// Turns an (already allowed) command into the event to record.
+    fn decide(&self, command: &Command) -> OrderEvent {
+        match command.clone() {
+            Command::Place { customer_id, items } => OrderEvent::Placed { customer_id, items },
+            Command::Pay { payment_id, amount } => OrderEvent::Paid { payment_id, amount },
+            Command::Ship {
+                tracking_number,
+                product_ids,
+            } => OrderEvent::Shipped {
+                tracking_number,
+                product_ids: product_ids.unwrap_or_else(|| self.outstanding()),
+            },
+            Command::Deliver => OrderEvent::Delivered,
+            Command::Cancel { reason } => OrderEvent::Cancelled { reason },
+            Command::Refund { amount, reason } => OrderEvent::Refunded { amount, reason },
+        }
+    }
+}
+
+/* ---------- Declarative transition table ---------- */
+
+pub type Guard = Box<dyn Fn(&OrderAggregate, &Command) -> Result<(), String>>;
+pub type Hook = Box<dyn Fn(&EventRecord, &OrderAggregate)>;
+
+/// One row of the table: `command` is allowed in any `from` state and must land in a `to` state.
+pub struct Transition {
+    pub command: CommandKind,
+    pub from: Vec<OrderStatus>,
+    pub to: Vec<OrderStatus>,
+    guards: Vec<Guard>,
+    hooks: Vec<Hook>,
+}
+
+#[derive(Default)]
+pub struct TransitionTable {
+    transitions: Vec<Transition>,
+}
+
+impl TransitionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow(mut self, command: CommandKind, from: &[OrderStatus], to: &[OrderStatus]) -> Self {
+        self.transitions.push(Transition {
+            command,
+            from: from.to_vec(),
+            to: to.to_vec(),
+            guards: Vec::new(),
+            hooks: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a guard to every row for `command`; all guards must pass.
+    pub fn guard(
+        mut self,
+        command: CommandKind,
+        guard: impl Fn(&OrderAggregate, &Command) -> Result<(), String> + Clone + 'static,
+    ) -> Self {
+        for t in self.transitions.iter_mut().filter(|t| t.command == command) {
+            t.guards.push(Box::new(guard.clone()));
+        }
+        self
+    }
+
+    /// Adds a side-effect hook, run after the event for `command` has been persisted.
+    pub fn hook(mut self, command: CommandKind, hook: impl Fn(&EventRecord, &OrderAggregate) + Clone + 'static) -> Self {
+        for t in self.transitions.iter_mut().filter(|t| t.command == command) {
+            t.hooks.push(Box::new(hook.clone()));
+        }
+        self
+    }
+
+    pub fn find(&self, command: CommandKind, from: OrderStatus) -> Option<&Transition> {
+        self.transitions
+            .iter()
+            .find(|t| t.command == command && t.from.contains(&from))
+    }
+
+    /// The order lifecycle, including partial shipments and refunds.
+    pub fn standard() -> Self {
+        use CommandKind as C;
+        use OrderStatus::*;
+
+        Self::new()
+            .allow(C::Place, &[New], &[Pending])
+            .allow(C::Pay, &[Pending], &[Paid])
+            // A partial refund can come before fulfilment, so the order must
+            // still be able to ship and deliver from `PartiallyRefunded`.
+            .allow(C::Ship, &[Paid, PartiallyShipped, PartiallyRefunded], &[PartiallyShipped, Shipped])
+            .allow(C::Deliver, &[Shipped, PartiallyRefunded], &[Delivered])
+            .allow(C::Cancel, &[Pending, Paid], &[Cancelled])
+            // Paid orders can be cancelled, so the money must still be
+            // refundable afterwards; the guard below caps it at what was paid.
+            .allow(
+                C::Refund,
+                &[Paid, PartiallyShipped, Shipped, Delivered, PartiallyRefunded, Cancelled],
+                &[PartiallyRefunded, Refunded, Cancelled],
+            )
+            .guard(C::Place, |_, cmd| match cmd {
+                Command::Place { items, .. } if items.is_empty() => {
+                    Err("Order must contain at least one item".into())
+                }
+                _ => Ok(()),
+            })
+            .guard(C::Pay, |order, cmd| match cmd {
+                Command::Pay { amount, .. } if (amount - order.total()).abs() > AMOUNT_EPSILON => {
+                    Err(format!("Payment {amount:.2} does not match total {:.2}", order.total()))
+                }
+                _ => Ok(()),
+            })
+            .guard(C::Ship, |order, cmd| {
+                if order.outstanding().is_empty() {
+                    return Err("Nothing left to ship".into());
+                }
+                let Command::Ship { product_ids: Some(ids), .. } = cmd else {
+                    return Ok(());
+                };
+                if ids.is_empty() {
+                    return Err("Shipment must contain at least one item".into());
+                }
+                let mut outstanding = order.outstanding();
+                for id in ids {
+                    match outstanding.iter().position(|o| o == id) {
+                        Some(pos) => {
+                            outstanding.remove(pos);
+                        }
+                        None => return Err(format!("Product {id} is not awaiting shipment")),
+                    }
+                }
+                Ok(())
+            })
+            .guard(C::Deliver, |order, _| {
+                if order.delivered {
+                    Err("Order was already delivered".into())
+                } else if !order.outstanding().is_empty() {
+                    Err("Order has items that have not shipped".into())
+                } else {
+                    Ok(())
+                }
+            })
+            .guard(C::Refund, |order, cmd| match cmd {
+                Command::Refund { amount, .. } if *amount <= 0.0 => Err("Refund must be positive".into()),
+                Command::Refund { amount, .. } if order.refunded + amount > order.paid + AMOUNT_EPSILON => {
+                    Err(format!(
+                        "Refund {amount:.2} exceeds remaining {:.2}",
+                        order.paid - order.refunded
+                    ))
+                }
+                _ => Ok(()),
+            })
+    }
+}
+
+/* ---------- Storage ---------- */
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum StoreError {
+    #[error("order {order_id} already has an event at seq {seq}")]
+    Conflict { order_id: u64, seq: u64 },
+
+    #[error("request '{0}' was already recorded")]
+    DuplicateRequest(String),
+
+    #[error("storage backend: {0}")]
+    Backend(String),
+}
+
+/// Append-only event log. Implementations must reject a second event with the
+/// same `(order_id, seq)` or the same `request_id`.
+pub trait EventStore {
+    fn append(&mut self, record: &EventRecord) -> Result<(), StoreError>;
+    fn load(&self, order_id: u64) -> Result<Vec<EventRecord>, StoreError>;
+    fn find_request(&self, request_id: &str) -> Result<Option<EventRecord>, StoreError>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    records: Vec<EventRecord>,
+    by_request: HashMap<String, usize>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventStore for InMemoryStore {
+    fn append(&mut self, record: &EventRecord) -> Result<(), StoreError> {
+        if self.by_request.contains_key(&record.request_id) {
+            return Err(StoreError::DuplicateRequest(record.request_id.clone()));
+        }
+        let current = self.records.iter().filter(|r| r.order_id == record.order_id).count() as u64;
+        if record.seq != current + 1 {
+            return Err(StoreError::Conflict {
+                order_id: record.order_id,
+                seq: record.seq,
+            });
+        }
+        self.by_request.insert(record.request_id.clone(), self.records.len());
+        self.records.push(record.clone());
+        Ok(())
+    }
+
+    fn load(&self, order_id: u64) -> Result<Vec<EventRecord>, StoreError> {
+        Ok(self.records.iter().filter(|r| r.order_id == order_id).cloned().collect())
+    }
+
+    fn find_request(&self, request_id: &str) -> Result<Option<EventRecord>, StoreError> {
+        Ok(self.by_request.get(request_id).map(|&i| self.records[i].clone()))
+    }
+}
+
+/// SQLite-backed log; statuses and events are stored as JSON text.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open(path).map_err(backend)?)
+    }
+
+    pub fn in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory().map_err(backend)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, StoreError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS order_events (
+                order_id INTEGER NOT NULL,
+                seq INTEGER NOT NULL,
+                request_id TEXT NOT NULL UNIQUE,
+                from_status TEXT NOT NULL,
+                to_status TEXT NOT NULL,
+                event TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                PRIMARY KEY (order_id, seq)
+            )",
+            [],
+        )
+        .map_err(backend)?;
+        Ok(Self { conn })
+    }
+
+    fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<(i64, i64, String, String, String, String, i64)> {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            row.get(5)?,
+            row.get(6)?,
+        ))
+    }
+
+    fn decode(
+        (order_id, seq, request_id, from, to, event, timestamp): (i64, i64, String, String, String, String, i64),
+    ) -> Result<EventRecord, StoreError> {
+        let json = |e: serde_json::Error| StoreError::Backend(e.to_string());
+        Ok(EventRecord {
+            order_id: order_id as u64,
+            seq: seq as u64,
+            request_id,
+            from: serde_json::from_str(&from).map_err(json)?,
+            to: serde_json::from_str(&to).map_err(json)?,
+            event: serde_json::from_str(&event).map_err(json)?,
+            timestamp: timestamp as u64,
+        })
+    }
+}
+
+fn backend(e: rusqlite::Error) -> StoreError {
+    StoreError::Backend(e.to_string())
+}
+
+const SELECT_EVENTS: &str =
+    "SELECT order_id, seq, request_id, from_status, to_status, event, timestamp FROM order_events";
+
+impl EventStore for SqliteStore {
+    fn append(&mut self, record: &EventRecord) -> Result<(), StoreError> {
+        // checked up front so both stores report duplicates before seq conflicts
+        if self.find_request(&record.request_id)?.is_some() {
+            return Err(StoreError::DuplicateRequest(record.request_id.clone()));
+        }
+        let json = |e: serde_json::Error| StoreError::Backend(e.to_string());
+        let from = serde_json::to_string(&record.from).map_err(json)?;
+        let to = serde_json::to_string(&record.to).map_err(json)?;
+        let event = serde_json::to_string(&record.event).map_err(json)?;
+
+        let result = self.conn.execute(
+            "INSERT INTO order_events (order_id, seq, request_id, from_status, to_status, event, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.order_id as i64,
+                record.seq as i64,
+                record.request_id,
+                from,
+                to,
+                event,
+                record.timestamp as i64
+            ],
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(e, msg)) if e.code == rusqlite::ErrorCode::ConstraintViolation => {
+                if msg.as_deref().is_some_and(|m| m.contains("request_id")) {
+                    Err(StoreError::DuplicateRequest(record.request_id.clone()))
+                } else {
+                    Err(StoreError::Conflict {
+                        order_id: record.order_id,
+                        seq: record.seq,
+                    })
+                }
+            }
+            Err(e) => Err(backend(e)),
+        }
+    }
+
+    fn load(&self, order_id: u64) -> Result<Vec<EventRecord>, StoreError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{SELECT_EVENTS} WHERE order_id = ?1 ORDER BY seq"))
+            .map_err(backend)?;
+        let rows = stmt
+            .query_map(params![order_id as i64], Self::row_to_record)
+            .map_err(backend)?;
+        rows.map(|row| Self::decode(row.map_err(backend)?)).collect()
+    }
+
+    fn find_request(&self, request_id: &str) -> Result<Option<EventRecord>, StoreError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{SELECT_EVENTS} WHERE request_id = ?1"))
+            .map_err(backend)?;
+        let mut rows = stmt
+            .query_map(params![request_id], Self::row_to_record)
+            .map_err(backend)?;
+        rows.next()
+            .map(|row| Self::decode(row.map_err(backend)?))
+            .transpose()
+    }
+}
+
+/* ---------- Engine ---------- */
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum WorkflowError {
+    #[error("cannot {command:?} an order in state {from:?}")]
+    InvalidTransition { from: OrderStatus, command: CommandKind },
+
+    #[error("rejected: {0}")]
+    GuardRejected(String),
+
+    #[error("request '{request_id}' was already used for order {order_id}")]
+    RequestIdReused { request_id: String, order_id: u64 },
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Applied(EventRecord),
+    /// The request id was seen before; nothing changed and the original record is returned.
+    Duplicate(EventRecord),
+}
+
+impl Outcome {
+    pub fn record(&self) -> &EventRecord {
+        match self {
+            Outcome::Applied(r) | Outcome::Duplicate(r) => r,
+        }
+    }
+}
+
+pub struct WorkflowEngine<S: EventStore> {
+    store: S,
+    table: TransitionTable,
+    clock: Box<dyn Fn() -> u64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+impl<S: EventStore> WorkflowEngine<S> {
+    pub fn new(store: S, table: TransitionTable) -> Self {
+        Self {
+            store,
+            table,
+            clock: Box::new(unix_now),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: impl Fn() -> u64 + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Handles a command exactly once per `request_id`.
+    pub fn handle(&mut self, request_id: &str, order_id: u64, command: Command) -> Result<Outcome, WorkflowError> {
+        if let Some(previous) = self.store.find_request(request_id)? {
+            if previous.order_id != order_id {
+                return Err(WorkflowError::RequestIdReused {
+                    request_id: request_id.to_string(),
+                    order_id: previous.order_id,
+                });
+            }
+            return Ok(Outcome::Duplicate(previous));
+        }
+
+        let mut order = self.order(order_id)?;
+        let from = order.status;
+        let transition = self
+            .table
+            .find(command.kind(), from)
+            .ok_or(WorkflowError::InvalidTransition {
+                from,
+                command: command.kind(),
+            })?;
+        for guard in &transition.guards {
+            guard(&order, &command).map_err(WorkflowError::GuardRejected)?;
+        }
+
+        let event = order.decide(&command);
+        let timestamp = (self.clock)();
+        order.apply(&event, timestamp);
+        if !transition.to.contains(&order.status) {
+            return Err(WorkflowError::InvalidTransition {
+                from,
+                command: command.kind(),
+            });
+        }
+
+        let record = EventRecord {
+            order_id,
+            seq: order.version,
+            request_id: request_id.to_string(),
+            from,
+            to: order.status,
+            event,
+            timestamp,
+        };
+        self.store.append(&record)?;
+
+        for hook in &transition.hooks {
+            hook(&record, &order);
+        }
+        Ok(Outcome::Applied(record))
+    }
+
+    /// Current state, rebuilt by replaying the log.
+    pub fn order(&self, order_id: u64) -> Result<OrderAggregate, WorkflowError> {
+        Ok(OrderAggregate::replay(order_id, &self.store.load(order_id)?))
+    }
+
+    pub fn history(&self, order_id: u64) -> Result<Vec<EventRecord>, WorkflowError> {
+        Ok(self.store.load(order_id)?)
+    }
+}
+
+/* ============================================================
+ * Demo (cargo run)
+ * ============================================================
+ */
+
+fn main() {
+    let items = vec![Item {
+        product_id: 1,
+        name: "Widget".into(),
+        price: 9.99,
+    }];
+
+    println!("== Runtime enum state machine ==");
+    let order = OrderState::new_pending(items.clone(), 42);
+    let order = order.pay("PAY123".into()).unwrap();
+    println!("Can cancel after paying: {}", order.can_cancel());
+    let order = order.ship("TRACK123".into()).unwrap();
+    let order = order.deliver().unwrap();
+    println!("Final state: {}", order.status_string());
+
+    println!("\n== Typestate machine ==");
+    let order = Order::<Pending>::new(42, items.clone()).unwrap();
+    let order = order.pay("PAY123".into()).unwrap();
+    let order = order.ship("TRACK123".into());
+    let order = order.deliver();
+    println!("Delivered order {} for customer {}", order.id(), order.customer_id());
+
+    let cancelled = OrderState::new_pending(items.clone(), 7).cancel("out of stock".into()).unwrap();
+    println!("Enum cancel: {}", cancelled.status_string());
+    let cancelled = Order::<Pending>::new(7, items.clone())
+        .unwrap()
+        .pay("PAY7".into())
+        .unwrap()
+        .cancel("fraud check".into());
+    println!("Typestate cancel: order {} ({} items)", cancelled.id(), cancelled.items().len());
+    let _: Order<Cancelled> = Order::<Pending>::new(8, items).unwrap().cancel("test".into());
+
+    println!("\n== Workflow engine (event log + replay) ==");
+    let table = TransitionTable::standard().hook(CommandKind::Ship, |record, order| {
+        println!(
+            "  hook: shipped {:?} for order {} ({} outstanding)",
+            record.event,
+            record.order_id,
+            order.outstanding().len()
+        );
+    });
+    let mut engine = WorkflowEngine::new(InMemoryStore::new(), table);
+    let two_items = vec![
+        Item { product_id: 1, name: "Widget".into(), price: 9.99 },
+        Item { product_id: 2, name: "Gadget".into(), price: 20.01 },
+    ];
+    let commands = [
+        ("req-1", Command::Place { customer_id: 42, items: two_items }),
+        ("req-2", Command::Pay { payment_id: "PAY123".into(), amount: 30.0 }),
+        ("req-3", Command::Ship { tracking_number: "T1".into(), product_ids: Some(vec![1]) }),
+        ("req-3", Command::Ship { tracking_number: "T1".into(), product_ids: Some(vec![1]) }),
+        ("req-4", Command::Ship { tracking_number: "T2".into(), product_ids: None }),
+        ("req-5", Command::Refund { amount: 5.0, reason: "damaged box".into() }),
+        ("req-6", Command::Cancel { reason: "too late".into() }),
+    ];
+    for (request_id, command) in commands {
+        match engine.handle(request_id, 7, command) {
+            Ok(Outcome::Applied(r)) => println!("{request_id}: {:?} -> {:?}", r.from, r.to),
+            Ok(Outcome::Duplicate(r)) => println!("{request_id}: duplicate of seq {}", r.seq),
+            Err(e) => println!("{request_id}: {e}"),
+        }
+    }
+    let order = engine.order(7).unwrap();
+    println!("Replayed: {:?}, paid {:.2}, refunded {:.2}", order.status, order.paid, order.refunded);
+}
+
+/* ============================================================
+ * Tests (cargo test)
+ * ============================================================
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_items() -> Vec<Item> {
+        vec![Item {
+            product_id: 1,
+            name: "Widget".into(),
+            price: 9.99,
+        }]
+    }
+
+    #[test]
+    fn test_enum_valid_transitions() {
+        let order = OrderState::new_pending(sample_items(), 1);
+        let order = order.pay("pay".into()).unwrap();
+        let order = order.ship("track".into()).unwrap();
+        let order = order.deliver().unwrap();
+
+        assert_eq!(order.status_string(), "Delivered");
+    }
+
+    #[test]
+    fn test_enum_invalid_transitions() {
+        let order = OrderState::new_pending(sample_items(), 1);
+        assert!(order.clone().ship("track".into()).is_err());
+
+        let order = order.pay("pay".into()).unwrap();
+        assert!(order.clone().pay("pay2".into()).is_err());
+    }
+
+    #[test]
+    fn test_enum_cancellation_rules() {
+        let order = OrderState::new_pending(sample_items(), 1);
+        assert!(order.can_cancel());
+
+        let order = order.pay("pay".into()).unwrap();
+        assert!(order.can_cancel());
+
+        let order = order.ship("track".into()).unwrap();
+        assert!(!order.can_cancel());
+    }
+
+    #[test]
+    fn test_typestate_valid_flow() {
+        let order = Order::<Pending>::new(1, sample_items()).unwrap();
+        let order = order.pay("pay".into()).unwrap();
+        let order = order.ship("track".into());
+        let order = order.deliver();
+
+        assert_eq!(order.customer_id(), 1);
+    }
+
+    #[test]
+    fn test_typestate_common_methods() {
+        let order = Order::<Pending>::new(1, sample_items()).unwrap();
+        assert_eq!(order.items().len(), 1);
+        assert_eq!(order.customer_id(), 1);
+    }
+
+    fn two_items() -> Vec<Item> {
+        vec![
+            Item { product_id: 1, name: "Widget".into(), price: 10.0 },
+            Item { product_id: 2, name: "Gadget".into(), price: 5.0 },
+        ]
+    }
+
+    fn engine<S: EventStore>(store: S) -> WorkflowEngine<S> {
+        let tick = std::cell::Cell::new(100);
+        WorkflowEngine::new(store, TransitionTable::standard()).with_clock(move || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        })
+    }
+
+    fn place_and_pay<S: EventStore>(engine: &mut WorkflowEngine<S>, order_id: u64) {
+        engine
+            .handle("place", order_id, Command::Place { customer_id: 9, items: two_items() })
+            .unwrap();
+        engine
+            .handle("pay", order_id, Command::Pay { payment_id: "p".into(), amount: 15.0 })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_workflow_logs_every_transition_with_timestamps() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+        engine
+            .handle("ship", 1, Command::Ship { tracking_number: "t".into(), product_ids: None })
+            .unwrap();
+        engine.handle("deliver", 1, Command::Deliver).unwrap();
+
+        let log = engine.history(1).unwrap();
+        let path: Vec<_> = log.iter().map(|r| (r.seq, r.from, r.to, r.timestamp)).collect();
+        assert_eq!(
+            path,
+            vec![
+                (1, OrderStatus::New, OrderStatus::Pending, 101),
+                (2, OrderStatus::Pending, OrderStatus::Paid, 102),
+                (3, OrderStatus::Paid, OrderStatus::Shipped, 103),
+                (4, OrderStatus::Shipped, OrderStatus::Delivered, 104),
+            ]
+        );
+        assert_eq!(engine.order(1).unwrap().status, OrderStatus::Delivered);
+    }
+
+    #[test]
+    fn test_workflow_table_and_guards_reject_commands() {
+        let mut engine = engine(InMemoryStore::new());
+        assert_eq!(
+            engine.handle("r1", 1, Command::Deliver),
+            Err(WorkflowError::InvalidTransition {
+                from: OrderStatus::New,
+                command: CommandKind::Deliver
+            })
+        );
+        assert!(matches!(
+            engine.handle("r2", 1, Command::Place { customer_id: 1, items: vec![] }),
+            Err(WorkflowError::GuardRejected(_))
+        ));
+
+        engine
+            .handle("r3", 1, Command::Place { customer_id: 1, items: two_items() })
+            .unwrap();
+        assert!(matches!(
+            engine.handle("r4", 1, Command::Pay { payment_id: "p".into(), amount: 1.0 }),
+            Err(WorkflowError::GuardRejected(_))
+        ));
+        // rejected commands leave no trace in the log
+        assert_eq!(engine.history(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_workflow_partial_shipments_and_refunds() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+
+        let ship = |ids: Vec<u64>| Command::Ship { tracking_number: "t".into(), product_ids: Some(ids) };
+        let first = engine.handle("s1", 1, ship(vec![2])).unwrap();
+        assert_eq!(first.record().to, OrderStatus::PartiallyShipped);
+        assert!(matches!(engine.handle("s2", 1, ship(vec![2])), Err(WorkflowError::GuardRejected(_))));
+        assert!(engine.handle("d1", 1, Command::Deliver).is_err());
+        let second = engine.handle("s3", 1, ship(vec![1])).unwrap();
+        assert_eq!(second.record().to, OrderStatus::Shipped);
+
+        let refund = |amount: f64| Command::Refund { amount, reason: "r".into() };
+        assert_eq!(engine.handle("r1", 1, refund(5.0)).unwrap().record().to, OrderStatus::PartiallyRefunded);
+        assert!(matches!(engine.handle("r2", 1, refund(11.0)), Err(WorkflowError::GuardRejected(_))));
+        assert_eq!(engine.handle("r3", 1, refund(10.0)).unwrap().record().to, OrderStatus::Refunded);
+        assert!(engine.handle("r4", 1, refund(1.0)).is_err());
+
+        let order = engine.order(1).unwrap();
+        assert_eq!(order.tracking_numbers.len(), 2);
+        assert!((order.refunded - 15.0).abs() < AMOUNT_EPSILON);
+    }
+
+    #[test]
+    fn test_workflow_commands_are_idempotent_by_request_id() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+        let cancel = || Command::Cancel { reason: "changed mind".into() };
+
+        let first = engine.handle("cancel-1", 1, cancel()).unwrap();
+        let again = engine.handle("cancel-1", 1, cancel()).unwrap();
+        assert!(matches!(first, Outcome::Applied(_)));
+        assert_eq!(again, Outcome::Duplicate(first.record().clone()));
+        assert_eq!(engine.history(1).unwrap().len(), 3);
+
+        assert_eq!(
+            engine.handle("cancel-1", 2, cancel()),
+            Err(WorkflowError::RequestIdReused { request_id: "cancel-1".into(), order_id: 1 })
+        );
+    }
+
+    #[test]
+    fn test_workflow_hooks_run_after_persisting() {
+        let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        let table = TransitionTable::standard().hook(CommandKind::Pay, move |record, order| {
+            sink.borrow_mut().push((record.seq, order.payment_id.clone()));
+        });
+        let mut engine = WorkflowEngine::new(InMemoryStore::new(), table);
+        place_and_pay(&mut engine, 3);
+        assert_eq!(*seen.borrow(), vec![(2, Some("p".to_string()))]);
+    }
+
+    #[test]
+    fn test_replay_rebuilds_identical_state() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+        engine
+            .handle("s", 1, Command::Ship { tracking_number: "t".into(), product_ids: Some(vec![1]) })
+            .unwrap();
+
+        let log = engine.history(1).unwrap();
+        let mut incremental = OrderAggregate::new(1);
+        for record in &log {
+            incremental.apply(&record.event, record.timestamp);
+        }
+        assert_eq!(OrderAggregate::replay(1, &log), incremental);
+        assert_eq!(incremental.outstanding(), vec![2]);
+        assert_eq!(incremental.version, 3);
+    }
+
+    #[test]
+    fn test_in_memory_store_rejects_conflicts() {
+        let mut store = InMemoryStore::new();
+        let record = EventRecord {
+            order_id: 1,
+            seq: 1,
+            request_id: "a".into(),
+            from: OrderStatus::New,
+            to: OrderStatus::Pending,
+            event: OrderEvent::Placed { customer_id: 1, items: sample_items() },
+            timestamp: 0,
+        };
+        store.append(&record).unwrap();
+        assert_eq!(store.append(&record), Err(StoreError::DuplicateRequest("a".into())));
+        let stale = EventRecord { request_id: "b".into(), ..record };
+        assert_eq!(store.append(&stale), Err(StoreError::Conflict { order_id: 1, seq: 1 }));
+    }
+
+    #[test]
+    fn test_partially_refunded_order_still_ships_and_delivers() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+        let refund = Command::Refund { amount: 5.0, reason: "discount".into() };
+        assert_eq!(engine.handle("r1", 1, refund).unwrap().record().to, OrderStatus::PartiallyRefunded);
+
+        assert!(matches!(engine.handle("d0", 1, Command::Deliver), Err(WorkflowError::GuardRejected(_))));
+        let ship = Command::Ship { tracking_number: "t".into(), product_ids: None };
+        assert_eq!(engine.handle("s1", 1, ship).unwrap().record().to, OrderStatus::Shipped);
+        assert_eq!(engine.handle("d1", 1, Command::Deliver).unwrap().record().to, OrderStatus::Delivered);
+
+        // refunded after delivery: neither shipping nor delivering again
+        let refund = Command::Refund { amount: 1.0, reason: "late".into() };
+        assert_eq!(engine.handle("r2", 1, refund).unwrap().record().to, OrderStatus::PartiallyRefunded);
+        let ship = Command::Ship { tracking_number: "t2".into(), product_ids: None };
+        assert!(matches!(engine.handle("s2", 1, ship), Err(WorkflowError::GuardRejected(_))));
+        assert!(matches!(engine.handle("d2", 1, Command::Deliver), Err(WorkflowError::GuardRejected(_))));
+        assert_eq!(engine.order(1).unwrap().refunded, 6.0);
+    }
+
+    #[test]
+    fn test_paid_order_can_be_refunded_after_cancel() {
+        let mut engine = engine(InMemoryStore::new());
+        place_and_pay(&mut engine, 1);
+        let cancel = Command::Cancel { reason: "changed mind".into() };
+        assert_eq!(engine.handle("c1", 1, cancel).unwrap().record().to, OrderStatus::Cancelled);
+
+        let refund = |amount: f64| Command::Refund { amount, reason: "cancelled".into() };
+        // a partial refund keeps the order cancelled, so it cannot ship
+        assert_eq!(engine.handle("r1", 1, refund(5.0)).unwrap().record().to, OrderStatus::Cancelled);
+        let ship = Command::Ship { tracking_number: "t".into(), product_ids: None };
+        assert!(engine.handle("s1", 1, ship).is_err());
+        assert!(matches!(engine.handle("r2", 1, refund(11.0)), Err(WorkflowError::GuardRejected(_))));
+        assert_eq!(engine.handle("r3", 1, refund(10.0)).unwrap().record().to, OrderStatus::Refunded);
+        assert!((engine.order(1).unwrap().refunded - 15.0).abs() < AMOUNT_EPSILON);
+
+        // nothing was paid, so there is nothing to refund
+        engine.handle("p2", 2, Command::Place { customer_id: 1, items: two_items() }).unwrap();
+        engine.handle("c2", 2, Command::Cancel { reason: "oops".into() }).unwrap();
+        assert!(matches!(engine.handle("r4", 2, refund(1.0)), Err(WorkflowError::GuardRejected(_))));
+    }
+
+    #[test]
+    fn test_sqlite_store_persists_and_replays() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("orders.db");
+        let path = path.to_str().unwrap();
+        {
+            let mut engine = engine(SqliteStore::open(path).unwrap());
+            place_and_pay(&mut engine, 5);
+            engine.handle("refund", 5, Command::Refund { amount: 2.5, reason: "late".into() }).unwrap();
+        }
+
+        // a fresh process: same file, state comes back from the log
+        let mut engine = engine(SqliteStore::open(path).unwrap());
+        let order = engine.order(5).unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyRefunded);
+        assert_eq!(order.items, two_items());
+        assert_eq!(order.payment_id.as_deref(), Some("p"));
+        assert!(matches!(
+            engine.handle("refund", 5, Command::Refund { amount: 2.5, reason: "late".into() }),
+            Ok(Outcome::Duplicate(_))
+        ));
+        assert_eq!(engine.history(5).unwrap().len(), 3);
+
+        let mut store = SqliteStore::in_memory().unwrap();
+        let record = engine.history(5).unwrap().remove(0);
+        store.append(&record).unwrap();
+        assert_eq!(store.append(&record), Err(StoreError::DuplicateRequest("place".into())));
+        let stale = EventRecord { request_id: "other".into(), ..record };
+        assert_eq!(store.append(&stale), Err(StoreError::Conflict { order_id: 5, seq: 1 }));
+    }
+}
\ No newline at end of file
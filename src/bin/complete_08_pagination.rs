@@ -1,13 +1,18 @@
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::fs;
 use std::marker::PhantomData;
+use std::path::PathBuf;
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
 
 // =============================================================================
 // Core types shared across milestones
@@ -24,16 +29,45 @@ pub struct PageResponse<T> {
 #[derive(Debug, Clone)]
 pub enum FetchError {
     Http(String),
+    /// Non-success response other than throttling: status code and body excerpt.
+    Status(u16, String),
     Deserialization(String),
-    RateLimitExceeded,
+    /// 429 (or 503) from the server, with its `Retry-After` hint if any.
+    RateLimitExceeded(Option<Duration>),
+    /// Loading or saving a checkpoint failed.
+    Checkpoint(String),
+}
+
+impl FetchError {
+    /// The server's `Retry-After` hint, if this is a throttling error.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FetchError::RateLimitExceeded(wait) => *wait,
+            _ => None,
+        }
+    }
+
+    /// Client errors will fail the same way again; everything else may pass.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::Status(code, _) => *code >= 500 || *code == 408,
+            FetchError::Deserialization(_) | FetchError::Checkpoint(_) => false,
+            FetchError::Http(_) | FetchError::RateLimitExceeded(_) => true,
+        }
+    }
 }
 
 impl fmt::Display for FetchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FetchError::Http(msg) => write!(f, "http error: {}", msg),
+            FetchError::Status(code, body) => write!(f, "http status {}: {}", code, body),
             FetchError::Deserialization(msg) => write!(f, "deserialization error: {}", msg),
-            FetchError::RateLimitExceeded => write!(f, "rate limit exceeded"),
+            FetchError::Checkpoint(msg) => write!(f, "checkpoint error: {}", msg),
+            FetchError::RateLimitExceeded(None) => write!(f, "rate limit exceeded"),
+            FetchError::RateLimitExceeded(Some(wait)) => {
+                write!(f, "rate limit exceeded, retry after {:?}", wait)
+            }
         }
     }
 }
@@ -50,6 +84,7 @@ pub struct RateLimiter {
     max_tokens: f64,
     refill_rate: f64,
     last_refill: Instant,
+    paused_until: Option<Instant>,
 }
 
 impl RateLimiter {
@@ -60,10 +95,15 @@ impl RateLimiter {
             max_tokens: tokens,
             refill_rate: tokens,
             last_refill: Instant::now(),
+            paused_until: None,
         }
     }
 
     pub fn acquire(&mut self) {
+        if let Some(until) = self.paused_until.take() {
+            thread::sleep(until.saturating_duration_since(Instant::now()));
+            self.last_refill = Instant::now();
+        }
         self.refill();
         while self.tokens < 1.0 {
             let missing = 1.0 - self.tokens;
@@ -75,6 +115,39 @@ impl RateLimiter {
         self.tokens -= 1.0;
     }
 
+    /// Non-blocking [`acquire`](Self::acquire) for async callers: takes a
+    /// token, or returns how long to wait before asking again.
+    pub fn try_acquire(&mut self) -> Result<(), Duration> {
+        if let Some(until) = self.paused_until {
+            let now = Instant::now();
+            if until > now {
+                return Err(until - now);
+            }
+            self.paused_until = None;
+            self.last_refill = now;
+        }
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_rate,
+            ))
+        }
+    }
+
+    /// Server-side throttling: hold every caller for `wait`, then restart
+    /// from an empty bucket so requests resume at the steady rate.
+    pub fn pause(&mut self, wait: Duration) {
+        let until = Instant::now() + wait;
+        self.paused_until = Some(
+            self.paused_until
+                .map_or(until, |current| current.max(until)),
+        );
+        self.tokens = 0.0;
+    }
+
     fn refill(&mut self) {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_refill).as_secs_f64();
@@ -89,6 +162,8 @@ pub struct RetryPolicy {
     pub initial_backoff: Duration,
     pub multiplier: f64,
     pub max_backoff: Duration,
+    /// Longest server-requested `Retry-After` we are willing to wait out.
+    pub max_retry_after: Duration,
 }
 
 impl RetryPolicy {
@@ -98,6 +173,7 @@ impl RetryPolicy {
             initial_backoff,
             multiplier: 2.0,
             max_backoff: Duration::from_secs(2),
+            max_retry_after: Duration::from_secs(60),
         }
     }
 
@@ -105,6 +181,20 @@ impl RetryPolicy {
         Self::new(3, Duration::from_millis(5))
     }
 
+    /// Delay before retrying after the `attempts`-th failure, or `None` to give
+    /// up. A `Retry-After` hint replaces the backoff unless it exceeds
+    /// `max_retry_after`.
+    pub fn next_delay(&self, attempts: usize, err: &FetchError) -> Option<Duration> {
+        if attempts >= self.max_attempts || !err.is_retryable() {
+            return None;
+        }
+        match err.retry_after() {
+            Some(wait) if wait > self.max_retry_after => None,
+            Some(wait) => Some(wait),
+            None => Some(self.delay_for_attempt(attempts - 1)),
+        }
+    }
+
     pub fn delay_for_attempt(&self, attempt: usize) -> Duration {
         let factor = self.multiplier.powi(attempt as i32);
         let delay = self.initial_backoff.mul_f64(factor);
@@ -120,7 +210,7 @@ impl RetryPolicy {
 // Pagination strategies
 // =============================================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PaginationStrategy {
     Offset {
         offset: usize,
@@ -175,6 +265,34 @@ impl PaginationStrategy {
             }
         }
     }
+
+    /// Advances as if the current page came back full, so later positions
+    /// can be computed before any response arrives. Not possible for cursors.
+    fn skip_page(&mut self) -> bool {
+        match self {
+            PaginationStrategy::Offset { offset, page_size } => {
+                *offset += *page_size;
+                true
+            }
+            PaginationStrategy::PageNumber { page, .. } => {
+                *page += 1;
+                true
+            }
+            PaginationStrategy::Cursor { .. } => false,
+        }
+    }
+
+    /// The server returned fewer items than asked for yet says more follow:
+    /// it caps the page size, so offsets from [`skip_page`](Self::skip_page)
+    /// would jump past items.
+    fn came_back_short<T>(&self, response: &PageResponse<T>) -> bool {
+        match self {
+            PaginationStrategy::Offset { page_size, .. } => {
+                response.has_more && response.items.len() < *page_size
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -264,6 +382,7 @@ where
             }
             match self.backend.fetch_page(&self.endpoint, &params) {
                 Ok(response) => {
+                    self.strategy.advance(&response);
                     let mut next_buffer = VecDeque::with_capacity(response.items.len());
                     for value in response.items {
                         match serde_json::from_value::<T>(value) {
@@ -277,16 +396,19 @@ where
 
                     self.buffer = next_buffer;
                     self.more_pages_available = response.has_more;
-                    self.strategy.advance(&response);
                     return Ok(());
                 }
                 Err(err) => {
                     attempts += 1;
-                    if attempts >= self.retry_policy.max_attempts {
+                    let Some(delay) = self.retry_policy.next_delay(attempts, &err) else {
                         self.more_pages_available = false;
                         return Err(err);
+                    };
+                    match &mut self.rate_limiter {
+                        // the limiter sleeps before the next acquire()
+                        Some(limiter) if err.retry_after().is_some() => limiter.pause(delay),
+                        _ => thread::sleep(delay),
                     }
-                    thread::sleep(self.retry_policy.delay_for_attempt(attempts - 1));
                 }
             }
         }
@@ -513,7 +635,7 @@ impl ApiClient {
     pub fn new(base_url: impl Into<String>) -> Self {
         Self {
             base_url: base_url.into(),
-            backend: Arc::new(HttpBackend {}),
+            backend: Arc::new(HttpBackend::new()),
         }
     }
 
@@ -536,20 +658,6 @@ impl ApiClient {
     }
 }
 
-struct HttpBackend;
-
-impl PaginatedBackend for HttpBackend {
-    fn fetch_page(
-        &self,
-        _endpoint: &str,
-        _params: &PaginationParams,
-    ) -> Result<PageResponse<Value>, FetchError> {
-        Err(FetchError::Http(
-            "HTTP backend not implemented in example".to_string(),
-        ))
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 enum StrategyKind {
     Offset,
@@ -677,6 +785,881 @@ where
     }
 }
 
+// =============================================================================
+// Milestone 7: HTTP backend
+// =============================================================================
+
+/// How [`PaginationParams`] become query parameters for one API.
+///
+/// Each template is a `key=value&...` string whose values may reference
+/// `{offset}`, `{limit}`, `{cursor}`, `{page}` and `{per_page}`. A pair whose
+/// placeholder has no value (the cursor on the first page) is left out.
+#[derive(Debug, Clone)]
+pub struct QueryTemplate {
+    offset: String,
+    cursor: String,
+    page: String,
+    /// Added to the zero-based page counter, for APIs that count from 1.
+    page_base: usize,
+}
+
+impl Default for QueryTemplate {
+    fn default() -> Self {
+        Self {
+            offset: "offset={offset}&limit={limit}".to_string(),
+            cursor: "cursor={cursor}&limit={limit}".to_string(),
+            page: "page={page}&per_page={per_page}".to_string(),
+            page_base: 0,
+        }
+    }
+}
+
+impl QueryTemplate {
+    pub fn offset(mut self, template: impl Into<String>) -> Self {
+        self.offset = template.into();
+        self
+    }
+
+    pub fn cursor(mut self, template: impl Into<String>) -> Self {
+        self.cursor = template.into();
+        self
+    }
+
+    pub fn page(mut self, template: impl Into<String>, page_base: usize) -> Self {
+        self.page = template.into();
+        self.page_base = page_base;
+        self
+    }
+
+    pub fn render(&self, params: &PaginationParams) -> Vec<(String, String)> {
+        let (template, values): (&str, Vec<(&str, Option<String>)>) = match params {
+            PaginationParams::Offset { offset, limit } => (
+                &self.offset,
+                vec![
+                    ("offset", Some(offset.to_string())),
+                    ("limit", Some(limit.to_string())),
+                ],
+            ),
+            PaginationParams::Cursor { cursor, limit } => (
+                &self.cursor,
+                vec![
+                    ("cursor", cursor.clone()),
+                    ("limit", Some(limit.to_string())),
+                ],
+            ),
+            PaginationParams::PageNumber { page, per_page } => (
+                &self.page,
+                vec![
+                    ("page", Some((page + self.page_base).to_string())),
+                    ("per_page", Some(per_page.to_string())),
+                ],
+            ),
+        };
+
+        template
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter_map(|pair| {
+                let (key, raw) = pair.split_once('=').unwrap_or((pair, ""));
+                let mut value = raw.to_string();
+                for (name, substitute) in &values {
+                    let placeholder = format!("{{{}}}", name);
+                    if value.contains(&placeholder) {
+                        value = value.replace(&placeholder, substitute.as_deref()?);
+                    }
+                }
+                Some((key.to_string(), value))
+            })
+            .collect()
+    }
+}
+
+/// Blocking [`PaginatedBackend`] over `reqwest`.
+///
+/// Next pages are discovered, in order of preference, from an RFC 8288
+/// `Link: <...>; rel="next"` header, a cursor field in the JSON body, or HAL
+/// `_links.next.href`. Discovered links are handed back as the cursor, and a
+/// cursor that is an absolute URL is fetched as-is.
+///
+/// Requests run on a private single-threaded runtime, so `fetch_page` must
+/// not be called from inside another tokio runtime.
+pub struct HttpBackend {
+    client: reqwest::Client,
+    runtime: OnceLock<tokio::runtime::Runtime>,
+    template: QueryTemplate,
+    items_pointer: Option<String>,
+    cursor_pointers: Vec<String>,
+}
+
+impl Default for HttpBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpBackend {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            runtime: OnceLock::new(),
+            template: QueryTemplate::default(),
+            items_pointer: None,
+            cursor_pointers: [
+                "/next_cursor",
+                "/nextCursor",
+                "/next_page_token",
+                "/meta/next_cursor",
+            ]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
+        }
+    }
+
+    pub fn with_template(mut self, template: QueryTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// JSON pointer to the item array. Without it, a top-level array,
+    /// `items`, `data`, `results` or the first array in HAL `_embedded` is used.
+    pub fn with_items_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.items_pointer = Some(pointer.into());
+        self
+    }
+
+    /// JSON pointers tried, in order, for a next-page cursor.
+    pub fn with_cursor_pointers<I, S>(mut self, pointers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.cursor_pointers = pointers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn request_url(&self, endpoint: &str, params: &PaginationParams) -> Result<Url, FetchError> {
+        if let PaginationParams::Cursor {
+            cursor: Some(cursor),
+            ..
+        } = params
+        {
+            if let Ok(url) = Url::parse(cursor) {
+                if matches!(url.scheme(), "http" | "https") {
+                    return Ok(url);
+                }
+            }
+        }
+
+        let mut url = Url::parse(endpoint)
+            .map_err(|err| FetchError::Http(format!("invalid url {}: {}", endpoint, err)))?;
+        let pairs = self.template.render(params);
+        if !pairs.is_empty() {
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+        Ok(url)
+    }
+
+    async fn fetch(
+        &self,
+        url: Url,
+    ) -> Result<(Url, reqwest::header::HeaderMap, Value), FetchError> {
+        let response = self
+            .client
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|err| FetchError::Http(err.to_string()))?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                && headers.contains_key(reqwest::header::RETRY_AFTER))
+        {
+            let retry_after = headers
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| parse_retry_after(value, SystemTime::now()));
+            return Err(FetchError::RateLimitExceeded(retry_after));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let excerpt: String = body.chars().take(200).collect();
+            return Err(FetchError::Status(status.as_u16(), excerpt));
+        }
+
+        let body = response
+            .json::<Value>()
+            .await
+            .map_err(|err| FetchError::Deserialization(err.to_string()))?;
+        Ok((url, headers, body))
+    }
+
+    fn parse_page(
+        &self,
+        url: &Url,
+        headers: &reqwest::header::HeaderMap,
+        mut body: Value,
+        params: &PaginationParams,
+    ) -> Result<PageResponse<Value>, FetchError> {
+        let link_next = headers
+            .get_all(reqwest::header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(|value| parse_link_header(value, "next"))
+            .and_then(|href| url.join(&href).ok());
+        let cursor_next = self
+            .cursor_pointers
+            .iter()
+            .filter_map(|pointer| body.pointer(pointer))
+            .find_map(|value| match value {
+                Value::String(s) if !s.is_empty() => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            });
+        let hal_next = body
+            .pointer("/_links/next/href")
+            .and_then(Value::as_str)
+            .and_then(|href| url.join(href).ok());
+
+        let next_cursor = link_next
+            .map(String::from)
+            .or(cursor_next)
+            .or(hal_next.map(String::from));
+        let explicit_more = ["/has_more", "/hasMore", "/meta/has_more"]
+            .iter()
+            .find_map(|pointer| body.pointer(pointer).and_then(Value::as_bool));
+        let total = headers
+            .get("x-total-count")
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .or_else(|| {
+                ["/total", "/total_count", "/meta/total"]
+                    .iter()
+                    .find_map(|pointer| body.pointer(pointer).and_then(Value::as_u64))
+                    .map(|total| total as usize)
+            });
+
+        let items = match self.take_items(&mut body) {
+            Some(items) => items,
+            None => {
+                return Err(FetchError::Deserialization(
+                    "response contains no item array".to_string(),
+                ))
+            }
+        };
+
+        // Without any explicit signal, a full page suggests there may be more.
+        let limit = match params {
+            PaginationParams::Offset { limit, .. } | PaginationParams::Cursor { limit, .. } => {
+                *limit
+            }
+            PaginationParams::PageNumber { per_page, .. } => *per_page,
+        };
+        let full_page = !items.is_empty() && items.len() >= limit;
+        let has_more = explicit_more.unwrap_or(match params {
+            PaginationParams::Cursor { .. } => next_cursor.is_some(),
+            _ => next_cursor.is_some() || full_page,
+        });
+
+        Ok(PageResponse {
+            items,
+            next_cursor,
+            has_more,
+            total,
+        })
+    }
+
+    fn take_items(&self, body: &mut Value) -> Option<Vec<Value>> {
+        let target = match &self.items_pointer {
+            Some(pointer) => body.pointer_mut(pointer)?,
+            None if body.is_array() => body,
+            None => {
+                let key = ["items", "data", "results"]
+                    .into_iter()
+                    .find(|key| body.get(key).is_some_and(Value::is_array));
+                match key {
+                    Some(key) => body.get_mut(key)?,
+                    None => body
+                        .get_mut("_embedded")?
+                        .as_object_mut()?
+                        .values_mut()
+                        .find(|value| value.is_array())?,
+                }
+            }
+        };
+        match target.take() {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl PaginatedBackend for HttpBackend {
+    fn fetch_page(
+        &self,
+        endpoint: &str,
+        params: &PaginationParams,
+    ) -> Result<PageResponse<Value>, FetchError> {
+        let url = self.request_url(endpoint, params)?;
+        let runtime = match self.runtime.get() {
+            Some(runtime) => runtime,
+            None => {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|err| FetchError::Http(format!("runtime: {}", err)))?;
+                self.runtime.get_or_init(|| runtime)
+            }
+        };
+        let (url, headers, body) = runtime.block_on(self.fetch(url))?;
+        self.parse_page(&url, &headers, body, params)
+    }
+}
+
+/// Target of the first link in an RFC 8288 `Link` header whose `rel`
+/// contains `rel` (relation types are space-separated and case-insensitive).
+fn parse_link_header(header: &str, rel: &str) -> Option<String> {
+    let mut rest = header;
+    while let Some(open) = rest.find('<') {
+        let close = open + rest[open..].find('>')?;
+        let target = &rest[open + 1..close];
+        // parameters run until the next link, i.e. the next top-level ','
+        let params_end = rest[close..]
+            .find(",")
+            .map(|i| close + i)
+            .unwrap_or(rest.len());
+        let params = &rest[close + 1..params_end];
+        let matches = params.split(';').any(|param| {
+            let Some((name, value)) = param.split_once('=') else {
+                return false;
+            };
+            name.trim().eq_ignore_ascii_case("rel")
+                && value
+                    .trim()
+                    .trim_matches('"')
+                    .split_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case(rel))
+        });
+        if matches {
+            return Some(target.to_string());
+        }
+        rest = &rest[params_end..];
+    }
+    None
+}
+
+/// `Retry-After` as delta-seconds or an IMF-fixdate
+/// (`Sun, 06 Nov 1994 08:49:37 GMT`); dates in the past mean "now".
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| m == month)? as i64
+        + 1;
+    let (day, year): (i64, i64) = (day.parse().ok()?, year.parse().ok()?);
+    let mut hms = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+
+    // days since 1970-01-01 (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    let at = days * 86_400 + h * 3600 + m * 60 + s;
+
+    let now = now.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    Some(Duration::from_secs((at - now).max(0) as u64))
+}
+
+// =============================================================================
+// Milestone 8: Async streams, checkpoints and fan-out
+// =============================================================================
+
+/// Async counterpart of [`PaginatedBackend`].
+pub trait AsyncPaginatedBackend: Send + Sync {
+    fn fetch_page_async<'a>(
+        &'a self,
+        endpoint: &'a str,
+        params: &'a PaginationParams,
+    ) -> BoxFuture<'a, Result<PageResponse<Value>, FetchError>>;
+}
+
+impl AsyncPaginatedBackend for HttpBackend {
+    fn fetch_page_async<'a>(
+        &'a self,
+        endpoint: &'a str,
+        params: &'a PaginationParams,
+    ) -> BoxFuture<'a, Result<PageResponse<Value>, FetchError>> {
+        Box::pin(async move {
+            let url = self.request_url(endpoint, params)?;
+            let (url, headers, body) = self.fetch(url).await?;
+            self.parse_page(&url, &headers, body, params)
+        })
+    }
+}
+
+/// Runs a blocking backend on tokio's blocking pool. Not for [`HttpBackend`],
+/// whose blocking side refuses to run inside a runtime; use it directly.
+pub struct BlockingAdapter(pub Arc<dyn PaginatedBackend>);
+
+impl AsyncPaginatedBackend for BlockingAdapter {
+    fn fetch_page_async<'a>(
+        &'a self,
+        endpoint: &'a str,
+        params: &'a PaginationParams,
+    ) -> BoxFuture<'a, Result<PageResponse<Value>, FetchError>> {
+        let backend = Arc::clone(&self.0);
+        let endpoint = endpoint.to_string();
+        let params = params.clone();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || backend.fetch_page(&endpoint, &params))
+                .await
+                .map_err(|err| FetchError::Http(format!("fetch task failed: {}", err)))?
+        })
+    }
+}
+
+/// Where a crawl stands after a page: enough to resume it after a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub endpoint: String,
+    /// Position of the next page to fetch.
+    pub strategy: PaginationStrategy,
+    pub pages_fetched: usize,
+    pub items_emitted: usize,
+    pub exhausted: bool,
+}
+
+pub trait CheckpointStore: Send + Sync {
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>, FetchError>;
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), FetchError>;
+}
+
+#[derive(Default)]
+pub struct MemoryCheckpointStore {
+    checkpoints: Mutex<HashMap<String, Checkpoint>>,
+}
+
+impl CheckpointStore for MemoryCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>, FetchError> {
+        Ok(self.checkpoints.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), FetchError> {
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), checkpoint.clone());
+        Ok(())
+    }
+}
+
+/// One `<key>.json` file per crawl, replaced atomically via rename so a
+/// crash mid-write leaves the previous checkpoint intact. Keys are
+/// percent-encoded into file names, so any key stays inside `dir`.
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", Self::file_stem(key)))
+    }
+
+    /// Keeps ASCII alphanumerics, `-` and `_`; every other byte (separators,
+    /// dots, ...) becomes `%XX`, so distinct keys map to distinct names.
+    fn file_stem(key: &str) -> String {
+        let mut stem = String::with_capacity(key.len());
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                stem.push(byte as char);
+            } else {
+                stem.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        stem
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>, FetchError> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|err| FetchError::Checkpoint(err.to_string())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(FetchError::Checkpoint(err.to_string())),
+        }
+    }
+
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), FetchError> {
+        let to_err = |err: std::io::Error| FetchError::Checkpoint(err.to_string());
+        let bytes = serde_json::to_vec(checkpoint)
+            .map_err(|err| FetchError::Checkpoint(err.to_string()))?;
+        fs::create_dir_all(&self.dir).map_err(to_err)?;
+        let tmp = self.dir.join(format!(".{}.json.tmp", Self::file_stem(key)));
+        fs::write(&tmp, bytes).map_err(to_err)?;
+        fs::rename(&tmp, self.path(key)).map_err(to_err)
+    }
+}
+
+/// A page of items together with the checkpoint that follows it.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub checkpoint: Checkpoint,
+}
+
+struct FetchContext {
+    backend: Arc<dyn AsyncPaginatedBackend>,
+    endpoint: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Mutex<RateLimiter>>,
+}
+
+impl FetchContext {
+    async fn fetch(&self, params: &PaginationParams) -> Result<PageResponse<Value>, FetchError> {
+        let mut attempts = 0usize;
+        loop {
+            if let Some(limiter) = &self.rate_limiter {
+                loop {
+                    let wait = limiter.lock().unwrap().try_acquire();
+                    match wait {
+                        Ok(()) => break,
+                        Err(wait) => tokio::time::sleep(wait).await,
+                    }
+                }
+            }
+            match self.backend.fetch_page_async(&self.endpoint, params).await {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    attempts += 1;
+                    let delay = self
+                        .retry_policy
+                        .next_delay(attempts, &err)
+                        .ok_or_else(|| err.clone())?;
+                    match &self.rate_limiter {
+                        // throttling applies to every concurrent fetch, not just this one
+                        Some(limiter) if err.retry_after().is_some() => {
+                            limiter.lock().unwrap().pause(delay)
+                        }
+                        _ => tokio::time::sleep(delay).await,
+                    }
+                }
+            }
+        }
+    }
+}
+
+type RawPages = BoxStream<'static, (PaginationStrategy, Result<PageResponse<Value>, FetchError>)>;
+
+/// Async, resumable pagination.
+///
+/// [`pages`](Self::pages) yields each page with the checkpoint after it. With
+/// a [`CheckpointStore`], a page's checkpoint is saved when the consumer asks
+/// for the next page, i.e. once the page has been handled; a crash replays
+/// at most the page in flight (at-least-once delivery).
+pub struct StreamingPaginator {
+    backend: Arc<dyn AsyncPaginatedBackend>,
+    endpoint: String,
+    strategy: PaginationStrategy,
+    retry_policy: RetryPolicy,
+    rate_limit: Option<f64>,
+    concurrency: usize,
+    store: Option<(Arc<dyn CheckpointStore>, String)>,
+    pages_fetched: usize,
+    items_emitted: usize,
+    exhausted: bool,
+}
+
+impl StreamingPaginator {
+    pub fn new(
+        backend: Arc<dyn AsyncPaginatedBackend>,
+        endpoint: impl Into<String>,
+        strategy: PaginationStrategy,
+    ) -> Self {
+        Self {
+            backend,
+            endpoint: endpoint.into(),
+            strategy,
+            retry_policy: RetryPolicy::default(),
+            rate_limit: None,
+            concurrency: 1,
+            store: None,
+            pages_fetched: 0,
+            items_emitted: 0,
+            exhausted: false,
+        }
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// Fetch up to `pages` pages at once and merge them back in order.
+    ///
+    /// Only offset and page-number strategies can address pages ahead of
+    /// time; cursor pagination stays sequential. Offsets ahead are computed
+    /// from the requested page size; if the server returns a shorter page and
+    /// still reports more, the requests in flight are dropped and fetching
+    /// continues one page at a time from the end of that page. Up to
+    /// `pages - 1` requests past the end are discarded.
+    pub fn fan_out(mut self, pages: usize) -> Self {
+        self.concurrency = pages.max(1);
+        self
+    }
+
+    pub fn with_checkpoints(
+        mut self,
+        store: Arc<dyn CheckpointStore>,
+        key: impl Into<String>,
+    ) -> Self {
+        self.store = Some((store, key.into()));
+        self
+    }
+
+    /// Continues from a checkpoint instead of the initial strategy.
+    pub fn resume_from(mut self, checkpoint: Checkpoint) -> Result<Self, FetchError> {
+        if checkpoint.endpoint != self.endpoint {
+            return Err(FetchError::Checkpoint(format!(
+                "checkpoint is for {}, not {}",
+                checkpoint.endpoint, self.endpoint
+            )));
+        }
+        if std::mem::discriminant(&checkpoint.strategy) != std::mem::discriminant(&self.strategy) {
+            return Err(FetchError::Checkpoint(
+                "checkpoint uses a different pagination strategy".to_string(),
+            ));
+        }
+        self.strategy = checkpoint.strategy;
+        self.pages_fetched = checkpoint.pages_fetched;
+        self.items_emitted = checkpoint.items_emitted;
+        self.exhausted = checkpoint.exhausted;
+        Ok(self)
+    }
+
+    /// Resumes from the configured store's checkpoint, if one was saved.
+    pub fn resume(self) -> Result<Self, FetchError> {
+        let saved = match &self.store {
+            Some((store, key)) => store.load(key)?,
+            None => None,
+        };
+        match saved {
+            Some(checkpoint) => self.resume_from(checkpoint),
+            None => Ok(self),
+        }
+    }
+
+    pub fn pages<T>(self) -> BoxStream<'static, Result<Page<T>, FetchError>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let context = Arc::new(FetchContext {
+            backend: self.backend,
+            endpoint: self.endpoint.clone(),
+            retry_policy: self.retry_policy,
+            rate_limiter: self
+                .rate_limit
+                .map(|rate| Mutex::new(RateLimiter::new(rate))),
+        });
+        let fan_out = self.concurrency > 1 && self.strategy.clone().skip_page();
+        let raw = if self.exhausted {
+            stream::empty().boxed()
+        } else if fan_out {
+            Self::fanned_out_pages(context, self.strategy, self.concurrency)
+        } else {
+            Self::sequential_pages(context, self.strategy)
+        };
+
+        let state = PageState {
+            raw,
+            endpoint: self.endpoint,
+            store: self.store,
+            pending: None,
+            done: self.exhausted,
+            pages_fetched: self.pages_fetched,
+            items_emitted: self.items_emitted,
+            _marker: PhantomData,
+        };
+        stream::unfold(state, PageState::step).boxed()
+    }
+
+    pub fn items<T>(self) -> BoxStream<'static, Result<T, FetchError>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self.pages::<T>()
+            .flat_map(|page| match page {
+                Ok(page) => stream::iter(page.items.into_iter().map(Ok).collect::<Vec<_>>()),
+                Err(err) => stream::iter(vec![Err(err)]),
+            })
+            .boxed()
+    }
+
+    fn sequential_pages(context: Arc<FetchContext>, strategy: PaginationStrategy) -> RawPages {
+        stream::unfold(Some(strategy), move |strategy| {
+            let context = Arc::clone(&context);
+            async move {
+                let mut strategy = strategy?;
+                let response = context.fetch(&strategy.current_params()).await;
+                let more = match &response {
+                    Ok(response) => {
+                        strategy.advance(response);
+                        response.has_more
+                    }
+                    Err(_) => false,
+                };
+                let next = more.then(|| strategy.clone());
+                Some(((strategy, response), next))
+            }
+        })
+        .boxed()
+    }
+
+    fn fanned_out_pages(
+        context: Arc<FetchContext>,
+        strategy: PaginationStrategy,
+        concurrency: usize,
+    ) -> RawPages {
+        let positions = stream::iter(std::iter::successors(Some(strategy), |current| {
+            let mut next = current.clone();
+            next.skip_page();
+            Some(next)
+        }));
+        let fetch_context = Arc::clone(&context);
+        let fanned: RawPages = positions
+            .map(move |position| {
+                let context = Arc::clone(&fetch_context);
+                async move {
+                    let response = context.fetch(&position.current_params()).await;
+                    (position, response)
+                }
+            })
+            // `buffered` keeps output in request order: the ordered merge
+            .buffered(concurrency)
+            .boxed();
+
+        stream::unfold(FanOut::Ahead(fanned, context), |state| async move {
+            match state {
+                FanOut::Ahead(mut pages, context) => {
+                    let (position, response) = pages.next().await?;
+                    let mut after = position.clone();
+                    if let Ok(page) = &response {
+                        if position.came_back_short(page) {
+                            // positions fetched ahead are wrong; go on one page at a time
+                            after.advance(page);
+                            let rest = Self::sequential_pages(context, after.clone());
+                            return Some(((after, response), FanOut::Sequential(rest)));
+                        }
+                    }
+                    after.skip_page();
+                    Some(((after, response), FanOut::Ahead(pages, context)))
+                }
+                FanOut::Sequential(mut pages) => {
+                    let next = pages.next().await?;
+                    Some((next, FanOut::Sequential(pages)))
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Fan-out progress: pages requested ahead (each with the position it was
+/// fetched at), or the sequential fallback after a short page.
+enum FanOut {
+    Ahead(RawPages, Arc<FetchContext>),
+    Sequential(RawPages),
+}
+
+struct PageState<T> {
+    raw: RawPages,
+    endpoint: String,
+    store: Option<(Arc<dyn CheckpointStore>, String)>,
+    pending: Option<Checkpoint>,
+    done: bool,
+    pages_fetched: usize,
+    items_emitted: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> PageState<T> {
+    async fn step(mut self) -> Option<(Result<Page<T>, FetchError>, Self)> {
+        // the consumer is back for more, so the previous page is handled
+        if let Some(checkpoint) = self.pending.take() {
+            if let Some((store, key)) = &self.store {
+                if let Err(err) = store.save(key, &checkpoint) {
+                    self.done = true;
+                    return Some((Err(err), self));
+                }
+            }
+        }
+        if self.done {
+            return None;
+        }
+
+        let (strategy, response) = self.raw.next().await?;
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                self.done = true;
+                return Some((Err(err), self));
+            }
+        };
+
+        let exhausted = !response.has_more || response.items.is_empty();
+        let items = match response
+            .items
+            .into_iter()
+            .map(serde_json::from_value::<T>)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(items) => items,
+            Err(err) => {
+                self.done = true;
+                return Some((Err(FetchError::Deserialization(err.to_string())), self));
+            }
+        };
+
+        self.pages_fetched += 1;
+        self.items_emitted += items.len();
+        self.done = exhausted;
+        let checkpoint = Checkpoint {
+            endpoint: self.endpoint.clone(),
+            strategy,
+            pages_fetched: self.pages_fetched,
+            items_emitted: self.items_emitted,
+            exhausted,
+        };
+        self.pending = Some(checkpoint.clone());
+        Some((Ok(Page { items, checkpoint }), self))
+    }
+}
+
 // =============================================================================
 // Tests covering all milestones
 // =============================================================================
@@ -819,7 +1802,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/items", &make_items(6));
 
-        let iter = UniversalPaginatedIterator::new(
+        let iter = UniversalPaginatedIterator::<TestItem>::new(
             backend,
             endpoint,
             PaginationStrategy::Offset {
@@ -839,7 +1822,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/cursor", &make_items(5));
 
-        let iter = UniversalPaginatedIterator::new(
+        let iter = UniversalPaginatedIterator::<TestItem>::new(
             backend,
             endpoint,
             PaginationStrategy::Cursor {
@@ -859,7 +1842,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/slow", &make_items(6));
 
-        let mut iter = UniversalPaginatedIterator::new(
+        let mut iter = UniversalPaginatedIterator::<TestItem>::new(
             backend,
             endpoint,
             PaginationStrategy::Offset {
@@ -882,7 +1865,7 @@ mod tests {
         let endpoint = register_dataset(&backend, base_url, "/retry", &make_items(4));
         backend.set_failures(&endpoint, 2);
 
-        let iter = UniversalPaginatedIterator::new(
+        let iter = UniversalPaginatedIterator::<TestItem>::new(
             backend.clone(),
             endpoint.clone(),
             PaginationStrategy::Offset {
@@ -903,7 +1886,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/cache", &make_items(5));
 
-        let iter = UniversalPaginatedIterator::new(
+        let iter = UniversalPaginatedIterator::<TestItem>::new(
             backend.clone(),
             endpoint.clone(),
             PaginationStrategy::Offset {
@@ -928,7 +1911,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/cache-clone", &make_items(4));
 
-        let iter = UniversalPaginatedIterator::new(
+        let iter = UniversalPaginatedIterator::<TestItem>::new(
             backend.clone(),
             endpoint.clone(),
             PaginationStrategy::Offset {
@@ -939,7 +1922,7 @@ mod tests {
 
         let cached = CachedPaginatedIterator::with_cache(iter);
         let mut iter1 = cached.clone_iter();
-        let mut iter2 = cached.clone_iter();
+        let iter2 = cached.clone_iter();
 
         let collected1: Vec<_> = iter1.by_ref().map(Result::unwrap).collect();
         let count_after_first = backend.request_count(&endpoint);
@@ -956,7 +1939,7 @@ mod tests {
         let base_url = "mock://api";
         let endpoint = register_dataset(&backend, base_url, "/prefetch", &make_items(30));
 
-        let iter_seq = UniversalPaginatedIterator::new(
+        let iter_seq = UniversalPaginatedIterator::<TestItem>::new(
             backend.clone(),
             endpoint.clone(),
             PaginationStrategy::Offset {
@@ -969,7 +1952,7 @@ mod tests {
         let _: Vec<_> = iter_seq.map(Result::unwrap).collect();
         let seq_time = start_seq.elapsed();
 
-        let iter_prefetch = UniversalPaginatedIterator::new(
+        let iter_prefetch = UniversalPaginatedIterator::<TestItem>::new(
             backend,
             endpoint,
             PaginationStrategy::Offset {
@@ -1033,6 +2016,603 @@ mod tests {
 
         assert_eq!(items.len(), 12);
     }
+
+    mod http {
+        use super::*;
+        use serde_json::json;
+        use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        /// The server gets its own runtime; the backend under test blocks on
+        /// a private one, as it would in a synchronous caller.
+        fn start_server() -> (tokio::runtime::Runtime, MockServer) {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let server = runtime.block_on(MockServer::start());
+            (runtime, server)
+        }
+
+        fn mount(runtime: &tokio::runtime::Runtime, server: &MockServer, mock: Mock) {
+            runtime.block_on(mock.mount(server));
+        }
+
+        fn items_json(range: std::ops::Range<usize>) -> Vec<Value> {
+            range
+                .map(|id| json!({ "id": id, "name": format!("Item {}", id) }))
+                .collect()
+        }
+
+        #[test]
+        fn query_template_renders_and_skips_missing_cursor() {
+            let template =
+                QueryTemplate::default().page("page[number]={page}&page[size]={per_page}", 1);
+            assert_eq!(
+                template.render(&PaginationParams::PageNumber {
+                    page: 0,
+                    per_page: 10
+                }),
+                vec![
+                    ("page[number]".to_string(), "1".to_string()),
+                    ("page[size]".to_string(), "10".to_string())
+                ]
+            );
+            assert_eq!(
+                template.render(&PaginationParams::Cursor {
+                    cursor: None,
+                    limit: 5
+                }),
+                vec![("limit".to_string(), "5".to_string())]
+            );
+        }
+
+        #[test]
+        fn link_and_retry_after_parsing() {
+            let header = r#"<https://api.test/items?page=1>; rel="prev first", <https://api.test/items?page=3>; rel="next""#;
+            assert_eq!(
+                parse_link_header(header, "next").as_deref(),
+                Some("https://api.test/items?page=3")
+            );
+            assert_eq!(
+                parse_link_header(header, "first").as_deref(),
+                Some("https://api.test/items?page=1")
+            );
+            assert_eq!(parse_link_header("<x>; rel=last", "next"), None);
+
+            let now = UNIX_EPOCH + Duration::from_secs(784_111_747); // 06 Nov 1994 08:49:07 GMT
+            assert_eq!(
+                parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now),
+                Some(Duration::from_secs(30))
+            );
+            assert_eq!(parse_retry_after("7", now), Some(Duration::from_secs(7)));
+            assert_eq!(parse_retry_after("soon", now), None);
+        }
+
+        #[test]
+        fn offset_template_with_data_envelope() {
+            let (runtime, server) = start_server();
+            for (offset, range) in [("0", 0..2), ("2", 2..4), ("4", 4..5)] {
+                mount(
+                    &runtime,
+                    &server,
+                    Mock::given(method("GET"))
+                        .and(path("/items"))
+                        .and(query_param("skip", offset))
+                        .and(query_param("take", "2"))
+                        .respond_with(
+                            ResponseTemplate::new(200)
+                                .set_body_json(json!({ "data": items_json(range) })),
+                        ),
+                );
+            }
+
+            let backend = HttpBackend::new()
+                .with_template(QueryTemplate::default().offset("skip={offset}&take={limit}"));
+            let client = ApiClient::with_backend(server.uri(), Arc::new(backend));
+            let items: Vec<TestItem> = client
+                .paginated("/items")
+                .page_size(2)
+                .execute()
+                .map(Result::unwrap)
+                .collect();
+
+            assert_eq!(items, make_items(5));
+        }
+
+        #[test]
+        fn follows_link_header_then_json_cursor() {
+            let (runtime, server) = start_server();
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/items"))
+                    .and(query_param_is_missing("cursor"))
+                    .and(query_param_is_missing("page"))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header(
+                                "Link",
+                                format!("<{}/items?page=2>; rel=\"next\"", server.uri()).as_str(),
+                            )
+                            .set_body_json(items_json(0..2)),
+                    ),
+            );
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/items"))
+                    .and(query_param("page", "2"))
+                    .respond_with(
+                        ResponseTemplate::new(200).set_body_json(
+                            json!({ "items": items_json(2..4), "next_cursor": "abc" }),
+                        ),
+                    ),
+            );
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/items"))
+                    .and(query_param("cursor", "abc"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(
+                        json!({ "items": items_json(4..5), "next_cursor": null, "total": 5 }),
+                    )),
+            );
+
+            let client = ApiClient::with_backend(server.uri(), Arc::new(HttpBackend::new()));
+            let items: Vec<TestItem> = client
+                .paginated("/items")
+                .page_size(2)
+                .cursor_based()
+                .execute()
+                .map(Result::unwrap)
+                .collect();
+
+            assert_eq!(items, make_items(5));
+        }
+
+        #[test]
+        fn follows_relative_hal_links() {
+            let (runtime, server) = start_server();
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/orders"))
+                    .and(query_param_is_missing("page"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "_embedded": { "orders": items_json(0..3) },
+                        "_links": { "next": { "href": "/orders?page=2" } }
+                    }))),
+            );
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/orders"))
+                    .and(query_param("page", "2"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "_embedded": { "orders": items_json(3..4) },
+                        "_links": { "self": { "href": "/orders?page=2" } }
+                    }))),
+            );
+
+            let backend = HttpBackend::new().with_template(QueryTemplate::default().cursor(""));
+            let client = ApiClient::with_backend(server.uri(), Arc::new(backend));
+            let items: Vec<TestItem> = client
+                .paginated("/orders")
+                .cursor_based()
+                .execute()
+                .map(Result::unwrap)
+                .collect();
+
+            assert_eq!(items, make_items(4));
+        }
+
+        #[test]
+        fn honours_retry_after_on_429() {
+            let (runtime, server) = start_server();
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/items"))
+                    .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+                    .up_to_n_times(1)
+                    .with_priority(1),
+            );
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/items"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(items_json(0..2))),
+            );
+
+            let iter = UniversalPaginatedIterator::<TestItem>::new(
+                Arc::new(HttpBackend::new()),
+                format!("{}/items", server.uri()),
+                PaginationStrategy::Offset {
+                    offset: 0,
+                    page_size: 5,
+                },
+            )
+            .with_rate_limit(50.0)
+            .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)));
+
+            let start = Instant::now();
+            let items: Vec<_> = iter.map(Result::unwrap).collect();
+            assert_eq!(items, make_items(2));
+            assert!(start.elapsed() >= Duration::from_secs(1));
+        }
+
+        #[test]
+        fn gives_up_on_long_retry_after_and_client_errors() {
+            let (runtime, server) = start_server();
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/throttled"))
+                    .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600")),
+            );
+            mount(
+                &runtime,
+                &server,
+                Mock::given(path("/missing"))
+                    .respond_with(ResponseTemplate::new(404).set_body_string("nope")),
+            );
+
+            let backend: Arc<dyn PaginatedBackend> = Arc::new(HttpBackend::new());
+            let fetch_first = |endpoint: &str| {
+                UniversalPaginatedIterator::<TestItem>::new(
+                    Arc::clone(&backend),
+                    format!("{}{}", server.uri(), endpoint),
+                    PaginationStrategy::Offset {
+                        offset: 0,
+                        page_size: 5,
+                    },
+                )
+                .with_retry_policy(RetryPolicy::new(5, Duration::from_millis(1)))
+                .next()
+                .unwrap()
+            };
+
+            let start = Instant::now();
+            assert!(matches!(
+                fetch_first("/throttled"),
+                Err(FetchError::RateLimitExceeded(Some(wait))) if wait == Duration::from_secs(3600)
+            ));
+            assert!(
+                matches!(fetch_first("/missing"), Err(FetchError::Status(404, body)) if body == "nope")
+            );
+            assert!(start.elapsed() < Duration::from_secs(1));
+
+            let requests = runtime.block_on(server.received_requests()).unwrap();
+            assert_eq!(requests.len(), 2);
+        }
+
+        #[tokio::test]
+        async fn async_backend_fans_out_page_numbers() {
+            let server = MockServer::start().await;
+            for page in 1..=4 {
+                let range = ((page - 1) * 3)..(page * 3).min(10);
+                Mock::given(path("/records"))
+                    .and(query_param("p", page.to_string().as_str()))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_json(json!({ "results": items_json(range) })),
+                    )
+                    .mount(&server)
+                    .await;
+            }
+            Mock::given(path("/records"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [] })))
+                .with_priority(10)
+                .mount(&server)
+                .await;
+
+            let backend = HttpBackend::new()
+                .with_template(QueryTemplate::default().page("p={page}&n={per_page}", 1));
+            let items: Vec<TestItem> = StreamingPaginator::new(
+                Arc::new(backend),
+                format!("{}/records", server.uri()),
+                PaginationStrategy::PageNumber {
+                    page: 0,
+                    per_page: 3,
+                },
+            )
+            .fan_out(3)
+            .items()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+            assert_eq!(items, make_items(10));
+        }
+
+        #[tokio::test]
+        async fn fan_out_falls_back_when_server_caps_page_size() {
+            let server = MockServer::start().await;
+            // the API never returns more than 3 items, whatever `limit` says
+            for offset in 0..=15 {
+                let range = offset.min(10)..(offset + 3).min(10);
+                Mock::given(path("/capped"))
+                    .and(query_param("offset", offset.to_string().as_str()))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "items": items_json(range.clone()),
+                        "has_more": range.end < 10,
+                    })))
+                    .mount(&server)
+                    .await;
+            }
+
+            let store = Arc::new(MemoryCheckpointStore::default());
+            let pages: Vec<Page<TestItem>> = StreamingPaginator::new(
+                Arc::new(HttpBackend::new()),
+                format!("{}/capped", server.uri()),
+                PaginationStrategy::Offset {
+                    offset: 0,
+                    page_size: 5,
+                },
+            )
+            .fan_out(3)
+            .with_checkpoints(store.clone(), "capped")
+            .pages()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+            let items: Vec<TestItem> = pages.iter().flat_map(|p| p.items.clone()).collect();
+            assert_eq!(items, make_items(10));
+            assert_eq!(
+                pages[0].checkpoint.strategy,
+                PaginationStrategy::Offset {
+                    offset: 3,
+                    page_size: 5
+                }
+            );
+            let saved = store.load("capped").unwrap().unwrap();
+            assert!(saved.exhausted);
+            assert_eq!(saved.items_emitted, 10);
+            assert_eq!(
+                saved.strategy,
+                PaginationStrategy::Offset {
+                    offset: 10,
+                    page_size: 5
+                }
+            );
+        }
+    }
+
+    mod streaming {
+        use super::*;
+
+        /// Async backend whose early pages are the slowest, so an ordered
+        /// merge is observable.
+        struct SlowStartBackend {
+            items: Vec<Value>,
+            requests: Mutex<Vec<usize>>,
+        }
+
+        impl AsyncPaginatedBackend for SlowStartBackend {
+            fn fetch_page_async<'a>(
+                &'a self,
+                _endpoint: &'a str,
+                params: &'a PaginationParams,
+            ) -> BoxFuture<'a, Result<PageResponse<Value>, FetchError>> {
+                Box::pin(async move {
+                    let PaginationParams::PageNumber { page, per_page } = params else {
+                        return Err(FetchError::Http("page numbers only".into()));
+                    };
+                    self.requests.lock().unwrap().push(*page);
+                    tokio::time::sleep(Duration::from_millis(
+                        60u64.saturating_sub(*page as u64 * 10),
+                    ))
+                    .await;
+                    let start = (page * per_page).min(self.items.len());
+                    let end = (start + per_page).min(self.items.len());
+                    Ok(PageResponse {
+                        items: self.items[start..end].to_vec(),
+                        next_cursor: None,
+                        has_more: end < self.items.len(),
+                        total: Some(self.items.len()),
+                    })
+                })
+            }
+        }
+
+        fn blocking_mock(
+            endpoint: &str,
+            count: usize,
+        ) -> (Arc<MockBackend>, Arc<dyn AsyncPaginatedBackend>) {
+            let backend = Arc::new(MockBackend::new());
+            backend.add_endpoint(endpoint, &make_items(count));
+            let adapter = BlockingAdapter(backend.clone());
+            (backend, Arc::new(adapter))
+        }
+
+        #[tokio::test]
+        async fn cursor_stream_checkpoints_every_page() {
+            let (_, backend) = blocking_mock("mock://api/stream", 5);
+            let store = Arc::new(MemoryCheckpointStore::default());
+            let paginator = StreamingPaginator::new(
+                backend,
+                "mock://api/stream",
+                PaginationStrategy::Cursor {
+                    cursor: None,
+                    page_size: 2,
+                },
+            )
+            .with_checkpoints(store.clone(), "job");
+
+            let pages: Vec<Page<TestItem>> = paginator.pages().map(Result::unwrap).collect().await;
+            assert_eq!(pages.len(), 3);
+            assert_eq!(
+                pages[0].checkpoint.strategy,
+                PaginationStrategy::Cursor {
+                    cursor: Some("2".into()),
+                    page_size: 2
+                }
+            );
+
+            let saved = store.load("job").unwrap().unwrap();
+            assert!(saved.exhausted);
+            assert_eq!(saved.items_emitted, 5);
+            assert_eq!(saved.pages_fetched, 3);
+        }
+
+        #[tokio::test]
+        async fn resumes_after_crash_from_file_checkpoint() {
+            let dir = tempfile::tempdir().unwrap();
+            let endpoint = "mock://api/export";
+            let (backend, async_backend) = blocking_mock(endpoint, 10);
+            let strategy = PaginationStrategy::Offset {
+                offset: 0,
+                page_size: 3,
+            };
+            let store: Arc<dyn CheckpointStore> = Arc::new(FileCheckpointStore::new(dir.path()));
+
+            let mut first_run =
+                StreamingPaginator::new(async_backend.clone(), endpoint, strategy.clone())
+                    .with_checkpoints(store.clone(), "nightly")
+                    .pages::<TestItem>();
+            let mut seen: Vec<TestItem> = Vec::new();
+            seen.extend(first_run.next().await.unwrap().unwrap().items);
+            seen.extend(first_run.next().await.unwrap().unwrap().items);
+            // "crash" while the second page is being processed
+            drop(first_run);
+
+            let saved = store.load("nightly").unwrap().unwrap();
+            assert_eq!(
+                saved.strategy,
+                PaginationStrategy::Offset {
+                    offset: 3,
+                    page_size: 3
+                }
+            );
+
+            let rest: Vec<TestItem> =
+                StreamingPaginator::new(async_backend.clone(), endpoint, strategy.clone())
+                    .with_checkpoints(store.clone(), "nightly")
+                    .resume()
+                    .unwrap()
+                    .items()
+                    .map(Result::unwrap)
+                    .collect()
+                    .await;
+            // the unacknowledged page is delivered again
+            assert_eq!(rest.first().unwrap().id, 3);
+            seen.extend(rest);
+            let mut ids: Vec<_> = seen.iter().map(|item| item.id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids, (0..10).collect::<Vec<_>>());
+
+            let requests_before = backend.request_count(endpoint);
+            let nothing: Vec<Result<TestItem, FetchError>> =
+                StreamingPaginator::new(async_backend, endpoint, strategy)
+                    .with_checkpoints(store.clone(), "nightly")
+                    .resume()
+                    .unwrap()
+                    .items()
+                    .collect()
+                    .await;
+            assert!(nothing.is_empty());
+            assert_eq!(backend.request_count(endpoint), requests_before);
+        }
+
+        #[tokio::test]
+        async fn fan_out_merges_pages_in_order() {
+            let backend = Arc::new(SlowStartBackend {
+                items: make_items(23)
+                    .iter()
+                    .map(|item| serde_json::to_value(item).unwrap())
+                    .collect(),
+                requests: Mutex::new(Vec::new()),
+            });
+            let strategy = PaginationStrategy::PageNumber {
+                page: 0,
+                per_page: 5,
+            };
+
+            let start = Instant::now();
+            let pages: Vec<Page<TestItem>> =
+                StreamingPaginator::new(backend.clone(), "fan", strategy)
+                    .fan_out(5)
+                    .pages()
+                    .map(Result::unwrap)
+                    .collect()
+                    .await;
+            let elapsed = start.elapsed();
+
+            let items: Vec<TestItem> = pages.iter().flat_map(|p| p.items.clone()).collect();
+            assert_eq!(items, make_items(23));
+            assert_eq!(
+                pages.last().unwrap().checkpoint.strategy,
+                PaginationStrategy::PageNumber {
+                    page: 5,
+                    per_page: 5
+                }
+            );
+            // sequentially the five pages would take 60+50+40+30+20 ms
+            assert!(elapsed < Duration::from_millis(150), "{:?}", elapsed);
+            assert!(backend.requests.lock().unwrap().len() <= 5 + 4);
+        }
+
+        #[tokio::test]
+        async fn rejects_mismatched_checkpoint() {
+            let (_, backend) = blocking_mock("mock://api/a", 1);
+            let checkpoint = Checkpoint {
+                endpoint: "mock://api/b".into(),
+                strategy: PaginationStrategy::Offset {
+                    offset: 4,
+                    page_size: 2,
+                },
+                pages_fetched: 2,
+                items_emitted: 4,
+                exhausted: false,
+            };
+            let json = serde_json::to_string(&checkpoint).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Checkpoint>(&json).unwrap(),
+                checkpoint
+            );
+
+            let result = StreamingPaginator::new(
+                backend,
+                "mock://api/a",
+                PaginationStrategy::Offset {
+                    offset: 0,
+                    page_size: 2,
+                },
+            )
+            .resume_from(checkpoint);
+            assert!(matches!(result, Err(FetchError::Checkpoint(_))));
+        }
+
+        #[test]
+        fn file_store_keys_cannot_escape_directory() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path().join("checkpoints");
+            let store = FileCheckpointStore::new(&root);
+            let checkpoint = Checkpoint {
+                endpoint: "mock://api/a".into(),
+                strategy: PaginationStrategy::PageNumber {
+                    page: 3,
+                    per_page: 10,
+                },
+                pages_fetched: 3,
+                items_emitted: 30,
+                exhausted: false,
+            };
+
+            for key in ["../escape", "nested/job", "..", "mock://api/a"] {
+                store.save(key, &checkpoint).unwrap();
+                assert_eq!(store.load(key).unwrap(), Some(checkpoint.clone()));
+            }
+            assert_eq!(store.load("../escape2").unwrap(), None);
+
+            let names: Vec<_> = fs::read_dir(dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect();
+            assert_eq!(names, vec![std::ffi::OsString::from("checkpoints")]);
+            assert_eq!(fs::read_dir(&root).unwrap().count(), 4);
+        }
+    }
 }
 
-fn main() {}
\ No newline at end of file
+fn main() {}
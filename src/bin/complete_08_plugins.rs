@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 
 // =============================================================================
 // Milestone 1 & 2: Core plugin trait, static dispatch, and dynamic dispatch
@@ -150,6 +153,11 @@ trait PluginWithLifecycle {
 
     fn execute(&self) -> Result<String, String>;
 
+    /// Called for every event the host broadcasts; ignored by default.
+    fn on_event(&mut self, _name: &str, _payload: &str) -> Result<(), String> {
+        Ok(())
+    }
+
     fn cleanup(&mut self) -> Result<(), String> {
         Ok(())
     }
@@ -252,6 +260,1221 @@ impl EnhancedPluginManager {
     }
 }
 
+// =============================================================================
+// Milestone 4: Dynamic loading and dependency-ordered lifecycle
+// =============================================================================
+
+/// Everything the host needs from a plugin: lifecycle plus metadata.
+trait ManagedPlugin: PluginWithLifecycle + PluginMetadata {}
+
+impl<T: PluginWithLifecycle + PluginMetadata> ManagedPlugin for T {}
+
+/// A built-in plugin that only works once `Logger` is up.
+struct AuditPlugin {
+    sink: Option<String>,
+}
+
+impl PluginWithLifecycle for AuditPlugin {
+    fn name(&self) -> &str {
+        "Audit"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn initialize(&mut self, config: &PluginConfig) -> Result<(), String> {
+        self.sink = Some(config.get("audit_sink").unwrap_or("stdout").to_string());
+        Ok(())
+    }
+
+    fn execute(&self) -> Result<String, String> {
+        let sink = self.sink.as_deref().ok_or("Plugin not initialized")?;
+        Ok(format!("Auditing to {sink}"))
+    }
+
+    fn cleanup(&mut self) -> Result<(), String> {
+        self.sink = None;
+        Ok(())
+    }
+}
+
+impl PluginMetadata for AuditPlugin {
+    fn author(&self) -> &str {
+        "Plugin Team"
+    }
+
+    fn description(&self) -> &str {
+        "Records plugin activity through the logger"
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        vec!["Logger>=2.0.0"]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PluginError {
+    Load(String),
+    AbiMismatch {
+        expected: u32,
+        found: u32,
+    },
+    Duplicate(String),
+    NotFound(String),
+    MissingDependency {
+        plugin: String,
+        dependency: String,
+    },
+    IncompatibleVersion {
+        plugin: String,
+        dependency: String,
+        required: String,
+        found: String,
+    },
+    Cycle(Vec<String>),
+    DependencyUnavailable {
+        plugin: String,
+        dependency: String,
+    },
+    NotRunning(String),
+    Failed {
+        plugin: String,
+        message: String,
+    },
+    Panicked {
+        plugin: String,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Load(msg) => write!(f, "failed to load plugin: {msg}"),
+            PluginError::AbiMismatch { expected, found } => {
+                write!(f, "plugin ABI version {found}, host expects {expected}")
+            }
+            PluginError::Duplicate(name) => write!(f, "plugin '{name}' is already registered"),
+            PluginError::NotFound(name) => write!(f, "plugin '{name}' not found"),
+            PluginError::MissingDependency { plugin, dependency } => {
+                write!(f, "'{plugin}' depends on missing plugin '{dependency}'")
+            }
+            PluginError::IncompatibleVersion {
+                plugin,
+                dependency,
+                required,
+                found,
+            } => write!(
+                f,
+                "'{plugin}' requires '{dependency}' >= {required}, found {found}"
+            ),
+            PluginError::Cycle(names) => write!(f, "dependency cycle: {}", names.join(" -> ")),
+            PluginError::DependencyUnavailable { plugin, dependency } => {
+                write!(
+                    f,
+                    "'{plugin}' skipped: dependency '{dependency}' is not running"
+                )
+            }
+            PluginError::NotRunning(name) => write!(f, "plugin '{name}' is not running"),
+            PluginError::Failed { plugin, message } => write!(f, "'{plugin}' failed: {message}"),
+            PluginError::Panicked { plugin, message } => {
+                write!(f, "'{plugin}' panicked: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+// ---- C ABI ------------------------------------------------------------------
+//
+// A shared library exports `plugin_entry`, returning a pointer to a static
+// `PluginVTable`. Strings returned through `out` parameters are owned by the
+// plugin and handed back via `free_string`. Every entry point must catch its
+// own panics (unwinding across `extern "C"` aborts) and report them as
+// `STATUS_PANICKED`.
+
+const PLUGIN_ABI_VERSION: u32 = 1;
+const PLUGIN_ENTRY_SYMBOL: &CStr = c"plugin_entry";
+const STATUS_OK: i32 = 0;
+const STATUS_PANICKED: i32 = 2;
+
+#[repr(C)]
+struct PluginVTable {
+    abi_version: u32,
+    name: extern "C" fn() -> *const c_char,
+    version: extern "C" fn() -> *const c_char,
+    /// Comma-separated dependency specs, e.g. `Logger>=2.0.0,Cache`.
+    dependencies: extern "C" fn() -> *const c_char,
+    create: extern "C" fn() -> *mut c_void,
+    /// Config arrives as `key=value` lines.
+    initialize: extern "C" fn(*mut c_void, *const c_char, *mut *mut c_char) -> i32,
+    execute: extern "C" fn(*mut c_void, *mut *mut c_char) -> i32,
+    cleanup: extern "C" fn(*mut c_void, *mut *mut c_char) -> i32,
+    destroy: extern "C" fn(*mut c_void),
+    free_string: extern "C" fn(*mut c_char),
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+
+    pub const RTLD_NOW: c_int = 2;
+
+    // glibc >= 2.34 and macOS provide these in libc itself.
+    extern "C" {
+        pub fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub fn dlclose(handle: *mut c_void) -> c_int;
+        pub fn dlerror() -> *mut c_char;
+    }
+}
+
+/// An open shared library, closed on drop.
+struct Library {
+    handle: *mut c_void,
+}
+
+impl Library {
+    #[cfg(unix)]
+    fn open(path: &Path) -> Result<Self, PluginError> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| PluginError::Load(format!("{}: path contains NUL", path.display())))?;
+        // SAFETY: c_path is a valid NUL-terminated string.
+        let handle = unsafe { dl::dlopen(c_path.as_ptr(), dl::RTLD_NOW) };
+        if handle.is_null() {
+            return Err(PluginError::Load(format!(
+                "{}: {}",
+                path.display(),
+                last_dl_error()
+            )));
+        }
+        Ok(Self { handle })
+    }
+
+    #[cfg(not(unix))]
+    fn open(path: &Path) -> Result<Self, PluginError> {
+        Err(PluginError::Load(format!(
+            "{}: dynamic loading is only implemented for unix targets",
+            path.display()
+        )))
+    }
+
+    #[cfg(unix)]
+    fn symbol(&self, name: &CStr) -> Result<*mut c_void, PluginError> {
+        // SAFETY: handle came from a successful dlopen and is still open.
+        let symbol = unsafe { dl::dlsym(self.handle, name.as_ptr()) };
+        if symbol.is_null() {
+            return Err(PluginError::Load(format!(
+                "missing symbol {}: {}",
+                name.to_string_lossy(),
+                last_dl_error()
+            )));
+        }
+        Ok(symbol)
+    }
+
+    #[cfg(not(unix))]
+    fn symbol(&self, name: &CStr) -> Result<*mut c_void, PluginError> {
+        Err(PluginError::Load(format!(
+            "missing symbol {}",
+            name.to_string_lossy()
+        )))
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: every object created from this library is dropped first.
+        unsafe {
+            dl::dlclose(self.handle);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn last_dl_error() -> String {
+    // SAFETY: dlerror returns null or a NUL-terminated, thread-local message.
+    unsafe {
+        let err = dl::dlerror();
+        if err.is_null() {
+            "unknown error".to_string()
+        } else {
+            CStr::from_ptr(err).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// A plugin living in a shared library, adapted to [`PluginWithLifecycle`].
+///
+/// A `STATUS_PANICKED` report is re-raised as a host-side panic, so the host
+/// isolates dynamic and built-in plugins the same way.
+struct DynamicPlugin {
+    vtable: *const PluginVTable,
+    instance: *mut c_void,
+    name: String,
+    version: String,
+    dependencies: Vec<String>,
+    description: String,
+    // dropped last: the vtable and instance point into the library
+    _library: Library,
+}
+
+impl DynamicPlugin {
+    fn load(path: &Path) -> Result<Self, PluginError> {
+        let library = Library::open(path)?;
+        let entry = library.symbol(PLUGIN_ENTRY_SYMBOL)?;
+        // SAFETY: the plugin contract says `plugin_entry` has this signature.
+        let entry: extern "C" fn() -> *const PluginVTable = unsafe { std::mem::transmute(entry) };
+        let vtable = entry();
+        if vtable.is_null() {
+            return Err(PluginError::Load("plugin_entry returned null".to_string()));
+        }
+        // Only the leading ABI field is read before the version is known.
+        // SAFETY: vtable is non-null and every ABI version starts with abi_version.
+        let abi_version = unsafe { (*vtable).abi_version };
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                expected: PLUGIN_ABI_VERSION,
+                found: abi_version,
+            });
+        }
+        // SAFETY: ABI version matches, so the layout is ours; the library outlives it.
+        let table = unsafe { &*vtable };
+        let read = |f: extern "C" fn() -> *const c_char| {
+            let ptr = f();
+            if ptr.is_null() {
+                String::new()
+            } else {
+                // SAFETY: the plugin returns static NUL-terminated strings.
+                unsafe { CStr::from_ptr(ptr) }
+                    .to_string_lossy()
+                    .into_owned()
+            }
+        };
+        let name = read(table.name);
+        if name.is_empty() {
+            return Err(PluginError::Load("plugin has no name".to_string()));
+        }
+        let instance = (table.create)();
+        if instance.is_null() {
+            return Err(PluginError::Load(format!("{name}: create returned null")));
+        }
+
+        Ok(Self {
+            vtable,
+            instance,
+            version: read(table.version),
+            dependencies: read(table.dependencies)
+                .split(',')
+                .map(str::trim)
+                .filter(|dep| !dep.is_empty())
+                .map(String::from)
+                .collect(),
+            description: format!("Loaded from {}", path.display()),
+            name,
+            _library: library,
+        })
+    }
+
+    fn table(&self) -> &PluginVTable {
+        // SAFETY: checked in `load`; the library is kept open by `_library`.
+        unsafe { &*self.vtable }
+    }
+
+    fn call(&self, f: impl FnOnce(*mut *mut c_char) -> i32) -> Result<String, String> {
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let status = f(&mut out);
+        let message = if out.is_null() {
+            String::new()
+        } else {
+            // SAFETY: non-null `out` is a plugin-allocated C string, freed right after.
+            let message = unsafe { CStr::from_ptr(out) }
+                .to_string_lossy()
+                .into_owned();
+            (self.table().free_string)(out);
+            message
+        };
+        match status {
+            STATUS_OK => Ok(message),
+            STATUS_PANICKED => panic!("{message}"),
+            _ => Err(message),
+        }
+    }
+}
+
+impl Drop for DynamicPlugin {
+    fn drop(&mut self) {
+        (self.table().destroy)(self.instance);
+    }
+}
+
+impl PluginWithLifecycle for DynamicPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn initialize(&mut self, config: &PluginConfig) -> Result<(), String> {
+        let mut pairs: Vec<_> = config.settings.iter().collect();
+        pairs.sort();
+        let encoded: String = pairs.iter().map(|(k, v)| format!("{k}={v}\n")).collect();
+        let encoded = CString::new(encoded).map_err(|_| "config contains NUL".to_string())?;
+        let (init, instance) = (self.table().initialize, self.instance);
+        self.call(|out| init(instance, encoded.as_ptr(), out))
+            .map(drop)
+    }
+
+    fn execute(&self) -> Result<String, String> {
+        let (execute, instance) = (self.table().execute, self.instance);
+        self.call(|out| execute(instance, out))
+    }
+
+    fn cleanup(&mut self) -> Result<(), String> {
+        let (cleanup, instance) = (self.table().cleanup, self.instance);
+        self.call(|out| cleanup(instance, out)).map(drop)
+    }
+}
+
+impl PluginMetadata for DynamicPlugin {
+    fn author(&self) -> &str {
+        "unknown"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        self.dependencies.iter().map(String::as_str).collect()
+    }
+}
+
+// ---- Host -------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PluginState {
+    Registered,
+    Running,
+    /// `initialize` or `execute` returned an error or panicked.
+    Failed,
+    /// Not started because a dependency is not running.
+    Skipped,
+    Stopped,
+}
+
+struct HostedPlugin {
+    plugin: Box<dyn ManagedPlugin>,
+    state: PluginState,
+}
+
+/// `name` or `name>=major.minor.patch`.
+fn parse_dependency(spec: &str) -> (&str, Option<&str>) {
+    match spec.split_once(">=") {
+        Some((name, version)) => (name.trim(), Some(version.trim())),
+        None => (spec.trim(), None),
+    }
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(0))
+        .collect()
+}
+
+/// Compares component-wise, treating missing components as zero so that
+/// "2.0" satisfies ">=2.0.0".
+fn version_at_least(found: &str, required: &str) -> bool {
+    let (found, required) = (parse_version(found), parse_version(required));
+    let len = found.len().max(required.len());
+    let padded = |parts: &[u64]| {
+        (0..len)
+            .map(|i| parts.get(i).copied().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    padded(&found) >= padded(&required)
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Manager that starts plugins after their dependencies and stops them in
+/// reverse, containing failures and panics to the plugin that caused them
+/// (and whatever depends on it).
+struct PluginHost {
+    plugins: Vec<HostedPlugin>,
+    order: Vec<usize>,
+}
+
+impl PluginHost {
+    fn new() -> Self {
+        Self {
+            plugins: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    fn register(&mut self, plugin: Box<dyn ManagedPlugin>) -> Result<(), PluginError> {
+        if self.index_of(plugin.name()).is_some() {
+            return Err(PluginError::Duplicate(plugin.name().to_string()));
+        }
+        self.plugins.push(HostedPlugin {
+            plugin,
+            state: PluginState::Registered,
+        });
+        Ok(())
+    }
+
+    /// Loads a shared library plugin and registers it; returns its name.
+    fn load_library(&mut self, path: &Path) -> Result<String, PluginError> {
+        let plugin = DynamicPlugin::load(path)?;
+        let name = plugin.name.clone();
+        self.register(Box::new(plugin))?;
+        Ok(name)
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.plugins.iter().position(|p| p.plugin.name() == name)
+    }
+
+    fn state(&self, name: &str) -> Option<PluginState> {
+        self.index_of(name).map(|i| self.plugins[i].state)
+    }
+
+    fn plugins(&self) -> impl Iterator<Item = (&dyn ManagedPlugin, PluginState)> {
+        self.plugins.iter().map(|p| (p.plugin.as_ref(), p.state))
+    }
+
+    /// Dependencies of each plugin, as indices, after checking that they
+    /// exist and satisfy any version requirement.
+    fn dependency_graph(&self) -> Result<Vec<Vec<usize>>, PluginError> {
+        self.plugins
+            .iter()
+            .map(|hosted| {
+                hosted
+                    .plugin
+                    .dependencies()
+                    .into_iter()
+                    .map(|spec| {
+                        let (dependency, required) = parse_dependency(spec);
+                        let missing = || PluginError::MissingDependency {
+                            plugin: hosted.plugin.name().to_string(),
+                            dependency: dependency.to_string(),
+                        };
+                        let index = self.index_of(dependency).ok_or_else(missing)?;
+                        let found = self.plugins[index].plugin.version();
+                        if let Some(required) = required {
+                            if !version_at_least(found, required) {
+                                return Err(PluginError::IncompatibleVersion {
+                                    plugin: hosted.plugin.name().to_string(),
+                                    dependency: dependency.to_string(),
+                                    required: required.to_string(),
+                                    found: found.to_string(),
+                                });
+                            }
+                        }
+                        Ok(index)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Topological start order (Kahn's algorithm, ties broken by name).
+    fn resolve_order(&self) -> Result<Vec<String>, PluginError> {
+        Ok(self
+            .resolve()?
+            .into_iter()
+            .map(|i| self.plugins[i].plugin.name().to_string())
+            .collect())
+    }
+
+    fn resolve(&self) -> Result<Vec<usize>, PluginError> {
+        let graph = self.dependency_graph()?;
+        let mut remaining: Vec<usize> = graph.iter().map(Vec::len).collect();
+        let mut dependents = vec![Vec::new(); graph.len()];
+        for (plugin, deps) in graph.iter().enumerate() {
+            for &dep in deps {
+                dependents[dep].push(plugin);
+            }
+        }
+
+        let mut ready: std::collections::BTreeMap<&str, usize> = remaining
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count == 0)
+            .map(|(i, _)| (self.plugins[i].plugin.name(), i))
+            .collect();
+        let mut order = Vec::with_capacity(graph.len());
+        while let Some((_, next)) = ready.pop_first() {
+            order.push(next);
+            for &dependent in &dependents[next] {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    ready.insert(self.plugins[dependent].plugin.name(), dependent);
+                }
+            }
+        }
+
+        if order.len() < graph.len() {
+            return Err(PluginError::Cycle(self.find_cycle(&graph, &remaining)));
+        }
+        Ok(order)
+    }
+
+    /// Follows unresolved dependencies from an unresolved plugin until one
+    /// repeats; the repeated stretch is a cycle.
+    fn find_cycle(&self, graph: &[Vec<usize>], remaining: &[usize]) -> Vec<String> {
+        let Some(mut current) = remaining.iter().position(|&count| count > 0) else {
+            return Vec::new();
+        };
+        let mut path: Vec<usize> = Vec::new();
+        while !path.contains(&current) {
+            path.push(current);
+            current = match graph[current].iter().find(|&&dep| remaining[dep] > 0) {
+                Some(&dep) => dep,
+                None => break,
+            };
+        }
+        let start = path.iter().position(|&i| i == current).unwrap_or(0);
+        let mut cycle: Vec<String> = path[start..]
+            .iter()
+            .map(|&i| self.plugins[i].plugin.name().to_string())
+            .collect();
+        cycle.push(self.plugins[current].plugin.name().to_string());
+        cycle
+    }
+
+    /// Initializes every plugin in dependency order. Resolution problems
+    /// abort the start; per-plugin failures are returned and only disable
+    /// that plugin and its dependents.
+    fn start(&mut self, config: &PluginConfig) -> Result<Vec<PluginError>, PluginError> {
+        self.order = self.resolve()?;
+        let graph = self.dependency_graph()?;
+        let mut errors = Vec::new();
+
+        for &index in &self.order {
+            let unavailable = graph[index]
+                .iter()
+                .find(|&&dep| self.plugins[dep].state != PluginState::Running);
+            let hosted = &self.plugins[index];
+            let name = hosted.plugin.name().to_string();
+            if let Some(&dep) = unavailable {
+                errors.push(PluginError::DependencyUnavailable {
+                    plugin: name,
+                    dependency: self.plugins[dep].plugin.name().to_string(),
+                });
+                self.plugins[index].state = PluginState::Skipped;
+                continue;
+            }
+
+            let hosted = &mut self.plugins[index];
+            let result = panic::catch_unwind(AssertUnwindSafe(|| hosted.plugin.initialize(config)));
+            hosted.state = match result {
+                Ok(Ok(())) => PluginState::Running,
+                Ok(Err(message)) => {
+                    errors.push(PluginError::Failed {
+                        plugin: name,
+                        message,
+                    });
+                    PluginState::Failed
+                }
+                Err(payload) => {
+                    errors.push(PluginError::Panicked {
+                        plugin: name,
+                        message: panic_message(payload),
+                    });
+                    PluginState::Failed
+                }
+            };
+        }
+        Ok(errors)
+    }
+
+    fn execute(&mut self, name: &str) -> Result<String, PluginError> {
+        let index = self
+            .index_of(name)
+            .ok_or_else(|| PluginError::NotFound(name.to_string()))?;
+        let hosted = &mut self.plugins[index];
+        if hosted.state != PluginState::Running {
+            return Err(PluginError::NotRunning(name.to_string()));
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| hosted.plugin.execute())) {
+            Ok(Ok(output)) => Ok(output),
+            Ok(Err(message)) => Err(PluginError::Failed {
+                plugin: name.to_string(),
+                message,
+            }),
+            Err(payload) => {
+                // a plugin that panicked may have broken invariants; retire it
+                hosted.state = PluginState::Failed;
+                Err(PluginError::Panicked {
+                    plugin: name.to_string(),
+                    message: panic_message(payload),
+                })
+            }
+        }
+    }
+
+    /// Delivers an event to every running plugin in start order. Errors
+    /// are collected; a panicking plugin is retired like in `execute`.
+    fn emit(&mut self, event: &str, payload: &str) -> Vec<PluginError> {
+        let mut errors = Vec::new();
+        for &index in &self.order {
+            let hosted = &mut self.plugins[index];
+            if hosted.state != PluginState::Running {
+                continue;
+            }
+            let name = hosted.plugin.name().to_string();
+            match panic::catch_unwind(AssertUnwindSafe(|| hosted.plugin.on_event(event, payload))) {
+                Ok(Ok(())) => {}
+                Ok(Err(message)) => errors.push(PluginError::Failed {
+                    plugin: name,
+                    message,
+                }),
+                Err(payload) => {
+                    hosted.state = PluginState::Failed;
+                    errors.push(PluginError::Panicked {
+                        plugin: name,
+                        message: panic_message(payload),
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    /// Cleans up running plugins in reverse start order, then unloads all.
+    fn shutdown(&mut self) -> Vec<PluginError> {
+        let mut errors = Vec::new();
+        for &index in self.order.iter().rev() {
+            let hosted = &mut self.plugins[index];
+            if hosted.state != PluginState::Running {
+                continue;
+            }
+            let name = hosted.plugin.name().to_string();
+            match panic::catch_unwind(AssertUnwindSafe(|| hosted.plugin.cleanup())) {
+                Ok(Ok(())) => {}
+                Ok(Err(message)) => errors.push(PluginError::Failed {
+                    plugin: name,
+                    message,
+                }),
+                Err(payload) => errors.push(PluginError::Panicked {
+                    plugin: name,
+                    message: panic_message(payload),
+                }),
+            }
+            hosted.state = PluginState::Stopped;
+        }
+        self.order.clear();
+        self.plugins.clear();
+        errors
+    }
+}
+
+// =============================================================================
+// Milestone 5: Sandboxed WASM plugins
+// =============================================================================
+//
+// A WASM plugin is a module importing host functions from `env` and exporting
+// `memory` plus any of:
+//
+//   init() -> i32                       called by `initialize`
+//   execute() -> i32                    called by `execute`
+//   on_event(name_ptr, name_len, payload_ptr, payload_len) -> i32
+//   alloc(len) -> i32                   required when `on_event` is exported
+//   cleanup() -> i32                    called by `cleanup`
+//
+// A zero status is success. Text comes back through `env.output(ptr, len)`;
+// on failure it becomes the error message.
+
+const WASM_DEFAULT_FUEL: u64 = 1_000_000;
+const WASM_DEFAULT_MAX_MEMORY: usize = 1 << 20;
+const WASM_MAX_OUTPUT: usize = 64 * 1024;
+
+/// Optional host functions. Everything except `env.output` must be granted
+/// through the comma-separated `wasm.capabilities` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Capability {
+    /// `log(ptr, len)`
+    Log,
+    /// `config_get(key_ptr, key_len, out_ptr, out_cap) -> i32`, returning the
+    /// value length or -1 when unset; `wasm.*` settings are never exposed.
+    Config,
+    /// `clock_ms() -> i64`
+    Clock,
+}
+
+impl Capability {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "log" => Some(Capability::Log),
+            "config" => Some(Capability::Config),
+            "clock" => Some(Capability::Clock),
+            _ => None,
+        }
+    }
+
+    fn for_import(import: &str) -> Option<Self> {
+        match import {
+            "log" => Some(Capability::Log),
+            "config_get" => Some(Capability::Config),
+            "clock_ms" => Some(Capability::Clock),
+            _ => None,
+        }
+    }
+}
+
+/// Per-plugin sandbox limits, read from `wasm.fuel`, `wasm.max_memory` (bytes)
+/// and `wasm.capabilities`. Fuel is a budget per call, not per lifetime.
+#[derive(Debug, Clone, PartialEq)]
+struct WasmLimits {
+    fuel: u64,
+    max_memory: usize,
+    capabilities: std::collections::BTreeSet<Capability>,
+}
+
+impl WasmLimits {
+    fn from_config(config: &PluginConfig) -> Result<Self, String> {
+        let number = |key: &str, default: u64| match config.get(key) {
+            Some(value) => value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{key} must be a non-negative integer, got '{value}'")),
+            None => Ok(default),
+        };
+        let capabilities = config
+            .get("wasm.capabilities")
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Capability::parse(name).ok_or(format!("unknown capability '{name}'")))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            fuel: number("wasm.fuel", WASM_DEFAULT_FUEL)?,
+            max_memory: number("wasm.max_memory", WASM_DEFAULT_MAX_MEMORY as u64)? as usize,
+            capabilities,
+        })
+    }
+}
+
+struct HostState {
+    output: String,
+    logs: Vec<String>,
+    settings: HashMap<String, String>,
+    limits: wasmi::StoreLimits,
+}
+
+/// Greets with the configured `greeting`, followed by the last event payload.
+const DEMO_WASM_PLUGIN: &str = r#"
+(module
+  (import "env" "output" (func $output (param i32 i32)))
+  (import "env" "log" (func $log (param i32 i32)))
+  (import "env" "config_get" (func $config_get (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "greeting")
+  (data (i32.const 16) "initialized")
+  (data (i32.const 32) ": ")
+  (data (i32.const 40) "greeting not configured")
+  (global $heap (mut i32) (i32.const 1024))
+  (global $greeting_len (mut i32) (i32.const 0))
+  (global $last_ptr (mut i32) (i32.const 0))
+  (global $last_len (mut i32) (i32.const 0))
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "init") (result i32)
+    (global.set $greeting_len
+      (call $config_get (i32.const 0) (i32.const 8) (i32.const 128) (i32.const 64)))
+    (if (i32.lt_s (global.get $greeting_len) (i32.const 0))
+      (then
+        (call $output (i32.const 40) (i32.const 23))
+        (return (i32.const 1))))
+    (call $log (i32.const 16) (i32.const 11))
+    (i32.const 0))
+
+  (func (export "on_event") (param i32 i32 i32 i32) (result i32)
+    (global.set $last_ptr (local.get 2))
+    (global.set $last_len (local.get 3))
+    (i32.const 0))
+
+  (func (export "execute") (result i32)
+    (call $output (i32.const 128) (global.get $greeting_len))
+    (if (global.get $last_len)
+      (then
+        (call $output (i32.const 32) (i32.const 2))
+        (call $output (global.get $last_ptr) (global.get $last_len))))
+    (i32.const 0)))
+"#;
+
+/// A live instance: only exists between `initialize` and `cleanup`.
+struct Sandbox {
+    store: wasmi::Store<HostState>,
+    instance: wasmi::Instance,
+    limits: WasmLimits,
+}
+
+impl Sandbox {
+    /// Runs an export with a fresh fuel budget and maps its status, output
+    /// and traps to a plugin result. `Ok(None)` means it is not exported.
+    fn call(&mut self, export: &str, params: &[wasmi::Val]) -> Result<Option<String>, String> {
+        let Some(func) = self.instance.get_func(&self.store, export) else {
+            return Ok(None);
+        };
+        self.store.data_mut().output.clear();
+        self.store
+            .set_fuel(self.limits.fuel)
+            .map_err(|err| err.to_string())?;
+        let mut results = [wasmi::Val::I32(0)];
+        func.call(&mut self.store, params, &mut results)
+            .map_err(|err| describe_wasm_error(&err, &self.limits))?;
+        let output = std::mem::take(&mut self.store.data_mut().output);
+        match results[0] {
+            wasmi::Val::I32(0) => Ok(Some(output)),
+            wasmi::Val::I32(status) if output.is_empty() => {
+                Err(format!("{export} returned status {status}"))
+            }
+            _ => Err(output),
+        }
+    }
+
+    /// Copies `bytes` into guest memory obtained from the guest's `alloc`.
+    fn write_guest(&mut self, bytes: &[u8]) -> Result<i32, String> {
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "alloc")
+            .map_err(|_| "on_event requires an exported alloc(i32) -> i32".to_string())?;
+        let len = i32::try_from(bytes.len()).map_err(|_| "event too large".to_string())?;
+        self.store
+            .set_fuel(self.limits.fuel)
+            .map_err(|err| err.to_string())?;
+        let ptr = alloc
+            .call(&mut self.store, len)
+            .map_err(|err| describe_wasm_error(&err, &self.limits))?;
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or("plugin does not export memory")?;
+        memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|_| format!("alloc returned out-of-bounds pointer {ptr}"))?;
+        Ok(ptr)
+    }
+}
+
+fn describe_wasm_error(err: &wasmi::Error, limits: &WasmLimits) -> String {
+    use wasmi::core::TrapCode;
+
+    match err.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => format!("fuel limit of {} exhausted", limits.fuel),
+        Some(TrapCode::GrowthOperationLimited) => {
+            format!("memory limit of {} bytes exceeded", limits.max_memory)
+        }
+        Some(code) => format!("trapped: {code}"),
+        None => err.to_string(),
+    }
+}
+
+/// Reads a UTF-8 string out of the calling instance's exported memory.
+fn read_guest_str(
+    caller: &wasmi::Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<String, wasmi::Error> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(wasmi::Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("plugin does not export memory"))?;
+    let start = ptr as u32 as usize;
+    let bytes = start
+        .checked_add(len as u32 as usize)
+        .and_then(|end| memory.data(caller).get(start..end))
+        .ok_or_else(|| wasmi::Error::new("string out of bounds"))?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| wasmi::Error::new("string is not UTF-8"))
+}
+
+fn wasm_linker(
+    engine: &wasmi::Engine,
+    capabilities: &std::collections::BTreeSet<Capability>,
+) -> Result<wasmi::Linker<HostState>, String> {
+    let mut linker = wasmi::Linker::<HostState>::new(engine);
+    let result = (|| {
+        linker.func_wrap(
+            "env",
+            "output",
+            |mut caller: wasmi::Caller<'_, HostState>, ptr: i32, len: i32| {
+                let text = read_guest_str(&caller, ptr, len)?;
+                let output = &mut caller.data_mut().output;
+                if output.len() + text.len() > WASM_MAX_OUTPUT {
+                    return Err(wasmi::Error::new("output limit exceeded"));
+                }
+                output.push_str(&text);
+                Ok(())
+            },
+        )?;
+        if capabilities.contains(&Capability::Log) {
+            linker.func_wrap(
+                "env",
+                "log",
+                |mut caller: wasmi::Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let line = read_guest_str(&caller, ptr, len)?;
+                    caller.data_mut().logs.push(line);
+                    Ok(())
+                },
+            )?;
+        }
+        if capabilities.contains(&Capability::Config) {
+            linker.func_wrap(
+                "env",
+                "config_get",
+                |mut caller: wasmi::Caller<'_, HostState>,
+                 key_ptr: i32,
+                 key_len: i32,
+                 out_ptr: i32,
+                 out_cap: i32|
+                 -> Result<i32, wasmi::Error> {
+                    let key = read_guest_str(&caller, key_ptr, key_len)?;
+                    let Some(value) = caller.data().settings.get(&key).cloned() else {
+                        return Ok(-1);
+                    };
+                    let len = value.len().min(out_cap.max(0) as usize);
+                    let memory = caller
+                        .get_export("memory")
+                        .and_then(wasmi::Extern::into_memory)
+                        .ok_or_else(|| wasmi::Error::new("plugin does not export memory"))?;
+                    memory
+                        .write(
+                            &mut caller,
+                            out_ptr as u32 as usize,
+                            &value.as_bytes()[..len],
+                        )
+                        .map_err(|_| wasmi::Error::new("buffer out of bounds"))?;
+                    Ok(value.len() as i32)
+                },
+            )?;
+        }
+        if capabilities.contains(&Capability::Clock) {
+            linker.func_wrap("env", "clock_ms", || -> i64 {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis() as i64)
+            })?;
+        }
+        Ok::<_, wasmi::errors::LinkerError>(())
+    })();
+    result.map_err(|err| err.to_string())?;
+    Ok(linker)
+}
+
+/// A [`PluginWithLifecycle`] running untrusted WASM under fuel and memory
+/// limits, with host functions restricted to granted capabilities.
+struct WasmPlugin {
+    name: String,
+    version: String,
+    dependencies: Vec<String>,
+    engine: wasmi::Engine,
+    module: wasmi::Module,
+    // `execute` takes `&self`, but calling into WASM needs the store mutably
+    sandbox: std::cell::RefCell<Option<Sandbox>>,
+    logs: Vec<String>,
+}
+
+impl WasmPlugin {
+    /// Compiles a module from WAT text or a binary `.wasm` image.
+    fn new(name: &str, version: &str, source: impl AsRef<[u8]>) -> Result<Self, String> {
+        let wasm = wat::parse_bytes(source.as_ref()).map_err(|err| err.to_string())?;
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = wasmi::Module::new(&engine, &wasm[..]).map_err(|err| err.to_string())?;
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            dependencies: Vec::new(),
+            engine,
+            module,
+            sandbox: std::cell::RefCell::new(None),
+            logs: Vec::new(),
+        })
+    }
+
+    fn from_file(name: &str, version: &str, path: &Path) -> Result<Self, String> {
+        let source = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::new(name, version, source)
+    }
+
+    fn with_dependencies(mut self, dependencies: &[&str]) -> Self {
+        self.dependencies = dependencies.iter().map(|d| d.to_string()).collect();
+        self
+    }
+
+    /// Lines the plugin wrote through `env.log`, including from finished runs.
+    fn logs(&self) -> Vec<String> {
+        let mut logs = self.logs.clone();
+        if let Some(sandbox) = self.sandbox.borrow().as_ref() {
+            logs.extend(sandbox.store.data().logs.iter().cloned());
+        }
+        logs
+    }
+
+    /// Rejects imports outside `env` or not covered by a granted capability,
+    /// so a missing grant is reported by name instead of as a link error.
+    fn check_imports(&self, limits: &WasmLimits) -> Result<(), String> {
+        for import in self.module.imports() {
+            let (module, name) = (import.module(), import.name());
+            if module != "env" {
+                return Err(format!("unsupported import {module}.{name}"));
+            }
+            if name == "output" {
+                continue;
+            }
+            match Capability::for_import(name) {
+                Some(capability) if limits.capabilities.contains(&capability) => {}
+                Some(capability) => {
+                    return Err(format!(
+                        "import env.{name} needs capability {capability:?}, which is not granted"
+                    ))
+                }
+                None => return Err(format!("unknown host function env.{name}")),
+            }
+        }
+        Ok(())
+    }
+
+    fn with_sandbox<R>(
+        &self,
+        f: impl FnOnce(&mut Sandbox) -> Result<R, String>,
+    ) -> Result<R, String> {
+        let mut sandbox = self.sandbox.borrow_mut();
+        f(sandbox.as_mut().ok_or("Plugin not initialized")?)
+    }
+}
+
+impl PluginWithLifecycle for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn initialize(&mut self, config: &PluginConfig) -> Result<(), String> {
+        let limits = WasmLimits::from_config(config)?;
+        self.check_imports(&limits)?;
+
+        let settings = config
+            .settings
+            .iter()
+            .filter(|(key, _)| !key.starts_with("wasm."))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let store_limits = wasmi::StoreLimitsBuilder::new()
+            .memory_size(limits.max_memory)
+            .instances(1)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = wasmi::Store::new(
+            &self.engine,
+            HostState {
+                output: String::new(),
+                logs: Vec::new(),
+                settings,
+                limits: store_limits,
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(limits.fuel).map_err(|err| err.to_string())?;
+
+        let linker = wasm_linker(&self.engine, &limits.capabilities)?;
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| describe_wasm_error(&err, &limits))?;
+
+        let mut sandbox = Sandbox {
+            store,
+            instance,
+            limits,
+        };
+        sandbox.call("init", &[])?;
+        *self.sandbox.get_mut() = Some(sandbox);
+        Ok(())
+    }
+
+    fn execute(&self) -> Result<String, String> {
+        self.with_sandbox(|sandbox| {
+            sandbox
+                .call("execute", &[])?
+                .ok_or_else(|| "plugin does not export execute".to_string())
+        })
+    }
+
+    fn on_event(&mut self, name: &str, payload: &str) -> Result<(), String> {
+        self.with_sandbox(|sandbox| {
+            if sandbox
+                .instance
+                .get_func(&sandbox.store, "on_event")
+                .is_none()
+            {
+                return Ok(());
+            }
+            let name_ptr = sandbox.write_guest(name.as_bytes())?;
+            let payload_ptr = sandbox.write_guest(payload.as_bytes())?;
+            let params = [
+                wasmi::Val::I32(name_ptr),
+                wasmi::Val::I32(name.len() as i32),
+                wasmi::Val::I32(payload_ptr),
+                wasmi::Val::I32(payload.len() as i32),
+            ];
+            sandbox.call("on_event", &params).map(drop)
+        })
+    }
+
+    fn cleanup(&mut self) -> Result<(), String> {
+        let mut sandbox = self
+            .sandbox
+            .get_mut()
+            .take()
+            .ok_or("Plugin not initialized")?;
+        let result = sandbox.call("cleanup", &[]);
+        self.logs.append(&mut sandbox.store.data_mut().logs);
+        result.map(drop)
+    }
+}
+
+impl PluginMetadata for WasmPlugin {
+    fn author(&self) -> &str {
+        "unknown"
+    }
+
+    fn description(&self) -> &str {
+        "Sandboxed WASM plugin"
+    }
+
+    fn dependencies(&self) -> Vec<&str> {
+        self.dependencies.iter().map(String::as_str).collect()
+    }
+}
+
 fn main() {
     println!("=== Milestone 1: Static dispatch ===");
     let greeter = GreeterPlugin {
@@ -297,6 +1520,103 @@ fn main() {
     } else {
         println!("Cleanup errors: {errors:?}");
     }
+
+    println!("\n=== Milestone 4: Dependency-ordered host ===");
+    let mut host = PluginHost::new();
+    // registration order does not matter; Audit still starts after Logger
+    host.register(Box::new(AuditPlugin { sink: None }))
+        .expect("fresh host");
+    host.register(Box::new(LoggingPlugin::new()))
+        .expect("fresh host");
+    if let Some(path) = std::env::args().nth(1) {
+        match host.load_library(Path::new(&path)) {
+            Ok(name) => println!("Loaded dynamic plugin {name}"),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+
+    match host.resolve_order() {
+        Ok(order) => println!("Start order: {}", order.join(" -> ")),
+        Err(err) => eprintln!("{err}"),
+    }
+    match host.start(&config) {
+        Ok(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+        Err(err) => eprintln!("Cannot start plugins: {err}"),
+    }
+    for (plugin, state) in host.plugins() {
+        println!(
+            "  {} v{} by {} [{state:?}]: {}",
+            plugin.name(),
+            plugin.version(),
+            plugin.author(),
+            plugin.description()
+        );
+    }
+    for name in ["Logger", "Audit"] {
+        match host.execute(name) {
+            Ok(output) => println!("{name}: {output}"),
+            Err(err) => eprintln!("{err} (state: {:?})", host.state(name)),
+        }
+    }
+    for err in host.shutdown() {
+        eprintln!("{err}");
+    }
+
+    println!("\n=== Milestone 5: Sandboxed WASM plugin ===");
+    let mut config = PluginConfig::new();
+    config.set("greeting".to_string(), "Hello from WASM".to_string());
+    config.set("wasm.capabilities".to_string(), "log,config".to_string());
+    config.set("wasm.fuel".to_string(), "10000".to_string());
+
+    match WasmPlugin::new("Greeter", "0.1.0", DEMO_WASM_PLUGIN) {
+        Ok(mut greeter) => {
+            let result = greeter
+                .initialize(&config)
+                .and_then(|()| greeter.on_event("deploy", "v2 rolled out"))
+                .and_then(|()| greeter.execute());
+            match result {
+                Ok(output) => println!("Greeter: {output}"),
+                Err(err) => eprintln!("Greeter failed: {err}"),
+            }
+            let _ = greeter.cleanup();
+            println!("Greeter logs: {:?}", greeter.logs());
+        }
+        Err(err) => eprintln!("Cannot compile WASM plugin: {err}"),
+    }
+
+    // an optional second argument names a .wat/.wasm plugin to host
+    let Some(path) = std::env::args().nth(2) else {
+        return;
+    };
+    let mut host = PluginHost::new();
+    host.register(Box::new(LoggingPlugin::new()))
+        .expect("fresh host");
+    let name = Path::new(&path)
+        .file_stem()
+        .map_or("Wasm".into(), |stem| stem.to_string_lossy());
+    match WasmPlugin::from_file(&name, "0.1.0", Path::new(&path)) {
+        Ok(plugin) => host
+            .register(Box::new(plugin.with_dependencies(&["Logger"])))
+            .expect("unique name"),
+        Err(err) => {
+            eprintln!("Cannot load WASM plugin: {err}");
+            return;
+        }
+    }
+    match host.start(&config) {
+        Ok(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+        Err(err) => eprintln!("Cannot start plugins: {err}"),
+    }
+    for err in host.emit("deploy", "v2 rolled out") {
+        eprintln!("{err}");
+    }
+    match host.execute(&name) {
+        Ok(output) => println!("{name}: {output}"),
+        Err(err) => eprintln!("{err}"),
+    }
+    for err in host.shutdown() {
+        eprintln!("{err}");
+    }
 }
 
 #[cfg(test)]
@@ -386,4 +1706,518 @@ mod tests {
         assert!(plugin.description().contains("logging"));
         assert!(plugin.dependencies().is_empty());
     }
+
+    /// Test double with configurable dependencies and failure modes.
+    struct Stub {
+        name: &'static str,
+        version: &'static str,
+        deps: Vec<&'static str>,
+        panic_on_init: bool,
+        panic_on_execute: bool,
+        log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    fn stub(
+        name: &'static str,
+        deps: Vec<&'static str>,
+        log: &std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    ) -> Stub {
+        Stub {
+            name,
+            version: "1.0.0",
+            deps,
+            panic_on_init: false,
+            panic_on_execute: false,
+            log: log.clone(),
+        }
+    }
+
+    impl PluginWithLifecycle for Stub {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn version(&self) -> &str {
+            self.version
+        }
+
+        fn initialize(&mut self, _config: &PluginConfig) -> Result<(), String> {
+            if self.panic_on_init {
+                panic!("init exploded");
+            }
+            self.log.borrow_mut().push(format!("init {}", self.name));
+            Ok(())
+        }
+
+        fn execute(&self) -> Result<String, String> {
+            if self.panic_on_execute {
+                panic!("execute exploded");
+            }
+            Ok(self.name.to_string())
+        }
+
+        fn cleanup(&mut self) -> Result<(), String> {
+            self.log.borrow_mut().push(format!("cleanup {}", self.name));
+            Ok(())
+        }
+    }
+
+    impl PluginMetadata for Stub {
+        fn author(&self) -> &str {
+            "test"
+        }
+
+        fn description(&self) -> &str {
+            "stub"
+        }
+
+        fn dependencies(&self) -> Vec<&str> {
+            self.deps.clone()
+        }
+    }
+
+    #[test]
+    fn test_host_orders_by_dependencies() {
+        let log = Default::default();
+        let mut host = PluginHost::new();
+        host.register(Box::new(stub("App", vec!["Cache", "Db"], &log)))
+            .unwrap();
+        host.register(Box::new(stub("Cache", vec!["Db"], &log)))
+            .unwrap();
+        host.register(Box::new(stub("Db", vec![], &log))).unwrap();
+        host.register(Box::new(stub("Auth", vec![], &log))).unwrap();
+        assert_eq!(
+            host.register(Box::new(stub("Db", vec![], &log))),
+            Err(PluginError::Duplicate("Db".to_string()))
+        );
+
+        assert_eq!(
+            host.resolve_order().unwrap(),
+            ["Auth", "Db", "Cache", "App"]
+        );
+        assert!(host.start(&PluginConfig::new()).unwrap().is_empty());
+        assert_eq!(host.execute("App").unwrap(), "App");
+        assert!(host.shutdown().is_empty());
+        assert_eq!(
+            *log.borrow(),
+            [
+                "init Auth",
+                "init Db",
+                "init Cache",
+                "init App",
+                "cleanup App",
+                "cleanup Cache",
+                "cleanup Db",
+                "cleanup Auth"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_host_rejects_bad_dependency_graphs() {
+        let log = Default::default();
+        let mut host = PluginHost::new();
+        host.register(Box::new(stub("A", vec!["B"], &log))).unwrap();
+        host.register(Box::new(stub("B", vec!["C"], &log))).unwrap();
+        host.register(Box::new(stub("C", vec!["A"], &log))).unwrap();
+        host.register(Box::new(stub("D", vec![], &log))).unwrap();
+        assert_eq!(
+            host.resolve_order(),
+            Err(PluginError::Cycle(
+                ["A", "B", "C", "A"].map(String::from).to_vec()
+            ))
+        );
+        assert!(host.start(&PluginConfig::new()).is_err());
+        assert!(log.borrow().is_empty());
+
+        let mut host = PluginHost::new();
+        host.register(Box::new(stub("A", vec!["Missing"], &log)))
+            .unwrap();
+        assert_eq!(
+            host.resolve_order(),
+            Err(PluginError::MissingDependency {
+                plugin: "A".to_string(),
+                dependency: "Missing".to_string(),
+            })
+        );
+
+        let mut host = PluginHost::new();
+        host.register(Box::new(AuditPlugin { sink: None })).unwrap();
+        host.register(Box::new(stub("Logger", vec![], &log)))
+            .unwrap();
+        let err = host.resolve_order().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'Audit' requires 'Logger' >= 2.0.0, found 1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_version_requirements_pad_missing_components() {
+        assert!(version_at_least("2.0", "2.0.0"));
+        assert!(version_at_least("2.0.0", "2"));
+        assert!(version_at_least("2.1", "2.0.5"));
+        assert!(version_at_least("10.0.0", "9.9"));
+        assert!(!version_at_least("2", "2.0.1"));
+        assert!(!version_at_least("1.9.9", "2.0"));
+    }
+
+    #[test]
+    fn test_host_isolates_panicking_plugins() {
+        let log = Default::default();
+        let mut host = PluginHost::new();
+        let mut broken = stub("Broken", vec![], &log);
+        broken.panic_on_init = true;
+        let mut flaky = stub("Flaky", vec![], &log);
+        flaky.panic_on_execute = true;
+        host.register(Box::new(broken)).unwrap();
+        host.register(Box::new(stub("Dependent", vec!["Broken"], &log)))
+            .unwrap();
+        host.register(Box::new(flaky)).unwrap();
+        host.register(Box::new(stub("Healthy", vec![], &log)))
+            .unwrap();
+
+        let errors = host.start(&PluginConfig::new()).unwrap();
+        assert_eq!(
+            errors,
+            [
+                PluginError::Panicked {
+                    plugin: "Broken".to_string(),
+                    message: "init exploded".to_string(),
+                },
+                PluginError::DependencyUnavailable {
+                    plugin: "Dependent".to_string(),
+                    dependency: "Broken".to_string(),
+                },
+            ]
+        );
+        assert_eq!(host.state("Broken"), Some(PluginState::Failed));
+        assert_eq!(host.state("Dependent"), Some(PluginState::Skipped));
+        assert_eq!(host.state("Healthy"), Some(PluginState::Running));
+
+        assert!(matches!(
+            host.execute("Flaky"),
+            Err(PluginError::Panicked { .. })
+        ));
+        assert_eq!(
+            host.execute("Flaky"),
+            Err(PluginError::NotRunning("Flaky".to_string()))
+        );
+        assert_eq!(host.execute("Healthy").unwrap(), "Healthy");
+
+        assert!(host.shutdown().is_empty());
+        assert_eq!(
+            *log.borrow(),
+            ["init Flaky", "init Healthy", "cleanup Healthy"]
+        );
+    }
+
+    const DYNAMIC_PLUGIN_SOURCE: &str = r#"
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[repr(C)]
+pub struct PluginVTable {
+    abi_version: u32,
+    name: extern "C" fn() -> *const c_char,
+    version: extern "C" fn() -> *const c_char,
+    dependencies: extern "C" fn() -> *const c_char,
+    create: extern "C" fn() -> *mut c_void,
+    initialize: extern "C" fn(*mut c_void, *const c_char, *mut *mut c_char) -> i32,
+    execute: extern "C" fn(*mut c_void, *mut *mut c_char) -> i32,
+    cleanup: extern "C" fn(*mut c_void, *mut *mut c_char) -> i32,
+    destroy: extern "C" fn(*mut c_void),
+    free_string: extern "C" fn(*mut c_char),
+}
+
+struct State {
+    greeting: String,
+}
+
+fn guard(out: *mut *mut c_char, f: impl FnOnce() -> Result<String, String>) -> i32 {
+    let (status, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(message)) => (0, message),
+        Ok(Err(message)) => (1, message),
+        Err(_) => (2, "shouter panicked".to_string()),
+    };
+    unsafe { *out = CString::new(message).unwrap().into_raw() };
+    status
+}
+
+extern "C" fn name() -> *const c_char { c"Shouter".as_ptr() }
+extern "C" fn version() -> *const c_char { c"0.3.0".as_ptr() }
+extern "C" fn dependencies() -> *const c_char { c"Logger>=2.0.0".as_ptr() }
+extern "C" fn create() -> *mut c_void {
+    Box::into_raw(Box::new(State { greeting: String::new() })).cast()
+}
+extern "C" fn initialize(instance: *mut c_void, config: *const c_char, out: *mut *mut c_char) -> i32 {
+    guard(out, || {
+        let state = unsafe { &mut *instance.cast::<State>() };
+        let config = unsafe { CStr::from_ptr(config) }.to_str().map_err(|e| e.to_string())?;
+        state.greeting = config
+            .lines()
+            .find_map(|line| line.strip_prefix("greeting="))
+            .ok_or("greeting not configured")?
+            .to_string();
+        Ok(String::new())
+    })
+}
+extern "C" fn execute(instance: *mut c_void, out: *mut *mut c_char) -> i32 {
+    guard(out, || {
+        let state = unsafe { &*instance.cast::<State>() };
+        if state.greeting == "panic" {
+            panic!("boom");
+        }
+        Ok(state.greeting.to_uppercase())
+    })
+}
+extern "C" fn cleanup(_instance: *mut c_void, out: *mut *mut c_char) -> i32 {
+    guard(out, || Ok(String::new()))
+}
+extern "C" fn destroy(instance: *mut c_void) {
+    drop(unsafe { Box::from_raw(instance.cast::<State>()) });
+}
+extern "C" fn free_string(s: *mut c_char) {
+    drop(unsafe { CString::from_raw(s) });
+}
+
+static VTABLE: PluginVTable = PluginVTable {
+    abi_version: ABI_VERSION,
+    name, version, dependencies, create, initialize, execute, cleanup, destroy, free_string,
+};
+
+#[no_mangle]
+pub extern "C" fn plugin_entry() -> *const PluginVTable {
+    &VTABLE
+}
+"#;
+
+    /// Builds the test plugin as a cdylib with `$RUSTC` (or `rustc`), or
+    /// `None` when that compiler cannot be started.
+    fn build_dynamic_plugin(dir: &Path, abi_version: u32) -> Option<std::path::PathBuf> {
+        let source = dir.join(format!("shouter_v{abi_version}.rs"));
+        std::fs::write(
+            &source,
+            format!("const ABI_VERSION: u32 = {abi_version};\n{DYNAMIC_PLUGIN_SOURCE}"),
+        )
+        .unwrap();
+        let library = dir.join(format!(
+            "{}shouter_v{abi_version}{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = match std::process::Command::new(&rustc)
+            .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+            .arg(&library)
+            .arg(&source)
+            .status()
+        {
+            Ok(status) => status,
+            Err(err) => {
+                eprintln!("skipping dynamic plugin test: cannot run {rustc}: {err}");
+                return None;
+            }
+        };
+        assert!(status.success(), "{rustc} failed to build {}", source.display());
+        Some(library)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_load_dynamic_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let Some(library) = build_dynamic_plugin(dir.path(), PLUGIN_ABI_VERSION) else {
+            return;
+        };
+        let wrong_abi = build_dynamic_plugin(dir.path(), PLUGIN_ABI_VERSION + 1).unwrap();
+
+        let mut host = PluginHost::new();
+        assert_eq!(
+            host.load_library(&wrong_abi).err(),
+            Some(PluginError::AbiMismatch {
+                expected: PLUGIN_ABI_VERSION,
+                found: PLUGIN_ABI_VERSION + 1,
+            })
+        );
+        assert!(matches!(
+            host.load_library(&dir.path().join("missing.so")),
+            Err(PluginError::Load(_))
+        ));
+
+        assert_eq!(host.load_library(&library).unwrap(), "Shouter");
+        assert!(matches!(
+            host.resolve_order(),
+            Err(PluginError::MissingDependency { .. })
+        ));
+        host.register(Box::new(LoggingPlugin::new())).unwrap();
+        assert_eq!(host.resolve_order().unwrap(), ["Logger", "Shouter"]);
+
+        let mut config = PluginConfig::new();
+        config.set("greeting".to_string(), "hello".to_string());
+        assert!(host.start(&config).unwrap().is_empty());
+        assert_eq!(host.execute("Shouter").unwrap(), "HELLO");
+        assert!(host.shutdown().is_empty());
+
+        // a panic inside the library is caught there and surfaced as one here
+        let mut host = PluginHost::new();
+        host.register(Box::new(LoggingPlugin::new())).unwrap();
+        host.load_library(&library).unwrap();
+        let mut config = PluginConfig::new();
+        config.set("greeting".to_string(), "panic".to_string());
+        assert!(host.start(&config).unwrap().is_empty());
+        assert_eq!(
+            host.execute("Shouter"),
+            Err(PluginError::Panicked {
+                plugin: "Shouter".to_string(),
+                message: "shouter panicked".to_string(),
+            })
+        );
+        assert_eq!(host.execute("Logger").unwrap(), "Logging at level: INFO");
+        assert!(host.shutdown().is_empty());
+    }
+
+    fn wasm_config(capabilities: &str) -> PluginConfig {
+        let mut config = PluginConfig::new();
+        config.set("greeting".to_string(), "hi".to_string());
+        config.set("wasm.capabilities".to_string(), capabilities.to_string());
+        config
+    }
+
+    #[test]
+    fn test_wasm_plugin_lifecycle_and_events() {
+        let mut plugin = WasmPlugin::new("Greeter", "0.1.0", DEMO_WASM_PLUGIN).unwrap();
+        assert_eq!(plugin.execute(), Err("Plugin not initialized".to_string()));
+
+        plugin.initialize(&wasm_config("log, config")).unwrap();
+        assert_eq!(plugin.execute().unwrap(), "hi");
+        plugin.on_event("deploy", "v2").unwrap();
+        plugin.on_event("deploy", "v3").unwrap();
+        assert_eq!(plugin.execute().unwrap(), "hi: v3");
+        plugin.cleanup().unwrap();
+        assert_eq!(plugin.logs(), ["initialized"]);
+
+        // a non-zero status surfaces whatever the plugin wrote as the error
+        let mut config = wasm_config("log,config");
+        config.settings.remove("greeting");
+        assert_eq!(
+            plugin.initialize(&config),
+            Err("greeting not configured".to_string())
+        );
+    }
+
+    #[test]
+    fn test_wasm_capabilities_come_from_config() {
+        let mut plugin = WasmPlugin::new("Greeter", "0.1.0", DEMO_WASM_PLUGIN).unwrap();
+        let err = plugin.initialize(&wasm_config("log")).unwrap_err();
+        assert!(
+            err.contains("env.config_get needs capability Config"),
+            "{err}"
+        );
+        let err = plugin.initialize(&wasm_config("log,net")).unwrap_err();
+        assert_eq!(err, "unknown capability 'net'");
+
+        // limits are host policy and are not readable through config_get
+        let mut config = wasm_config("log,config");
+        config.settings.remove("greeting");
+        config.set("wasm.greeting".to_string(), "leaked".to_string());
+        assert_eq!(
+            plugin.initialize(&config),
+            Err("greeting not configured".to_string())
+        );
+    }
+
+    const SPIN_PLUGIN: &str = r#"
+(module
+  (global $calls (mut i32) (i32.const 0))
+  (func (export "execute") (result i32)
+    (global.set $calls (i32.add (global.get $calls) (i32.const 1)))
+    ;; every other call loops forever
+    (if (i32.rem_u (global.get $calls) (i32.const 2))
+      (then (loop $forever (br $forever))))
+    (i32.const 0)))
+"#;
+
+    #[test]
+    fn test_wasm_fuel_limit_is_per_call() {
+        let mut plugin = WasmPlugin::new("Spin", "0.1.0", SPIN_PLUGIN).unwrap();
+        let mut config = wasm_config("");
+        config.set("wasm.fuel".to_string(), "5000".to_string());
+        plugin.initialize(&config).unwrap();
+
+        assert_eq!(
+            plugin.execute(),
+            Err("fuel limit of 5000 exhausted".to_string())
+        );
+        // the budget is refilled, so the next (terminating) call succeeds
+        assert_eq!(plugin.execute(), Ok(String::new()));
+        assert!(plugin.execute().is_err());
+    }
+
+    const GROW_PLUGIN: &str = r#"
+(module
+  (import "env" "output" (func $output (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "grew")
+  (func (export "execute") (result i32)
+    (drop (memory.grow (i32.const 1)))
+    (call $output (i32.const 0) (i32.const 4))
+    (i32.const 0)))
+"#;
+
+    #[test]
+    fn test_wasm_memory_cap() {
+        let two_pages = (2 * 65536).to_string();
+        let mut plugin = WasmPlugin::new("Grow", "0.1.0", GROW_PLUGIN).unwrap();
+        let mut config = wasm_config("");
+        config.set("wasm.max_memory".to_string(), two_pages.clone());
+        plugin.initialize(&config).unwrap();
+
+        assert_eq!(plugin.execute().unwrap(), "grew");
+        assert_eq!(
+            plugin.execute(),
+            Err(format!("memory limit of {two_pages} bytes exceeded"))
+        );
+
+        // a module whose initial memory is already too large never starts
+        let big = GROW_PLUGIN.replace(
+            "(memory (export \"memory\") 1)",
+            "(memory (export \"memory\") 4)",
+        );
+        let mut plugin = WasmPlugin::new("Big", "0.1.0", big).unwrap();
+        assert!(plugin.initialize(&config).is_err());
+        assert_eq!(plugin.execute(), Err("Plugin not initialized".to_string()));
+    }
+
+    #[test]
+    fn test_host_drives_wasm_plugins() {
+        let log = Default::default();
+        let mut host = PluginHost::new();
+        host.register(Box::new(
+            WasmPlugin::new("Greeter", "0.1.0", DEMO_WASM_PLUGIN)
+                .unwrap()
+                .with_dependencies(&["Base"]),
+        ))
+        .unwrap();
+        host.register(Box::new(
+            WasmPlugin::new("Spin", "0.1.0", SPIN_PLUGIN).unwrap(),
+        ))
+        .unwrap();
+        host.register(Box::new(stub("Base", vec![], &log))).unwrap();
+
+        let mut config = wasm_config("log,config");
+        config.set("wasm.fuel".to_string(), "5000".to_string());
+        assert!(host.start(&config).unwrap().is_empty());
+        assert_eq!(host.resolve_order().unwrap(), ["Base", "Greeter", "Spin"]);
+
+        assert!(host.emit("deploy", "v2").is_empty());
+        assert_eq!(host.execute("Greeter").unwrap(), "hi: v2");
+        assert!(matches!(
+            host.execute("Spin"),
+            Err(PluginError::Failed { .. })
+        ));
+        // a runaway plugin does not affect the others
+        assert_eq!(host.execute("Greeter").unwrap(), "hi: v2");
+        assert!(host.shutdown().is_empty());
+    }
 }
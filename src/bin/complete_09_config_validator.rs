@@ -1,7 +1,8 @@
 use colored::Colorize;
 use regex::Regex;
+use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 use thiserror::Error;
@@ -51,6 +52,21 @@ pub enum ConfigError {
         min: i64,
         max: i64,
     },
+
+    #[error("Unknown field '{field}'")]
+    UnknownField {
+        field: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("{message}")]
+    RuleViolation { field: String, message: String },
+
+    #[error("Field '{field}' is deprecated: {note}")]
+    Deprecated { field: String, note: String },
+
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
 }
 
 impl ConfigError {
@@ -101,7 +117,8 @@ impl ConfigParser {
     }
 
     pub fn parse_toml(content: &str) -> Result<Value, ConfigError> {
-        let toml_value: toml::Value = toml::from_str(content).map_err(ConfigError::from)?;
+        let toml_value: toml::Value =
+            toml::from_str(content).map_err(|err| toml_parse_error(&err, content))?;
         serde_json::to_value(toml_value)
             .map_err(|err| ConfigError::parse_error(0, 0, format!("Failed to convert TOML: {err}")))
     }
@@ -146,6 +163,18 @@ impl From<toml::de::Error> for ConfigError {
     }
 }
 
+/// Like the `From` conversion, but resolves the error span to a line and
+/// column in `content`.
+fn toml_parse_error(err: &toml::de::Error, content: &str) -> ConfigError {
+    match err.span() {
+        Some(span) => {
+            let location = location_at(content, span.start);
+            ConfigError::parse_error(location.line, location.column, err.message())
+        }
+        None => ConfigError::from(err.clone()),
+    }
+}
+
 // =============================================================================
 // Milestone 3: Validation with error accumulation
 // =============================================================================
@@ -615,12 +644,16 @@ impl ErrorFormatter {
                     output.push_str(&Self::format_context(location.line, location.column, lines));
                 }
             }
-            ConfigError::MissingField { suggestion, .. } => {
+            ConfigError::MissingField { suggestion, .. }
+            | ConfigError::UnknownField { suggestion, .. } => {
                 if let Some(s) = suggestion {
                     output.push_str(&format!("   Hint: Did you mean '{}'?\n", s));
                 }
             }
-            ConfigError::OutOfRange { .. } => {}
+            ConfigError::OutOfRange { .. }
+            | ConfigError::RuleViolation { .. }
+            | ConfigError::Deprecated { .. }
+            | ConfigError::InvalidSchema(_) => {}
         }
 
         output
@@ -676,6 +709,20 @@ impl ErrorFormatter {
     }
 }
 
+/// Errors as in [`ErrorFormatter::format_errors`], followed by any
+/// deprecation warnings.
+pub fn format_schema_report(report: &SchemaReport, source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut output = ErrorFormatter::format_errors(&report.errors, source);
+    if !report.warnings.is_empty() {
+        output.push_str(&format!("\n{}\n", "Warnings".bold().yellow()));
+        for (idx, warning) in report.warnings.iter().enumerate() {
+            output.push_str(&ErrorFormatter::format_single_error(warning, idx + 1, &lines));
+        }
+    }
+    output
+}
+
 pub fn format_validation_result(result: Result<(), Vec<ConfigError>>, source: &str) -> String {
     match result {
         Ok(()) => "Configuration valid".green().to_string(),
@@ -683,6 +730,1102 @@ pub fn format_validation_result(result: Result<(), Vec<ConfigError>>, source: &s
     }
 }
 
+// =============================================================================
+// Milestone 7: Declarative schema documents
+// =============================================================================
+//
+// A schema document describes a config contract without Rust code:
+//
+//   version = "2"
+//   service = "billing"
+//
+//   [fields."database.port"]          # dotted keys create implicit tables
+//   type = "integer"
+//   min = 1
+//   max = 65535
+//   default = 5432
+//
+//   [fields.upstreams]                # an array of tables
+//   type = "array"
+//   min_items = 1
+//   [fields.upstreams.items.fields.url]
+//   type = "string"
+//   required = true
+//   pattern = "^https?://"
+//
+//   [[rules]]                         # cross-field rule, relative to its table
+//   when = "tls.enabled"
+//   require = ["tls.cert", "tls.key"]
+//
+// The same structure can be written as JSON.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Integer,
+    #[serde(alias = "number")]
+    Float,
+    #[serde(alias = "bool")]
+    Boolean,
+    Array,
+    #[serde(alias = "object")]
+    Table,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+            FieldType::Float => "float",
+            FieldType::Boolean => "boolean",
+            FieldType::Array => "array",
+            FieldType::Table => "table",
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Float => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Array => value.is_array(),
+            FieldType::Table => value.is_object(),
+        }
+    }
+}
+
+/// One field as written in the schema document.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSpec {
+    #[serde(rename = "type")]
+    pub kind: Option<FieldType>,
+    #[serde(default)]
+    pub required: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    #[serde(rename = "enum")]
+    pub allowed: Option<Vec<Value>>,
+    pub pattern: Option<String>,
+    pub default: Option<Value>,
+    /// Migration note shown as a warning whenever the field is set.
+    pub deprecated: Option<String>,
+    pub min_items: Option<usize>,
+    pub max_items: Option<usize>,
+    /// Schema for every element of an array.
+    pub items: Option<Box<FieldSpec>>,
+    /// Sub-fields of a table; keys may be dotted paths.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldSpec>,
+    #[serde(default)]
+    pub rules: Vec<RuleSpec>,
+    /// Accept keys not listed in `fields` instead of reporting them.
+    #[serde(default)]
+    pub allow_unknown: bool,
+}
+
+/// "If `when` holds, `require` must be set and `forbid` must not be."
+/// `when` holds if the field equals `equals`, or, without `equals`, if it is
+/// set to anything but `false` or null. Paths are relative to the table the
+/// rule is declared in.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSpec {
+    pub when: String,
+    pub equals: Option<Value>,
+    #[serde(default)]
+    pub require: Vec<String>,
+    #[serde(default)]
+    pub forbid: Vec<String>,
+    pub message: Option<String>,
+}
+
+impl RuleSpec {
+    fn applies(&self, table: &Value) -> bool {
+        match (get_nested_value(table, &self.when), &self.equals) {
+            (Some(value), Some(expected)) => value == expected,
+            (Some(value), None) => !matches!(value, Value::Null | Value::Bool(false)),
+            (None, _) => false,
+        }
+    }
+
+    /// `table` is the path of the table the rule is declared in.
+    fn describe_condition(&self, table: &str) -> String {
+        let when = join_path(table, &self.when);
+        match &self.equals {
+            Some(expected) => format!("'{when}' is {expected}"),
+            None => format!("'{when}' is set"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaDocument {
+    version: Option<String>,
+    service: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, FieldSpec>,
+    #[serde(default)]
+    rules: Vec<RuleSpec>,
+    #[serde(default)]
+    allow_unknown: bool,
+}
+
+/// A [`FieldSpec`] after dotted keys are expanded and patterns compiled.
+#[derive(Debug, Default)]
+struct CompiledField {
+    spec: FieldSpec,
+    pattern: Option<Regex>,
+    items: Option<Box<CompiledField>>,
+    fields: BTreeMap<String, CompiledField>,
+    /// Created by a dotted key rather than declared; such a table is only
+    /// optional if nothing inside it is required.
+    implicit: bool,
+}
+
+impl CompiledField {
+    fn compile(mut spec: FieldSpec, path: &str) -> Result<Self, ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidSchema(format!("{path}: {reason}"));
+
+        if !spec.fields.is_empty() && !matches!(spec.kind, None | Some(FieldType::Table)) {
+            return Err(invalid("only tables can declare fields".to_string()));
+        }
+        if spec.items.is_some() && spec.kind != Some(FieldType::Array) {
+            return Err(invalid("only arrays can declare items".to_string()));
+        }
+        let pattern = spec
+            .pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|err| invalid(format!("bad pattern: {err}")))?;
+        if let (Some(kind), Some(default)) = (spec.kind, &spec.default) {
+            if !kind.accepts(default) {
+                return Err(invalid(format!(
+                    "default {default} is not a {}",
+                    kind.name()
+                )));
+            }
+        }
+
+        let mut fields = BTreeMap::new();
+        for (key, child) in std::mem::take(&mut spec.fields) {
+            insert_dotted(&mut fields, &key, child, path)?;
+        }
+        let items = match spec.items.take() {
+            Some(items) => Some(Box::new(Self::compile(*items, &format!("{path}[]"))?)),
+            None => None,
+        };
+        Ok(Self {
+            spec,
+            pattern,
+            items,
+            fields,
+            implicit: false,
+        })
+    }
+
+    fn has_defaults(&self) -> bool {
+        self.spec.default.is_some() || self.fields.values().any(Self::has_defaults)
+    }
+
+    /// Whether a config missing this table still has required keys in it.
+    fn has_required(&self) -> bool {
+        self.fields
+            .values()
+            .any(|child| child.spec.required || (child.implicit && child.has_required()))
+    }
+}
+
+/// Places `spec` at a dotted `key`, creating implicit optional tables for the
+/// leading segments and merging with tables declared elsewhere.
+fn insert_dotted(
+    fields: &mut BTreeMap<String, CompiledField>,
+    key: &str,
+    spec: FieldSpec,
+    parent: &str,
+) -> Result<(), ConfigError> {
+    let (head, rest) = match key.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (key, None),
+    };
+    let path = join_path(parent, head);
+    match rest {
+        Some(rest) => {
+            let table = fields
+                .entry(head.to_string())
+                .or_insert_with(|| CompiledField {
+                    implicit: true,
+                    ..CompiledField::default()
+                });
+            if !matches!(table.spec.kind, None | Some(FieldType::Table)) {
+                return Err(ConfigError::InvalidSchema(format!("{path}: not a table")));
+            }
+            insert_dotted(&mut table.fields, rest, spec, &path)
+        }
+        None => {
+            let mut compiled = CompiledField::compile(spec, &path)?;
+            // merge sub-fields already placed here through `a.b` style keys
+            if let Some(implicit) = fields.remove(head) {
+                for (name, child) in implicit.fields {
+                    if compiled.fields.insert(name.clone(), child).is_some() {
+                        return Err(ConfigError::InvalidSchema(format!(
+                            "{}: declared twice",
+                            join_path(&path, &name)
+                        )));
+                    }
+                }
+            }
+            if !compiled.fields.is_empty()
+                && !matches!(compiled.spec.kind, None | Some(FieldType::Table))
+            {
+                return Err(ConfigError::InvalidSchema(format!("{path}: not a table")));
+            }
+            fields.insert(head.to_string(), compiled);
+            Ok(())
+        }
+    }
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+/// Outcome of [`DeclarativeSchema::validate`]: the config with defaults
+/// filled in, hard errors, and deprecation warnings.
+#[derive(Debug)]
+pub struct SchemaReport {
+    pub config: Value,
+    pub errors: Vec<ConfigError>,
+    pub warnings: Vec<ConfigError>,
+}
+
+impl SchemaReport {
+    pub fn into_result(self) -> Result<Value, Vec<ConfigError>> {
+        if self.errors.is_empty() {
+            Ok(self.config)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// A config contract loaded from a TOML or JSON schema document.
+#[derive(Debug)]
+pub struct DeclarativeSchema {
+    pub version: Option<String>,
+    pub service: Option<String>,
+    root: CompiledField,
+}
+
+impl DeclarativeSchema {
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let document: SchemaDocument =
+            toml::from_str(content).map_err(|err| toml_parse_error(&err, content))?;
+        Self::compile(document)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let document: SchemaDocument = serde_json::from_str(content).map_err(ConfigError::from)?;
+        Self::compile(document)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            ConfigError::parse_error(0, 0, format!("Failed to read {}: {err}", path.display()))
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&content),
+            _ => Self::from_toml(&content),
+        }
+    }
+
+    fn compile(document: SchemaDocument) -> Result<Self, ConfigError> {
+        let root = FieldSpec {
+            kind: Some(FieldType::Table),
+            required: true,
+            fields: document.fields,
+            rules: document.rules,
+            allow_unknown: document.allow_unknown,
+            ..FieldSpec::default()
+        };
+        Ok(Self {
+            version: document.version,
+            service: document.service,
+            root: CompiledField::compile(root, "")?,
+        })
+    }
+
+    pub fn validate(&self, config: &Value) -> SchemaReport {
+        let mut report = SchemaReport {
+            config: config.clone(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let mut value = std::mem::take(&mut report.config);
+        check_field(&self.root, "", &mut value, &mut report);
+        report.config = value;
+        report
+    }
+}
+
+fn check_field(field: &CompiledField, path: &str, value: &mut Value, report: &mut SchemaReport) {
+    let spec = &field.spec;
+    let display_path = if path.is_empty() { "root" } else { path };
+
+    if let Some(note) = &spec.deprecated {
+        report.warnings.push(ConfigError::Deprecated {
+            field: display_path.to_string(),
+            note: note.clone(),
+        });
+    }
+
+    let kind = spec
+        .kind
+        .or_else(|| (!field.fields.is_empty()).then_some(FieldType::Table));
+    if let Some(kind) = kind {
+        if !kind.accepts(value) {
+            report.errors.push(ConfigError::invalid_type(
+                display_path,
+                kind.name(),
+                value_type_name(value),
+                Location { line: 0, column: 0 },
+            ));
+            return;
+        }
+    }
+
+    if let Some(allowed) = &spec.allowed {
+        if !allowed.contains(value) {
+            let choices: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            let mut reason = format!("must be one of {}", choices.join(", "));
+            if let Some(text) = value.as_str() {
+                let names: Vec<&str> = allowed.iter().filter_map(Value::as_str).collect();
+                if let Some(similar) = find_similar_field(text, &names) {
+                    reason.push_str(&format!(" (did you mean '{similar}'?)"));
+                }
+            }
+            report
+                .errors
+                .push(invalid_value(display_path, value, reason));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        check_range(spec, display_path, value, number, report);
+    }
+
+    if let (Some(pattern), Some(text)) = (&field.pattern, value.as_str()) {
+        if !pattern.is_match(text) {
+            report.errors.push(invalid_value(
+                display_path,
+                value,
+                format!("does not match pattern {}", pattern.as_str()),
+            ));
+        }
+    }
+
+    if let Value::Array(elements) = value {
+        let len = elements.len();
+        if spec.min_items.is_some_and(|min| len < min)
+            || spec.max_items.is_some_and(|max| len > max)
+        {
+            let bounds = match (spec.min_items, spec.max_items) {
+                (Some(min), Some(max)) => format!("between {min} and {max}"),
+                (Some(min), None) => format!("at least {min}"),
+                (None, _) => format!("at most {}", spec.max_items.unwrap_or_default()),
+            };
+            report.errors.push(invalid_value(
+                display_path,
+                &Value::from(len),
+                format!("must have {bounds} items"),
+            ));
+        }
+        if let Some(items) = &field.items {
+            for (index, element) in elements.iter_mut().enumerate() {
+                check_field(items, &format!("{path}[{index}]"), element, report);
+            }
+        }
+    }
+
+    if let Value::Object(_) = value {
+        check_table(field, path, value, report);
+    }
+}
+
+fn check_range(
+    spec: &FieldSpec,
+    path: &str,
+    value: &Value,
+    number: f64,
+    report: &mut SchemaReport,
+) {
+    let below = spec.min.is_some_and(|min| number < min);
+    let above = spec.max.is_some_and(|max| number > max);
+    if !below && !above {
+        return;
+    }
+    let integral = |bound: f64| bound.fract() == 0.0 && bound.abs() < i64::MAX as f64;
+    match (value.as_i64(), spec.min, spec.max) {
+        (Some(value), Some(min), Some(max)) if integral(min) && integral(max) => {
+            report.errors.push(ConfigError::OutOfRange {
+                field: path.to_string(),
+                value,
+                min: min as i64,
+                max: max as i64,
+            });
+        }
+        _ => {
+            let reason = match (spec.min, spec.max) {
+                (Some(min), Some(max)) => format!("must be between {min} and {max}"),
+                (Some(min), None) => format!("must be at least {min}"),
+                (None, _) => format!("must be at most {}", spec.max.unwrap_or_default()),
+            };
+            report.errors.push(invalid_value(path, value, reason));
+        }
+    }
+}
+
+fn check_table(field: &CompiledField, path: &str, value: &mut Value, report: &mut SchemaReport) {
+    let Value::Object(table) = value else {
+        return;
+    };
+    let section = if path.is_empty() { "root" } else { path };
+
+    if !field.fields.is_empty() && !field.spec.allow_unknown {
+        let known: Vec<&str> = field.fields.keys().map(String::as_str).collect();
+        for key in table.keys() {
+            if !field.fields.contains_key(key) {
+                report.errors.push(ConfigError::UnknownField {
+                    field: join_path(path, key),
+                    suggestion: find_similar_field(key, &known),
+                });
+            }
+        }
+    }
+
+    for (name, child) in &field.fields {
+        let child_path = join_path(path, name);
+        if !table.contains_key(name) {
+            if let Some(default) = &child.spec.default {
+                table.insert(name.clone(), default.clone());
+                continue;
+            }
+            if child.spec.required {
+                report
+                    .errors
+                    .push(ConfigError::missing_field(section, name, None));
+                continue;
+            }
+            if !child.has_defaults() {
+                if child.implicit && child.has_required() {
+                    // report the required keys of a dotted section left out entirely
+                    let mut empty = Value::Object(serde_json::Map::new());
+                    check_field(child, &child_path, &mut empty, report);
+                }
+                continue;
+            }
+            // materialize an implicit table so nested defaults have a home
+            table.insert(name.clone(), Value::Object(serde_json::Map::new()));
+        }
+        if let Some(child_value) = table.get_mut(name) {
+            check_field(child, &child_path, child_value, report);
+        }
+    }
+
+    for rule in &field.spec.rules {
+        if !rule.applies(value) {
+            continue;
+        }
+        let violations = rule
+            .require
+            .iter()
+            .filter(|target| get_nested_value(value, target).is_none())
+            .map(|target| (target, "required"))
+            .chain(
+                rule.forbid
+                    .iter()
+                    .filter(|target| get_nested_value(value, target).is_some())
+                    .map(|target| (target, "not allowed")),
+            );
+        for (target, verb) in violations {
+            let field = join_path(path, target);
+            let message = rule.message.clone().unwrap_or_else(|| {
+                format!("'{field}' is {verb} when {}", rule.describe_condition(path))
+            });
+            report
+                .errors
+                .push(ConfigError::RuleViolation { field, message });
+        }
+    }
+}
+
+fn invalid_value(path: &str, value: &Value, reason: String) -> ConfigError {
+    ConfigError::InvalidValue {
+        field: path.to_string(),
+        value: value.to_string(),
+        reason,
+        location: Location { line: 0, column: 0 },
+    }
+}
+// =============================================================================
+// Milestone 8: Source spans, rustc-style snippets and SARIF
+// =============================================================================
+
+/// Byte range into the original source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl From<std::ops::Range<usize>> for Span {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        Span {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+/// Where every key and value of a parsed config sits in its source. Paths
+/// use the notation of validation errors: `database.port`,
+/// `upstreams[1].url`, and `""` for the document itself.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    source: String,
+    values: HashMap<String, Span>,
+    keys: HashMap<String, Span>,
+}
+
+pub struct SpannedConfig {
+    pub value: Value,
+    pub source_map: SourceMap,
+}
+
+impl ConfigParser {
+    pub fn parse_json_spanned(content: &str) -> Result<SpannedConfig, ConfigError> {
+        let value = Self::parse_json(content)?;
+        let mut source_map = SourceMap::new(content);
+        JsonSpanner {
+            src: content,
+            pos: 0,
+            map: &mut source_map,
+        }
+        .value("");
+        Ok(SpannedConfig { value, source_map })
+    }
+
+    pub fn parse_toml_spanned(content: &str) -> Result<SpannedConfig, ConfigError> {
+        let value = Self::parse_toml(content)?;
+        let document = toml_edit::ImDocument::parse(content).map_err(|err| {
+            let location = err
+                .span()
+                .map(|span| location_at(content, span.start))
+                .unwrap_or(Location { line: 0, column: 0 });
+            ConfigError::parse_error(location.line, location.column, err.message())
+        })?;
+        let mut source_map = SourceMap::new(content);
+        source_map.record_toml_table(document.as_table(), "");
+        Ok(SpannedConfig { value, source_map })
+    }
+
+    pub fn parse_file_spanned(path: &Path) -> Result<SpannedConfig, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            ConfigError::parse_error(0, 0, format!("Failed to read {}: {err}", path.display()))
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::parse_json_spanned(&content),
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::parse_toml_spanned(&content),
+            _ if content.trim_start().starts_with(['{', '[']) => Self::parse_json_spanned(&content),
+            _ => Self::parse_toml_spanned(&content),
+        }
+    }
+}
+
+/// 1-based line and character column of a byte offset.
+fn location_at(source: &str, offset: usize) -> Location {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// Walks JSON that serde_json already accepted, recording spans.
+struct JsonSpanner<'a> {
+    src: &'a str,
+    pos: usize,
+    map: &'a mut SourceMap,
+}
+
+impl JsonSpanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, path: &str) {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.object(path),
+            Some(b'[') => self.array(path),
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                while matches!(self.peek(), Some(b) if !b",]} \t\r\n".contains(&b)) {
+                    self.pos += 1;
+                }
+            }
+        }
+        self.map.values.insert(
+            path.to_string(),
+            Span {
+                start,
+                end: self.pos,
+            },
+        );
+    }
+
+    fn object(&mut self, path: &str) {
+        self.pos += 1;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    let start = self.pos;
+                    let child = join_path(path, &self.string());
+                    self.map.keys.insert(
+                        child.clone(),
+                        Span {
+                            start,
+                            end: self.pos,
+                        },
+                    );
+                    self.skip_whitespace();
+                    self.pos += 1; // ':'
+                    self.value(&child);
+                }
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str) {
+        self.pos += 1;
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    self.value(&format!("{path}[{index}]"));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Consumes a string literal and returns it unescaped.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_str(&self.src[start..self.pos.min(self.src.len())]).unwrap_or_default()
+    }
+}
+
+impl SourceMap {
+    fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn record_toml_table(&mut self, table: &toml_edit::Table, path: &str) {
+        if let Some(span) = table.span() {
+            self.values.insert(path.to_string(), span.into());
+        }
+        for (key, item) in table.iter() {
+            let child = join_path(path, key);
+            if let Some(span) = table.key(key).and_then(toml_edit::Key::span) {
+                self.keys.insert(child.clone(), span.into());
+            }
+            match item {
+                toml_edit::Item::Table(table) => self.record_toml_table(table, &child),
+                toml_edit::Item::ArrayOfTables(tables) => {
+                    if let Some(span) = tables.span() {
+                        self.values.insert(child.clone(), span.into());
+                    }
+                    for (index, table) in tables.iter().enumerate() {
+                        self.record_toml_table(table, &format!("{child}[{index}]"));
+                    }
+                }
+                toml_edit::Item::Value(value) => self.record_toml_value(value, &child),
+                toml_edit::Item::None => {}
+            }
+        }
+    }
+
+    fn record_toml_value(&mut self, value: &toml_edit::Value, path: &str) {
+        if let Some(span) = value.span() {
+            self.values.insert(path.to_string(), span.into());
+        }
+        match value {
+            toml_edit::Value::Array(array) => {
+                for (index, element) in array.iter().enumerate() {
+                    self.record_toml_value(element, &format!("{path}[{index}]"));
+                }
+            }
+            toml_edit::Value::InlineTable(table) => {
+                for (key, element) in table.iter() {
+                    let child = join_path(path, key);
+                    if let Some(span) = table.key(key).and_then(toml_edit::Key::span) {
+                        self.keys.insert(child.clone(), span.into());
+                    }
+                    self.record_toml_value(element, &child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn value_span(&self, path: &str) -> Option<Span> {
+        self.values.get(path).copied()
+    }
+
+    pub fn key_span(&self, path: &str) -> Option<Span> {
+        self.keys.get(path).copied()
+    }
+
+    pub fn location(&self, offset: usize) -> Location {
+        location_at(&self.source, offset)
+    }
+
+    /// Where an error should point: the offending value, the key of an
+    /// unknown or deprecated field, or the table a missing field belongs to
+    /// (its nearest written ancestor, or the document start for the root).
+    pub fn span_for(&self, error: &ConfigError) -> Option<Span> {
+        let key_or_value = |path: &str| self.key_span(path).or_else(|| self.value_span(path));
+        match error {
+            ConfigError::ParseError { line, col, .. } => {
+                let offset = self.offset_of(*line, *col)?;
+                let end = self.source[offset..]
+                    .chars()
+                    .next()
+                    .map_or(offset, |c| offset + c.len_utf8());
+                Some(Span { start: offset, end })
+            }
+            ConfigError::InvalidType { field, .. }
+            | ConfigError::InvalidValue { field, .. }
+            | ConfigError::OutOfRange { field, .. } => self.value_span(field),
+            ConfigError::UnknownField { field, .. } | ConfigError::Deprecated { field, .. } => {
+                key_or_value(field)
+            }
+            ConfigError::MissingField { section, .. } => {
+                let mut path = section.as_str();
+                while !path.is_empty() {
+                    if let Some(span) = key_or_value(path) {
+                        return Some(span);
+                    }
+                    path = path.rsplit_once('.').map_or("", |(parent, _)| parent);
+                }
+                let first = self.source.chars().next()?;
+                Some(Span {
+                    start: 0,
+                    end: first.len_utf8(),
+                })
+            }
+            // a forbidden field exists; a required one points at its table
+            ConfigError::RuleViolation { field, .. } => key_or_value(field).or_else(|| {
+                let parent = field.rsplit_once('.').map_or("", |(parent, _)| parent);
+                key_or_value(parent).filter(|_| !parent.is_empty())
+            }),
+            ConfigError::InvalidSchema(_) => None,
+        }
+    }
+
+    fn offset_of(&self, line: usize, column: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        let line_start = if line == 1 {
+            0
+        } else {
+            self.source.match_indices('\n').nth(line - 2)?.0 + 1
+        };
+        let text = self.source[line_start..].split('\n').next().unwrap_or("");
+        let within = text
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(text.len(), |(i, _)| i);
+        Some(line_start + within)
+    }
+
+    /// Fills in the `location` of type and value errors so that
+    /// [`ErrorFormatter::format_context`] can underline them.
+    pub fn locate(&self, errors: &mut [ConfigError]) {
+        for error in errors.iter_mut() {
+            let Some(span) = self.span_for(error) else {
+                continue;
+            };
+            let found = self.location(span.start);
+            if let ConfigError::InvalidType { location, .. }
+            | ConfigError::InvalidValue { location, .. } = error
+            {
+                *location = found;
+            }
+        }
+    }
+
+    /// Renders an error the way rustc does:
+    ///
+    /// ```text
+    /// error[invalid-type]: Invalid type for field 'database.port': ...
+    ///  --> config.toml:3:8
+    ///   |
+    /// 3 | port = "5432"
+    ///   |        ^^^^^^
+    ///   |
+    ///   = help: did you mean 'port'?
+    /// ```
+    pub fn render(&self, error: &ConfigError, origin: &str) -> String {
+        let severity = match error.severity() {
+            Severity::Error => "error".red().bold(),
+            Severity::Warning => "warning".yellow().bold(),
+        };
+        let mut output = format!(
+            "{severity}[{}]: {}\n",
+            error.code(),
+            error.to_string().bold()
+        );
+
+        let Some(span) = self.span_for(error) else {
+            output.push_str(&format!(" --> {origin}\n"));
+            if let Some(help) = error.help() {
+                output.push_str(&format!("  = help: {help}\n"));
+            }
+            return output;
+        };
+
+        let start = self.location(span.start);
+        let gutter = " ".repeat(start.line.to_string().len());
+        let line_text = self.source.lines().nth(start.line - 1).unwrap_or("");
+        // only the first line of a multi-line span is underlined
+        let line_end = self.source[span.start..]
+            .find('\n')
+            .map_or(self.source.len(), |i| span.start + i);
+        let width = self.source[span.start..span.end.min(line_end).max(span.start)]
+            .chars()
+            .count()
+            .max(1);
+
+        let bar = "|".blue().bold();
+        output.push_str(&format!(
+            "{gutter}{} {origin}:{}:{}\n",
+            "-->".blue().bold(),
+            start.line,
+            start.column
+        ));
+        output.push_str(&format!("{gutter} {bar}\n"));
+        output.push_str(&format!(
+            "{} {bar} {line_text}\n",
+            start.line.to_string().blue().bold()
+        ));
+        let carets = "^".repeat(width);
+        let carets = match error.severity() {
+            Severity::Error => carets.red().bold(),
+            Severity::Warning => carets.yellow().bold(),
+        };
+        output.push_str(&format!(
+            "{gutter} {bar} {}{carets}\n",
+            " ".repeat(start.column - 1)
+        ));
+        if let Some(help) = error.help() {
+            output.push_str(&format!("{gutter} {bar}\n{gutter} = help: {help}\n"));
+        }
+        output
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl ConfigError {
+    /// Stable identifier, used as the SARIF rule id.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConfigError::ParseError { .. } => "parse-error",
+            ConfigError::MissingField { .. } => "missing-field",
+            ConfigError::InvalidType { .. } => "invalid-type",
+            ConfigError::InvalidValue { .. } => "invalid-value",
+            ConfigError::OutOfRange { .. } => "out-of-range",
+            ConfigError::UnknownField { .. } => "unknown-field",
+            ConfigError::RuleViolation { .. } => "rule-violation",
+            ConfigError::Deprecated { .. } => "deprecated",
+            ConfigError::InvalidSchema(_) => "invalid-schema",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            ConfigError::Deprecated { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    fn help(&self) -> Option<String> {
+        match self {
+            ConfigError::MissingField {
+                suggestion: Some(s),
+                ..
+            }
+            | ConfigError::UnknownField {
+                suggestion: Some(s),
+                ..
+            } => Some(format!("did you mean '{s}'?")),
+            _ => None,
+        }
+    }
+
+    fn description(code: &str) -> &'static str {
+        match code {
+            "parse-error" => "The file is not valid TOML or JSON",
+            "missing-field" => "A required field is missing",
+            "invalid-type" => "A field has the wrong type",
+            "invalid-value" => "A field has a value the schema does not allow",
+            "out-of-range" => "A number is outside its allowed range",
+            "unknown-field" => "A field is not part of the schema",
+            "rule-violation" => "A cross-field rule is violated",
+            "deprecated" => "A deprecated field is set",
+            _ => "The schema itself is invalid",
+        }
+    }
+}
+
+/// Collects diagnostics for one or more files into a SARIF 2.1.0 log, the
+/// format CI systems use for inline annotations.
+#[derive(Debug, Default)]
+pub struct SarifLog {
+    rules: Vec<&'static str>,
+    results: Vec<Value>,
+}
+
+impl SarifLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, uri: &str, source_map: &SourceMap, errors: &[ConfigError]) {
+        for error in errors {
+            if !self.rules.contains(&error.code()) {
+                self.rules.push(error.code());
+            }
+            let level = match error.severity() {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let mut location = serde_json::json!({
+                "physicalLocation": { "artifactLocation": { "uri": uri } }
+            });
+            if let Some(span) = source_map.span_for(error) {
+                let start = source_map.location(span.start);
+                let end = source_map.location(span.end.max(span.start + 1));
+                location["physicalLocation"]["region"] = serde_json::json!({
+                    "startLine": start.line,
+                    "startColumn": start.column,
+                    "endLine": end.line,
+                    "endColumn": end.column,
+                    "byteOffset": span.start,
+                    "byteLength": span.end - span.start,
+                });
+            }
+            let mut message = error.to_string();
+            if let Some(help) = error.help() {
+                message.push_str(&format!(" ({help})"));
+            }
+            self.results.push(serde_json::json!({
+                "ruleId": error.code(),
+                "level": level,
+                "message": { "text": message },
+                "locations": [location],
+            }));
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let rules: Vec<Value> = self
+            .rules
+            .iter()
+            .map(|code| {
+                serde_json::json!({
+                    "id": code,
+                    "shortDescription": { "text": ConfigError::description(code) },
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "config-validator",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": self.results,
+            }]
+        })
+    }
+}
 // =============================================================================
 // Example usage
 // =============================================================================
@@ -705,8 +1848,134 @@ fn main() {
         Ok(()) => println!("{}", "✓ Configuration valid".green()),
         Err(errors) => println!("{}", ErrorFormatter::format_errors(&errors, sample)),
     }
+
+    // The same contract, declared as data instead of code.
+    let schema = DeclarativeSchema::from_toml(SERVICE_SCHEMA).expect("valid schema");
+    let sample = r#"
+[database]
+host = "localhost"
+port = 5432
+max_conections = 200
+
+[server]
+address = "0.0.0.0:8080"
+timeout = 30
+keepalive = true
+
+[tls]
+enabled = true
+cert = "/etc/ssl/app.pem"
+
+[[upstreams]]
+url = "http://billing:9000"
+weight = 3
+
+[[upstreams]]
+url = "billing-backup"
+"#;
+    let config = ConfigParser::parse_toml(sample).expect("valid sample config");
+    let report = schema.validate(&config);
+    println!(
+        "\nSchema {} v{}:",
+        schema.service.as_deref().unwrap_or("unnamed"),
+        schema.version.as_deref().unwrap_or("0")
+    );
+    println!("{}", format_schema_report(&report, sample));
+
+    // The same findings, pointing into the source.
+    let spanned = ConfigParser::parse_toml_spanned(sample).expect("valid sample config");
+    let diagnostics: Vec<ConfigError> = report.errors.into_iter().chain(report.warnings).collect();
+    if std::env::args().any(|arg| arg == "--sarif") {
+        let mut log = SarifLog::new();
+        log.add_file("service.toml", &spanned.source_map, &diagnostics);
+        println!("{:#}", log.to_json());
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{}", spanned.source_map.render(diagnostic, "service.toml"));
+        }
+    }
 }
 
+const SERVICE_SCHEMA: &str = r#"
+version = "2"
+service = "billing"
+
+[fields."database.host"]
+type = "string"
+required = true
+
+[fields."database.port"]
+type = "integer"
+required = true
+min = 1
+max = 65535
+
+[fields."database.max_connections"]
+type = "integer"
+min = 1
+max = 500
+default = 100
+
+[fields."database.username"]
+type = "string"
+
+[fields."database.password"]
+type = "string"
+
+[fields.server]
+type = "table"
+required = true
+
+[fields."server.address"]
+type = "string"
+required = true
+pattern = '^[^:]+:\d+$'
+
+[fields."server.timeout"]
+type = "integer"
+required = true
+min = 1
+
+[fields."server.keepalive"]
+type = "boolean"
+deprecated = "connections are always kept alive since v2"
+
+[fields."log.level"]
+type = "string"
+enum = ["debug", "info", "warn", "error"]
+default = "info"
+
+[fields."tls.enabled"]
+type = "boolean"
+default = false
+
+[fields."tls.cert"]
+type = "string"
+
+[fields."tls.key"]
+type = "string"
+
+[fields.upstreams]
+type = "array"
+min_items = 1
+
+[fields.upstreams.items.fields.url]
+type = "string"
+required = true
+pattern = "^https?://"
+
+[fields.upstreams.items.fields.weight]
+type = "integer"
+min = 1
+max = 100
+default = 1
+
+[[rules]]
+when = "tls.enabled"
+equals = true
+require = ["tls.cert", "tls.key"]
+"#;
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -1008,4 +2277,358 @@ mod tests {
         ];
         assert!(ErrorFormatter::format_summary(&two).contains("2 errors"));
     }
+
+    #[test]
+    fn test_declarative_schema_types_ranges_enums_defaults() {
+        let schema = DeclarativeSchema::from_toml(SERVICE_SCHEMA).unwrap();
+        assert_eq!(schema.service.as_deref(), Some("billing"));
+
+        let config = json!({
+            "database": {"host": "db", "port": 5432},
+            "server": {"address": "0.0.0.0:80", "timeout": 5}
+        });
+        let filled = schema.validate(&config).into_result().unwrap();
+        assert_eq!(filled["database"]["max_connections"], json!(100));
+        assert_eq!(filled["log"]["level"], json!("info"));
+        assert_eq!(filled["tls"]["enabled"], json!(false));
+
+        let config = json!({
+            "database": {"host": 7, "port": 70000, "max_connections": 0},
+            "server": {"address": "nowhere", "timeout": 5},
+            "log": {"level": "debg"}
+        });
+        let errors = schema.validate(&config).errors;
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert!(
+            matches!(&errors[0], ConfigError::InvalidType { field, .. } if field == "database.host")
+        );
+        assert!(matches!(
+            &errors[1],
+            ConfigError::OutOfRange { field, value: 0, min: 1, max: 500 } if field == "database.max_connections"
+        ));
+        assert!(matches!(
+            &errors[2],
+            ConfigError::OutOfRange { value: 70000, .. }
+        ));
+        assert!(errors[3].to_string().contains("did you mean 'debug'?"));
+        assert!(errors[4].to_string().contains("server.address"));
+    }
+
+    #[test]
+    fn test_declarative_schema_from_json_with_arrays_of_tables() {
+        let schema = DeclarativeSchema::from_json(
+            r#"{
+                "fields": {
+                    "upstreams": {
+                        "type": "array",
+                        "min_items": 1,
+                        "max_items": 3,
+                        "items": {
+                            "type": "table",
+                            "fields": {
+                                "url": {"type": "string", "required": true},
+                                "weight": {"type": "float", "min": 0.5, "default": 1}
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let report = schema.validate(&json!({"upstreams": [{"url": "a"}, {"weight": 0.1}]}));
+        assert_eq!(report.config["upstreams"][0]["weight"], json!(1));
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Missing required field: 'url' in section [upstreams[1]]",
+                "Invalid value for field 'upstreams[1].weight': must be at least 0.5",
+            ]
+        );
+
+        let errors = schema.validate(&json!({"upstreams": []})).errors;
+        assert!(errors[0]
+            .to_string()
+            .contains("must have between 1 and 3 items"));
+        // an absent optional array is fine
+        assert!(schema.validate(&json!({})).errors.is_empty());
+    }
+
+    #[test]
+    fn test_declarative_schema_cross_field_rules() {
+        let schema = DeclarativeSchema::from_toml(
+            r#"
+            allow_unknown = true
+
+            [fields.tls]
+            type = "table"
+
+            [[fields.tls.rules]]
+            when = "enabled"
+            require = ["cert"]
+            forbid = ["insecure_skip_verify"]
+
+            [[rules]]
+            when = "mode"
+            equals = "cluster"
+            require = ["peers"]
+            message = "cluster mode needs a peer list"
+            "#,
+        )
+        .unwrap();
+
+        let ok = json!({"tls": {"enabled": false, "insecure_skip_verify": true}, "mode": "solo"});
+        assert!(schema.validate(&ok).errors.is_empty());
+
+        let bad =
+            json!({"tls": {"enabled": true, "insecure_skip_verify": true}, "mode": "cluster"});
+        let messages: Vec<String> = schema
+            .validate(&bad)
+            .errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "'tls.cert' is required when 'tls.enabled' is set",
+                "'tls.insecure_skip_verify' is not allowed when 'tls.enabled' is set",
+                "cluster mode needs a peer list",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declarative_schema_reports_keys_of_absent_dotted_section() {
+        let schema = DeclarativeSchema::from_toml(
+            r#"
+            [fields."cache.url"]
+            type = "string"
+            required = true
+
+            [fields."cache.pool.size"]
+            type = "integer"
+            required = true
+
+            [fields."metrics.port"]
+            type = "integer"
+            "#,
+        )
+        .unwrap();
+
+        let report = schema.validate(&json!({}));
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Missing required field: 'size' in section [cache.pool]",
+                "Missing required field: 'url' in section [cache]",
+            ]
+        );
+        // nothing is materialized for sections without defaults
+        assert_eq!(report.config, json!({}));
+
+        let report = schema.validate(&json!({"cache": {"url": "redis://", "pool": {"size": 4}}}));
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_declarative_schema_unknown_fields_and_deprecations() {
+        let schema = DeclarativeSchema::from_toml(SERVICE_SCHEMA).unwrap();
+        let source = "[database]\nhost = \"db\"\nprot = 5432\n";
+        let mut config = ConfigParser::parse_toml(source).unwrap();
+        config["server"] = json!({"address": "h:1", "timeout": 1, "keepalive": true});
+        config["extra"] = json!(1);
+
+        let report = schema.validate(&config);
+        assert!(matches!(
+            &report.errors[..],
+            [
+                ConfigError::UnknownField { suggestion: None, .. },
+                ConfigError::UnknownField { field, suggestion: Some(hint) },
+                ConfigError::MissingField { field: missing, .. },
+            ] if field == "database.prot" && hint == "port" && missing == "port"
+        ));
+        assert_eq!(report.warnings.len(), 1);
+
+        let formatted = format_schema_report(&report, source);
+        assert!(formatted.contains("Did you mean 'port'?"));
+        assert!(formatted.contains("deprecated: connections are always kept alive"));
+    }
+
+    #[test]
+    fn test_declarative_schema_rejects_bad_documents() {
+        let reject = |doc: &str| DeclarativeSchema::from_toml(doc).unwrap_err().to_string();
+
+        assert!(reject("[fields.a]\npattern = \"(\"\n").contains("a: bad pattern"));
+        assert!(reject("[fields.a]\ntype = \"integer\"\ndefault = \"x\"\n")
+            .contains("default \"x\" is not a integer"));
+        assert!(
+            reject("[fields.\"a.b\"]\ntype = \"string\"\n[fields.a]\ntype = \"integer\"\n")
+                .contains("a: not a table")
+        );
+        // typos in the schema itself are caught, not silently ignored
+        assert!(matches!(
+            DeclarativeSchema::from_toml("[fields.a]\nrequried = true\n"),
+            Err(ConfigError::ParseError { .. })
+        ));
+        assert!(DeclarativeSchema::from_file(Path::new("/nonexistent/schema.toml")).is_err());
+    }
+
+    #[test]
+    fn test_json_source_map_spans() {
+        let source = "{\n  \"database\": {\"port\": \"5432\", \"a\\\"b\": 1},\n  \"hosts\": [\"x\", {\"name\": true}]\n}";
+        let spanned = ConfigParser::parse_json_spanned(source).unwrap();
+        let map = &spanned.source_map;
+        let text = |span: Option<Span>| &source[span.unwrap().start..span.unwrap().end];
+
+        assert_eq!(text(map.value_span("database.port")), "\"5432\"");
+        assert_eq!(text(map.key_span("database.port")), "\"port\"");
+        assert_eq!(text(map.value_span("database.a\"b")), "1");
+        assert_eq!(text(map.value_span("hosts[0]")), "\"x\"");
+        assert_eq!(text(map.value_span("hosts[1].name")), "true");
+        assert_eq!(
+            map.location(map.value_span("hosts[1].name").unwrap().start),
+            Location {
+                line: 3,
+                column: 27
+            }
+        );
+
+        let mut errors = Validator::validate_config(&spanned.value).unwrap_err();
+        map.locate(&mut errors);
+        let port = errors
+            .iter()
+            .find(
+                |e| matches!(e, ConfigError::InvalidType { field, .. } if field == "database.port"),
+            )
+            .unwrap();
+        assert!(matches!(
+            port,
+            ConfigError::InvalidType {
+                location: Location {
+                    line: 2,
+                    column: 24
+                },
+                ..
+            }
+        ));
+        let formatted = ErrorFormatter::format_errors(&errors, source);
+        assert!(formatted.contains("\"database\": {\"port\": \"5432\""));
+    }
+
+    #[test]
+    fn test_toml_source_map_and_rendering() {
+        colored::control::set_override(false);
+        let source = "[database]\nhost = \"db\"\nport = \"5432\"\nmeta = { owner = 7 }\n\n[[upstreams]]\nurl = \"a\"\n\n[[upstreams]]\nurl = \"b\"\n";
+        let spanned = ConfigParser::parse_toml_spanned(source).unwrap();
+        let map = &spanned.source_map;
+        let text = |span: Option<Span>| &source[span.unwrap().start..span.unwrap().end];
+        assert_eq!(text(map.value_span("database.meta.owner")), "7");
+        assert_eq!(text(map.value_span("upstreams[1].url")), "\"b\"");
+        assert_eq!(text(map.key_span("database.port")), "port");
+
+        let error = ConfigError::invalid_type(
+            "database.port",
+            "integer",
+            "string",
+            Location { line: 0, column: 0 },
+        );
+        assert_eq!(
+            map.render(&error, "app.toml"),
+            "error[invalid-type]: Invalid type for field 'database.port': expected integer, got string\n \
+             --> app.toml:3:8\n  |\n3 | port = \"5432\"\n  |        ^^^^^^\n"
+        );
+
+        let unknown = ConfigError::UnknownField {
+            field: "upstreams[1].url".to_string(),
+            suggestion: Some("uri".to_string()),
+        };
+        let rendered = map.render(&unknown, "app.toml");
+        assert!(rendered.starts_with("error[unknown-field]"));
+        assert!(rendered.contains("  --> app.toml:10:1\n"));
+        assert!(rendered.ends_with("   = help: did you mean 'uri'?\n"));
+
+        let missing = ConfigError::missing_field("root", "server", None);
+        assert_eq!(map.span_for(&missing), Some(Span { start: 0, end: 1 }));
+        assert!(map
+            .render(&missing, "app.toml")
+            .contains(" --> app.toml:1:1\n"));
+        // a section absent from the file points at its nearest written parent
+        let nested = ConfigError::missing_field("database.pool", "size", None);
+        assert_eq!(text(map.span_for(&nested)), "database");
+        let empty = SourceMap::default();
+        assert_eq!(empty.span_for(&missing), None);
+        assert!(empty
+            .render(&missing, "app.toml")
+            .ends_with(" --> app.toml\n"));
+    }
+
+    #[test]
+    fn test_toml_parse_errors_have_positions() {
+        let error = ConfigParser::parse_toml("[database]\nport = = 1\n").unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ConfigError::ParseError {
+                    line: 2,
+                    col: 8,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+        assert!(matches!(
+            ConfigParser::parse_toml_spanned("a = \"unterminated\n"),
+            Err(ConfigError::ParseError { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        let schema = DeclarativeSchema::from_toml(SERVICE_SCHEMA).unwrap();
+        let source = "[database]\nhost = \"db\"\nport = 0\n\n[server]\naddress = \"h:1\"\ntimeout = 1\nkeepalive = true\n";
+        let spanned = ConfigParser::parse_toml_spanned(source).unwrap();
+        let report = schema.validate(&spanned.value);
+        let diagnostics: Vec<ConfigError> =
+            report.errors.into_iter().chain(report.warnings).collect();
+
+        let mut log = SarifLog::new();
+        log.add_file("config/app.toml", &spanned.source_map, &diagnostics);
+        log.add_file(
+            "config/empty.toml",
+            &SourceMap::default(),
+            &[ConfigError::missing_field("root", "server", None)],
+        );
+        let sarif = log.to_json();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        let rule_ids: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| rule["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rule_ids, ["out-of-range", "deprecated", "missing-field"]);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["level"], "error");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "config/app.toml");
+        assert_eq!(
+            location["region"],
+            json!({"startLine": 3, "startColumn": 8, "endLine": 3, "endColumn": 9, "byteOffset": 30, "byteLength": 1})
+        );
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            8
+        );
+        assert!(results[2]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+    }
 }
@@ -1,31 +1,163 @@
 use std::fmt;
+use std::marker::PhantomData;
 
 // =============================================================================
 // Milestone 1: Generic parser trait
 // =============================================================================
 
+/// A parse failure.
+///
+/// `expected` is the sorted, deduplicated set of things that would have been
+/// accepted at the failure point; `or` merges the sets of alternatives that
+/// fail at the same place and otherwise keeps the failure that got furthest.
+/// Parsers only know the input they were handed, so they record how much
+/// input was `remaining`; `run_parser` turns that into an absolute `position`.
 #[derive(Debug, Clone, PartialEq)]
 struct ParseError {
     message: String,
     position: usize,
+    expected: Vec<String>,
+    remaining: usize,
+    committed: bool,
 }
 
 impl ParseError {
-    fn new(message: String, position: usize) -> Self {
-        Self { message, position }
+    /// "expected `what`" at the start of `input`.
+    fn expected<I: Input>(what: impl Into<String>, input: I) -> Self {
+        let message = match input.next_char() {
+            Some((ch, _)) => format!("found {:?}", ch),
+            None => "reached end of input".to_string(),
+        };
+        Self {
+            expected: vec![what.into()],
+            ..Self::custom(message, input)
+        }
+    }
+
+    /// A failure with a free-form message at the start of `input`.
+    fn custom<I: Input>(message: impl Into<String>, input: I) -> Self {
+        Self {
+            message: message.into(),
+            position: 0,
+            expected: Vec::new(),
+            remaining: input.input_len(),
+            committed: false,
+        }
+    }
+
+    /// Combine the failures of two alternatives tried on the same input.
+    fn merge(mut self, other: ParseError) -> Self {
+        if other.remaining < self.remaining {
+            return other;
+        }
+        if other.remaining == self.remaining {
+            self.expected.extend(other.expected);
+            self.expected.sort();
+            self.expected.dedup();
+            self.committed |= other.committed;
+        }
+        self
+    }
+
+    /// Fill in the absolute position for an input of `input_len` tokens.
+    fn locate(mut self, input_len: usize) -> Self {
+        self.position = input_len.saturating_sub(self.remaining);
+        self
     }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} at position {}", self.message, self.position)
+        if self.expected.is_empty() {
+            return write!(f, "{} at position {}", self.message, self.position);
+        }
+        let alternatives = match self.expected.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => unreachable!(),
+        };
+        write!(
+            f,
+            "expected {} but {} at position {}",
+            alternatives, self.message, self.position
+        )
     }
 }
 
-type ParseResult<'a, T> = Result<(T, &'a str), ParseError>;
+/// Something a parser can consume: `&str` yields chars, `&[u8]` yields each
+/// byte as the char with the same code point.
+trait Input: Copy {
+    fn input_len(&self) -> usize;
+
+    /// The next token and its length in input units.
+    fn next_char(&self) -> Option<(char, usize)>;
+
+    fn slice_from(&self, offset: usize) -> Self;
+
+    fn slice_to(&self, offset: usize) -> Self;
+
+    fn starts_with_str(&self, prefix: &str) -> bool;
+
+    /// The input as text; byte input is decoded as (lossy) UTF-8.
+    fn to_text(&self) -> String;
+}
+
+impl Input for &str {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn next_char(&self) -> Option<(char, usize)> {
+        self.chars().next().map(|ch| (ch, ch.len_utf8()))
+    }
+
+    fn slice_from(&self, offset: usize) -> Self {
+        &self[offset..]
+    }
+
+    fn slice_to(&self, offset: usize) -> Self {
+        &self[..offset]
+    }
+
+    fn starts_with_str(&self, prefix: &str) -> bool {
+        self.starts_with(prefix)
+    }
+
+    fn to_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Input for &[u8] {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn next_char(&self) -> Option<(char, usize)> {
+        self.first().map(|&byte| (char::from(byte), 1))
+    }
+
+    fn slice_from(&self, offset: usize) -> Self {
+        &self[offset..]
+    }
+
+    fn slice_to(&self, offset: usize) -> Self {
+        &self[..offset]
+    }
+
+    fn starts_with_str(&self, prefix: &str) -> bool {
+        self.starts_with(prefix.as_bytes())
+    }
+
+    fn to_text(&self) -> String {
+        String::from_utf8_lossy(self).into_owned()
+    }
+}
+
+type ParseResult<I, T> = Result<(T, I), ParseError>;
 
 trait ParserGeneric<Output> {
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, Output>;
+    fn parse<'a>(&self, input: &'a str) -> ParseResult<&'a str, Output>;
 }
 
 struct CharParser {
@@ -33,27 +165,16 @@ struct CharParser {
 }
 
 impl CharParser {
-    fn parse_internal<'a>(&self, input: &'a str) -> ParseResult<'a, char> {
-        let mut chars = input.char_indices();
-        match chars.next() {
-            Some((idx, ch)) if ch == self.expected => {
-                let len = ch.len_utf8();
-                Ok((ch, &input[idx + len..]))
-            }
-            Some((_, ch)) => Err(ParseError::new(
-                format!("Expected '{}' but found '{}'", self.expected, ch),
-                0,
-            )),
-            None => Err(ParseError::new(
-                format!("Expected '{}' but reached end of input", self.expected),
-                0,
-            )),
+    fn parse_internal<I: Input>(&self, input: I) -> ParseResult<I, char> {
+        match input.next_char() {
+            Some((ch, len)) if ch == self.expected => Ok((ch, input.slice_from(len))),
+            _ => Err(ParseError::expected(format!("{:?}", self.expected), input)),
         }
     }
 }
 
 impl ParserGeneric<char> for CharParser {
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, char> {
+    fn parse<'a>(&self, input: &'a str) -> ParseResult<&'a str, char> {
         self.parse_internal(input)
     }
 }
@@ -61,25 +182,19 @@ impl ParserGeneric<char> for CharParser {
 struct DigitParser;
 
 impl DigitParser {
-    fn parse_internal<'a>(&self, input: &'a str) -> ParseResult<'a, u32> {
-        let mut chars = input.char_indices();
-        match chars.next() {
-            Some((idx, ch)) if ch.is_ascii_digit() => {
-                let len = ch.len_utf8();
+    fn parse_internal<I: Input>(&self, input: I) -> ParseResult<I, u32> {
+        match input.next_char() {
+            Some((ch, len)) if ch.is_ascii_digit() => {
                 let value = ch.to_digit(10).unwrap();
-                Ok((value, &input[idx + len..]))
+                Ok((value, input.slice_from(len)))
             }
-            Some((_, ch)) => Err(ParseError::new(
-                format!("Expected digit but found '{}'", ch),
-                0,
-            )),
-            None => Err(ParseError::new("Expected digit but input was empty".to_string(), 0)),
+            _ => Err(ParseError::expected("digit", input)),
         }
     }
 }
 
 impl ParserGeneric<u32> for DigitParser {
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, u32> {
+    fn parse<'a>(&self, input: &'a str) -> ParseResult<&'a str, u32> {
         self.parse_internal(input)
     }
 }
@@ -88,54 +203,67 @@ fn run_parser_generic<Output, P: ParserGeneric<Output>>(
     parser: P,
     input: &str,
 ) -> Result<Output, ParseError> {
-    parser.parse(input).map(|(value, _)| value)
+    parser
+        .parse(input)
+        .map(|(value, _)| value)
+        .map_err(|err| err.locate(input.len()))
 }
 
 // =============================================================================
 // Milestone 2: Associated type parser trait
 // =============================================================================
 
-trait Parser {
+/// The value a parser produces. It lives apart from `Parser` so that a parser
+/// has one `Output` whichever kind of input it is run on.
+trait ParserOutput {
     type Output;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, Self::Output>;
+trait Parser<I: Input>: ParserOutput {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output>;
 }
 
-impl Parser for CharParser {
+impl ParserOutput for CharParser {
     type Output = char;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, char> {
+impl<I: Input> Parser<I> for CharParser {
+    fn parse(&self, input: I) -> ParseResult<I, char> {
         self.parse_internal(input)
     }
 }
 
-impl Parser for DigitParser {
+impl ParserOutput for DigitParser {
     type Output = u32;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, u32> {
+impl<I: Input> Parser<I> for DigitParser {
+    fn parse(&self, input: I) -> ParseResult<I, u32> {
         self.parse_internal(input)
     }
 }
 
-fn run_parser<P: Parser>(parser: P, input: &str) -> Result<P::Output, ParseError> {
-    parser.parse(input).map(|(value, _)| value)
+fn run_parser<I: Input, P: Parser<I>>(parser: P, input: I) -> Result<P::Output, ParseError> {
+    parser
+        .parse(input)
+        .map(|(value, _)| value)
+        .map_err(|err| err.locate(input.input_len()))
 }
 
 struct StringParser {
     expected: String,
 }
 
-impl Parser for StringParser {
+impl ParserOutput for StringParser {
     type Output = String;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, String> {
-        if input.starts_with(&self.expected) {
-            Ok((self.expected.clone(), &input[self.expected.len()..]))
+impl<I: Input> Parser<I> for StringParser {
+    fn parse(&self, input: I) -> ParseResult<I, String> {
+        if input.starts_with_str(&self.expected) {
+            Ok((self.expected.clone(), input.slice_from(self.expected.len())))
         } else {
-            Err(ParseError::new(
-                format!("Expected \"{}\"", self.expected),
-                0,
-            ))
+            Err(ParseError::expected(format!("{:?}", self.expected), input))
         }
     }
 }
@@ -149,21 +277,28 @@ struct MapParser<P, F> {
     mapper: F,
 }
 
-impl<P, F, NewOutput> Parser for MapParser<P, F>
+impl<P, F, NewOutput> ParserOutput for MapParser<P, F>
 where
-    P: Parser,
+    P: ParserOutput,
     F: Fn(P::Output) -> NewOutput,
 {
     type Output = NewOutput;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, NewOutput> {
+impl<I, P, F, NewOutput> Parser<I> for MapParser<P, F>
+where
+    I: Input,
+    P: Parser<I>,
+    F: Fn(P::Output) -> NewOutput,
+{
+    fn parse(&self, input: I) -> ParseResult<I, NewOutput> {
         let (value, remaining) = self.parser.parse(input)?;
         let mapped = (self.mapper)(value);
         Ok((mapped, remaining))
     }
 }
 
-trait ParserExt: Parser + Sized {
+trait ParserExt: ParserOutput + Sized {
     fn map<F, NewOutput>(self, mapper: F) -> MapParser<Self, F>
     where
         F: Fn(Self::Output) -> NewOutput,
@@ -176,30 +311,100 @@ trait ParserExt: Parser + Sized {
 
     fn and_then<P2>(self, other: P2) -> AndThenParser<Self, P2>
     where
-        P2: Parser,
+        P2: ParserOutput,
     {
         AndThenParser {
             first: self,
             second: other,
         }
     }
+
+    /// Try `self`, then `other` on the same input.
+    fn or<P2>(self, other: P2) -> OrParser<Self, P2>
+    where
+        P2: ParserOutput<Output = Self::Output>,
+    {
+        OrParser {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Zero or more repetitions.
+    fn many0(self) -> ManyParser<Self> {
+        ManyParser {
+            parser: self,
+            min: 0,
+        }
+    }
+
+    /// One or more repetitions.
+    fn many1(self) -> ManyParser<Self> {
+        ManyParser {
+            parser: self,
+            min: 1,
+        }
+    }
+
+    /// Zero or more repetitions separated by `separator`.
+    fn sep_by<S: ParserOutput>(self, separator: S) -> SepByParser<Self, S> {
+        SepByParser {
+            parser: self,
+            separator,
+        }
+    }
+
+    fn opt(self) -> OptParser<Self> {
+        OptParser { parser: self }
+    }
+
+    /// Return the input consumed by `self` instead of its value.
+    fn recognize<I: Input>(self) -> RecognizeParser<Self, I> {
+        RecognizeParser {
+            parser: self,
+            input: PhantomData,
+        }
+    }
+
+    /// Commit to this branch: a failure past this point is reported as is
+    /// instead of letting `or`, `opt` or the repetitions try something else.
+    fn cut(self) -> CutParser<Self> {
+        CutParser { parser: self }
+    }
+
+    /// Report a failure that consumed nothing as "expected `name`".
+    fn label(self, name: &str) -> LabelParser<Self> {
+        LabelParser {
+            parser: self,
+            name: name.to_string(),
+        }
+    }
+
+    /// On failure, skip ahead with `sync` and yield the error as a value so
+    /// that parsing can continue and report several errors at once.
+    fn recover_with<S: ParserOutput>(self, sync: S) -> RecoverParser<Self, S> {
+        RecoverParser { parser: self, sync }
+    }
 }
 
-impl<P: Parser> ParserExt for P {}
+impl<P: ParserOutput> ParserExt for P {}
 
 struct AndThenParser<P1, P2> {
     first: P1,
     second: P2,
 }
 
-impl<P1, P2> Parser for AndThenParser<P1, P2>
-where
-    P1: Parser,
-    P2: Parser,
-{
+impl<P1: ParserOutput, P2: ParserOutput> ParserOutput for AndThenParser<P1, P2> {
     type Output = (P1::Output, P2::Output);
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, Self::Output> {
+impl<I, P1, P2> Parser<I> for AndThenParser<P1, P2>
+where
+    I: Input,
+    P1: Parser<I>,
+    P2: Parser<I>,
+{
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
         let (first_value, remaining) = self.first.parse(input)?;
         let (second_value, final_remaining) = self.second.parse(remaining)?;
         Ok(((first_value, second_value), final_remaining))
@@ -208,34 +413,16 @@ where
 
 struct NumberParser;
 
-impl Parser for NumberParser {
+impl ParserOutput for NumberParser {
     type Output = u32;
+}
 
-    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, u32> {
-        let mut end = 0;
-        for (idx, ch) in input.char_indices() {
-            if ch.is_ascii_digit() {
-                end = idx + ch.len_utf8();
-            } else {
-                break;
-            }
-        }
-
-        if end == 0 {
-            return Err(ParseError::new(
-                "Expected one or more digits".to_string(),
-                0,
-            ));
-        }
-
-        let digits = &input[..end];
-        let remaining = &input[end..];
-        match digits.parse::<u32>() {
+impl<I: Input> Parser<I> for NumberParser {
+    fn parse(&self, input: I) -> ParseResult<I, u32> {
+        let (digits, remaining) = take_while1("digit", |ch| ch.is_ascii_digit()).parse(input)?;
+        match digits.to_text().parse::<u32>() {
             Ok(value) => Ok((value, remaining)),
-            Err(_) => Err(ParseError::new(
-                "Failed to parse integer".to_string(),
-                0,
-            )),
+            Err(_) => Err(ParseError::custom("integer does not fit in u32", input)),
         }
     }
 }
@@ -249,11 +436,843 @@ fn parse_addition(input: &str) -> Result<u32, ParseError> {
     run_parser(parser, input)
 }
 
+// =============================================================================
+// Milestone 4: Alternation, repetition and error recovery
+// =============================================================================
+
+struct OrParser<P1, P2> {
+    first: P1,
+    second: P2,
+}
+
+impl<P1: ParserOutput, P2> ParserOutput for OrParser<P1, P2> {
+    type Output = P1::Output;
+}
+
+impl<I, P1, P2> Parser<I> for OrParser<P1, P2>
+where
+    I: Input,
+    P1: Parser<I>,
+    P2: Parser<I, Output = P1::Output>,
+{
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        match self.first.parse(input) {
+            Err(err) if !err.committed => {
+                self.second.parse(input).map_err(|other| err.merge(other))
+            }
+            result => result,
+        }
+    }
+}
+
+struct ManyParser<P> {
+    parser: P,
+    min: usize,
+}
+
+impl<P: ParserOutput> ParserOutput for ManyParser<P> {
+    type Output = Vec<P::Output>;
+}
+
+impl<I: Input, P: Parser<I>> Parser<I> for ManyParser<P> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        let mut items = Vec::new();
+        let mut rest = input;
+        loop {
+            match self.parser.parse(rest) {
+                Ok((item, next)) => {
+                    let progressed = next.input_len() < rest.input_len();
+                    items.push(item);
+                    rest = next;
+                    // An item that matches nothing would match forever.
+                    if !progressed {
+                        break;
+                    }
+                }
+                Err(err) if err.committed || items.len() < self.min => return Err(err),
+                Err(_) => break,
+            }
+        }
+        Ok((items, rest))
+    }
+}
+
+struct SepByParser<P, S> {
+    parser: P,
+    separator: S,
+}
+
+impl<P: ParserOutput, S> ParserOutput for SepByParser<P, S> {
+    type Output = Vec<P::Output>;
+}
+
+impl<I: Input, P: Parser<I>, S: Parser<I>> Parser<I> for SepByParser<P, S> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        let (first, mut rest) = match self.parser.parse(input) {
+            Ok(parsed) => parsed,
+            Err(err) if err.committed => return Err(err),
+            Err(_) => return Ok((Vec::new(), input)),
+        };
+        let mut items = vec![first];
+        loop {
+            let after_separator = match self.separator.parse(rest) {
+                Ok((_, next)) => next,
+                Err(err) if err.committed => return Err(err),
+                Err(_) => break,
+            };
+            // A separator must be followed by another item.
+            let (item, next) = self.parser.parse(after_separator)?;
+            items.push(item);
+            rest = next;
+        }
+        Ok((items, rest))
+    }
+}
+
+struct OptParser<P> {
+    parser: P,
+}
+
+impl<P: ParserOutput> ParserOutput for OptParser<P> {
+    type Output = Option<P::Output>;
+}
+
+impl<I: Input, P: Parser<I>> Parser<I> for OptParser<P> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        match self.parser.parse(input) {
+            Ok((value, rest)) => Ok((Some(value), rest)),
+            Err(err) if err.committed => Err(err),
+            Err(_) => Ok((None, input)),
+        }
+    }
+}
+
+struct RecognizeParser<P, I> {
+    parser: P,
+    input: PhantomData<fn(I)>,
+}
+
+impl<P, I> ParserOutput for RecognizeParser<P, I> {
+    type Output = I;
+}
+
+impl<I: Input, P: Parser<I>> Parser<I> for RecognizeParser<P, I> {
+    fn parse(&self, input: I) -> ParseResult<I, I> {
+        let (_, rest) = self.parser.parse(input)?;
+        let consumed = input.input_len() - rest.input_len();
+        Ok((input.slice_to(consumed), rest))
+    }
+}
+
+struct CutParser<P> {
+    parser: P,
+}
+
+impl<P: ParserOutput> ParserOutput for CutParser<P> {
+    type Output = P::Output;
+}
+
+impl<I: Input, P: Parser<I>> Parser<I> for CutParser<P> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        self.parser.parse(input).map_err(|mut err| {
+            err.committed = true;
+            err
+        })
+    }
+}
+
+struct LabelParser<P> {
+    parser: P,
+    name: String,
+}
+
+impl<P: ParserOutput> ParserOutput for LabelParser<P> {
+    type Output = P::Output;
+}
+
+impl<I: Input, P: Parser<I>> Parser<I> for LabelParser<P> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        self.parser.parse(input).map_err(|err| {
+            if err.committed || err.remaining < input.input_len() {
+                err
+            } else {
+                ParseError::expected(self.name.clone(), input)
+            }
+        })
+    }
+}
+
+struct RecoverParser<P, S> {
+    parser: P,
+    sync: S,
+}
+
+impl<P: ParserOutput, S> ParserOutput for RecoverParser<P, S> {
+    type Output = Result<P::Output, ParseError>;
+}
+
+impl<I: Input, P: Parser<I>, S: Parser<I>> Parser<I> for RecoverParser<P, S> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        match self.parser.parse(input) {
+            Ok((value, rest)) => Ok((Ok(value), rest)),
+            Err(err) => match self.sync.parse(input) {
+                Ok((_, rest)) => Ok((Err(err), rest)),
+                Err(_) => Err(err),
+            },
+        }
+    }
+}
+
+struct DelimitedParser<L, P, R> {
+    left: L,
+    parser: P,
+    right: R,
+}
+
+impl<L, P: ParserOutput, R> ParserOutput for DelimitedParser<L, P, R> {
+    type Output = P::Output;
+}
+
+impl<I: Input, L: Parser<I>, P: Parser<I>, R: Parser<I>> Parser<I> for DelimitedParser<L, P, R> {
+    fn parse(&self, input: I) -> ParseResult<I, Self::Output> {
+        let (_, rest) = self.left.parse(input)?;
+        let (value, rest) = self.parser.parse(rest)?;
+        let (_, rest) = self.right.parse(rest)?;
+        Ok((value, rest))
+    }
+}
+
+/// `parser` between `left` and `right`, keeping only its value.
+fn delimited<L, P, R>(left: L, parser: P, right: R) -> DelimitedParser<L, P, R> {
+    DelimitedParser {
+        left,
+        parser,
+        right,
+    }
+}
+
+/// `parser` after `prefix`.
+fn preceded<L, P>(prefix: L, parser: P) -> DelimitedParser<L, P, Empty> {
+    delimited(prefix, parser, Empty)
+}
+
+/// `parser` followed by `suffix`.
+fn terminated<P, R>(parser: P, suffix: R) -> DelimitedParser<Empty, P, R> {
+    delimited(Empty, parser, suffix)
+}
+
+/// Matches nothing and always succeeds.
+struct Empty;
+
+impl ParserOutput for Empty {
+    type Output = ();
+}
+
+impl<I: Input> Parser<I> for Empty {
+    fn parse(&self, input: I) -> ParseResult<I, ()> {
+        Ok(((), input))
+    }
+}
+
+/// Succeeds only at the end of the input.
+struct Eof;
+
+impl ParserOutput for Eof {
+    type Output = ();
+}
+
+impl<I: Input> Parser<I> for Eof {
+    fn parse(&self, input: I) -> ParseResult<I, ()> {
+        if input.input_len() == 0 {
+            Ok(((), input))
+        } else {
+            Err(ParseError::expected("end of input", input))
+        }
+    }
+}
+
+struct Tag {
+    expected: &'static str,
+}
+
+impl ParserOutput for Tag {
+    type Output = ();
+}
+
+impl<I: Input> Parser<I> for Tag {
+    fn parse(&self, input: I) -> ParseResult<I, ()> {
+        if input.starts_with_str(self.expected) {
+            Ok(((), input.slice_from(self.expected.len())))
+        } else {
+            Err(ParseError::expected(format!("{:?}", self.expected), input))
+        }
+    }
+}
+
+fn tag(expected: &'static str) -> Tag {
+    Tag { expected }
+}
+
+struct SatisfyParser<F> {
+    description: &'static str,
+    predicate: F,
+}
+
+impl<F> ParserOutput for SatisfyParser<F> {
+    type Output = char;
+}
+
+impl<I: Input, F: Fn(char) -> bool> Parser<I> for SatisfyParser<F> {
+    fn parse(&self, input: I) -> ParseResult<I, char> {
+        match input.next_char() {
+            Some((ch, len)) if (self.predicate)(ch) => Ok((ch, input.slice_from(len))),
+            _ => Err(ParseError::expected(self.description, input)),
+        }
+    }
+}
+
+/// One token matching `predicate`.
+fn satisfy<F: Fn(char) -> bool>(description: &'static str, predicate: F) -> SatisfyParser<F> {
+    SatisfyParser {
+        description,
+        predicate,
+    }
+}
+
+struct TakeWhileParser<F> {
+    description: &'static str,
+    predicate: F,
+    min: usize,
+}
+
+impl<F> ParserOutput for TakeWhileParser<F> {
+    type Output = ();
+}
+
+impl<I: Input, F: Fn(char) -> bool> Parser<I> for TakeWhileParser<F> {
+    fn parse(&self, input: I) -> ParseResult<I, ()> {
+        let mut rest = input;
+        let mut count = 0;
+        while let Some((ch, len)) = rest.next_char() {
+            if !(self.predicate)(ch) {
+                break;
+            }
+            rest = rest.slice_from(len);
+            count += 1;
+        }
+        if count < self.min {
+            return Err(ParseError::expected(self.description, rest));
+        }
+        Ok(((), rest))
+    }
+}
+
+/// The longest run of tokens matching `predicate`, possibly empty.
+fn take_while0<I: Input, F: Fn(char) -> bool>(
+    predicate: F,
+) -> RecognizeParser<TakeWhileParser<F>, I> {
+    TakeWhileParser {
+        description: "",
+        predicate,
+        min: 0,
+    }
+    .recognize()
+}
+
+/// The longest non-empty run of tokens matching `predicate`.
+fn take_while1<I: Input, F: Fn(char) -> bool>(
+    description: &'static str,
+    predicate: F,
+) -> RecognizeParser<TakeWhileParser<F>, I> {
+    TakeWhileParser {
+        description,
+        predicate,
+        min: 1,
+    }
+    .recognize()
+}
+
+/// Skips any whitespace.
+fn whitespace() -> TakeWhileParser<fn(char) -> bool> {
+    TakeWhileParser {
+        description: "whitespace",
+        predicate: char::is_whitespace,
+        min: 0,
+    }
+}
+
+struct FnParser<F, I, T> {
+    function: F,
+    types: PhantomData<fn(I) -> T>,
+}
+
+impl<F, I, T> ParserOutput for FnParser<F, I, T> {
+    type Output = T;
+}
+
+impl<I: Input, T, F: Fn(I) -> ParseResult<I, T>> Parser<I> for FnParser<F, I, T> {
+    fn parse(&self, input: I) -> ParseResult<I, T> {
+        (self.function)(input)
+    }
+}
+
+/// Wrap a function as a parser. Recursive grammars are written as functions
+/// that refer to themselves through `from_fn`.
+fn from_fn<I: Input, T, F: Fn(I) -> ParseResult<I, T>>(function: F) -> FnParser<F, I, T> {
+    FnParser {
+        function,
+        types: PhantomData,
+    }
+}
+
+// =============================================================================
+// Milestone 5: Operator precedence (Pratt parsing)
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Assoc {
+    Left,
+    Right,
+}
+
+struct InfixOperator<T> {
+    symbol: &'static str,
+    precedence: u8,
+    assoc: Assoc,
+    fold: Box<dyn Fn(T, T) -> T>,
+}
+
+struct PrefixOperator<T> {
+    symbol: &'static str,
+    precedence: u8,
+    apply: Box<dyn Fn(T) -> T>,
+}
+
+/// Precedence climbing over `atom`s joined by registered operators. Higher
+/// precedence binds tighter; whitespace around operators is skipped.
+struct PrattParser<A, T> {
+    atom: A,
+    infix: Vec<InfixOperator<T>>,
+    prefix: Vec<PrefixOperator<T>>,
+}
+
+impl<A: ParserOutput<Output = T>, T> PrattParser<A, T> {
+    fn new(atom: A) -> Self {
+        Self {
+            atom,
+            infix: Vec::new(),
+            prefix: Vec::new(),
+        }
+    }
+
+    fn infix(
+        mut self,
+        symbol: &'static str,
+        precedence: u8,
+        assoc: Assoc,
+        fold: impl Fn(T, T) -> T + 'static,
+    ) -> Self {
+        self.infix.push(InfixOperator {
+            symbol,
+            precedence,
+            assoc,
+            fold: Box::new(fold),
+        });
+        // Longest symbol first so that "**" is not read as "*".
+        self.infix
+            .sort_by_key(|op| std::cmp::Reverse(op.symbol.len()));
+        self
+    }
+
+    fn prefix(
+        mut self,
+        symbol: &'static str,
+        precedence: u8,
+        apply: impl Fn(T) -> T + 'static,
+    ) -> Self {
+        self.prefix.push(PrefixOperator {
+            symbol,
+            precedence,
+            apply: Box::new(apply),
+        });
+        self.prefix
+            .sort_by_key(|op| std::cmp::Reverse(op.symbol.len()));
+        self
+    }
+
+    fn expression<I: Input>(&self, input: I, min_precedence: u8) -> ParseResult<I, T>
+    where
+        A: Parser<I>,
+    {
+        let (_, input) = whitespace().parse(input)?;
+        let prefix = self
+            .prefix
+            .iter()
+            .find(|op| input.starts_with_str(op.symbol));
+        let (mut lhs, mut rest) = match prefix {
+            Some(op) => {
+                let after = input.slice_from(op.symbol.len());
+                let (operand, rest) = self.expression(after, op.precedence)?;
+                ((op.apply)(operand), rest)
+            }
+            None => self.atom.parse(input).map_err(|err| {
+                self.prefix.iter().fold(err, |err, op| {
+                    err.merge(ParseError::expected(format!("{:?}", op.symbol), input))
+                })
+            })?,
+        };
+
+        loop {
+            let (_, probe) = whitespace().parse(rest)?;
+            let Some(op) = self
+                .infix
+                .iter()
+                .find(|op| probe.starts_with_str(op.symbol))
+            else {
+                break;
+            };
+            if op.precedence < min_precedence {
+                break;
+            }
+            let next_min = match op.assoc {
+                Assoc::Left => op.precedence + 1,
+                Assoc::Right => op.precedence,
+            };
+            let after = probe.slice_from(op.symbol.len());
+            let (rhs, next) = self.expression(after, next_min)?;
+            lhs = (op.fold)(lhs, rhs);
+            rest = next;
+        }
+        Ok((lhs, rest))
+    }
+}
+
+impl<A: ParserOutput<Output = T>, T> ParserOutput for PrattParser<A, T> {
+    type Output = T;
+}
+
+impl<I: Input, A: Parser<I> + ParserOutput<Output = T>, T> Parser<I> for PrattParser<A, T> {
+    fn parse(&self, input: I) -> ParseResult<I, T> {
+        self.expression(input, 0)
+    }
+}
+
+fn arithmetic<I: Input>(input: I) -> ParseResult<I, f64> {
+    let digits = || DigitParser.many1();
+    let number = digits()
+        .and_then(preceded(CharParser { expected: '.' }, digits()).opt())
+        .recognize()
+        .map(|text: I| text.to_text().parse::<f64>().unwrap());
+    let parenthesized = delimited(
+        CharParser { expected: '(' },
+        from_fn(arithmetic),
+        preceded(whitespace(), CharParser { expected: ')' }).cut(),
+    );
+
+    PrattParser::new(number.or(parenthesized))
+        .infix("+", 1, Assoc::Left, |a, b| a + b)
+        .infix("-", 1, Assoc::Left, |a, b| a - b)
+        .infix("*", 2, Assoc::Left, |a, b| a * b)
+        .infix("/", 2, Assoc::Left, |a, b| a / b)
+        .prefix("-", 3, |a: f64| -a)
+        .infix("^", 4, Assoc::Right, f64::powf)
+        .parse(input)
+}
+
+fn evaluate(expression: &str) -> Result<f64, ParseError> {
+    run_parser(
+        terminated(from_fn(arithmetic), preceded(whitespace(), Eof)),
+        expression,
+    )
+}
+
+// =============================================================================
+// Milestone 6: JSON and INI grammars
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+fn json_value<I: Input>(input: I) -> ParseResult<I, JsonValue> {
+    let value = tag("null")
+        .map(|_| JsonValue::Null)
+        .or(tag("true").map(|_| JsonValue::Bool(true)))
+        .or(tag("false").map(|_| JsonValue::Bool(false)))
+        .or(from_fn(json_number).map(JsonValue::Number))
+        .or(from_fn(json_string).map(JsonValue::String))
+        .or(from_fn(json_array).map(JsonValue::Array))
+        .or(from_fn(json_object).map(JsonValue::Object))
+        .label("value");
+    delimited(whitespace(), value, whitespace()).parse(input)
+}
+
+fn json_number<I: Input>(input: I) -> ParseResult<I, f64> {
+    let digits = || take_while1("digit", |ch| ch.is_ascii_digit());
+    let integer = CharParser { expected: '0' }
+        .map(|_| ())
+        .or(satisfy("digit", |ch| ('1'..='9').contains(&ch))
+            .and_then(take_while0(|ch| ch.is_ascii_digit()))
+            .map(|_| ()));
+    let fraction = preceded(CharParser { expected: '.' }, digits().cut());
+    let exponent = preceded(
+        satisfy("'e'", |ch| ch == 'e' || ch == 'E'),
+        preceded(satisfy("sign", |ch| ch == '+' || ch == '-').opt(), digits()).cut(),
+    );
+
+    let (text, rest) = CharParser { expected: '-' }
+        .opt()
+        .and_then(integer)
+        .and_then(fraction.opt())
+        .and_then(exponent.opt())
+        .recognize()
+        .parse(input)?;
+    match text.to_text().parse::<f64>() {
+        Ok(value) => Ok((value, rest)),
+        Err(err) => Err(ParseError::custom(
+            format!("invalid number: {}", err),
+            input,
+        )),
+    }
+}
+
+fn json_string<I: Input>(input: I) -> ParseResult<I, String> {
+    let unescaped = take_while1("character", |ch| ch != '"' && ch != '\\' && ch >= ' ')
+        .map(|text: I| text.to_text());
+    let escaped = preceded(
+        CharParser { expected: '\\' },
+        from_fn(json_escape).map(String::from).cut(),
+    );
+    let body = unescaped.or(escaped).many0().map(|parts| parts.concat());
+
+    preceded(
+        CharParser { expected: '"' },
+        terminated(body, CharParser { expected: '"' }).cut(),
+    )
+    .parse(input)
+}
+
+fn json_escape<I: Input>(input: I) -> ParseResult<I, char> {
+    let (ch, rest) = satisfy("escape character", |ch| "\"\\/bfnrtu".contains(ch)).parse(input)?;
+    let simple = match ch {
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'u' => return json_unicode_escape(rest),
+        other => other,
+    };
+    Ok((simple, rest))
+}
+
+/// The four hex digits after `\u`, combining a UTF-16 surrogate pair.
+fn json_unicode_escape<I: Input>(input: I) -> ParseResult<I, char> {
+    let code_unit = |input: I| -> ParseResult<I, u32> {
+        let (_, rest) = (0..4).try_fold(((), input), |(_, rest), _| {
+            satisfy("hex digit", |ch| ch.is_ascii_hexdigit())
+                .map(|_| ())
+                .parse(rest)
+        })?;
+        let text = input
+            .slice_to(input.input_len() - rest.input_len())
+            .to_text();
+        Ok((u32::from_str_radix(&text, 16).unwrap(), rest))
+    };
+
+    let (high, rest) = code_unit(input)?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return char::from_u32(high)
+            .map(|ch| (ch, rest))
+            .ok_or_else(|| ParseError::custom("unpaired surrogate in \\u escape", input));
+    }
+    let (low, after) = preceded(tag("\\u"), from_fn(code_unit)).parse(rest)?;
+    if !(0xDC00..0xE000).contains(&low) {
+        return Err(ParseError::custom(
+            "unpaired surrogate in \\u escape",
+            input,
+        ));
+    }
+    let combined = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+    Ok((char::from_u32(combined).unwrap(), after))
+}
+
+fn json_array<I: Input>(input: I) -> ParseResult<I, Vec<JsonValue>> {
+    let items = terminated(
+        from_fn(json_value).sep_by(CharParser { expected: ',' }),
+        whitespace(),
+    );
+    preceded(
+        CharParser { expected: '[' },
+        terminated(items, CharParser { expected: ']' }).cut(),
+    )
+    .parse(input)
+}
+
+fn json_object<I: Input>(input: I) -> ParseResult<I, Vec<(String, JsonValue)>> {
+    let key = delimited(
+        whitespace(),
+        from_fn(json_string).label("string"),
+        whitespace(),
+    );
+    let member = terminated(key, CharParser { expected: ':' }.cut()).and_then(from_fn(json_value));
+    let members = terminated(member.sep_by(CharParser { expected: ',' }), whitespace());
+    preceded(
+        CharParser { expected: '{' },
+        terminated(members, CharParser { expected: '}' }).cut(),
+    )
+    .parse(input)
+}
+
+fn parse_json<I: Input>(input: I) -> Result<JsonValue, ParseError> {
+    run_parser(terminated(from_fn(json_value), Eof), input)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct IniSection {
+    name: String,
+    entries: Vec<(String, String)>,
+}
+
+/// A parsed INI file. Keys before the first header belong to the section
+/// named "". Malformed lines are skipped and reported in `errors`.
+#[derive(Debug, Clone, PartialEq)]
+struct IniDocument {
+    sections: Vec<IniSection>,
+    errors: Vec<ParseError>,
+}
+
+impl IniDocument {
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .filter(|candidate| candidate.name == section)
+            .flat_map(|candidate| candidate.entries.iter())
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+enum IniLine {
+    Blank,
+    Section(String),
+    Entry(String, String),
+}
+
+fn ini_line<I: Input>(input: I) -> ParseResult<I, IniLine> {
+    let inline_space = || take_while0(|ch| ch == ' ' || ch == '\t');
+    let end_of_line = || {
+        preceded(
+            inline_space(),
+            tag("\r\n").or(tag("\n")).or(Eof).label("end of line"),
+        )
+    };
+    let rest_of_line = || take_while0(|ch| ch != '\n' && ch != '\r');
+
+    let comment = satisfy("comment", |ch| ch == ';' || ch == '#')
+        .and_then(rest_of_line())
+        .map(|_| IniLine::Blank);
+    let header = preceded(
+        CharParser { expected: '[' },
+        terminated(
+            take_while1("section name", |ch| ch != ']' && ch != '\n' && ch != '\r'),
+            CharParser { expected: ']' },
+        )
+        .cut(),
+    )
+    .map(|name: I| IniLine::Section(name.to_text().trim().to_string()));
+    let key = take_while1("key", |ch| ch.is_ascii_alphanumeric() || "_-.".contains(ch));
+    let separator = preceded(
+        inline_space(),
+        satisfy("'=' or ':'", |ch| ch == '=' || ch == ':'),
+    );
+    let entry = terminated(key, separator.cut())
+        .and_then(rest_of_line())
+        .map(|(key, value): (I, I)| {
+            IniLine::Entry(key.to_text(), value.to_text().trim().to_string())
+        });
+
+    let content = comment.or(header).or(entry).opt();
+    delimited(inline_space(), content, end_of_line().cut())
+        .map(|line| line.unwrap_or(IniLine::Blank))
+        .parse(input)
+}
+
+fn parse_ini<I: Input>(input: I) -> IniDocument {
+    let skip_line = take_while0(|ch| ch != '\n').and_then(CharParser { expected: '\n' }.opt());
+    let lines = from_fn(ini_line).recover_with(skip_line).many0();
+    // Every line either parses or is skipped, so this cannot fail.
+    let lines = lines
+        .parse(input)
+        .map(|(lines, _)| lines)
+        .unwrap_or_default();
+
+    let mut document = IniDocument {
+        sections: vec![IniSection {
+            name: String::new(),
+            entries: Vec::new(),
+        }],
+        errors: Vec::new(),
+    };
+    for line in lines {
+        match line {
+            Ok(IniLine::Blank) => {}
+            Ok(IniLine::Section(name)) => document.sections.push(IniSection {
+                name,
+                entries: Vec::new(),
+            }),
+            Ok(IniLine::Entry(key, value)) => {
+                let section = document.sections.last_mut().unwrap();
+                section.entries.push((key, value));
+            }
+            Err(err) => document.errors.push(err.locate(input.input_len())),
+        }
+    }
+    if document.sections[0].entries.is_empty() {
+        document.sections.remove(0);
+    }
+    document
+}
+
 fn main() {
     match parse_addition("12+30") {
         Ok(value) => println!("12+30 = {}", value),
         Err(err) => eprintln!("Failed to parse expression: {}", err),
     }
+
+    for expression in [
+        "1 + 2 * 3",
+        "-2 ^ 2",
+        "2 ^ 3 ^ 2",
+        "(1 + 2) * 3 - 4 / 8",
+        "1 + * 2",
+    ] {
+        match evaluate(expression) {
+            Ok(value) => println!("{} = {}", expression, value),
+            Err(err) => println!("{}: {}", expression, err),
+        }
+    }
+
+    let json = r#"{"name": "widget", "tags": ["a", "é"], "size": [1.5e2, -3], "ok": true}"#;
+    println!("{:?}", parse_json(json));
+    println!("{:?}", parse_json(json.as_bytes()));
+    if let Err(err) = parse_json(r#"{"items": [1, 2 3]}"#) {
+        println!("invalid JSON: {}", err);
+    }
+
+    let ini = "; service settings\nname = demo\n\n[server]\nhost = localhost\nport: 8080\n[broken\nretries = 3\n";
+    let document = parse_ini(ini);
+    println!("server.port = {:?}", document.get("server", "port"));
+    for err in &document.errors {
+        println!("invalid INI: {}", err);
+    }
 }
 
 #[cfg(test)]
@@ -372,4 +1391,180 @@ mod tests {
         let result = run_parser(parser, "x5 items").unwrap();
         assert_eq!(result, "x5");
     }
+
+    #[test]
+    fn test_or_merges_expected_sets_at_furthest_failure() {
+        let keyword = tag("let")
+            .or(tag("fn"))
+            .or(CharParser { expected: '{' }.map(|_| ()));
+        let err = run_parser(keyword, "x").unwrap_err();
+        assert_eq!(err.expected, vec!["\"fn\"", "\"let\"", "'{'"]);
+        assert_eq!(
+            err.to_string(),
+            "expected \"fn\", \"let\" or '{' but found 'x' at position 0"
+        );
+
+        // The alternative that got further wins over the one that failed early.
+        let pair = CharParser { expected: 'a' }
+            .and_then(CharParser { expected: 'b' })
+            .map(|_| ())
+            .or(CharParser { expected: 'c' }.map(|_| ()));
+        let err = run_parser(pair, "ax").unwrap_err();
+        assert_eq!(err.expected, vec!["'b'"]);
+        assert_eq!(err.position, 1);
+    }
+
+    #[test]
+    fn test_repetition_and_recognize_on_str_and_bytes() {
+        let digits = DigitParser.many1();
+        assert_eq!(digits.parse("123x"), Ok((vec![1, 2, 3], "x")));
+        assert!(DigitParser.many1().parse("x").is_err());
+        assert_eq!(DigitParser.many0().parse("x"), Ok((vec![], "x")));
+
+        let list = NumberParser.sep_by(CharParser { expected: ',' });
+        assert_eq!(list.parse("1,22,333;"), Ok((vec![1, 22, 333], ";")));
+        assert_eq!(list.parse(";"), Ok((vec![], ";")));
+        assert!(list.parse("1,;").is_err());
+
+        let bytes: &[u8] = b"12ab";
+        let run = DigitParser.many1().recognize();
+        assert_eq!(run.parse(bytes), Ok((&b"12"[..], &b"ab"[..])));
+        assert_eq!(
+            CharParser { expected: '-' }.opt().parse(bytes),
+            Ok((None, bytes))
+        );
+        assert_eq!(run_parser(NumberParser, &b"4x"[..]), Ok(4));
+    }
+
+    #[test]
+    fn test_cut_prevents_backtracking() {
+        let call = || {
+            preceded(
+                tag("f("),
+                terminated(NumberParser, CharParser { expected: ')' }),
+            )
+        };
+        let name = || take_while1("name", |ch| ch.is_ascii_alphabetic()).map(|_| 0);
+
+        // Without a cut the parser falls back to the second alternative.
+        assert_eq!(call().or(name()).parse("f(1]"), Ok((0, "(1]")));
+
+        let err = run_parser(
+            preceded(
+                tag("f("),
+                terminated(NumberParser, CharParser { expected: ')' }).cut(),
+            )
+            .or(name()),
+            "f(1]",
+        )
+        .unwrap_err();
+        assert_eq!(err.expected, vec!["')'"]);
+        assert_eq!(err.position, 3);
+    }
+
+    #[test]
+    fn test_pratt_precedence_and_associativity() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(evaluate("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(evaluate(" 1.5 * -2 "), Ok(-3.0));
+
+        let err = evaluate("1 + * 2").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.expected, vec!["\"-\"", "'('", "digit"]);
+
+        let err = evaluate("(1 + 2").unwrap_err();
+        assert_eq!(err.expected, vec!["')'"]);
+        assert_eq!(err.position, 6);
+    }
+
+    #[test]
+    fn test_json_grammar() {
+        let text =
+            r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"\n\u00e9\ud83d\ude00"}, "d": []} "#;
+        let expected = JsonValue::Object(vec![
+            (
+                "a".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.0),
+                    JsonValue::Number(-25.0),
+                    JsonValue::Bool(true),
+                    JsonValue::Null,
+                ]),
+            ),
+            (
+                "b".to_string(),
+                JsonValue::Object(vec![(
+                    "c".to_string(),
+                    JsonValue::String("x\"\né😀".to_string()),
+                )]),
+            ),
+            ("d".to_string(), JsonValue::Array(vec![])),
+        ]);
+        assert_eq!(parse_json(text), Ok(expected.clone()));
+        assert_eq!(parse_json(text.as_bytes()), Ok(expected));
+
+        // Raw UTF-8 survives byte input.
+        assert_eq!(
+            parse_json("\"héllo\"".as_bytes()),
+            Ok(JsonValue::String("héllo".to_string()))
+        );
+        assert_eq!(parse_json("0"), Ok(JsonValue::Number(0.0)));
+        assert!(parse_json("01").is_err());
+        assert!(parse_json("1.").is_err());
+        assert!(parse_json("\"\\x\"").is_err());
+        assert!(parse_json("\"\\ud83d\"").is_err());
+        assert!(parse_json("[1] 2").is_err());
+    }
+
+    #[test]
+    fn test_json_errors_report_furthest_failure() {
+        let err = parse_json(r#"{"items": [1, 2 3]}"#).unwrap_err();
+        assert_eq!(err.position, 16);
+        assert_eq!(err.to_string(), "expected ']' but found '3' at position 16");
+
+        let err = parse_json("[1, ]").unwrap_err();
+        assert_eq!(err.expected, vec!["value"]);
+        assert_eq!(err.position, 4);
+
+        let err = parse_json(r#"{"a" 1}"#).unwrap_err();
+        assert_eq!(err.expected, vec!["':'"]);
+        assert_eq!(err.position, 5);
+
+        let err = parse_json("{1: 2}").unwrap_err();
+        assert_eq!(err.expected, vec!["'}'"]);
+        assert_eq!(err.position, 1);
+    }
+
+    #[test]
+    fn test_ini_grammar_with_recovery() {
+        let text = "top = 1\n; comment\n# another\n\n[server]\nhost = localhost \nport: 8080\r\n[broken\nname value\n[db]\nurl=postgres://x";
+        let document = parse_ini(text);
+
+        let names: Vec<&str> = document.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["", "server", "db"]);
+        assert_eq!(document.get("", "top"), Some("1"));
+        assert_eq!(document.get("server", "host"), Some("localhost"));
+        assert_eq!(document.get("server", "port"), Some("8080"));
+        assert_eq!(document.get("db", "url"), Some("postgres://x"));
+        assert_eq!(document.get("db", "missing"), None);
+
+        assert_eq!(document.errors.len(), 2);
+        assert_eq!(document.errors[0].expected, vec!["']'"]);
+        assert_eq!(
+            document.errors[0].position,
+            text.find("[broken").unwrap() + 7
+        );
+        assert_eq!(document.errors[1].expected, vec!["'=' or ':'"]);
+        assert_eq!(
+            document.errors[1].position,
+            text.find("name value").unwrap() + 5
+        );
+
+        let document = parse_ini("[a]\nk = v\n".as_bytes());
+        assert_eq!(document.get("a", "k"), Some("v"));
+        assert!(document.errors.is_empty());
+    }
 }